@@ -0,0 +1,101 @@
+//! Timings of structure generation over a grid of configurations.
+//!
+//! Each cell is generated `repeat` times and the best and median wall-clock
+//! times are reported, together with the peak resident memory of the process
+//! during those runs. The peak is read from `/proc/self/status` and reset
+//! between cells through `/proc/self/clear_refs`, so it is only available on
+//! Linux; elsewhere the column shows `-`.
+
+use std::{fs, time::Instant};
+
+use treeamps_core::{GenConfig, generate_tensor_structures};
+
+/// One row of the table.
+struct Timing {
+    n: u8,
+    deg: u32,
+    ee: u32,
+    count: usize,
+    best_ms: f64,
+    median_ms: f64,
+    peak_kib: Option<u64>,
+}
+
+/// Time the default one-polarization-per-leg basis for every `n` in `ns` and
+/// EE count in `ees`, with the degree `n − ee` that this basis implies.
+pub fn run(ns: &[u8], ees: &[u32], repeat: usize) {
+    let repeat = repeat.max(1);
+    println!(
+        "Generation benchmark (repeat={}, parallel={})",
+        repeat,
+        if cfg!(feature = "parallel") {
+            "on"
+        } else {
+            "off"
+        }
+    );
+    println!(
+        "  {:>3} {:>4} {:>3} {:>10} {:>11} {:>11} {:>10}",
+        "n", "deg", "ee", "count", "best ms", "median ms", "peak MiB"
+    );
+    for &n in ns {
+        for &ee in ees {
+            // Each EE factor uses up two of the n polarizations.
+            if 2 * ee > n as u32 {
+                continue;
+            }
+            let t = time_generation(n, n as u32 - ee, ee, repeat);
+            println!(
+                "  {:>3} {:>4} {:>3} {:>10} {:>11.2} {:>11.2} {:>10}",
+                t.n,
+                t.deg,
+                t.ee,
+                t.count,
+                t.best_ms,
+                t.median_ms,
+                t.peak_kib
+                    .map_or("-".to_string(), |k| format!("{:.1}", k as f64 / 1024.0))
+            );
+        }
+    }
+}
+
+fn time_generation(n: u8, deg: u32, ee: u32, repeat: usize) -> Timing {
+    let cfg = GenConfig {
+        n_legs: n,
+        ..GenConfig::default()
+    };
+    reset_peak_rss();
+    let mut count = 0;
+    let mut times: Vec<f64> = (0..repeat)
+        .map(|_| {
+            let start = Instant::now();
+            let ts = generate_tensor_structures(&cfg, deg, ee);
+            let elapsed = start.elapsed().as_secs_f64() * 1e3;
+            count = ts.len();
+            elapsed
+        })
+        .collect();
+    times.sort_by(f64::total_cmp);
+    Timing {
+        n,
+        deg,
+        ee,
+        count,
+        best_ms: times[0],
+        median_ms: times[times.len() / 2],
+        peak_kib: peak_rss_kib(),
+    }
+}
+
+/// Start a new peak-memory measurement; a no-op where unsupported.
+fn reset_peak_rss() {
+    let _ = fs::write("/proc/self/clear_refs", "5");
+}
+
+/// Peak resident set size since the last [`reset_peak_rss`], in KiB.
+fn peak_rss_kib() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmHWM:"))?;
+    line.split_whitespace().nth(1)?.parse().ok()
+}
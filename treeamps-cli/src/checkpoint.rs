@@ -0,0 +1,142 @@
+//! Checkpoints that let an interrupted `gen-ts` run pick up where it stopped.
+//!
+//! A checkpoint is a small text file holding the arguments of the run and the
+//! position of the search. The structures found so far go to a companion
+//! `.partial` file, one formatted line each, which is cut back to the length
+//! recorded in the checkpoint when the run resumes.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use treeamps_core::{GenConfig, SearchCursor, TensorStructure, resume_tensor_structures};
+
+const HEADER: &str = "treeamps gen-ts checkpoint";
+
+#[derive(Clone, Debug)]
+pub struct Checkpoint {
+    /// Command-line arguments of the run, starting with `gen-ts`.
+    pub args: Vec<String>,
+    pub cursor: SearchCursor,
+    /// Structures in the partial output.
+    pub found: u64,
+    /// Length of the partial output in bytes.
+    pub partial_len: u64,
+}
+
+impl Checkpoint {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let bad = |line: &str| format!("{}: unexpected line {:?}", path.display(), line);
+        let mut lines = text.lines();
+        if lines.next() != Some(HEADER) {
+            return Err(format!("{}: not a gen-ts checkpoint", path.display()));
+        }
+        let mut checkpoint = Checkpoint {
+            args: Vec::new(),
+            cursor: SearchCursor::default(),
+            found: 0,
+            partial_len: 0,
+        };
+        for line in lines {
+            match line.split_once(' ') {
+                Some(("arg", a)) => checkpoint.args.push(a.to_string()),
+                Some(("cursor", c)) => checkpoint.cursor = c.parse()?,
+                Some(("found", f)) => {
+                    let (found, len) = f.split_once(' ').ok_or_else(|| bad(line))?;
+                    checkpoint.found = found.parse().map_err(|_| bad(line))?;
+                    checkpoint.partial_len = len.parse().map_err(|_| bad(line))?;
+                }
+                _ => return Err(bad(line)),
+            }
+        }
+        Ok(checkpoint)
+    }
+
+    /// Write the checkpoint next to `path` and move it into place, so an
+    /// interruption never leaves a half-written file behind.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let tmp = with_suffix(path, ".tmp");
+        let mut w = BufWriter::new(File::create(&tmp)?);
+        writeln!(w, "{}", HEADER)?;
+        writeln!(w, "cursor {}", self.cursor)?;
+        writeln!(w, "found {} {}", self.found, self.partial_len)?;
+        for a in &self.args {
+            writeln!(w, "arg {}", a)?;
+        }
+        w.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        fs::rename(tmp, path)
+    }
+}
+
+/// The file the structures found so far are written to.
+pub fn partial_path(checkpoint: &Path) -> PathBuf {
+    with_suffix(checkpoint, ".partial")
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    name.into()
+}
+
+/// Run the search to the end, saving a checkpoint to `path` at most every
+/// `every` and appending each structure to the partial output as `format`
+/// renders it. Starts from `resume` if given.
+///
+/// Returns the number of structures; the partial output then holds all of them.
+pub fn generate(
+    cfg: &GenConfig,
+    deg: u32,
+    ee: u32,
+    path: &Path,
+    every: Duration,
+    resume: Option<Checkpoint>,
+    format: impl Fn(&TensorStructure) -> String,
+) -> Result<u64, String> {
+    let io_err = |e: io::Error| format!("{}: {}", path.display(), e);
+    let mut checkpoint = resume.unwrap_or_else(|| Checkpoint {
+        args: std::env::args().skip(1).collect(),
+        cursor: SearchCursor::default(),
+        found: 0,
+        partial_len: 0,
+    });
+    let mut search = resume_tensor_structures(cfg, deg, ee, &checkpoint.cursor)?;
+
+    let partial = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(partial_path(path))
+        .map_err(io_err)?;
+    // Drop whatever was written after the last checkpoint.
+    partial.set_len(checkpoint.partial_len).map_err(io_err)?;
+    let mut partial = BufWriter::new(partial);
+    io::Seek::seek(&mut partial, io::SeekFrom::End(0)).map_err(io_err)?;
+
+    let mut last_save = Instant::now();
+    while let Some(t) = search.next() {
+        let line = format(&t);
+        writeln!(partial, "{}", line).map_err(io_err)?;
+        checkpoint.found += 1;
+        checkpoint.partial_len += line.len() as u64 + 1;
+        if last_save.elapsed() >= every {
+            partial.flush().map_err(io_err)?;
+            checkpoint.cursor = search.cursor();
+            checkpoint.save(path).map_err(io_err)?;
+            last_save = Instant::now();
+        }
+    }
+    partial.flush().map_err(io_err)?;
+    checkpoint.cursor = search.cursor();
+    checkpoint.save(path).map_err(io_err)?;
+    Ok(checkpoint.found)
+}
+
+/// The lines of the partial output, in order.
+pub fn read_partial(path: &Path) -> io::Result<impl Iterator<Item = io::Result<String>>> {
+    Ok(BufReader::new(File::open(partial_path(path))?).lines())
+}
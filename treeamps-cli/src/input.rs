@@ -0,0 +1,101 @@
+//! Structure sets saved by an earlier run, read back instead of regenerated.
+//!
+//! The format of a file is recognized from its first bytes: the structure
+//! file of `--format binary`, the object of `--format json` (or a bare array
+//! of structures), and otherwise the text listing, of which only the numbered
+//! `  i) ...` lines are read.
+
+use std::{fs, path::Path};
+
+use serde_json::Value;
+use treeamps_core::{GenConfig, STRUCTURE_FILE_MAGIC, TensorStructure, read_structure_file};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputFormat {
+    Text,
+    Json,
+    Binary,
+}
+
+impl InputFormat {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Json => "json",
+            Self::Binary => "binary",
+        }
+    }
+
+    /// The format of a file that starts with `head`.
+    pub fn detect(head: &[u8]) -> Self {
+        if head.starts_with(STRUCTURE_FILE_MAGIC) {
+            Self::Binary
+        } else if matches!(head.trim_ascii_start().first(), Some(b'{' | b'[')) {
+            Self::Json
+        } else {
+            Self::Text
+        }
+    }
+}
+
+/// The contents of a saved structure file.
+pub struct Saved {
+    pub format: InputFormat,
+    /// The configuration of the run, which only JSON files record.
+    pub config: Option<GenConfig>,
+    pub structures: Vec<TensorStructure>,
+}
+
+/// Read the structures saved in `path`, in the order they were written.
+pub fn read_structures(path: &Path) -> Result<Saved, String> {
+    let context = |e: String| format!("{}: {}", path.display(), e);
+    let bytes = fs::read(path).map_err(|e| context(e.to_string()))?;
+    let format = InputFormat::detect(&bytes);
+    let mut saved = Saved {
+        format,
+        config: None,
+        structures: Vec::new(),
+    };
+    match format {
+        InputFormat::Binary => {
+            saved.structures = read_structure_file(path)
+                .and_then(|structures| structures.collect())
+                .map_err(|e| context(e.to_string()))?;
+        }
+        InputFormat::Json => {
+            let value: Value =
+                serde_json::from_slice(&bytes).map_err(|e| context(e.to_string()))?;
+            let structures = match value {
+                Value::Object(mut object) => {
+                    if let Some(config) = object.remove("config").filter(|c| !c.is_null()) {
+                        saved.config = Some(
+                            serde_json::from_value(config).map_err(|e| context(e.to_string()))?,
+                        );
+                    }
+                    object.remove("structures").ok_or_else(|| {
+                        context("no structures listed, e.g. from --count-only".to_string())
+                    })?
+                }
+                array => array,
+            };
+            saved.structures =
+                serde_json::from_value(structures).map_err(|e| context(e.to_string()))?;
+        }
+        InputFormat::Text => {
+            let text = String::from_utf8(bytes).map_err(|e| context(e.to_string()))?;
+            for (k, line) in text.lines().enumerate() {
+                let Some((index, structure)) = line.trim_start().split_once(") ") else {
+                    continue;
+                };
+                if index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+                    continue;
+                }
+                let t = structure
+                    .parse()
+                    .map_err(|e| context(format!("line {}: {}", k + 1, e)))?;
+                saved.structures.push(t);
+            }
+        }
+    }
+    Ok(saved)
+}
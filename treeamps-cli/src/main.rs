@@ -1,13 +1,82 @@
-use clap::{Parser, Subcommand};
-use treeamps_core::{GenConfig, generate_tensor_structures};
+mod bench;
+mod checkpoint;
+mod input;
+mod output;
+
+use std::{
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use checkpoint::Checkpoint;
+use clap::{Args, Parser, Subcommand};
+use output::{FormatOptions, Listing, OutputFormat};
+use serde_json::Value;
+use treeamps_core::{
+    Ansatz, Canonicalization, Catalog, GenConfig, Helicity, KinematicPoint, LegIndex, LinearSystem,
+    Mass, MomentumTwistors, Permutation, PolarizationPattern, ScalarFactor, SpillConfig, Spin,
+    SpinorConfig, SpinorPoint, Statistics, TensorExpression, TensorStructure, Transversality,
+    bcj_ansatz, bcj_relations, color_dressed_basis, color_graph_basis, color_orderings,
+    count_tensor_structures, cubic_graphs, ddm_basis, ddm_decomposition, double_copy_candidates,
+    feynman_diagrams, gauge_invariant_combinations, generate_field_strength_structures,
+    generate_spinor_structures, generate_tensor_structures, generate_tensor_structures_range,
+    generate_tensor_structures_spilled, generate_tensor_structures_with_catalog,
+    generate_tensor_structures_with_progress, gram_reduce, group_by_permutation_orbit,
+    independent_orderings, kk_relations, operator_basis, predict_structure_count, project_diagrams,
+    random_seed, reduce_expression, schouten_reduce, strip_polarizations, trace_structures,
+    tree_graphs, tree_topologies, try_generate_tensor_structures, ward_filter,
+};
 
 fn main() {
     let cli = Cli::parse();
     match cli.cmd {
-        Command::GenTs { n, deg, ee } => run_gen_ts(n, deg, ee),
-        // All solver/symbolic functionality has been removed for now; `solve`
-        // is intentionally omitted to keep this CLI focused on tensor-structure
-        // generation via `gen-ts`.
+        Command::GenTs(args) => match &args.resume {
+            Some(path) => resume_gen_ts(path),
+            None => run_gen_ts(*args, None),
+        },
+        Command::GenSpinor {
+            hel,
+            angle,
+            square,
+            schouten,
+            format,
+            format_options,
+        } => run_gen_spinor(&hel, angle, square, schouten, format, &format_options),
+        Command::Relations {
+            n,
+            list,
+            ddm,
+            color_graphs,
+        } => run_relations(n, list, ddm, color_graphs),
+        Command::Solve {
+            n,
+            deg,
+            max_pp,
+            symmetric,
+            cyclic,
+            no_ward,
+            list,
+        } => run_solve(n, deg, max_pp, &symmetric, cyclic, no_ward, list),
+        Command::Point {
+            n,
+            d,
+            seed,
+            helicities,
+            twistors,
+        } => run_point(n, d, seed, helicities.as_deref(), twistors),
+        Command::Feynman { n, diagrams } => run_feynman(n, diagrams),
+        Command::Topologies {
+            n,
+            max_valence,
+            list,
+        } => run_topologies(n, max_valence, list),
+        Command::Bench { n, ee, repeat } => bench::run(&n, &ee, repeat),
+        Command::Convert {
+            file,
+            format,
+            format_options,
+        } => run_convert(&file, format, &format_options),
     }
 }
 
@@ -24,40 +93,620 @@ struct Cli {
 #[derive(Subcommand, Debug)]
 enum Command {
     /// Generate tensor structures for fixed degree and EE count
-    GenTs {
+    GenTs(Box<GenTsArgs>),
+
+    /// Generate spinor-bracket monomials with fixed helicities
+    GenSpinor {
+        /// Helicity per leg, one character each: '+', '-' (gluons) or '0' (scalars)
+        #[arg(long, allow_hyphen_values = true)]
+        hel: String,
+
+        /// Number of angle brackets ⟨ij⟩
+        #[arg(long, default_value_t = 0)]
+        angle: u32,
+
+        /// Number of square brackets [ij]
+        #[arg(long, default_value_t = 0)]
+        square: u32,
+
+        /// Keep only monomials independent under Schouten identities
+        #[arg(long)]
+        schouten: bool,
+
+        /// Output format of the listed structures
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
+        #[command(flatten)]
+        format_options: FormatOptions,
+    },
+
+    /// Kleiss–Kuijf and BCJ relations among color-ordered amplitudes
+    Relations {
         /// Number of external legs
-        #[arg(long, default_value_t = 3)]
+        #[arg(long, default_value_t = 4)]
         n: u8,
 
-        /// Total number of factors (degree); leave 0 to infer from n and ee
-        #[arg(long, default_value_t = 0)]
+        /// Print every relation, not just the counts
+        #[arg(long)]
+        list: bool,
+
+        /// Also print the DDM half-ladder color basis expanded in traces
+        #[arg(long)]
+        ddm: bool,
+
+        /// Also print every f^{abc} color graph expanded in the DDM basis
+        #[arg(long)]
+        color_graphs: bool,
+    },
+
+    /// Solve an ansatz over all structures of one degree for Ward identities and leg symmetries
+    ///
+    /// Factorization is not imposed, as the ansatz is a polynomial without poles; check a
+    /// candidate amplitude on its poles with `check_factorization` in treeamps-core.
+    Solve {
+        /// Number of external legs
+        #[arg(long, default_value_t = 4)]
+        n: u8,
+
+        /// Total number of factors; every EE count is included
+        #[arg(long)]
         deg: u32,
 
-        /// Number of EE contractions; leave 0 to infer from n and deg
-        #[arg(long, default_value_t = 0)]
-        ee: u32,
+        /// Allow PP factors to fill up --deg, at most this many per structure
+        #[arg(long)]
+        max_pp: Option<u32>,
+
+        /// Require Bose symmetry under exchanging these legs, e.g. 1,2,3
+        #[arg(long, value_delimiter = ',', value_name = "LEGS")]
+        symmetric: Vec<u8>,
+
+        /// Require invariance under the cyclic relabeling i → i+1, as for a color-ordered amplitude
+        #[arg(long)]
+        cyclic: bool,
+
+        /// Skip the Ward identities
+        #[arg(long)]
+        no_ward: bool,
+
+        /// Also print the ansatz and the solved equations
+        #[arg(long)]
+        list: bool,
+    },
+
+    /// Print an exact rational massless phase-space point with transverse polarizations
+    Point {
+        /// Number of external legs
+        #[arg(long, default_value_t = 4)]
+        n: u8,
+
+        /// Spacetime dimension
+        #[arg(long, default_value_t = 4)]
+        d: u32,
+
+        /// Seed to reproduce a point; a fresh one is drawn and printed if omitted
+        #[arg(long)]
+        seed: Option<u64>,
+
+        /// Helicity per leg, '+' or '-': sample a 4D spinor point with helicity polarizations
+        #[arg(long, allow_hyphen_values = true)]
+        helicities: Option<String>,
+
+        /// Derive a 4D spinor point from random momentum twistors (n >= 4)
+        #[arg(long)]
+        twistors: bool,
+    },
+
+    /// Project the color-ordered Yang–Mills Feynman diagrams onto the tensor-structure basis
+    Feynman {
+        /// Number of external gluons
+        #[arg(long, default_value_t = 4)]
+        n: u8,
+
+        /// Also print every diagram's numerator
+        #[arg(long)]
+        diagrams: bool,
+    },
+
+    /// Tree graph topologies with cubic and higher-valence vertices
+    Topologies {
+        /// Number of external legs
+        #[arg(long, default_value_t = 5)]
+        n: u8,
+
+        /// Largest vertex valence: 3 for cubic graphs, 4 to add quartic vertices
+        #[arg(long, default_value_t = 4)]
+        max_valence: usize,
+
+        /// Print every labeled graph, not just one per topology
+        #[arg(long)]
+        list: bool,
+    },
+
+    /// Time structure generation over a grid of leg and EE counts
+    Bench {
+        /// Numbers of external legs, e.g. 4,5,6
+        #[arg(long, value_delimiter = ',', default_values_t = [4, 5, 6, 7])]
+        n: Vec<u8>,
+
+        /// Numbers of EE contractions; the degree is n minus this
+        #[arg(long, value_delimiter = ',', default_values_t = [0, 1, 2])]
+        ee: Vec<u32>,
+
+        /// Runs per configuration; the best and median times are reported
+        #[arg(long, default_value_t = 3)]
+        repeat: usize,
     },
+
+    /// Read a saved structure set and print it in another format
+    Convert {
+        /// Output of gen-ts or gen-spinor in the text, JSON or binary format,
+        /// which is recognized by its contents
+        file: PathBuf,
+
+        /// Output format of the listed structures
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
+        #[command(flatten)]
+        format_options: FormatOptions,
+    },
+}
+
+#[derive(Args, Debug)]
+struct GenTsArgs {
+    /// Number of external legs
+    #[arg(long, default_value_t = 3)]
+    n: u8,
+
+    /// Total number of factors (degree); leave 0 to infer from n and ee
+    #[arg(long, default_value_t = 0)]
+    deg: u32,
+
+    /// Number of EE contractions; leave 0 to infer from n and deg
+    #[arg(long, default_value_t = 0)]
+    ee: u32,
+
+    /// Leg whose momentum is eliminated by momentum conservation (default: last leg)
+    #[arg(long, conflicts_with = "no_elim")]
+    elim: Option<u8>,
+
+    /// Keep every momentum (redundant basis, no momentum conservation applied)
+    #[arg(long)]
+    no_elim: bool,
+
+    /// Mark a leg as massive (repeatable); allows p_i·e_i for that leg
+    #[arg(long = "massive", value_name = "LEG")]
+    massive: Vec<u8>,
+
+    /// Include p_i·p_i factors for massive legs
+    #[arg(long)]
+    mass_squares: bool,
+
+    /// Mark a leg as a scalar, carrying no polarization (repeatable)
+    #[arg(long = "scalar", value_name = "LEG")]
+    scalars: Vec<u8>,
+
+    /// Mark a leg as spin-2, carrying two polarizations (repeatable)
+    #[arg(long = "tensor", value_name = "LEG")]
+    tensors: Vec<u8>,
+
+    /// Also enumerate parity-odd structures with one Levi-Civita factor
+    #[arg(long)]
+    parity_odd: bool,
+
+    /// Mark a leg as a fermion, entering through a spinor bilinear (repeatable)
+    #[arg(long = "fermion", value_name = "LEG")]
+    fermions: Vec<u8>,
+
+    /// Mark a leg as off shell, e.g. a form-factor momentum q (repeatable)
+    #[arg(long = "off-shell", value_name = "LEG")]
+    off_shell: Vec<u8>,
+
+    /// Exact number of polarizations per leg, e.g. 2,1,1,0 (overrides the spins)
+    #[arg(long, value_delimiter = ',', value_name = "K1,K2,...")]
+    pols: Vec<u8>,
+
+    /// Allow p_i·e_i for this leg, e.g. an axial-gauge leg (repeatable)
+    #[arg(long = "non-transverse", value_name = "LEG")]
+    non_transverse: Vec<u8>,
+
+    /// Drop a factor such as p1·e2 from the catalog (repeatable)
+    #[arg(long = "forbid", value_name = "FACTOR")]
+    forbidden: Vec<ScalarFactor>,
+
+    /// Allow PP factors to fill up --deg, at most this many per structure
+    #[arg(long)]
+    max_pp: Option<u32>,
+
+    /// Number of loop momenta l_1..l_L to add to the alphabet
+    #[arg(long, default_value_t = 0)]
+    loops: u8,
+
+    /// Maximum total power of loop momenta per structure
+    #[arg(long)]
+    max_loop_power: Option<u32>,
+
+    /// Keep only structures with exactly this many momenta (PP counts 2, PE 1, EE 0)
+    #[arg(long)]
+    momentum_power: Option<u32>,
+
+    /// Build gauge-invariant structures from field strengths F_i instead of bare e_i
+    #[arg(long)]
+    field_strengths: bool,
+
+    /// With --field-strengths: momenta beyond one per F_i (two per chain or PP factor)
+    #[arg(long, default_value_t = 0, requires = "field_strengths")]
+    extra_momenta: u32,
+
+    /// Build an operator basis of this mass dimension, removing IBP/EOM redundancies
+    #[arg(long, conflicts_with = "field_strengths")]
+    operator_dim: Option<u32>,
+
+    /// Restrict to one helicity sector, one '+' or '-' per leg (e.g. --helicities=--++)
+    #[arg(long, allow_hyphen_values = true, conflicts_with_all = ["self_dual", "anti_self_dual"])]
+    helicities: Option<String>,
+
+    /// Restrict to the self-dual sector (every leg '+'), where all EE factors vanish
+    #[arg(long, conflicts_with = "anti_self_dual")]
+    self_dual: bool,
+
+    /// Restrict to the anti-self-dual sector (every leg '-')
+    #[arg(long)]
+    anti_self_dual: bool,
+
+    /// Also report counts in every helicity sector, by number of '-' legs
+    #[arg(long)]
+    sectors: bool,
+
+    /// Only count the structures, without building or printing them
+    #[arg(long)]
+    count_only: bool,
+
+    /// Sort the structures through files in this directory instead of memory, for very large bases
+    #[arg(
+        long,
+        value_name = "DIR",
+        conflicts_with_all = [
+            "count_only", "orbits", "dim", "find_relations", "ward", "gauge_invariant",
+            "double_copy", "color_traces", "bcj", "sectors",
+        ]
+    )]
+    spill_dir: Option<PathBuf>,
+
+    /// Stop with an error rather than hold more than this many structures in memory (0: no limit)
+    #[arg(long, value_name = "N", default_value_t = 100_000_000)]
+    max_count: u64,
+
+    /// Save progress to this file while generating, so an interrupted run can be resumed
+    #[arg(
+        long = "checkpoint",
+        value_name = "FILE",
+        conflicts_with_all = [
+            "spill_dir", "cyclic", "count_only", "orbits", "dim", "find_relations", "ward",
+            "gauge_invariant", "double_copy", "color_traces", "bcj", "sectors",
+        ]
+    )]
+    checkpoint_file: Option<PathBuf>,
+
+    /// Seconds between two saves of the --checkpoint file
+    #[arg(long, default_value_t = 60, requires = "checkpoint_file")]
+    checkpoint_secs: u64,
+
+    /// Resume the run saved in this --checkpoint file, with the options it was started with
+    #[arg(long, value_name = "FILE", exclusive = true)]
+    resume: Option<PathBuf>,
+
+    /// Report search progress on stderr while generating
+    #[arg(long, conflicts_with_all = ["count_only", "spill_dir", "checkpoint_file"])]
+    progress: bool,
+
+    /// Output format of the listed structures
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = OutputFormat::Text,
+        conflicts_with_all = [
+            "checkpoint_file", "mandelstam", "open_indices", "orbits", "dim", "find_relations",
+            "ward", "gauge_invariant", "double_copy", "color_traces", "bcj", "sectors",
+        ]
+    )]
+    format: OutputFormat,
+
+    #[command(flatten)]
+    format_options: FormatOptions,
+
+    /// Print PP factors as Mandelstam invariants s_ij
+    #[arg(long)]
+    mandelstam: bool,
+
+    /// Print structures with polarizations stripped, as tensors with free Lorentz indices
+    #[arg(long, conflicts_with = "mandelstam")]
+    open_indices: bool,
+
+    /// Identify structures related by a cyclic relabeling of the legs; rotations move the
+    /// eliminated momentum, so this needs --no-elim
+    #[arg(long, requires = "no_elim")]
+    cyclic: bool,
+
+    /// Also group the output into orbits under all leg permutations
+    #[arg(long)]
+    orbits: bool,
+
+    /// Report structures that become dependent in this spacetime dimension
+    #[arg(long, value_name = "D")]
+    dim: Option<u32>,
+
+    /// Find every linear relation among the listed structures by numerical evaluation
+    #[arg(long)]
+    find_relations: bool,
+
+    /// Report which structures satisfy the Ward identity e_i → p_i on every gauge leg
+    #[arg(long)]
+    ward: bool,
+
+    /// Solve for gauge-invariant combinations of all structures at this --deg (every EE count)
+    #[arg(long)]
+    gauge_invariant: bool,
+
+    /// Also list the graviton candidates from squaring the listed structures
+    #[arg(long)]
+    double_copy: bool,
+
+    /// Pair the listed structures with trace-basis color factors of up to this many traces
+    #[arg(long)]
+    color_traces: Option<usize>,
+
+    /// Solve the BCJ numerator ansatz built from all structures at this --deg (every EE count)
+    #[arg(long)]
+    bcj: bool,
 }
 
-fn run_gen_ts(n: u8, mut deg: u32, mut ee: u32) {
+/// Restart the `gen-ts` run saved in the checkpoint at `path`.
+fn resume_gen_ts(path: &Path) {
+    let checkpoint = Checkpoint::load(path).unwrap_or_else(|e| {
+        eprintln!("--resume: {}", e);
+        std::process::exit(1);
+    });
+    let args = std::iter::once("treeamps".to_string()).chain(checkpoint.args.iter().cloned());
+    match Cli::try_parse_from(args).map(|cli| cli.cmd) {
+        Ok(Command::GenTs(args)) if args.checkpoint_file.is_some() => {
+            run_gen_ts(*args, Some(checkpoint))
+        }
+        _ => {
+            eprintln!(
+                "--resume: {} does not hold a gen-ts --checkpoint run",
+                path.display()
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run_gen_ts(args: GenTsArgs, resume: Option<Checkpoint>) {
+    let GenTsArgs {
+        n,
+        mut deg,
+        mut ee,
+        elim,
+        no_elim,
+        massive,
+        mass_squares,
+        scalars,
+        tensors,
+        parity_odd,
+        fermions,
+        off_shell,
+        pols,
+        non_transverse,
+        forbidden,
+        max_pp,
+        loops,
+        max_loop_power,
+        momentum_power,
+        helicities,
+        self_dual,
+        anti_self_dual,
+        sectors,
+        field_strengths,
+        extra_momenta,
+        operator_dim,
+        count_only,
+        spill_dir,
+        max_count,
+        checkpoint_file,
+        checkpoint_secs,
+        resume: _,
+        progress,
+        format: output_format,
+        format_options,
+        mandelstam,
+        open_indices,
+        cyclic,
+        orbits,
+        dim,
+        find_relations,
+        ward,
+        gauge_invariant,
+        double_copy,
+        color_traces,
+        bcj,
+    } = args;
     if n == 0 {
         eprintln!("--n must be >= 1");
         std::process::exit(1);
     }
-    // For gluon bases we always enforce "one polarization per leg".
-    // The constraint 2*EE + PE = n and deg = EE + PE implies
-    // deg = n - ee and ee = n - deg. Enforce consistency and
-    // allow one to be inferred from the other when left as zero.
+    if let Some(k) = elim
+        && (k == 0 || k > n)
     {
-        let implied_deg = n as u32 - ee;
-        let implied_ee = n as u32 - deg;
+        eprintln!("--elim must be between 1 and --n");
+        std::process::exit(1);
+    }
+    check_legs("--massive", &massive, n);
+    check_legs("--scalar", &scalars, n);
+    check_legs("--tensor", &tensors, n);
+    if let Some(k) = scalars.iter().find(|k| tensors.contains(k)) {
+        eprintln!("leg {} cannot be both --scalar and --tensor", k);
+        std::process::exit(1);
+    }
+    check_legs("--fermion", &fermions, n);
+    check_legs("--off-shell", &off_shell, n);
+    if !pols.is_empty() && pols.len() != n as usize {
+        eprintln!("--pols must list one count per leg");
+        std::process::exit(1);
+    }
+    check_legs("--non-transverse", &non_transverse, n);
+    let helicities = if self_dual {
+        vec![Helicity::Plus; n as usize]
+    } else if anti_self_dual {
+        vec![Helicity::Minus; n as usize]
+    } else {
+        helicities.map_or_else(Vec::new, |h| parse_helicities(&h, n))
+    };
+
+    let cfg = GenConfig {
+        n_legs: n,
+        pol_pattern: if pols.is_empty() {
+            PolarizationPattern::OnePerLeg
+        } else {
+            PolarizationPattern::ExactlyPerLeg(pols.clone())
+        },
+        leg_transversality: (1..=n)
+            .map(|k| {
+                if non_transverse.contains(&k) {
+                    Transversality::None
+                } else {
+                    Transversality::ForbidPiDotEi
+                }
+            })
+            .collect(),
+        eliminate_momentum: !no_elim,
+        eliminated_momentum: elim.map(LegIndex),
+        masses: (1..=n)
+            .map(|k| {
+                if massive.contains(&k) {
+                    Mass::Massive
+                } else {
+                    Mass::Massless
+                }
+            })
+            .collect(),
+        include_mass_squares: mass_squares,
+        spins: (1..=n)
+            .map(|k| {
+                if scalars.contains(&k) {
+                    Spin::Scalar
+                } else if tensors.contains(&k) {
+                    Spin::Tensor
+                } else {
+                    Spin::Vector
+                }
+            })
+            .collect(),
+        include_parity_odd: parity_odd,
+        statistics: (1..=n)
+            .map(|k| {
+                if fermions.contains(&k) {
+                    Statistics::Fermion
+                } else {
+                    Statistics::Boson
+                }
+            })
+            .collect(),
+        forbidden_factors: forbidden.clone(),
+        max_pp,
+        canonicalization: if cyclic {
+            Canonicalization::Cyclic
+        } else {
+            Canonicalization::Labelled
+        },
+        off_shell_legs: off_shell.iter().map(|&k| LegIndex(k)).collect(),
+        n_loops: loops,
+        max_loop_power,
+        momentum_power,
+        helicities: helicities.clone(),
+        ..GenConfig::default()
+    };
+
+    let elim: Value = cfg
+        .eliminated_leg()
+        .map_or("none".to_string(), |l| format!("p{}", l.0))
+        .into();
+    let display = |t: &TensorStructure| t.to_string();
+
+    if let Some(dim) = operator_dim {
+        let ob = operator_basis(&cfg, dim);
+        let listing = Listing {
+            title: "Operator basis",
+            params: vec![("n", n.into()), ("dim", dim.into()), ("elim", elim)],
+            count: ob.basis.len() as u64,
+            totals: vec![("redundant", ob.redundant.len().into())],
+            config: Some(&cfg),
+            predicted: None,
+        };
+        exit_on_io_error(output::print(
+            &mut *output_format.formatter(&display, &format_options),
+            &listing,
+            Some(&mut ob.basis.into_iter().map(Ok)),
+        ));
+        return;
+    }
+
+    if field_strengths {
+        let ts = generate_field_strength_structures(&cfg, extra_momenta);
+        let listing = Listing {
+            title: "Field-strength structures",
+            params: vec![
+                ("n", n.into()),
+                ("extra_momenta", extra_momenta.into()),
+                ("elim", elim),
+            ],
+            count: ts.len() as u64,
+            totals: Vec::new(),
+            config: Some(&cfg),
+            predicted: None,
+        };
+        exit_on_io_error(output::print(
+            &mut *output_format.formatter(&display, &format_options),
+            &listing,
+            Some(&mut ts.into_iter().map(Ok)),
+        ));
+        return;
+    }
+
+    // Every leg carries exactly as many polarizations as its spin demands
+    // (one per gluon). The constraint 2*EE + PE = npol and deg = EE + PE
+    // implies deg = npol - ee and ee = npol - deg. Enforce consistency and
+    // allow one to be inferred from the other when left as zero.
+    //
+    // ε factors and fermion bilinears absorb a variable number of
+    // polarizations, and --max-pp and loop momenta let PP or l·p factors fill
+    // up the degree, so with any of them present --deg must be given explicitly.
+    if parity_odd || !fermions.is_empty() || max_pp.is_some() || loops > 0 {
+        if deg == 0 {
+            eprintln!("--deg is required with --parity-odd, --fermion, --max-pp or --loops");
+            std::process::exit(1);
+        }
+    } else {
+        let npol: u32 = cfg
+            .polarization_targets()
+            .map_or(n as u32, |t| t.iter().sum());
+        if ee > npol || deg > npol {
+            eprintln!(
+                "--deg and --ee must not exceed the number of polarizations ({})",
+                npol
+            );
+            std::process::exit(1);
+        }
+        let implied_deg = npol - ee;
+        let implied_ee = npol - deg;
 
         // If both deg and ee are nonzero, require mutual consistency.
         if deg != 0 && ee != 0 {
             if deg != implied_deg || ee != implied_ee {
                 eprintln!(
-                    "Inconsistent inputs for one-pol-per-leg: n = {}, deg = {}, ee = {}. Expected deg = n - ee = {} and ee = n - deg = {}.",
-                    n, deg, ee, implied_deg, implied_ee,
+                    "Inconsistent inputs for one-pol-per-leg: npol = {}, deg = {}, ee = {}. Expected deg = npol - ee = {} and ee = npol - deg = {}.",
+                    npol, deg, ee, implied_deg, implied_ee,
                 );
                 std::process::exit(1);
             }
@@ -67,7 +716,7 @@ fn run_gen_ts(n: u8, mut deg: u32, mut ee: u32) {
             ee = implied_ee;
         } else if deg == 0 && ee == 0 {
             // Default: pure PE basis with no EE contractions (min-0 case)
-            deg = n as u32;
+            deg = npol;
             ee = 0;
         }
     }
@@ -77,28 +726,299 @@ fn run_gen_ts(n: u8, mut deg: u32, mut ee: u32) {
         std::process::exit(1);
     }
 
-    let mut cfg = GenConfig::default();
-    cfg.n_legs = n;
+    let one_pol_per_leg = cfg
+        .polarization_targets()
+        .is_some_and(|t| t[1..].iter().all(|&k| k == 1));
+    let listing = |count: u64| Listing {
+        title: "Tensor structures",
+        params: vec![
+            ("n", n.into()),
+            ("deg", deg.into()),
+            ("ee", ee.into()),
+            ("elim", elim.clone()),
+            ("one_pol_per_leg", one_pol_per_leg.into()),
+        ],
+        count,
+        totals: Vec::new(),
+        config: Some(&cfg),
+        predicted: predict_structure_count(&cfg, deg, ee),
+    };
 
-    let ts = generate_tensor_structures(&cfg, deg, ee);
-    println!(
-        "Tensor structures (n={}, deg={}, ee={}, elim=p{}, one_pol_per_leg={}) count={}",
-        n,
-        deg,
-        ee,
-        n,
-        true,
-        ts.len()
-    );
-    for (i, t) in ts.iter().enumerate() {
-        println!("  {}) {}", i + 1, t.to_string());
+    if count_only {
+        let count = count_tensor_structures(&cfg, deg, ee);
+        exit_on_io_error(output::print(
+            &mut *output_format.formatter(&display, &format_options),
+            &listing(count),
+            None,
+        ));
+        return;
+    }
+
+    let format = |t: &TensorStructure| {
+        if mandelstam {
+            t.to_mandelstam_string(&cfg)
+        } else if open_indices {
+            let terms: Vec<String> = strip_polarizations(t)
+                .iter()
+                .map(|(c, u)| {
+                    if c.to_string() == "1" {
+                        u.to_string()
+                    } else {
+                        format!("{} {}", c, u)
+                    }
+                })
+                .collect();
+            terms.join(" + ")
+        } else {
+            t.to_string()
+        }
+    };
+    let checkpointed = checkpoint_file.as_deref().map(|path| {
+        let every = Duration::from_secs(checkpoint_secs);
+        checkpoint::generate(&cfg, deg, ee, path, every, resume, format).unwrap_or_else(|e| {
+            eprintln!("--checkpoint: {}", e);
+            std::process::exit(1);
+        })
+    });
+    let spilled = spill_dir.map(|dir| {
+        generate_tensor_structures_spilled(&cfg, deg, ee, &SpillConfig::new(dir)).unwrap_or_else(
+            |e| {
+                eprintln!("--spill-dir: {}", e);
+                std::process::exit(1);
+            },
+        )
+    });
+    let limited = GenConfig {
+        max_structures: (max_count > 0).then_some(max_count),
+        ..cfg.clone()
+    };
+    let generated = if spilled.is_some() || checkpointed.is_some() {
+        Ok(Vec::new())
+    } else if progress {
+        generate_tensor_structures_with_progress(&limited, deg, ee, |p| {
+            eprint!(
+                "\rnodes={} found={} pruned={} bound_hits={}",
+                p.nodes, p.found, p.pruned, p.bound_hits
+            );
+            if p.finished {
+                eprintln!();
+            }
+        })
+    } else {
+        try_generate_tensor_structures(&limited, deg, ee)
+    };
+    let ts = generated.unwrap_or_else(|e| {
+        eprintln!("--max-count: {}; raise the limit or use --spill-dir", e);
+        std::process::exit(1);
+    });
+    let count = checkpointed
+        .or(spilled.as_ref().map(|s| s.len()))
+        .unwrap_or(ts.len() as u64);
+    let listing = listing(count);
+    let mut formatter = output_format.formatter(&format, &format_options);
+    match (&checkpoint_file, &spilled) {
+        (Some(path), _) => {
+            // The partial output already holds the rendered lines.
+            let read = checkpoint::read_partial(path).and_then(|lines| {
+                let mut w = io::stdout().lock();
+                formatter.begin(&mut w, &listing, true)?;
+                for (i, line) in lines.enumerate() {
+                    writeln!(w, "  {}) {}", i + 1, line?)?;
+                }
+                formatter.end(&mut w, &listing, true)
+            });
+            if let Err(e) = read {
+                eprintln!("--checkpoint: {}", e);
+                std::process::exit(1);
+            }
+            // The run is complete, so there is nothing left to resume.
+            let _ = std::fs::remove_file(checkpoint::partial_path(path));
+            let _ = std::fs::remove_file(path);
+        }
+        (None, Some(s)) => {
+            let read = s.iter().and_then(|mut structures| {
+                output::print(&mut *formatter, &listing, Some(&mut structures))
+            });
+            if let Err(e) = read {
+                eprintln!("--spill-dir: {}", e);
+                std::process::exit(1);
+            }
+        }
+        (None, None) => exit_on_io_error(output::print(
+            &mut *formatter,
+            &listing,
+            Some(&mut ts.iter().cloned().map(Ok)),
+        )),
+    }
+    // The analyses below only have a text rendering.
+    if output_format != OutputFormat::Text {
+        return;
+    }
+
+    if orbits {
+        let groups = group_by_permutation_orbit(&ts, n);
+        println!("\nOrbits under leg permutations: count={}", groups.len());
+        for (i, o) in groups.iter().enumerate() {
+            println!("  {}) {}  [size {}]", i + 1, o.representative, o.size);
+        }
+    }
+
+    if let Some(d) = dim {
+        match gram_reduce(&cfg, &ts, d) {
+            Ok(gr) => {
+                println!(
+                    "\nGram relations in d={}: kept={} eliminated={}",
+                    d,
+                    gr.kept.len(),
+                    gr.eliminated.len()
+                );
+                for (i, t) in gr.eliminated.iter().enumerate() {
+                    println!("  {}) {}", i + 1, t);
+                }
+            }
+            Err(e) => eprintln!("--dim: {}", e),
+        }
+    }
+
+    if find_relations {
+        match treeamps_core::find_relations(&cfg, &ts) {
+            Ok(relations) => {
+                println!("\nLinear relations: count={}", relations.len());
+                for (i, r) in relations.iter().enumerate() {
+                    println!("  {}) {} = 0", i + 1, r);
+                }
+            }
+            Err(e) => eprintln!("--find-relations: {}", e),
+        }
+    }
+
+    if ward {
+        let report = ward_filter(&cfg, &ts);
+        println!(
+            "\nWard identities: invariant={} violating={}",
+            report.invariant.len(),
+            report.violating.len()
+        );
+        for (i, t) in report.invariant.iter().enumerate() {
+            println!("  {}) {}", i + 1, t);
+        }
+    }
+
+    if gauge_invariant {
+        let catalog = Catalog::new(&cfg);
+        let all: Vec<_> = (0..=deg)
+            .flat_map(|k| generate_tensor_structures_with_catalog(&cfg, &catalog, deg, k))
+            .collect();
+        let combos = gauge_invariant_combinations(&cfg, &all);
+        println!(
+            "\nGauge-invariant combinations (deg={}, all ee, from {} structures): count={}",
+            deg,
+            all.len(),
+            combos.len()
+        );
+        for (i, c) in combos.iter().enumerate() {
+            println!("  {}) {}", i + 1, c);
+        }
+    }
+
+    if double_copy {
+        let squares = double_copy_candidates(&ts);
+        println!("\nDouble-copy candidates: count={}", squares.len());
+        for (i, t) in squares.iter().enumerate() {
+            println!("  {}) {}", i + 1, t);
+        }
+    }
+
+    if sectors {
+        println!("\nHelicity sectors (legs 1..k '-', the rest '+'):");
+        for k in 0..=n {
+            let sector = GenConfig {
+                helicities: (0..n)
+                    .map(|i| {
+                        if i < k {
+                            Helicity::Minus
+                        } else {
+                            Helicity::Plus
+                        }
+                    })
+                    .collect(),
+                ..cfg.clone()
+            };
+            let label = match k {
+                0 => " (self-dual)".to_string(),
+                _ if k == n => " (anti-self-dual)".to_string(),
+                _ => String::new(),
+            };
+            println!(
+                "  k={}{}: count={}",
+                k,
+                label,
+                generate_tensor_structures(&sector, deg, ee).len()
+            );
+        }
+    }
+
+    if let Some(max_traces) = color_traces {
+        let colors = trace_structures(n, max_traces);
+        println!(
+            "\nColor structures (trace basis, up to {} traces): count={}",
+            max_traces,
+            colors.len()
+        );
+        for (i, c) in colors.iter().enumerate() {
+            println!("  {}) {}", i + 1, c);
+        }
+        println!(
+            "Color-dressed terms: count={}",
+            color_dressed_basis(&colors, &ts).len()
+        );
+    }
+
+    if bcj {
+        let catalog = Catalog::new(&cfg);
+        let all: Vec<_> = (0..=deg)
+            .flat_map(|k| generate_tensor_structures_with_catalog(&cfg, &catalog, deg, k))
+            .collect();
+        let ansatz = bcj_ansatz(&cfg, &all);
+        println!(
+            "\nBCJ ansatz (deg={}, all ee): graphs={} masters={} parameters={} solutions={}",
+            deg,
+            ansatz.graphs.len(),
+            ansatz.masters.len(),
+            ansatz.parameters.len(),
+            ansatz.solutions.len()
+        );
+        for (i, solution) in ansatz.solutions.iter().enumerate() {
+            println!("  solution {}:", i + 1);
+            let numerators = ansatz.numerators(solution);
+            for m in &ansatz.masters {
+                let g = ansatz.graphs.iter().position(|g| g == m).unwrap_or(0);
+                println!("    n{} = {}", m, numerators[g]);
+            }
+        }
     }
 
-    // Canonical sanity checks for the 4-leg case, mirroring the C++ tool
-    if n == 4 {
-        // Mixed (EE)(PE)(PE) basis with one polarization per leg
+    // Canonical sanity checks for the massless 4-gluon case, mirroring the C++ tool
+    if n == 4
+        && massive.is_empty()
+        && scalars.is_empty()
+        && tensors.is_empty()
+        && !parity_odd
+        && fermions.is_empty()
+        && off_shell.is_empty()
+        && pols.is_empty()
+        && non_transverse.is_empty()
+        && forbidden.is_empty()
+        && helicities.is_empty()
+        && max_pp.is_none()
+        && loops == 0
+        && momentum_power.is_none()
+        && !cyclic
+    {
+        // Mixed (EE)(PE)(PE) basis with one polarization per leg. Each e_j
+        // pairs with 2 momenta once p4 and p1·e4 are eliminated, or 3 without.
         if deg == 3 && ee == 1 {
-            let expected_one_pol = 24i64;
+            let expected_one_pol = if cfg.eliminate_momentum { 24i64 } else { 54i64 };
             println!(
                 "\n[Sanity-one-pol-per-leg] expected count={}{}",
                 expected_one_pol,
@@ -125,3 +1045,393 @@ fn run_gen_ts(n: u8, mut deg: u32, mut ee: u32) {
         }
     }
 }
+
+fn run_gen_spinor(
+    hel: &str,
+    angle: u32,
+    square: u32,
+    schouten: bool,
+    format: OutputFormat,
+    format_options: &FormatOptions,
+) {
+    let mut weights = Vec::new();
+    for c in hel.chars() {
+        weights.push(match c {
+            '+' => 2,
+            '-' => -2,
+            '0' => 0,
+            _ => {
+                eprintln!("--hel accepts only '+', '-' and '0', got {:?}", c);
+                std::process::exit(1);
+            }
+        });
+    }
+    if weights.is_empty() || weights.len() > u8::MAX as usize {
+        eprintln!("--hel must list between 1 and 255 legs");
+        std::process::exit(1);
+    }
+
+    let cfg = SpinorConfig {
+        n_legs: weights.len() as u8,
+        helicity_weights: weights,
+    };
+    let mut ts = generate_spinor_structures(&cfg, angle, square);
+    if schouten {
+        // Non-crossing monomials are exactly those Schouten leaves unchanged.
+        ts.retain(|t| {
+            let e = TensorExpression::from(t.clone());
+            schouten_reduce(&e) == e
+        });
+    }
+    let listing = Listing {
+        title: "Spinor structures",
+        params: vec![
+            ("n", cfg.n_legs.into()),
+            ("hel", hel.into()),
+            ("angle", angle.into()),
+            ("square", square.into()),
+            ("schouten", schouten.into()),
+        ],
+        count: ts.len() as u64,
+        totals: Vec::new(),
+        config: None,
+        predicted: None,
+    };
+    let display = |t: &TensorStructure| t.to_string();
+    exit_on_io_error(output::print(
+        &mut *format.formatter(&display, format_options),
+        &listing,
+        Some(&mut ts.into_iter().map(Ok)),
+    ));
+}
+
+fn run_convert(file: &Path, format: OutputFormat, format_options: &FormatOptions) {
+    let saved = match input::read_structures(file) {
+        Ok(saved) => saved,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    let listing = Listing {
+        title: "Structures",
+        params: vec![
+            ("file", file.display().to_string().into()),
+            ("input", saved.format.name().into()),
+        ],
+        count: saved.structures.len() as u64,
+        totals: Vec::new(),
+        config: saved.config.as_ref(),
+        predicted: None,
+    };
+    let display = |t: &TensorStructure| t.to_string();
+    exit_on_io_error(output::print(
+        &mut *format.formatter(&display, format_options),
+        &listing,
+        Some(&mut saved.structures.into_iter().map(Ok)),
+    ));
+}
+
+fn exit_on_io_error(result: io::Result<()>) {
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+fn check_legs(flag: &str, legs: &[u8], n: u8) {
+    if legs.iter().any(|&k| k == 0 || k > n) {
+        eprintln!("{} legs must be between 1 and --n", flag);
+        std::process::exit(1);
+    }
+}
+
+fn run_solve(
+    n: u8,
+    deg: u32,
+    max_pp: Option<u32>,
+    symmetric: &[u8],
+    cyclic: bool,
+    no_ward: bool,
+    list: bool,
+) {
+    if n < 3 {
+        eprintln!("--n must be >= 3");
+        std::process::exit(1);
+    }
+    check_legs("--symmetric", symmetric, n);
+    let cfg = GenConfig {
+        n_legs: n,
+        max_pp,
+        ..GenConfig::default()
+    };
+
+    // Keep structures that are already in on-shell normal form, so the
+    // unknowns are independent and every free coefficient is physical.
+    let catalog = Catalog::new(&cfg);
+    let structures: Vec<_> = (0..=deg)
+        .flat_map(|k| generate_tensor_structures_with_catalog(&cfg, &catalog, deg, k))
+        .filter(|t| {
+            let e = TensorExpression::from(t.clone());
+            reduce_expression(&cfg, &e) == e
+        })
+        .collect();
+    let ansatz = Ansatz::new(&cfg, structures);
+    let mut system = LinearSystem {
+        n_unknowns: ansatz.len(),
+        ..LinearSystem::default()
+    };
+    if !no_ward {
+        system.extend(ansatz.ward_system(&cfg));
+    }
+    for pair in symmetric.windows(2) {
+        let swap = Permutation::transposition(n, LegIndex(pair[0]), LegIndex(pair[1]))
+            .expect("legs were checked against --n");
+        system.extend(ansatz.symmetry_system(&cfg, &swap));
+    }
+    if cyclic {
+        system.extend(ansatz.symmetry_system(&cfg, &Permutation::cyclic(n, 1)));
+    }
+    let solution = system
+        .solve()
+        .expect("homogeneous equations are consistent");
+
+    println!(
+        "Ansatz (n={}, deg={}, all ee) unknowns={} equations={} free={}",
+        n,
+        deg,
+        ansatz.len(),
+        system.equations.len(),
+        solution.free.len()
+    );
+    if list {
+        for (i, t) in ansatz.structures.iter().enumerate() {
+            println!("  c{} · {}", i + 1, t);
+        }
+        println!("\nSolved equations: count={}", solution.pivots.len());
+        for eq in &solution.pivots {
+            println!("  {}", eq);
+        }
+    }
+    println!("\nFree coefficients:");
+    for (f, values) in solution.free.iter().zip(solution.null_space()) {
+        println!("  c{}: {}", f + 1, ansatz.evaluate(&values));
+    }
+}
+
+fn run_point(n: u8, d: u32, seed: Option<u64>, helicities: Option<&str>, twistors: bool) {
+    if n < 3 {
+        eprintln!("--n must be >= 3");
+        std::process::exit(1);
+    }
+    let seed = seed.unwrap_or_else(random_seed);
+    if helicities.is_some() || twistors {
+        if d != 4 {
+            eprintln!("--helicities and --twistors need --d 4");
+            std::process::exit(1);
+        }
+        let spinors = if twistors {
+            let Some(z) = MomentumTwistors::sample(n, seed) else {
+                eprintln!("no momentum twistors found for n={} (needs n >= 4)", n);
+                std::process::exit(1);
+            };
+            println!("Momentum twistors (n={}) seed={}", n, seed);
+            for (i, zi) in z.z.iter().enumerate() {
+                println!("  Z{} = {}", i + 1, show(zi));
+            }
+            z.spinors()
+        } else {
+            SpinorPoint::sample(n, seed)
+        };
+        let point = match helicities {
+            Some(hel) => {
+                let hel = parse_helicities(hel, n);
+                spinors.and_then(|s| s.helicity_point(&hel))
+            }
+            None => spinors.map(|s| s.kinematic_point()),
+        };
+        let Some(point) = point else {
+            eprintln!("no spinor point found for n={} (needs n >= 4)", n);
+            std::process::exit(1);
+        };
+        println!("Spinor point (n={}) seed={}", n, seed);
+        for (i, (p, e)) in point.momenta.iter().zip(&point.polarizations).enumerate() {
+            println!("  p{} = {}", i + 1, show(p));
+            if helicities.is_some() {
+                println!("  e{} = {}", i + 1, show(e));
+            }
+        }
+        return;
+    }
+    let Some(point) = KinematicPoint::massless(n, d, seed) else {
+        eprintln!("no massless point found for n={} in d={}", n, d);
+        std::process::exit(1);
+    };
+    println!("Massless point (n={}, d={}) seed={}", n, d, seed);
+    for (i, (p, e)) in point.momenta.iter().zip(&point.polarizations).enumerate() {
+        println!("  p{} = {}", i + 1, show(p));
+        println!("  e{} = {}", i + 1, show(e));
+    }
+}
+
+fn show<T: std::fmt::Display>(v: &[T]) -> String {
+    let parts: Vec<String> = v.iter().map(|x| format!("{}", x)).collect();
+    format!("({})", parts.join(", "))
+}
+
+fn run_relations(n: u8, list: bool, ddm: bool, color_graphs: bool) {
+    if !(3..=8).contains(&n) {
+        eprintln!("--n must be between 3 and 8");
+        std::process::exit(1);
+    }
+    let kk = kk_relations(n);
+    let bcj = bcj_relations(n);
+    let all: Vec<_> = kk.iter().chain(&bcj).cloned().collect();
+    println!(
+        "Color orderings (n={}): count={}",
+        n,
+        color_orderings(n).len()
+    );
+    println!(
+        "KK relations: count={}  independent orderings={}",
+        kk.len(),
+        independent_orderings(n, &kk)
+    );
+    println!(
+        "BCJ relations: count={}  independent orderings with KK={}",
+        bcj.len(),
+        independent_orderings(n, &all)
+    );
+    if list {
+        for (i, r) in all.iter().enumerate() {
+            println!("  {}) {}", i + 1, r);
+        }
+    }
+    if ddm {
+        let basis = ddm_basis(n);
+        println!("\nDDM basis: count={}", basis.len());
+        for (i, h) in basis.iter().enumerate() {
+            let terms: Vec<String> = h
+                .to_trace_basis()
+                .iter()
+                .map(|(c, t)| format!("{:+} {}", c, t))
+                .collect();
+            println!("  {}) {} = {}", i + 1, h, terms.join(" "));
+        }
+    }
+    if color_graphs {
+        let graphs = cubic_graphs(n);
+        println!(
+            "\nColor graphs: count={}  independent under Jacobi={}",
+            graphs.len(),
+            color_graph_basis(n).len()
+        );
+        for (i, g) in graphs.iter().enumerate() {
+            let terms: Vec<String> = ddm_decomposition(g)
+                .iter()
+                .map(|(c, h)| format!("{:+} {}", c, h))
+                .collect();
+            println!("  {}) {} = {}", i + 1, g, terms.join(" "));
+        }
+    }
+}
+
+fn parse_helicities(s: &str, n: u8) -> Vec<Helicity> {
+    let out: Vec<Helicity> = s
+        .chars()
+        .map(|c| match c {
+            '+' => Helicity::Plus,
+            '-' => Helicity::Minus,
+            _ => {
+                eprintln!("--helicities accepts only '+' and '-', got {:?}", c);
+                std::process::exit(1);
+            }
+        })
+        .collect();
+    if out.len() != n as usize {
+        eprintln!("--helicities must list one helicity per leg");
+        std::process::exit(1);
+    }
+    out
+}
+
+fn run_topologies(n: u8, max_valence: usize, list: bool) {
+    if !(3..=8).contains(&n) {
+        eprintln!("--n must be between 3 and 8");
+        std::process::exit(1);
+    }
+    let topologies = tree_topologies(n, max_valence);
+    let graphs: usize = topologies.iter().map(|(_, k)| k).sum();
+    println!(
+        "Tree graphs (n={}, max valence={}): count={}  topologies={}",
+        n,
+        max_valence,
+        graphs,
+        topologies.len()
+    );
+    for (i, (g, k)) in topologies.iter().enumerate() {
+        println!(
+            "  {}) {}  valences={:?}  labelings={}",
+            i + 1,
+            g,
+            g.valences(),
+            k
+        );
+    }
+    if list {
+        println!();
+        for (i, g) in tree_graphs(n, max_valence).iter().enumerate() {
+            let poles: Vec<String> = g.poles().iter().map(|p| p.to_string()).collect();
+            println!("  {}) {}  poles: {}", i + 1, g, poles.join(" "));
+        }
+    }
+}
+
+fn run_feynman(n: u8, diagrams: bool) {
+    if !(3..=5).contains(&n) {
+        eprintln!("--n must be between 3 and 5");
+        std::process::exit(1);
+    }
+    let cfg = GenConfig {
+        n_legs: n,
+        ..GenConfig::default()
+    };
+    // Cubic diagrams carry n − 2 momenta, and each quartic vertex two fewer.
+    let mut basis = Vec::new();
+    for power in (0..=n as u32 - 2).rev().step_by(2) {
+        let sector = GenConfig {
+            momentum_power: Some(power),
+            ..cfg.clone()
+        };
+        for (_, structures) in generate_tensor_structures_range(&sector, 1..=n as u32 + power) {
+            basis.extend(structures.into_iter().filter(|t| {
+                let e = TensorExpression::from(t.clone());
+                reduce_expression(&cfg, &e) == e
+            }));
+        }
+    }
+    let all = feynman_diagrams(n);
+    let terms = match project_diagrams(&cfg, &all, &basis) {
+        Ok(terms) => terms,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    println!(
+        "Feynman diagrams (n={}): count={}  basis={}  terms={}",
+        n,
+        all.len(),
+        basis.len(),
+        terms.len()
+    );
+    if diagrams {
+        for (i, d) in all.iter().enumerate() {
+            println!("  {}) {}  N = {}", i + 1, d.graph, d.numerator);
+        }
+        println!();
+    }
+    for (c, r) in &terms {
+        println!("  {:+} {}", c, r);
+    }
+}
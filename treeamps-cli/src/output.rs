@@ -0,0 +1,458 @@
+//! Output formats shared by every subcommand that lists structures.
+//!
+//! A subcommand describes what it lists in a [`Listing`] and hands the
+//! structures to [`print`], which streams them through the [`Formatter`] for
+//! the `--format` the user chose.
+
+use std::io::{self, BufWriter, Write};
+
+use clap::{Args, ValueEnum};
+use serde_json::Value;
+use treeamps_core::{
+    FormNames, GenConfig, ScalarKind, TensorStructure, write_structure_header,
+    write_structure_record,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Numbered listing for reading
+    Text,
+    /// One JSON object with the configuration, counts and structures
+    Json,
+    /// One comma-separated row per structure, with its degree and EE and PP counts
+    Csv,
+    /// The same rows separated by tabs
+    Tsv,
+    /// A LaTeX align* or tabular environment, see --latex-env
+    Latex,
+    /// FORM declarations and local expressions T1, T2, ..., see --form-name
+    Form,
+    /// A Wolfram Language association from index to structure, see
+    /// --wolfram-style
+    Wolfram,
+    /// A compact binary structure file for reading back with `convert`
+    Binary,
+}
+
+/// Settings of individual formats, shared by the subcommands as flags.
+#[derive(Args, Clone, Debug)]
+pub struct FormatOptions {
+    /// Environment holding the structures with --format latex
+    #[arg(long, value_enum, default_value_t = LatexEnv::Align)]
+    pub latex_env: LatexEnv,
+
+    /// Rename a function or vector with --format form, e.g. dot=SP or
+    /// momentum=k; repeatable
+    #[arg(long = "form-name", value_name = "KIND=NAME", value_parser = parse_form_name)]
+    pub form_names: Vec<(String, String)>,
+
+    /// How --format wolfram writes each structure
+    #[arg(long, value_enum, default_value_t = WolframStyle::Dot)]
+    pub wolfram_style: WolframStyle,
+}
+
+impl FormatOptions {
+    fn form_names(&self) -> FormNames {
+        let mut names = FormNames::default();
+        for (kind, name) in &self.form_names {
+            // Checked by `parse_form_name` already.
+            let _ = names.set(kind, name);
+        }
+        names
+    }
+}
+
+/// Parses `KIND=NAME` for [`FormNames::set`].
+fn parse_form_name(s: &str) -> Result<(String, String), String> {
+    let (kind, name) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected KIND=NAME, got {:?}", s))?;
+    FormNames::default().set(kind, name)?;
+    Ok((kind.to_string(), name.to_string()))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum LatexEnv {
+    /// `align*`, one numbered line `T_{i} &= ...` per structure
+    Align,
+    /// `tabular`, one row `i & $...$` per structure
+    Table,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum WolframStyle {
+    /// A product `Dot[p[1], e[2]]*Dot[e[3], e[4]]`
+    Dot,
+    /// A list of factors `{{p[1], e[2]}, {e[3], e[4]}}`
+    List,
+}
+
+/// What a subcommand lists, apart from the structures themselves.
+pub struct Listing<'a> {
+    /// E.g. `Tensor structures`.
+    pub title: &'a str,
+    /// The parameters of the run, shown in parentheses after the title.
+    pub params: Vec<(&'static str, Value)>,
+    pub count: u64,
+    /// Further totals shown after the count.
+    pub totals: Vec<(&'static str, Value)>,
+    pub config: Option<&'a GenConfig>,
+    /// The count the Hilbert series predicts, where it applies.
+    pub predicted: Option<u64>,
+}
+
+impl Listing<'_> {
+    /// `title (k=v, ...) count=N k=v ...`, the header of the text listing.
+    fn summary(&self) -> String {
+        let pairs = |pairs: &[(&str, Value)], sep: &str| {
+            pairs
+                .iter()
+                .map(|(k, v)| format!("{}={}", k, plain(v)))
+                .collect::<Vec<_>>()
+                .join(sep)
+        };
+        let mut s = format!(
+            "{} ({}) count={}",
+            self.title,
+            pairs(&self.params, ", "),
+            self.count
+        );
+        if !self.totals.is_empty() {
+            s += " ";
+            s += &pairs(&self.totals, " ");
+        }
+        s
+    }
+}
+
+/// A value as the text formats show it: strings unquoted, `null` as `none`.
+fn plain(v: &Value) -> String {
+    match v {
+        Value::String(s) => s.clone(),
+        Value::Null => "none".to_string(),
+        v => v.to_string(),
+    }
+}
+
+/// Renders a listing in one output format.
+///
+/// `listed` is false when only the counts are printed, as with `--count-only`.
+pub trait Formatter {
+    fn begin(&mut self, w: &mut dyn Write, listing: &Listing, listed: bool) -> io::Result<()>;
+    /// Write the structure at 0-based `index`.
+    fn structure(&mut self, w: &mut dyn Write, index: usize, t: &TensorStructure)
+    -> io::Result<()>;
+    fn end(&mut self, w: &mut dyn Write, listing: &Listing, listed: bool) -> io::Result<()>;
+}
+
+impl OutputFormat {
+    /// The formatter for this format; the text listing shows each structure
+    /// as `render` returns it.
+    pub fn formatter<'a>(
+        self,
+        render: &'a dyn Fn(&TensorStructure) -> String,
+        options: &FormatOptions,
+    ) -> Box<dyn Formatter + 'a> {
+        match self {
+            Self::Text => Box::new(Text { render }),
+            Self::Json => Box::new(Json),
+            Self::Csv => Box::new(Table { sep: ',' }),
+            Self::Tsv => Box::new(Table { sep: '\t' }),
+            Self::Latex => Box::new(Latex {
+                env: options.latex_env,
+            }),
+            Self::Form => Box::new(Form {
+                names: options.form_names(),
+            }),
+            Self::Wolfram => Box::new(Wolfram {
+                style: options.wolfram_style,
+            }),
+            Self::Binary => Box::new(Binary),
+        }
+    }
+}
+
+/// Write `listing` to stdout through `formatter`, followed by `structures`
+/// unless they are `None`.
+pub fn print(
+    formatter: &mut dyn Formatter,
+    listing: &Listing,
+    structures: Option<&mut dyn Iterator<Item = io::Result<TensorStructure>>>,
+) -> io::Result<()> {
+    let mut w = BufWriter::new(io::stdout().lock());
+    let listed = structures.is_some();
+    formatter.begin(&mut w, listing, listed)?;
+    for (i, t) in structures.into_iter().flatten().enumerate() {
+        formatter.structure(&mut w, i, &t?)?;
+    }
+    formatter.end(&mut w, listing, listed)?;
+    w.flush()
+}
+
+/// The numbered listing, with the Hilbert-series check at the end.
+struct Text<'a> {
+    render: &'a dyn Fn(&TensorStructure) -> String,
+}
+
+impl Formatter for Text<'_> {
+    fn begin(&mut self, w: &mut dyn Write, listing: &Listing, _: bool) -> io::Result<()> {
+        writeln!(w, "{}", listing.summary())
+    }
+
+    fn structure(
+        &mut self,
+        w: &mut dyn Write,
+        index: usize,
+        t: &TensorStructure,
+    ) -> io::Result<()> {
+        writeln!(w, "  {}) {}", index + 1, (self.render)(t))
+    }
+
+    fn end(&mut self, w: &mut dyn Write, listing: &Listing, _: bool) -> io::Result<()> {
+        if let Some(predicted) = listing.predicted {
+            writeln!(
+                w,
+                "[Hilbert] predicted count={}{}",
+                predicted,
+                if predicted == listing.count {
+                    "  (OK)"
+                } else {
+                    "  (MISMATCH)"
+                }
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// One object holding the configuration, the parameters, the counts and the
+/// structures, one per line.
+struct Json;
+
+impl Formatter for Json {
+    fn begin(&mut self, w: &mut dyn Write, listing: &Listing, listed: bool) -> io::Result<()> {
+        write!(w, "{{\"config\":")?;
+        serde_json::to_writer(&mut *w, &listing.config)?;
+        for (k, v) in &listing.params {
+            write!(w, ",{}:{}", Value::from(*k), v)?;
+        }
+        write!(w, ",\"count\":{}", listing.count)?;
+        for (k, v) in &listing.totals {
+            write!(w, ",{}:{}", Value::from(*k), v)?;
+        }
+        write!(w, ",\"predicted\":")?;
+        serde_json::to_writer(&mut *w, &listing.predicted)?;
+        if listed {
+            write!(w, ",\"structures\":[")?;
+        }
+        Ok(())
+    }
+
+    fn structure(
+        &mut self,
+        w: &mut dyn Write,
+        index: usize,
+        t: &TensorStructure,
+    ) -> io::Result<()> {
+        w.write_all(if index == 0 { b"\n" } else { b",\n" })?;
+        serde_json::to_writer(&mut *w, t)?;
+        Ok(())
+    }
+
+    fn end(&mut self, w: &mut dyn Write, _: &Listing, listed: bool) -> io::Result<()> {
+        if listed {
+            write!(w, "\n]")?;
+        }
+        writeln!(w, "}}")
+    }
+}
+
+/// A header row and one row per structure with its degree, its EE and PP
+/// counts and its factors, separated by `sep`.
+struct Table {
+    sep: char,
+}
+
+impl Table {
+    /// Quote a CSV field if it needs it; TSV fields never hold tabs.
+    fn field(&self, s: &str) -> String {
+        if self.sep == ',' && s.contains([',', '"', '\n']) {
+            format!("\"{}\"", s.replace('"', "\"\""))
+        } else {
+            s.to_string()
+        }
+    }
+}
+
+impl Formatter for Table {
+    fn begin(&mut self, w: &mut dyn Write, _: &Listing, _: bool) -> io::Result<()> {
+        let header = ["index", "degree", "ee", "pp", "factors"];
+        writeln!(w, "{}", header.join(&self.sep.to_string()))
+    }
+
+    fn structure(
+        &mut self,
+        w: &mut dyn Write,
+        index: usize,
+        t: &TensorStructure,
+    ) -> io::Result<()> {
+        let count = |kind| t.factors.iter().filter(|f| f.kind == kind).count();
+        let row = [
+            (index + 1).to_string(),
+            t.degree().to_string(),
+            count(ScalarKind::EE).to_string(),
+            count(ScalarKind::PP).to_string(),
+            self.field(&t.to_string()),
+        ];
+        writeln!(w, "{}", row.join(&self.sep.to_string()))
+    }
+
+    fn end(&mut self, _: &mut dyn Write, _: &Listing, _: bool) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The structures in a LaTeX environment, after the summary as a comment.
+struct Latex {
+    env: LatexEnv,
+}
+
+impl Formatter for Latex {
+    fn begin(&mut self, w: &mut dyn Write, listing: &Listing, listed: bool) -> io::Result<()> {
+        writeln!(w, "% {}", listing.summary())?;
+        if listed {
+            match self.env {
+                LatexEnv::Align => writeln!(w, "\\begin{{align*}}")?,
+                LatexEnv::Table => writeln!(w, "\\begin{{tabular}}{{rl}}")?,
+            }
+        }
+        Ok(())
+    }
+
+    fn structure(
+        &mut self,
+        w: &mut dyn Write,
+        index: usize,
+        t: &TensorStructure,
+    ) -> io::Result<()> {
+        match self.env {
+            // A trailing `\\` would add an empty line to align*.
+            LatexEnv::Align => {
+                if index > 0 {
+                    writeln!(w, " \\\\")?;
+                }
+                write!(w, "  T_{{{}}} &= {}", index + 1, t.to_latex())
+            }
+            LatexEnv::Table => writeln!(w, "  {} & ${}$ \\\\", index + 1, t.to_latex()),
+        }
+    }
+
+    fn end(&mut self, w: &mut dyn Write, _: &Listing, listed: bool) -> io::Result<()> {
+        if !listed {
+            return Ok(());
+        }
+        match self.env {
+            LatexEnv::Align => {
+                writeln!(w)?;
+                writeln!(w, "\\end{{align*}}")
+            }
+            LatexEnv::Table => writeln!(w, "\\end{{tabular}}"),
+        }
+    }
+}
+
+/// FORM local expressions, one per structure, after the declarations they
+/// need.
+struct Form {
+    names: FormNames,
+}
+
+impl Formatter for Form {
+    fn begin(&mut self, w: &mut dyn Write, listing: &Listing, listed: bool) -> io::Result<()> {
+        writeln!(w, "* {}", listing.summary())?;
+        if listed {
+            let (legs, loops) = listing.config.map_or((0, 0), |c| (c.n_legs, c.n_loops));
+            write!(w, "{}", self.names.declarations(legs, loops))?;
+        }
+        Ok(())
+    }
+
+    fn structure(
+        &mut self,
+        w: &mut dyn Write,
+        index: usize,
+        t: &TensorStructure,
+    ) -> io::Result<()> {
+        writeln!(w, "L T{} = {};", index + 1, t.to_form(&self.names))
+    }
+
+    fn end(&mut self, _: &mut dyn Write, _: &Listing, _: bool) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// An association `<|1 -> ..., 2 -> ...|>` that `Get` returns, after the
+/// summary as a comment.
+struct Wolfram {
+    style: WolframStyle,
+}
+
+impl Formatter for Wolfram {
+    fn begin(&mut self, w: &mut dyn Write, listing: &Listing, listed: bool) -> io::Result<()> {
+        writeln!(w, "(* {} *)", listing.summary())?;
+        if listed {
+            write!(w, "<|")?;
+        }
+        Ok(())
+    }
+
+    fn structure(
+        &mut self,
+        w: &mut dyn Write,
+        index: usize,
+        t: &TensorStructure,
+    ) -> io::Result<()> {
+        let structure = match self.style {
+            WolframStyle::Dot => t.to_wolfram(),
+            WolframStyle::List => t.to_wolfram_list(),
+        };
+        w.write_all(if index == 0 { b"\n" } else { b",\n" })?;
+        write!(w, "  {} -> {}", index + 1, structure)
+    }
+
+    fn end(&mut self, w: &mut dyn Write, listing: &Listing, listed: bool) -> io::Result<()> {
+        if listed {
+            if listing.count > 0 {
+                writeln!(w)?;
+            }
+            writeln!(w, "|>")?;
+        }
+        Ok(())
+    }
+}
+
+/// The structure file that `treeamps_core::read_structure_file` reads, with
+/// no summary.
+struct Binary;
+
+impl Formatter for Binary {
+    fn begin(&mut self, mut w: &mut dyn Write, _: &Listing, listed: bool) -> io::Result<()> {
+        if listed {
+            write_structure_header(&mut w)?;
+        }
+        Ok(())
+    }
+
+    fn structure(
+        &mut self,
+        mut w: &mut dyn Write,
+        _: usize,
+        t: &TensorStructure,
+    ) -> io::Result<()> {
+        write_structure_record(&mut w, t)
+    }
+
+    fn end(&mut self, _: &mut dyn Write, _: &Listing, _: bool) -> io::Result<()> {
+        Ok(())
+    }
+}
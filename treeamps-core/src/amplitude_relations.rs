@@ -0,0 +1,248 @@
+use std::{collections::BTreeMap, fmt};
+
+use crate::{
+    dot_product::ScalarFactor,
+    expression::TensorExpression,
+    generator::GenConfig,
+    gram::{Echelon, P, add, from_rational, mul},
+    rational::int,
+    reduction::reduce_expression,
+    rng::SplitMix64,
+    symmetry::for_each_permutation,
+    tensor_structure::TensorStructure,
+    types::{LegIndex, Spin},
+};
+
+/// Cyclic ordering of the legs of a color-ordered amplitude `A(σ)`.
+///
+/// Stored rotated so that leg 1 comes first; partial amplitudes are cyclic.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct ColorOrdering(pub Vec<LegIndex>);
+
+impl ColorOrdering {
+    pub fn new(mut legs: Vec<LegIndex>) -> Self {
+        if let Some(k) = legs.iter().position(|&l| l == LegIndex(1)) {
+            legs.rotate_left(k);
+        }
+        Self(legs)
+    }
+}
+
+impl fmt::Display for ColorOrdering {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let legs: Vec<String> = self.0.iter().map(|l| l.0.to_string()).collect();
+        write!(f, "A({})", legs.join(","))
+    }
+}
+
+/// All `(n-1)!` cyclic orderings of legs `1..=n`, each starting with leg 1.
+pub fn color_orderings(n_legs: u8) -> Vec<ColorOrdering> {
+    let mut out = Vec::new();
+    for_each_permutation(n_legs.saturating_sub(1) as usize, |perm, _| {
+        let mut legs = vec![LegIndex(1)];
+        legs.extend(perm.iter().map(|&k| LegIndex(k as u8 + 2)));
+        out.push(ColorOrdering(legs));
+    });
+    out.sort();
+    out
+}
+
+/// A linear relation `Σ c_k A(σ_k) = 0` among color-ordered amplitudes.
+///
+/// Coefficients are polynomials in the momentum dot products `p_i·p_j`; the
+/// constant `1` is the empty structure.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AmplitudeRelation {
+    pub terms: Vec<(TensorExpression, ColorOrdering)>,
+}
+
+impl AmplitudeRelation {
+    /// Merge terms with equal orderings and drop vanishing ones.
+    fn collect(&mut self) {
+        self.terms.sort_by(|x, y| x.1.cmp(&y.1));
+        let mut merged: Vec<(TensorExpression, ColorOrdering)> = Vec::new();
+        for (c, o) in self.terms.drain(..) {
+            match merged.last_mut() {
+                Some((acc, last)) if *last == o => {
+                    acc.terms.extend(c.terms);
+                    acc.collect();
+                }
+                _ => merged.push((c, o)),
+            }
+        }
+        merged.retain(|(c, _)| !c.is_zero());
+        self.terms = merged;
+    }
+}
+
+impl fmt::Display for AmplitudeRelation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let terms: Vec<String> = self
+            .terms
+            .iter()
+            .map(|(c, o)| match c.terms.as_slice() {
+                [(k, t)] if t.degree() == 0 && *k == int(1) => o.to_string(),
+                [(k, t)] if t.degree() == 0 && *k == int(-1) => format!("-{}", o),
+                _ => format!("({}) {}", c, o),
+            })
+            .collect();
+        write!(f, "{} = 0", terms.join(" + "))
+    }
+}
+
+fn constant(c: i64) -> TensorExpression {
+    let mut e = TensorExpression::new();
+    e.push(int(c), TensorStructure::new());
+    e
+}
+
+/// Kleiss–Kuijf relations, one per ordering not ending in leg `n`.
+///
+/// `A(1, α, n, β) = (-1)^{|β|} Σ_{σ ∈ α ⧢ βᵀ} A(1, σ, n)`, which expresses
+/// every ordering through the `(n-2)!` with legs 1 and `n` adjacent. Reflection
+/// and photon decoupling identities follow from these.
+pub fn kk_relations(n_legs: u8) -> Vec<AmplitudeRelation> {
+    let n = LegIndex(n_legs);
+    let mut out = Vec::new();
+    for ordering in color_orderings(n_legs) {
+        let k = ordering
+            .0
+            .iter()
+            .position(|&l| l == n)
+            .expect("leg n is present");
+        if k + 1 == ordering.0.len() {
+            continue;
+        }
+        let alpha = &ordering.0[1..k];
+        let beta_t: Vec<LegIndex> = ordering.0[k + 1..].iter().rev().copied().collect();
+        let sign = if beta_t.len().is_multiple_of(2) {
+            -1
+        } else {
+            1
+        };
+        let mut relation = AmplitudeRelation {
+            terms: vec![(constant(1), ordering.clone())],
+        };
+        for sigma in shuffles(alpha, &beta_t) {
+            let mut legs = vec![LegIndex(1)];
+            legs.extend(sigma);
+            legs.push(n);
+            relation.terms.push((constant(sign), ColorOrdering(legs)));
+        }
+        relation.collect();
+        out.push(relation);
+    }
+    out
+}
+
+/// All interleavings of `a` and `b` that keep the order within each.
+fn shuffles(a: &[LegIndex], b: &[LegIndex]) -> Vec<Vec<LegIndex>> {
+    match (a.split_first(), b.split_first()) {
+        (None, _) => vec![b.to_vec()],
+        (_, None) => vec![a.to_vec()],
+        (Some((&x, rest_a)), Some((&y, rest_b))) => {
+            let mut out = Vec::new();
+            for mut s in shuffles(rest_a, b) {
+                s.insert(0, x);
+                out.push(s);
+            }
+            for mut s in shuffles(a, rest_b) {
+                s.insert(0, y);
+                out.push(s);
+            }
+            out
+        }
+    }
+}
+
+/// Fundamental BCJ relations under every relabeling of the legs.
+///
+/// `Σ_{i=3}^{n} (Σ_{j=3}^{i} s_{2j}) A(1, 3, …, i, 2, i+1, …, n) = 0` with
+/// `s_{2j} = 2 p_2·p_j` for massless legs. Together with [`kk_relations`]
+/// they leave `(n-3)!` independent orderings. Coefficients are not reduced
+/// with momentum conservation, so duplicates that only agree on shell are kept.
+pub fn bcj_relations(n_legs: u8) -> Vec<AmplitudeRelation> {
+    let mut base = Vec::new();
+    for i in 3..=n_legs {
+        let mut coefficient = TensorExpression::new();
+        for j in 3..=i {
+            coefficient.push(int(2), pp(LegIndex(2), LegIndex(j)));
+        }
+        coefficient.collect();
+        let mut legs = vec![LegIndex(1)];
+        legs.extend((3..=i).map(LegIndex));
+        legs.push(LegIndex(2));
+        legs.extend((i + 1..=n_legs).map(LegIndex));
+        base.push((coefficient, legs));
+    }
+
+    let mut out: Vec<AmplitudeRelation> = Vec::new();
+    for_each_permutation(n_legs as usize, |perm, _| {
+        let sigma = |l: LegIndex| LegIndex(perm[l.0 as usize - 1] as u8 + 1);
+        let mut relation = AmplitudeRelation::default();
+        for (c, legs) in &base {
+            let mut image = TensorExpression::new();
+            for (k, t) in &c.terms {
+                let (u, _) = t.relabel(&sigma);
+                image.push(k.clone(), u);
+            }
+            image.collect();
+            let legs = legs.iter().map(|&l| sigma(l)).collect();
+            relation.terms.push((image, ColorOrdering::new(legs)));
+        }
+        relation.collect();
+        if !relation.terms.is_empty() && !out.contains(&relation) {
+            out.push(relation);
+        }
+    });
+    out
+}
+
+fn pp(i: LegIndex, j: LegIndex) -> TensorStructure {
+    let mut t = TensorStructure::new();
+    t.factors.push(ScalarFactor::pp(i, j));
+    t
+}
+
+/// Number of orderings left independent by `relations`, for massless legs.
+///
+/// The coefficients are reduced with momentum conservation and evaluated at a
+/// random point, exactly modulo a 61-bit prime, so the count is that of
+/// generic kinematics. Expect `(n-2)!` for [`kk_relations`] alone and
+/// `(n-3)!` once [`bcj_relations`] are added.
+pub fn independent_orderings(n_legs: u8, relations: &[AmplitudeRelation]) -> usize {
+    let cfg = GenConfig {
+        n_legs,
+        spins: vec![Spin::Scalar; n_legs as usize],
+        ..Default::default()
+    };
+    let orderings = color_orderings(n_legs);
+    let column: BTreeMap<&ColorOrdering, usize> =
+        orderings.iter().enumerate().map(|(i, o)| (o, i)).collect();
+
+    // After reduction the remaining dot products are independent, so each
+    // gets its own random value.
+    let mut rng = SplitMix64::new(0x6263_6a5f_6b6b_2121);
+    let mut values: BTreeMap<ScalarFactor, u64> = BTreeMap::new();
+    let mut echelon = Echelon::default();
+    for relation in relations {
+        let mut row = vec![0; orderings.len()];
+        for (c, o) in &relation.terms {
+            let mut v = 0;
+            for (k, t) in reduce_expression(&cfg, c).terms {
+                let mut term = from_rational(&k);
+                for f in &t.factors {
+                    let x = *values
+                        .entry(f.clone())
+                        .or_insert_with(|| rng.next_u64() % P);
+                    term = mul(term, x);
+                }
+                v = add(v, term);
+            }
+            let k = column[o];
+            row[k] = add(row[k], v);
+        }
+        echelon.insert(row);
+    }
+    orderings.len() - echelon.rank()
+}
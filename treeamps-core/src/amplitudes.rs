@@ -0,0 +1,420 @@
+//! Reference values of tree amplitudes, for fitting and validating ansätze.
+//!
+//! Amplitudes are color-ordered, with all momenta incoming, and stripped of
+//! the coupling and of the overall factor `i`. BCFW recursion works in four
+//! dimensions at a [`SpinorPoint`], whose brackets are rational; Berends–Giele
+//! recursion works with explicit vectors at a [`KinematicPoint`] in any
+//! dimension and over any [`Coefficient`] field.
+
+use num_rational::BigRational;
+use num_traits::{One, Zero};
+
+use crate::{
+    coefficient::Coefficient,
+    dot_product::ScalarFactor,
+    kinematics::{KinematicPoint, minkowski},
+    rational::int,
+    spinor_kinematics::SpinorPoint,
+    types::{Helicity, LegIndex},
+};
+
+/// An external or internal gluon: spinors `λ`, `λ̃` and helicity.
+#[derive(Clone, Debug)]
+struct Gluon {
+    lambda: [BigRational; 2],
+    lambda_tilde: [BigRational; 2],
+    helicity: Helicity,
+}
+
+/// Color-ordered tree amplitude `A(1, …, n)` of gluons by BCFW recursion.
+///
+/// `helicities` lists one helicity per leg of `point`. Each step uses a
+/// `[i, j⟩` shift of a minus-helicity leg `i` and its left neighbour `j`,
+/// which never has the bad helicities `(+, −)`, and
+/// the three-point amplitudes are
+/// `A(1⁻, 2⁻, 3⁺) = ⟨12⟩³ / (⟨23⟩⟨31⟩)` and
+/// `A(1⁺, 2⁺, 3⁻) = −[12]³ / ([23][31])`, so MHV amplitudes come out as
+/// [`parke_taylor`]. Fails on a mismatched helicity list, or if an external
+/// bracket `⟨ij⟩` or `[ij]`, a pole, a shift or a three-point denominator
+/// happens to vanish at this point; channels the shift leaves without a pole
+/// are skipped.
+pub fn bcfw_gluon_amplitude(
+    point: &SpinorPoint,
+    helicities: &[Helicity],
+) -> Result<BigRational, String> {
+    if helicities.len() != point.n_legs() {
+        return Err(format!(
+            "{} helicities for {} legs",
+            helicities.len(),
+            point.n_legs()
+        ));
+    }
+    // Three legs always have one kind of bracket vanishing; `three_point`
+    // tells that apart from a degenerate point.
+    let n = point.n_legs() as u8;
+    if n > 3 {
+        for i in 1..=n {
+            for j in i + 1..=n {
+                let (a, b) = (LegIndex(i), LegIndex(j));
+                if point.angle(a, b).is_zero() || point.square(a, b).is_zero() {
+                    return Err(format!(
+                        "{} or {} vanishes at this point",
+                        ScalarFactor::angle(a, b),
+                        ScalarFactor::square(a, b)
+                    ));
+                }
+            }
+        }
+    }
+    let legs: Vec<Gluon> = point
+        .lambda
+        .iter()
+        .zip(&point.lambda_tilde)
+        .zip(helicities)
+        .map(|((l, t), &h)| Gluon {
+            lambda: l.clone(),
+            lambda_tilde: t.clone(),
+            helicity: h,
+        })
+        .collect();
+    recurse(&legs)
+}
+
+/// Parke–Taylor formula for MHV and anti-MHV amplitudes, `None` for other
+/// helicity configurations or when an adjacent bracket vanishes.
+///
+/// `A = ⟨ab⟩⁴ / (⟨12⟩⟨23⟩⋯⟨n1⟩)` for negative-helicity legs `a`, `b`, and
+/// `(−1)^n [ab]⁴ / ([12][23]⋯[n1])` for positive-helicity ones.
+pub fn parke_taylor(point: &SpinorPoint, helicities: &[Helicity]) -> Option<BigRational> {
+    let n = point.n_legs();
+    if helicities.len() != n {
+        return None;
+    }
+    let legs = |h: Helicity| -> Vec<LegIndex> {
+        (1..=n as u8)
+            .filter(|&i| helicities[i as usize - 1] == h)
+            .map(LegIndex)
+            .collect()
+    };
+    let (minus, plus) = (legs(Helicity::Minus), legs(Helicity::Plus));
+    let ring = |bracket: &dyn Fn(LegIndex, LegIndex) -> BigRational| -> BigRational {
+        (1..=n as u8)
+            .map(|i| bracket(LegIndex(i), LegIndex(i % n as u8 + 1)))
+            .product()
+    };
+    let pow4 = |x: BigRational| x.clone() * &x * &x * &x;
+    let (num, den) = match (minus.len(), plus.len()) {
+        (2, _) => (
+            pow4(point.angle(minus[0], minus[1])),
+            ring(&|i, j| point.angle(i, j)),
+        ),
+        (_, 2) => {
+            let sign = if n.is_multiple_of(2) { int(1) } else { int(-1) };
+            (
+                sign * pow4(point.square(plus[0], plus[1])),
+                ring(&|i, j| point.square(i, j)),
+            )
+        }
+        _ => return None,
+    };
+    (!den.is_zero()).then(|| num / den)
+}
+
+/// Color-ordered Berends–Giele current `J^μ(legs)` of gluons at `x`.
+///
+/// `J(i) = e_i`, and the current of two or more legs is the sum over splits
+/// into consecutive currents joined by the color-ordered three- and
+/// four-gluon vertices of Feynman gauge, times the propagator `1 / P²`. The
+/// vertices are normalized as
+///
+/// `V₃(J₁, J₂) = (J₁·J₂)(P₁ − P₂) + 2 (P₂·J₁) J₂ − 2 (P₁·J₂) J₁`,
+/// `V₄(J₁, J₂, J₃) = 2 (J₁·J₃) J₂ − (J₁·J₂) J₃ − (J₂·J₃) J₁`,
+///
+/// dropping the factors `i/√2` and `i/2` of the Lagrangian; each tree has
+/// `n − 2` powers of `1/√2` in total. Returns `None` for no legs or if a
+/// propagator is on shell.
+pub fn berends_giele_current<C: Coefficient>(
+    x: &KinematicPoint<C>,
+    legs: &[LegIndex],
+) -> Option<Vec<C>> {
+    let table = currents(x, legs)?;
+    if legs.len() == 1 {
+        return Some(table[0][0].clone());
+    }
+    let p = total(x, legs);
+    let p2 = minkowski(&p, &p);
+    if p2.is_zero() {
+        return None;
+    }
+    let mut j = vertices(x, legs, &table, 0, legs.len() - 1);
+    for c in &mut j {
+        *c /= p2.clone();
+    }
+    Some(j)
+}
+
+/// Color-ordered amplitude `A(1, …, n)` of gluons at `x` by Berends–Giele
+/// recursion, `e_n · V(1, …, n − 1)` with the vertex sum `V` of the current of
+/// the first `n − 1` legs, whose propagator is amputated.
+///
+/// Restoring the vertex factors dropped in [`berends_giele_current`] gives
+/// `i (1/√2)^{n−2}` times this value. With the exact polarizations of
+/// [`SpinorPoint::polarization`], which are `1/√2` times the usual ones, at
+/// [`SpinorPoint::helicity_point`] it is therefore half of
+/// [`bcfw_gluon_amplitude`].
+/// Returns `None` for fewer than three legs or if a propagator is on shell.
+pub fn berends_giele_amplitude<C: Coefficient>(x: &KinematicPoint<C>) -> Option<C> {
+    let n = x.momenta.len();
+    if n < 3 {
+        return None;
+    }
+    let legs: Vec<LegIndex> = (1..n as u8).map(LegIndex).collect();
+    let table = currents(x, &legs)?;
+    let v = vertices(x, &legs, &table, 0, n - 2);
+    Some(minkowski(&v, &x.polarizations[n - 1]))
+}
+
+/// `table[a][k]` is the current of `legs[a..=a + k]`, for all proper
+/// sub-ranges.
+fn currents<C: Coefficient>(x: &KinematicPoint<C>, legs: &[LegIndex]) -> Option<Vec<Vec<Vec<C>>>> {
+    if legs.is_empty() {
+        return None;
+    }
+    let m = legs.len();
+    let mut table: Vec<Vec<Vec<C>>> = legs
+        .iter()
+        .map(|l| vec![x.polarizations[l.0 as usize - 1].clone()])
+        .collect();
+    for k in 1..m - 1 {
+        for a in 0..m - k {
+            let p = total(x, &legs[a..=a + k]);
+            let p2 = minkowski(&p, &p);
+            if p2.is_zero() {
+                return None;
+            }
+            let mut j = vertices(x, legs, &table, a, a + k);
+            for c in &mut j {
+                *c /= p2.clone();
+            }
+            table[a].push(j);
+        }
+    }
+    Some(table)
+}
+
+/// Sum of vertices joining the currents of consecutive splits of
+/// `legs[a..=b]`, with `b > a`.
+fn vertices<C: Coefficient>(
+    x: &KinematicPoint<C>,
+    legs: &[LegIndex],
+    table: &[Vec<Vec<C>>],
+    a: usize,
+    b: usize,
+) -> Vec<C> {
+    let current = |i: usize, j: usize| &table[i][j - i];
+    let two = C::one() + C::one();
+    let mut out = vec![C::zero(); x.dimension()];
+    for k in a..b {
+        let (j1, j2) = (current(a, k), current(k + 1, b));
+        let (p1, p2) = (total(x, &legs[a..=k]), total(x, &legs[k + 1..=b]));
+        let j12 = minkowski(j1, j2);
+        let (p2j1, p1j2) = (minkowski(&p2, j1), minkowski(&p1, j2));
+        for mu in 0..out.len() {
+            out[mu] += j12.clone() * (p1[mu].clone() - p2[mu].clone())
+                + two.clone() * (p2j1.clone() * j2[mu].clone() - p1j2.clone() * j1[mu].clone());
+        }
+    }
+    for k in a..b {
+        for l in k + 1..b {
+            let (j1, j2, j3) = (current(a, k), current(k + 1, l), current(l + 1, b));
+            let (j13, j12, j23) = (minkowski(j1, j3), minkowski(j1, j2), minkowski(j2, j3));
+            for mu in 0..out.len() {
+                out[mu] += two.clone() * j13.clone() * j2[mu].clone()
+                    - j12.clone() * j3[mu].clone()
+                    - j23.clone() * j1[mu].clone();
+            }
+        }
+    }
+    out
+}
+
+/// `Σ_{i ∈ legs} p_i`.
+fn total<C: Coefficient>(x: &KinematicPoint<C>, legs: &[LegIndex]) -> Vec<C> {
+    let mut out = vec![C::zero(); x.dimension()];
+    for l in legs {
+        for (o, c) in out.iter_mut().zip(&x.momenta[l.0 as usize - 1]) {
+            *o += c.clone();
+        }
+    }
+    out
+}
+
+fn recurse(legs: &[Gluon]) -> Result<BigRational, String> {
+    let n = legs.len();
+    let minus = legs
+        .iter()
+        .filter(|g| g.helicity == Helicity::Minus)
+        .count();
+    if n == 3 {
+        return three_point(legs);
+    }
+    if minus < 2 || n - minus < 2 {
+        return Ok(BigRational::zero());
+    }
+    // Shift a minus leg i and its left neighbour j, rotated to the ends.
+    let i = (0..n)
+        .find(|&k| legs[k].helicity == Helicity::Minus)
+        .expect("at least two minus legs");
+    let legs: Vec<Gluon> = (0..n).map(|k| legs[(i + k) % n].clone()).collect();
+    let q = outer(&legs[0].lambda, &legs[n - 1].lambda_tilde);
+
+    let mut total = BigRational::zero();
+    let mut p = momentum(&legs[0]);
+    for k in 1..=n - 3 {
+        p = add(&p, &momentum(&legs[k]));
+        let p2 = dot(&p, &p);
+        let qp = dot(&q, &p);
+        if p2.is_zero() {
+            return Err("degenerate BCFW channel at this point".into());
+        }
+        // With `q·P = 0` the shifted `P̂² = P²` never vanishes: no pole here.
+        if qp.is_zero() {
+            continue;
+        }
+        let z = -p2.clone() / (int(2) * qp);
+        let hat = add(&p, &scale(&q, &z));
+        let (l, t) = factor(&hat).ok_or("vanishing internal momentum")?;
+
+        let mut left: Vec<Gluon> = legs[..=k].to_vec();
+        for (x, y) in left[0]
+            .lambda_tilde
+            .iter_mut()
+            .zip(&legs[n - 1].lambda_tilde)
+        {
+            *x += &z * y;
+        }
+        let mut right: Vec<Gluon> = legs[k + 1..].to_vec();
+        let last = right.len() - 1;
+        for (x, y) in right[last].lambda.iter_mut().zip(&legs[0].lambda) {
+            *x -= &z * y;
+        }
+        for h in [Helicity::Plus, Helicity::Minus] {
+            let mut l_side = left.clone();
+            l_side.push(Gluon {
+                lambda: l.clone(),
+                lambda_tilde: [-t[0].clone(), -t[1].clone()],
+                helicity: flip(h),
+            });
+            let mut r_side = vec![Gluon {
+                lambda: l.clone(),
+                lambda_tilde: t.clone(),
+                helicity: h,
+            }];
+            r_side.extend(right.iter().cloned());
+            let a = recurse(&l_side)?;
+            if a.is_zero() {
+                continue;
+            }
+            total += a * recurse(&r_side)? / &p2;
+        }
+    }
+    Ok(total)
+}
+
+/// Three-point amplitude, zero on the other kind of three-point kinematics,
+/// where all of its brackets vanish.
+///
+/// Fails if a denominator vanishes otherwise, i.e. when both kinds of
+/// brackets vanish or only some of them do.
+fn three_point(legs: &[Gluon]) -> Result<BigRational, String> {
+    let minus: Vec<usize> = (0..3)
+        .filter(|&k| legs[k].helicity == Helicity::Minus)
+        .collect();
+    let angle = |a: usize, b: usize| det(&legs[a].lambda, &legs[b].lambda);
+    let square = |a: usize, b: usize| det(&legs[b].lambda_tilde, &legs[a].lambda_tilde);
+    let vanish = |bracket: &dyn Fn(usize, usize) -> BigRational| {
+        (0..3).all(|k| bracket(k, (k + 1) % 3).is_zero())
+    };
+    let cube = |x: BigRational| x.clone() * &x * &x;
+    let degenerate = || Err("degenerate three-point kinematics at this point".to_string());
+    match minus.len() {
+        2 => {
+            if vanish(&angle) && !vanish(&square) {
+                return Ok(BigRational::zero());
+            }
+            let c = 3 - minus[0] - minus[1];
+            let (a, b) = ((c + 1) % 3, (c + 2) % 3);
+            let den = angle(b, c) * angle(c, a);
+            if den.is_zero() {
+                return degenerate();
+            }
+            // ⟨ab⟩⁴ / (⟨ab⟩⟨bc⟩⟨ca⟩) in cyclic order starting at a.
+            Ok(cube(angle(a, b)) / den)
+        }
+        1 => {
+            if vanish(&square) && !vanish(&angle) {
+                return Ok(BigRational::zero());
+            }
+            let c = minus[0];
+            let (a, b) = ((c + 1) % 3, (c + 2) % 3);
+            let den = square(b, c) * square(c, a);
+            if den.is_zero() {
+                return degenerate();
+            }
+            Ok(-cube(square(a, b)) / den)
+        }
+        _ => Ok(BigRational::zero()),
+    }
+}
+
+fn flip(h: Helicity) -> Helicity {
+    match h {
+        Helicity::Plus => Helicity::Minus,
+        Helicity::Minus => Helicity::Plus,
+    }
+}
+
+type Bispinor = [[BigRational; 2]; 2];
+
+fn outer(l: &[BigRational; 2], t: &[BigRational; 2]) -> Bispinor {
+    [
+        [&l[0] * &t[0], &l[0] * &t[1]],
+        [&l[1] * &t[0], &l[1] * &t[1]],
+    ]
+}
+
+fn momentum(g: &Gluon) -> Bispinor {
+    outer(&g.lambda, &g.lambda_tilde)
+}
+
+fn add(a: &Bispinor, b: &Bispinor) -> Bispinor {
+    [
+        [&a[0][0] + &b[0][0], &a[0][1] + &b[0][1]],
+        [&a[1][0] + &b[1][0], &a[1][1] + &b[1][1]],
+    ]
+}
+
+fn scale(a: &Bispinor, z: &BigRational) -> Bispinor {
+    [[&a[0][0] * z, &a[0][1] * z], [&a[1][0] * z, &a[1][1] * z]]
+}
+
+/// `a·b`, from `2 a·b = det(a + b) − det a − det b`.
+fn dot(a: &Bispinor, b: &Bispinor) -> BigRational {
+    (&a[0][0] * &b[1][1] + &a[1][1] * &b[0][0] - &a[0][1] * &b[1][0] - &a[1][0] * &b[0][1]) / int(2)
+}
+
+/// Spinors with `λ λ̃ = m` for a null bispinor, from a nonzero entry.
+fn factor(m: &Bispinor) -> Option<([BigRational; 2], [BigRational; 2])> {
+    let (a, b) = (0..2)
+        .flat_map(|a| (0..2).map(move |b| (a, b)))
+        .find(|&(a, b)| !m[a][b].is_zero())?;
+    let l = [m[0][b].clone(), m[1][b].clone()];
+    let t = [&m[a][0] / &m[a][b], &m[a][1] / &m[a][b]];
+    debug_assert!(t[b].is_one());
+    Some((l, t))
+}
+
+/// `a¹ b² − a² b¹`.
+fn det(a: &[BigRational; 2], b: &[BigRational; 2]) -> BigRational {
+    &a[0] * &b[1] - &a[1] * &b[0]
+}
@@ -0,0 +1,415 @@
+use std::{collections::BTreeMap, fmt};
+
+use num_rational::BigRational;
+use num_traits::{One, Zero};
+
+use nalgebra::{DMatrix, DVector};
+
+use crate::{
+    coefficient::Coefficient,
+    expression::TensorExpression,
+    generator::GenConfig,
+    kinematics::KinematicPoint,
+    linalg::{rank, row_reduce},
+    permutation::Relabeling,
+    rational::rational_to_f64,
+    reduction::reduce_expression,
+    rng::SplitMix64,
+    tensor_structure::TensorStructure,
+    ward::{gauge_legs, ward_variation},
+};
+
+/// An ansatz `Σ c_i T_i` with one unknown coefficient `c_i` per structure.
+///
+/// Each structure is reduced on shell once, so the ansatz can be compared
+/// against any expression by collecting the independent structures.
+#[derive(Clone, Debug)]
+pub struct Ansatz {
+    pub structures: Vec<TensorStructure>,
+    reduced: Vec<TensorExpression>,
+}
+
+/// One linear equation `Σ a_i c_i = b` in the unknowns of an [`Ansatz`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LinearEquation {
+    /// `(unknown, a_i)` pairs with nonzero `a_i`, sorted by unknown.
+    pub coefficients: Vec<(usize, BigRational)>,
+    pub rhs: BigRational,
+}
+
+/// The equations obtained by matching an ansatz against an expression.
+#[derive(Clone, Debug, Default)]
+pub struct LinearSystem {
+    pub n_unknowns: usize,
+    /// One equation per independent structure, labeled by that structure.
+    pub equations: Vec<(TensorStructure, LinearEquation)>,
+}
+
+/// General solution of a [`LinearSystem`].
+#[derive(Clone, Debug, Default)]
+pub struct Solution {
+    /// Unknowns left undetermined, in increasing order.
+    pub free: Vec<usize>,
+    /// One equation per remaining unknown, in reduced row echelon form: the
+    /// first coefficient is that unknown's, equal to 1, and the others belong
+    /// to free unknowns.
+    pub pivots: Vec<LinearEquation>,
+}
+
+impl Solution {
+    /// One solution, with every free unknown set to 0.
+    pub fn particular(&self) -> Vec<BigRational> {
+        let mut values = vec![BigRational::zero(); self.free.len() + self.pivots.len()];
+        for eq in &self.pivots {
+            values[eq.coefficients[0].0] = eq.rhs.clone();
+        }
+        values
+    }
+
+    /// One solution of the homogeneous system per free unknown, which is set
+    /// to 1 with the other free unknowns 0.
+    pub fn null_space(&self) -> Vec<Vec<BigRational>> {
+        let n = self.free.len() + self.pivots.len();
+        self.free
+            .iter()
+            .map(|&f| {
+                let mut values = vec![BigRational::zero(); n];
+                values[f] = BigRational::one();
+                for eq in &self.pivots {
+                    let (p, _) = eq.coefficients[0];
+                    if let Some((_, a)) = eq.coefficients.iter().find(|(i, _)| *i == f) {
+                        values[p] = -a;
+                    }
+                }
+                values
+            })
+            .collect()
+    }
+}
+
+impl Ansatz {
+    pub fn new(cfg: &GenConfig, structures: Vec<TensorStructure>) -> Self {
+        let reduced = structures
+            .iter()
+            .map(|t| reduce_expression(cfg, &TensorExpression::from(t.clone())))
+            .collect();
+        Self {
+            structures,
+            reduced,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.structures.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.structures.is_empty()
+    }
+
+    /// The ansatz at `c_i = values[i]`, reduced on shell.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `values` does not have one entry per structure.
+    pub fn evaluate(&self, values: &[BigRational]) -> TensorExpression {
+        assert_eq!(values.len(), self.len(), "one value per unknown");
+        let mut out = TensorExpression::new();
+        for (r, c) in self.reduced.iter().zip(values) {
+            if !c.is_zero() {
+                out.terms
+                    .extend(r.terms.iter().map(|(d, t)| (c * d, t.clone())));
+            }
+        }
+        out.collect();
+        out
+    }
+
+    /// The coefficient of each independent structure as a linear form in the unknowns.
+    pub fn collect(&self) -> Vec<(TensorStructure, Vec<(usize, BigRational)>)> {
+        let mut by_structure: BTreeMap<TensorStructure, Vec<(usize, BigRational)>> =
+            BTreeMap::new();
+        for (i, r) in self.reduced.iter().enumerate() {
+            for (c, t) in &r.terms {
+                by_structure
+                    .entry(t.clone())
+                    .or_default()
+                    .push((i, c.clone()));
+            }
+        }
+        by_structure.into_iter().collect()
+    }
+
+    /// Equations for `Σ c_i T_i = target` on shell, one per independent structure.
+    ///
+    /// Matching against the zero expression gives the homogeneous system whose
+    /// solutions are the on-shell relations among the structures.
+    pub fn linear_system(&self, cfg: &GenConfig, target: &TensorExpression) -> LinearSystem {
+        let mut rows: BTreeMap<TensorStructure, LinearEquation> = self
+            .collect()
+            .into_iter()
+            .map(|(t, coefficients)| {
+                let eq = LinearEquation {
+                    coefficients,
+                    rhs: BigRational::zero(),
+                };
+                (t, eq)
+            })
+            .collect();
+        for (c, t) in reduce_expression(cfg, target).terms {
+            rows.entry(t).or_default().rhs = c;
+        }
+        LinearSystem {
+            n_unknowns: self.len(),
+            equations: rows.into_iter().collect(),
+        }
+    }
+
+    /// Equations for the Ward identity `e_i → p_i` on every gauge leg.
+    ///
+    /// Each leg contributes one equation per independent structure in the
+    /// on-shell variation of the ansatz.
+    pub fn ward_system(&self, cfg: &GenConfig) -> LinearSystem {
+        let mut system = LinearSystem {
+            n_unknowns: self.len(),
+            ..LinearSystem::default()
+        };
+        for leg in gauge_legs(cfg) {
+            let images = self.structures.iter().map(|t| ward_variation(t, leg));
+            system.extend(self.homogeneous_system(cfg, images));
+        }
+        system
+    }
+
+    /// Equations for invariance of the ansatz under relabeling the legs by `sigma`.
+    ///
+    /// Signs from reordering ε slots or spinor brackets are kept, so fermion
+    /// exchanges are not antisymmetrized; pass the permutation of bosonic
+    /// legs only, or combine with a sign by hand.
+    pub fn symmetry_system(&self, cfg: &GenConfig, sigma: &impl Relabeling) -> LinearSystem {
+        let images = self.structures.iter().map(|t| {
+            let (image, sign) = t.relabel(sigma);
+            let mut e = TensorExpression::new();
+            e.push(BigRational::from_integer(sign.into()), image);
+            e.push(-BigRational::one(), t.clone());
+            e
+        });
+        self.homogeneous_system(cfg, images)
+    }
+
+    /// Fit the coefficients to a target amplitude given at `samples` random
+    /// points in `d` dimensions.
+    ///
+    /// The structures are evaluated exactly at each point and the equations
+    /// `Σ c_i T_i(x) = target(x)` solved. The fit is unique only with enough
+    /// samples and structures independent in `d` dimensions; otherwise free
+    /// unknowns remain in the solution. Fails if no kinematics exist, some
+    /// structure cannot be evaluated (see [`KinematicPoint::evaluate`]), or the
+    /// target is not in the span of the ansatz.
+    pub fn fit(
+        &self,
+        cfg: &GenConfig,
+        d: u32,
+        samples: usize,
+        mut target: impl FnMut(&KinematicPoint) -> BigRational,
+    ) -> Result<Solution, String> {
+        let mut rows = Vec::with_capacity(samples);
+        for x in self.sample(cfg, d, samples)? {
+            let mut row = self.values_at(&x)?;
+            row.push(target(&x));
+            rows.push(row);
+        }
+        solve_rows(rows, self.len()).ok_or_else(|| "target is not in the span of the ansatz".into())
+    }
+
+    /// Least-squares fit of the coefficients to a floating-point target.
+    ///
+    /// As [`fit`](Self::fit), but solved by singular value decomposition for
+    /// the minimal-norm coefficients, so underdetermined fits pick one
+    /// solution. Fails if the relative residual exceeds `1e-8`;
+    /// [`approximate_rational`](crate::approximate_rational) can recover
+    /// exact values from the result.
+    pub fn fit_f64(
+        &self,
+        cfg: &GenConfig,
+        d: u32,
+        samples: usize,
+        mut target: impl FnMut(&KinematicPoint) -> f64,
+    ) -> Result<Vec<f64>, String> {
+        let mut a = DMatrix::zeros(samples, self.len());
+        let mut b = DVector::zeros(samples);
+        for (k, x) in self.sample(cfg, d, samples)?.iter().enumerate() {
+            for (i, v) in self.values_at(x)?.iter().enumerate() {
+                a[(k, i)] = rational_to_f64(v);
+            }
+            b[k] = target(x);
+        }
+        let c = a.clone().svd(true, true).solve(&b, 1e-12)?;
+        let residual = (&a * &c - &b).norm() / b.norm().max(f64::MIN_POSITIVE);
+        if residual > 1e-8 {
+            return Err(format!(
+                "target is not in the span of the ansatz (relative residual {:e})",
+                residual
+            ));
+        }
+        Ok(c.iter().copied().collect())
+    }
+
+    fn sample(
+        &self,
+        cfg: &GenConfig,
+        d: u32,
+        samples: usize,
+    ) -> Result<Vec<KinematicPoint>, String> {
+        let mut rng = SplitMix64::new(0x6669_745f_7365_6564);
+        (0..samples)
+            .map(|_| KinematicPoint::random(cfg, d, &mut rng))
+            .collect::<Option<_>>()
+            .ok_or_else(|| format!("no kinematics found in {} dimensions", d))
+    }
+
+    fn values_at(&self, x: &KinematicPoint) -> Result<Vec<BigRational>, String> {
+        self.structures
+            .iter()
+            .map(|t| {
+                x.evaluate(t)
+                    .ok_or_else(|| format!("cannot evaluate {} numerically", t))
+            })
+            .collect()
+    }
+
+    /// Equations for `Σ c_i images[i] = 0` on shell, one per independent structure.
+    fn homogeneous_system(
+        &self,
+        cfg: &GenConfig,
+        images: impl Iterator<Item = TensorExpression>,
+    ) -> LinearSystem {
+        let mut rows: BTreeMap<TensorStructure, LinearEquation> = BTreeMap::new();
+        for (i, image) in images.enumerate() {
+            for (c, t) in reduce_expression(cfg, &image).terms {
+                rows.entry(t).or_default().coefficients.push((i, c));
+            }
+        }
+        LinearSystem {
+            n_unknowns: self.len(),
+            equations: rows.into_iter().collect(),
+        }
+    }
+}
+
+impl LinearSystem {
+    /// Rank of the coefficient matrix over the field `C`, or `None` if some
+    /// coefficient has no image there.
+    ///
+    /// Over a prime field the rank can only come out lower than over the
+    /// rationals, when the prime divides every nonzero maximal minor; for
+    /// 61-bit primes that is vanishingly rare and the elimination stays cheap.
+    pub fn rank<C: Coefficient>(&self) -> Option<usize> {
+        let mut matrix = Vec::with_capacity(self.equations.len());
+        for (_, eq) in &self.equations {
+            let mut row = vec![C::zero(); self.n_unknowns];
+            for (i, a) in &eq.coefficients {
+                row[*i] = C::from_rational(a)?;
+            }
+            matrix.push(row);
+        }
+        Some(rank(matrix, self.n_unknowns))
+    }
+
+    /// Append the equations of `other`, which must have the same unknowns.
+    pub fn extend(&mut self, other: LinearSystem) {
+        assert_eq!(self.n_unknowns, other.n_unknowns, "same unknowns");
+        self.equations.extend(other.equations);
+    }
+
+    /// The general solution by exact Gaussian elimination, or `None` if the
+    /// equations are inconsistent.
+    pub fn solve(&self) -> Option<Solution> {
+        let n = self.n_unknowns;
+        let rows = self
+            .equations
+            .iter()
+            .map(|(_, eq)| {
+                let mut row = vec![BigRational::zero(); n + 1];
+                for (i, a) in &eq.coefficients {
+                    row[*i] += a;
+                }
+                row[n] = eq.rhs.clone();
+                row
+            })
+            .collect();
+        solve_rows(rows, n)
+    }
+
+    /// Whether `values` solves every equation.
+    pub fn is_satisfied(&self, values: &[BigRational]) -> bool {
+        self.equations.iter().all(|(_, eq)| {
+            let lhs: BigRational = eq.coefficients.iter().map(|(i, a)| a * &values[*i]).sum();
+            lhs == eq.rhs
+        })
+    }
+}
+
+/// Solve the augmented rows `[a_1 … a_n | b]` by exact Gaussian elimination.
+fn solve_rows(mut matrix: Vec<Vec<BigRational>>, n: usize) -> Option<Solution> {
+    let pivots = row_reduce(&mut matrix, n + 1);
+    if pivots.last() == Some(&n) {
+        return None;
+    }
+    let free = (0..n).filter(|i| !pivots.contains(i)).collect();
+    let pivots = matrix
+        .into_iter()
+        .take(pivots.len())
+        .map(|row| LinearEquation {
+            coefficients: row[..n]
+                .iter()
+                .enumerate()
+                .filter(|(_, a)| !a.is_zero())
+                .map(|(i, a)| (i, a.clone()))
+                .collect(),
+            rhs: row[n].clone(),
+        })
+        .collect();
+    Some(Solution { free, pivots })
+}
+
+impl fmt::Display for LinearEquation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.coefficients.is_empty() {
+            write!(f, "0")?;
+        }
+        for (k, (i, a)) in self.coefficients.iter().enumerate() {
+            let (sign, mag) = if *a < BigRational::zero() {
+                ("-", -a)
+            } else {
+                ("+", a.clone())
+            };
+            match (k, sign) {
+                (0, "-") => write!(f, "-")?,
+                (0, _) => {}
+                _ => write!(f, " {} ", sign)?,
+            }
+            if mag.is_one() {
+                write!(f, "c{}", i + 1)?;
+            } else {
+                write!(f, "{} c{}", mag, i + 1)?;
+            }
+        }
+        write!(f, " = {}", self.rhs)
+    }
+}
+
+impl fmt::Display for Ansatz {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "0");
+        }
+        let terms: Vec<String> = self
+            .structures
+            .iter()
+            .enumerate()
+            .map(|(i, t)| format!("c{} · {}", i + 1, t))
+            .collect();
+        write!(f, "{}", terms.join(" + "))
+    }
+}
@@ -0,0 +1,197 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::Zero;
+
+use crate::{
+    cubic_graph::{CubicGraph, cubic_graphs},
+    expression::TensorExpression,
+    generator::GenConfig,
+    linalg::SparseMatrix,
+    operator_basis::Echelon,
+    rational::int,
+    reduction::reduce_expression,
+    symmetry::for_each_permutation,
+    tensor_structure::TensorStructure,
+    types::LegIndex,
+};
+
+/// A BCJ numerator ansatz with the kinematic Jacobi identities imposed.
+///
+/// Each master graph gets one free coefficient per independent ansatz
+/// element; every other graph's numerator is the relabeled numerator of its
+/// master, so crossing symmetry holds by construction. Automorphisms of the
+/// masters and the Jacobi identity through every propagator become linear
+/// constraints on the coefficients, and `solutions` spans what is left.
+#[derive(Clone, Debug)]
+pub struct BcjAnsatz {
+    /// All cubic graphs, in the order of [`cubic_graphs`].
+    pub graphs: Vec<CubicGraph>,
+    /// One graph per relabeling orbit; the numerators of the others follow.
+    pub masters: Vec<CubicGraph>,
+    /// Free coefficients, as `(index into masters, ansatz element)`.
+    pub parameters: Vec<(usize, TensorStructure)>,
+    /// Basis of coefficient vectors over `parameters` solving every constraint,
+    /// the primitive integer vectors of [`SparseMatrix::null_space`] unchanged.
+    pub solutions: Vec<Vec<BigInt>>,
+    /// `images[g][p]`: what parameter `p` contributes to the numerator of graph `g`.
+    images: Vec<Vec<TensorExpression>>,
+}
+
+impl BcjAnsatz {
+    /// Numerators of all graphs for one coefficient vector over `parameters`,
+    /// reduced on shell and in the order of `graphs`.
+    pub fn numerators(&self, solution: &[BigInt]) -> Vec<TensorExpression> {
+        self.images
+            .iter()
+            .map(|row| {
+                let mut out = TensorExpression::new();
+                for (e, c) in row.iter().zip(solution) {
+                    if !c.is_zero() {
+                        out += &(e.clone() * BigRational::from_integer(c.clone()));
+                    }
+                }
+                out
+            })
+            .collect()
+    }
+}
+
+/// Build the BCJ numerator ansatz for `cfg.n_legs` external legs.
+///
+/// Each element of `basis` is reduced on shell and kept if independent of the
+/// earlier ones; pass structures of the numerator's mass dimension (for
+/// Yang–Mills at tree level, `n - 2` momenta and all `ee` counts). Solutions
+/// are exact, scaled to coprime integers with a positive leading entry.
+///
+/// # Panics
+///
+/// Panics if a coefficient of a solution does not fit in an `i64`.
+pub fn bcj_ansatz(cfg: &GenConfig, basis: &[TensorStructure]) -> BcjAnsatz {
+    let n = cfg.n_legs;
+    let graphs = cubic_graphs(n);
+
+    let mut independent = Echelon::default();
+    let mut elements: Vec<(TensorStructure, TensorExpression)> = Vec::new();
+    for t in basis {
+        let r = reduce_expression(cfg, &TensorExpression::from(t.clone()));
+        if independent.insert(&r) {
+            elements.push((t.clone(), r));
+        }
+    }
+
+    let position: BTreeMap<&CubicGraph, usize> =
+        graphs.iter().enumerate().map(|(i, g)| (g, i)).collect();
+    // For each graph: its master and the relabeling (with sign) reaching it.
+    let mut assigned: Vec<Option<(usize, i8, Vec<LegIndex>)>> = vec![None; graphs.len()];
+    let mut masters = Vec::new();
+    let mut automorphisms: Vec<(usize, i8, Vec<LegIndex>)> = Vec::new();
+    for (g, graph) in graphs.iter().enumerate() {
+        if assigned[g].is_some() {
+            continue;
+        }
+        let m = masters.len();
+        masters.push(graph.clone());
+        for_each_permutation(n as usize, |perm, _| {
+            let image: Vec<LegIndex> = perm.iter().map(|&k| LegIndex(k as u8 + 1)).collect();
+            let sigma = |l: LegIndex| image[l.0 as usize - 1];
+            let (h, s) = graph.relabel(&sigma);
+            let k = position[&h];
+            if k == g && perm.iter().enumerate().any(|(i, &k)| i != k) {
+                automorphisms.push((m, s, image.clone()));
+            }
+            if assigned[k].is_none() {
+                assigned[k] = Some((m, s, image));
+            }
+        });
+    }
+
+    let parameters: Vec<(usize, TensorStructure)> = (0..masters.len())
+        .flat_map(|m| elements.iter().map(move |(t, _)| (m, t.clone())))
+        .collect();
+    let n_el = elements.len();
+    let relabeled = |e: &TensorExpression, s: i8, image: &[LegIndex]| {
+        let sigma = |l: LegIndex| image[l.0 as usize - 1];
+        let mut out = TensorExpression::new();
+        for (c, t) in &e.terms {
+            let (u, sign) = t.relabel(&sigma);
+            if sign != 0 {
+                out.push(c * int((s * sign) as i64), u);
+            }
+        }
+        reduce_expression(cfg, &out)
+    };
+
+    // Each constraint is a list of (parameter, contribution) pairs summing to zero.
+    let mut constraints: Vec<Vec<(usize, TensorExpression)>> = Vec::new();
+    for (m, s, image) in &automorphisms {
+        constraints.push(
+            elements
+                .iter()
+                .enumerate()
+                .map(|(p, (_, r))| (m * n_el + p, relabeled(r, *s, image) - r))
+                .collect(),
+        );
+    }
+
+    let images: Vec<Vec<TensorExpression>> = assigned
+        .iter()
+        .map(|a| {
+            let (m, s, image) = a.as_ref().expect("every graph lies in some orbit");
+            (0..parameters.len())
+                .map(|p| {
+                    if p / n_el == *m {
+                        relabeled(&elements[p % n_el].1, *s, image)
+                    } else {
+                        TensorExpression::new()
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut seen: BTreeSet<Vec<(usize, i8)>> = BTreeSet::new();
+    for graph in &graphs {
+        for triple in graph.jacobi_triples() {
+            let mut key: Vec<(usize, i8)> = triple.iter().map(|(h, s)| (position[h], *s)).collect();
+            key.sort();
+            if key[0].1 < 0 {
+                for (_, s) in &mut key {
+                    *s = -*s;
+                }
+            }
+            if !seen.insert(key.clone()) {
+                continue;
+            }
+            constraints.push(
+                (0..parameters.len())
+                    .map(|p| {
+                        let mut e = TensorExpression::new();
+                        for &(h, s) in &key {
+                            e += &(images[h][p].clone() * int(s as i64));
+                        }
+                        (p, e)
+                    })
+                    .collect(),
+            );
+        }
+    }
+
+    let matrix = SparseMatrix::from_expressions(
+        parameters.len(),
+        constraints
+            .iter()
+            .enumerate()
+            .flat_map(|(k, constraint)| constraint.iter().map(move |(p, e)| (k, *p, e))),
+    );
+    let solutions = matrix.null_space();
+
+    BcjAnsatz {
+        graphs,
+        masters,
+        parameters,
+        solutions,
+        images,
+    }
+}
@@ -0,0 +1,70 @@
+use std::{fmt, str::FromStr};
+
+use crate::types::{LegIndex, Slot};
+
+/// A fermion bilinear `ū_i γ·v u_j` with `v` a momentum or polarization.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BilinearFactor {
+    /// Leg of the barred spinor `ū_i`.
+    pub bar: LegIndex,
+    /// Vector contracted with the gamma matrix.
+    pub gamma: Slot,
+    /// Leg of the unbarred spinor `u_j`.
+    pub u: LegIndex,
+}
+
+impl BilinearFactor {
+    pub fn new(bar: LegIndex, gamma: Slot, u: LegIndex) -> Self {
+        Self { bar, gamma, u }
+    }
+
+    /// Apply the leg relabeling `sigma` to both spinors and the contracted vector.
+    pub fn relabel(&self, sigma: &impl Fn(LegIndex) -> LegIndex) -> Self {
+        Self {
+            bar: sigma(self.bar),
+            gamma: self.gamma.relabel(sigma),
+            u: sigma(self.u),
+        }
+    }
+
+    /// LaTeX form, e.g. `(\bar u_1\,\gamma\cdot p_2\,u_3)`.
+    pub fn to_latex(&self) -> String {
+        format!(
+            "(\\bar u_{}\\,\\gamma\\cdot {}\\,u_{})",
+            self.bar.latex_subscript(),
+            self.gamma.to_latex(),
+            self.u.latex_subscript()
+        )
+    }
+}
+
+/// Parses the `Display` form `(ū1 γ·p2 u3)`.
+impl FromStr for BilinearFactor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s
+            .trim()
+            .trim_start_matches('(')
+            .trim_end_matches(')')
+            .split_whitespace()
+            .collect();
+        let parsed = match parts[..] {
+            [bar, gamma, u] => bar
+                .strip_prefix('ū')
+                .zip(gamma.strip_prefix("γ·"))
+                .zip(u.strip_prefix('u')),
+            _ => None,
+        };
+        let ((bar, gamma), u) =
+            parsed.ok_or_else(|| format!("expected a bilinear like (ū1 γ·p2 u3), got {:?}", s))?;
+        Ok(Self::new(bar.parse()?, gamma.parse()?, u.parse()?))
+    }
+}
+
+impl fmt::Display for BilinearFactor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(ū{} γ·{} u{})", self.bar.0, self.gamma, self.u.0)
+    }
+}
@@ -0,0 +1,112 @@
+use std::{ops::Range, sync::Arc};
+
+use crate::{
+    dot_product::ScalarFactor,
+    generator::{GenConfig, factor_catalog},
+    types::{LegIndex, ScalarKind},
+};
+
+/// The dot-product alphabet of a [`GenConfig`], sorted and indexed once.
+///
+/// Building the catalog is the part of every generation call that does not
+/// depend on `(deg, ee)`; sweeps over many of them can build it once and pass
+/// it to [`generate_tensor_structures_with_catalog`]. Cloning is cheap, as
+/// the factors are shared.
+///
+/// [`generate_tensor_structures_with_catalog`]: crate::generator::generate_tensor_structures_with_catalog
+#[derive(Clone, Debug)]
+pub struct Catalog {
+    factors: Arc<[ScalarFactor]>,
+    /// The entries of each kind, indexed by `ScalarKind as usize`.
+    by_kind: [Range<usize>; 8],
+    /// Indices of the entries involving each external leg (slot 0 unused).
+    by_leg: Vec<Vec<usize>>,
+}
+
+impl Catalog {
+    /// The catalog implied by `cfg`, as [`factor_catalog`] lists it.
+    pub fn new(cfg: &GenConfig) -> Self {
+        Self::from_factors(cfg, &factor_catalog(cfg))
+    }
+
+    /// A catalog of explicit factors for use with `cfg`.
+    ///
+    /// Duplicates are dropped, as are factors that refer to legs beyond
+    /// `cfg.n_legs` or loop momenta beyond `cfg.n_loops`.
+    pub fn from_factors(cfg: &GenConfig, factors: &[ScalarFactor]) -> Self {
+        let in_range = |l: LegIndex| l.0 >= 1 && l.0 <= cfg.n_legs;
+        let in_loops = |l: LegIndex| l.0 >= 1 && l.0 <= cfg.n_loops;
+        let mut factors: Vec<ScalarFactor> = factors
+            .iter()
+            .filter(|f| match f.kind {
+                ScalarKind::LP | ScalarKind::LE => in_loops(f.a) && in_range(f.b),
+                ScalarKind::LL => in_loops(f.a) && in_loops(f.b),
+                _ => in_range(f.a) && in_range(f.b),
+            })
+            .cloned()
+            .collect();
+        factors.sort();
+        factors.dedup();
+
+        let by_kind = ScalarKind::ALL.map(|kind| {
+            let start = factors.partition_point(|f| f.kind < kind);
+            let end = factors.partition_point(|f| f.kind <= kind);
+            start..end
+        });
+        let mut by_leg = vec![Vec::new(); cfg.n_legs as usize + 1];
+        for (i, f) in factors.iter().enumerate() {
+            let legs = match f.kind {
+                ScalarKind::LP | ScalarKind::LE => [None, Some(f.b)],
+                ScalarKind::LL => [None, None],
+                _ => [Some(f.a), (f.b != f.a).then_some(f.b)],
+            };
+            for l in legs.into_iter().flatten() {
+                by_leg[l.0 as usize].push(i);
+            }
+        }
+
+        Self {
+            factors: factors.into(),
+            by_kind,
+            by_leg,
+        }
+    }
+
+    /// All factors, in ascending order.
+    pub fn factors(&self) -> &[ScalarFactor] {
+        &self.factors
+    }
+
+    pub(crate) fn shared(&self) -> Arc<[ScalarFactor]> {
+        self.factors.clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.factors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.factors.is_empty()
+    }
+
+    /// Position of `f` in [`factors`](Self::factors).
+    pub fn index_of(&self, f: &ScalarFactor) -> Option<usize> {
+        self.factors.binary_search(f).ok()
+    }
+
+    /// The indices of the factors of one kind, which are contiguous.
+    pub fn kind_range(&self, kind: ScalarKind) -> Range<usize> {
+        self.by_kind[kind as usize].clone()
+    }
+
+    /// The factors of one kind, e.g. every PP factor.
+    pub fn of_kind(&self, kind: ScalarKind) -> &[ScalarFactor] {
+        &self.factors[self.kind_range(kind)]
+    }
+
+    /// Indices of the factors that involve the momentum or polarization of
+    /// `leg`, in ascending order; empty for legs outside the configuration.
+    pub fn involving(&self, leg: LegIndex) -> &[usize] {
+        self.by_leg.get(leg.0 as usize).map_or(&[], Vec::as_slice)
+    }
+}
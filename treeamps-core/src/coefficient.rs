@@ -0,0 +1,72 @@
+use std::{
+    fmt,
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+
+use num_complex::Complex;
+use num_rational::BigRational;
+use num_traits::{One, Signed, Zero};
+
+/// A field of expression coefficients.
+///
+/// Implemented by the exact rationals [`BigRational`] and by the prime fields
+/// [`Fp`](crate::finite_field::Fp), so expressions and linear systems can be
+/// carried over either: rationals for exact answers, `F_p` for fast ranks and
+/// multi-prime reconstruction.
+pub trait Coefficient:
+    Clone
+    + fmt::Debug
+    + fmt::Display
+    + PartialEq
+    + Zero
+    + One
+    + Neg<Output = Self>
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + DivAssign
+{
+    /// Image of an exact rational, or `None` if its denominator is not invertible.
+    fn from_rational(c: &BigRational) -> Option<Self>;
+
+    /// An element chosen by 64 random bits, for sampling kinematic points.
+    fn from_random_bits(bits: u64) -> Self;
+
+    /// Whether `self` prints with a leading minus sign. Fields without an
+    /// order print every element as it is.
+    fn is_negative(&self) -> bool {
+        false
+    }
+}
+
+impl Coefficient for BigRational {
+    fn from_rational(c: &BigRational) -> Option<Self> {
+        Some(c.clone())
+    }
+
+    /// A small nonzero integer in `−9..=9`, keeping exact arithmetic cheap.
+    fn from_random_bits(bits: u64) -> Self {
+        let k = (bits % 18) as i64 - 9;
+        BigRational::from_integer((if k >= 0 { k + 1 } else { k }).into())
+    }
+
+    fn is_negative(&self) -> bool {
+        Signed::is_negative(self)
+    }
+}
+
+/// Gaussian rationals `a + i b`, for complex kinematics such as BCFW shifts.
+impl Coefficient for Complex<BigRational> {
+    fn from_rational(c: &BigRational) -> Option<Self> {
+        Some(Complex::new(c.clone(), BigRational::zero()))
+    }
+
+    /// A real small integer, as for [`BigRational`].
+    fn from_random_bits(bits: u64) -> Self {
+        Complex::new(BigRational::from_random_bits(bits), BigRational::zero())
+    }
+}
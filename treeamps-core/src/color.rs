@@ -0,0 +1,280 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::{
+    amplitude_relations::color_orderings,
+    cubic_graph::{CubicGraph, CubicTree, cubic_graphs},
+    gram::{Echelon, P},
+    tensor_structure::TensorStructure,
+    types::LegIndex,
+};
+
+/// A trace `Tr(T^{a_1} … T^{a_k})` of fundamental generators, one per leg.
+///
+/// Stored rotated so that its smallest leg comes first; traces are cyclic.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Trace(pub Vec<LegIndex>);
+
+impl Trace {
+    pub fn new(mut legs: Vec<LegIndex>) -> Self {
+        if let Some(k) = legs
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, l)| **l)
+            .map(|(k, _)| k)
+        {
+            legs.rotate_left(k);
+        }
+        Self(legs)
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let generators: Vec<String> = self.0.iter().map(|l| format!("T^a{}", l.0)).collect();
+        write!(f, "Tr({})", generators.join(" "))
+    }
+}
+
+/// A product of traces covering every leg once, in the trace basis.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct ColorStructure {
+    /// Sorted, so equal products compare equal.
+    pub traces: Vec<Trace>,
+}
+
+impl ColorStructure {
+    pub fn new(traces: Vec<Trace>) -> Self {
+        let mut traces = traces;
+        traces.sort();
+        Self { traces }
+    }
+
+    /// Apply the leg relabeling `sigma`; traces carry no sign.
+    pub fn relabel(&self, sigma: &impl Fn(LegIndex) -> LegIndex) -> Self {
+        Self::new(
+            self.traces
+                .iter()
+                .map(|t| Trace::new(t.0.iter().map(|&l| sigma(l)).collect()))
+                .collect(),
+        )
+    }
+}
+
+impl fmt::Display for ColorStructure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let traces: Vec<String> = self.traces.iter().map(|t| t.to_string()).collect();
+        write!(f, "{}", traces.join(" "))
+    }
+}
+
+/// The `(n-1)!` single traces `Tr(T^{a_1} T^{a_σ(2)} … T^{a_σ(n)})`.
+pub fn single_trace_structures(n_legs: u8) -> Vec<ColorStructure> {
+    color_orderings(n_legs)
+        .into_iter()
+        .map(|o| ColorStructure::new(vec![Trace(o.0)]))
+        .collect()
+}
+
+/// All products of at most `max_traces` traces over legs `1..=n`, sorted.
+///
+/// Each trace has at least two generators, since `Tr(T^a) = 0` for `SU(N)`;
+/// `max_traces = 1` gives [`single_trace_structures`].
+pub fn trace_structures(n_legs: u8, max_traces: usize) -> Vec<ColorStructure> {
+    let legs: Vec<LegIndex> = (1..=n_legs).map(LegIndex).collect();
+    let mut out = Vec::new();
+    partitions(&legs, max_traces, &mut Vec::new(), &mut out);
+    out.sort();
+    out
+}
+
+/// Split `rest` into blocks, the first always holding the smallest remaining
+/// leg, and emit every cyclic ordering of every block.
+fn partitions(
+    rest: &[LegIndex],
+    max_traces: usize,
+    done: &mut Vec<Trace>,
+    out: &mut Vec<ColorStructure>,
+) {
+    let Some((&first, others)) = rest.split_first() else {
+        out.push(ColorStructure::new(done.clone()));
+        return;
+    };
+    if done.len() == max_traces {
+        return;
+    }
+    // Subsets of `others` joining `first`, as bit masks.
+    for mask in 1u32..1 << others.len() {
+        let (inside, outside): (Vec<_>, Vec<_>) = others
+            .iter()
+            .enumerate()
+            .partition(|(k, _)| mask & (1 << k) != 0);
+        let inside: Vec<LegIndex> = inside.into_iter().map(|(_, &l)| l).collect();
+        let outside: Vec<LegIndex> = outside.into_iter().map(|(_, &l)| l).collect();
+        if outside.len() == 1 {
+            continue;
+        }
+        for order in color_orderings(inside.len() as u8 + 1) {
+            // Relabel the canonical orderings of `1..=k+1` onto this block.
+            let block = |l: LegIndex| {
+                if l.0 == 1 {
+                    first
+                } else {
+                    inside[l.0 as usize - 2]
+                }
+            };
+            done.push(Trace(order.0.into_iter().map(block).collect()));
+            partitions(&outside, max_traces, done, out);
+            done.pop();
+        }
+    }
+}
+
+/// Pair every color structure with every kinematic structure.
+///
+/// The products `C_i × T_j` span the color-dressed amplitude; with one
+/// coefficient each, `A = Σ c_ij C_i T_j`.
+pub fn color_dressed_basis(
+    colors: &[ColorStructure],
+    kinematics: &[TensorStructure],
+) -> Vec<(ColorStructure, TensorStructure)> {
+    colors
+        .iter()
+        .flat_map(|c| kinematics.iter().map(move |t| (c.clone(), t.clone())))
+        .collect()
+}
+
+/// A Del Duca–Dixon–Maltoni half ladder `f^{a_1 a_σ(2) b_1} f^{b_1 a_σ(3) b_2} … f^{b_{n-3} a_σ(n-1) a_n}`.
+///
+/// Holds the legs `1, σ(2), …, σ(n-1), n` in ladder order.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct HalfLadder(pub Vec<LegIndex>);
+
+impl HalfLadder {
+    /// The ladder as a cubic graph hanging off leg `n`, with the sign relating
+    /// the two orientations.
+    pub fn graph(&self) -> (CubicGraph, i8) {
+        let n = self.0.len() as u8;
+        let inner = &self.0[..self.0.len() - 1];
+        let mut tree = CubicTree::Leaf(inner[0]);
+        for &l in &inner[1..] {
+            tree = CubicTree::Node(Box::new(tree), Box::new(CubicTree::Leaf(l)));
+        }
+        CubicGraph::new(n, tree)
+    }
+
+    /// Expansion in single traces; see [`color_factor_traces`].
+    pub fn to_trace_basis(&self) -> Vec<(i64, Trace)> {
+        let (g, sign) = self.graph();
+        color_factor_traces(&g)
+            .into_iter()
+            .map(|(c, t)| (c * sign as i64, t))
+            .collect()
+    }
+}
+
+impl fmt::Display for HalfLadder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inner: Vec<String> = self.0[1..self.0.len() - 1]
+            .iter()
+            .map(|l| l.0.to_string())
+            .collect();
+        write!(
+            f,
+            "c({}|{}|{})",
+            self.0[0].0,
+            inner.join(","),
+            self.0[self.0.len() - 1].0
+        )
+    }
+}
+
+/// The `(n-2)!` half ladders with legs 1 and `n` at the ends, a basis of color
+/// factors modulo Jacobi identities.
+pub fn ddm_basis(n_legs: u8) -> Vec<HalfLadder> {
+    if n_legs < 3 {
+        return Vec::new();
+    }
+    let mut out: Vec<HalfLadder> = color_orderings(n_legs - 1)
+        .into_iter()
+        .map(|o| {
+            let mut legs = o.0;
+            legs.push(LegIndex(n_legs));
+            HalfLadder(legs)
+        })
+        .collect();
+    out.sort();
+    out
+}
+
+/// Expand the color factor of a cubic graph in single traces.
+///
+/// Uses `f̃^{abc} = Tr([T^a, T^b] T^c)`, i.e. `f̃ = i√2 f` for generators
+/// normalized to `Tr(T^a T^b) = δ^{ab}`. The graph hanging off leg `n` is then
+/// `Tr(C T^{a_n})` with `C` the nested commutator of its tree, so the result
+/// has `2^{n-2}` terms with coefficients `±1`. The `1/N` terms of `SU(N)`
+/// cancel inside commutators, so the expansion is exact.
+pub fn color_factor_traces(graph: &CubicGraph) -> Vec<(i64, Trace)> {
+    let mut terms: BTreeMap<Trace, i64> = BTreeMap::new();
+    for (c, mut word) in commutator_words(&graph.tree) {
+        word.push(LegIndex(graph.n_legs));
+        *terms.entry(Trace::new(word)).or_default() += c;
+    }
+    terms
+        .into_iter()
+        .filter(|(_, c)| *c != 0)
+        .map(|(t, c)| (c, t))
+        .collect()
+}
+
+/// Words of generators in the nested commutator of `t`, with signs.
+fn commutator_words(t: &CubicTree) -> Vec<(i64, Vec<LegIndex>)> {
+    match t {
+        CubicTree::Leaf(l) => vec![(1, vec![*l])],
+        CubicTree::Node(l, r) => {
+            let (left, right) = (commutator_words(l), commutator_words(r));
+            let mut out = Vec::with_capacity(2 * left.len() * right.len());
+            for (a, x) in &left {
+                for (b, y) in &right {
+                    out.push((a * b, x.iter().chain(y).copied().collect()));
+                    out.push((-a * b, y.iter().chain(x).copied().collect()));
+                }
+            }
+            out
+        }
+    }
+}
+
+/// Expand the color factor of `graph` in the DDM basis.
+///
+/// Only `c(1|σ|n)` contains the trace `Tr(T^{a_1} T^{a_σ} T^{a_n})`, so the
+/// coefficients are read off the trace expansion; they are integers.
+pub fn ddm_decomposition(graph: &CubicGraph) -> Vec<(i64, HalfLadder)> {
+    let n = LegIndex(graph.n_legs);
+    color_factor_traces(graph)
+        .into_iter()
+        .filter(|(_, t)| t.0.last() == Some(&n))
+        .map(|(c, t)| (c, HalfLadder(t.0)))
+        .collect()
+}
+
+/// Structure-constant graphs with `n` adjoint legs, independent under Jacobi.
+///
+/// Goes through [`cubic_graphs`] in canonical order and keeps each graph whose
+/// DDM expansion is independent of the earlier ones, ending with `(n-2)!` graphs.
+pub fn color_graph_basis(n_legs: u8) -> Vec<CubicGraph> {
+    let ddm = ddm_basis(n_legs);
+    let index: BTreeMap<&HalfLadder, usize> = ddm.iter().enumerate().map(|(i, h)| (h, i)).collect();
+    let mut echelon = Echelon::default();
+    let mut out = Vec::new();
+    for g in cubic_graphs(n_legs) {
+        let mut v = vec![0; ddm.len()];
+        for (c, h) in ddm_decomposition(&g) {
+            v[index[&h]] = c.rem_euclid(P as i64) as u64;
+        }
+        if echelon.insert(v) {
+            out.push(g);
+        }
+    }
+    out
+}
@@ -0,0 +1,287 @@
+use std::fmt;
+
+use crate::types::LegIndex;
+
+/// A rooted binary tree over external legs; each node is one cubic vertex.
+///
+/// A node `(L, R)` stands for the color factor `f^{a_L a_R a_parent}`, so
+/// swapping its children flips the sign.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum CubicTree {
+    Leaf(LegIndex),
+    Node(Box<CubicTree>, Box<CubicTree>),
+}
+
+impl CubicTree {
+    fn node(left: CubicTree, right: CubicTree) -> Self {
+        Self::Node(Box::new(left), Box::new(right))
+    }
+
+    /// Smallest leg below this node.
+    pub fn min_leg(&self) -> LegIndex {
+        match self {
+            Self::Leaf(l) => *l,
+            Self::Node(l, r) => l.min_leg().min(r.min_leg()),
+        }
+    }
+
+    /// All legs below this node, in tree order.
+    pub fn legs(&self) -> Vec<LegIndex> {
+        let mut out = Vec::new();
+        self.collect_legs(&mut out);
+        out
+    }
+
+    fn collect_legs(&self, out: &mut Vec<LegIndex>) {
+        match self {
+            Self::Leaf(l) => out.push(*l),
+            Self::Node(l, r) => {
+                l.collect_legs(out);
+                r.collect_legs(out);
+            }
+        }
+    }
+
+    /// Order every node's children by smallest leg and return the sign picked up.
+    pub fn canonicalize(&mut self) -> i8 {
+        match self {
+            Self::Leaf(_) => 1,
+            Self::Node(l, r) => {
+                let mut sign = l.canonicalize() * r.canonicalize();
+                if r.min_leg() < l.min_leg() {
+                    std::mem::swap(l, r);
+                    sign = -sign;
+                }
+                sign
+            }
+        }
+    }
+
+    fn relabel_leaves(&self, sigma: &impl Fn(LegIndex) -> LegIndex) -> Self {
+        match self {
+            Self::Leaf(l) => Self::Leaf(sigma(*l)),
+            Self::Node(l, r) => Self::node(l.relabel_leaves(sigma), r.relabel_leaves(sigma)),
+        }
+    }
+}
+
+impl fmt::Display for CubicTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Leaf(l) => write!(f, "{}", l.0),
+            Self::Node(l, r) => write!(f, "[{},{}]", l, r),
+        }
+    }
+}
+
+/// A tree-level cubic graph with `n` external legs and oriented vertices.
+///
+/// Stored as the binary tree over legs `1..n-1` hanging off leg `n`, with
+/// children in canonical order, so equal graphs compare equal and the
+/// orientation relative to the canonical one is carried as a separate sign.
+///
+/// Read as a contraction of structure constants it is also the color graph of
+/// `n` adjoint legs; see [`crate::color`] for its trace and DDM expansions.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct CubicGraph {
+    pub n_legs: u8,
+    pub tree: CubicTree,
+}
+
+impl CubicGraph {
+    /// Canonical graph for `tree` attached to leg `n_legs`, and the sign picked up.
+    pub fn new(n_legs: u8, mut tree: CubicTree) -> (Self, i8) {
+        let sign = tree.canonicalize();
+        (Self { n_legs, tree }, sign)
+    }
+
+    /// Leg sets of the internal propagators, `n - 3` of them.
+    ///
+    /// Each set lists the legs on the side away from leg `n`.
+    pub fn propagators(&self) -> Vec<Vec<LegIndex>> {
+        let mut out = Vec::new();
+        if let CubicTree::Node(l, r) = &self.tree {
+            collect_propagators(l, &mut out);
+            collect_propagators(r, &mut out);
+        }
+        out
+    }
+
+    /// Apply the leg relabeling `sigma` and return the canonical image with its sign.
+    ///
+    /// The vertex orientations travel with the legs; the image is re-hung
+    /// from leg `n`, which keeps the cyclic order at every vertex.
+    pub fn relabel(&self, sigma: &impl Fn(LegIndex) -> LegIndex) -> (Self, i8) {
+        let n = self.n_legs;
+        let tree = self.tree.relabel_leaves(sigma);
+        let root = sigma(LegIndex(n));
+        let tree = if root == LegIndex(n) {
+            tree
+        } else {
+            Unrooted::from_tree(&tree, root).hang_from(LegIndex(n))
+        };
+        Self::new(n, tree)
+    }
+
+    /// The three graphs of each kinematic/color Jacobi identity through a propagator.
+    ///
+    /// For a propagator between vertices `(A, B)` and its parent `(·, C)` the
+    /// entries are the canonical forms of `((A,B),C)`, `((B,C),A)` and
+    /// `((C,A),B)` with their signs `s_i`, so that `Σ s_i c_i = 0` for color
+    /// factors and BCJ numerators alike. Every identity is listed once per
+    /// propagator of each graph it involves, so each appears three times.
+    pub fn jacobi_triples(&self) -> Vec<[(CubicGraph, i8); 3]> {
+        local_triples(&self.tree)
+            .into_iter()
+            .map(|trees| trees.map(|t| Self::new(self.n_legs, t)))
+            .collect()
+    }
+}
+
+/// All Jacobi triples of `t`, as whole trees.
+fn local_triples(t: &CubicTree) -> Vec<[CubicTree; 3]> {
+    let CubicTree::Node(l, r) = t else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    // The orientation of the parent is common to all three terms, so either
+    // child can play the role of `(A, B)`.
+    for (x, c) in [(l, r), (r, l)] {
+        if let CubicTree::Node(a, b) = &**x {
+            let (a, b, c): (&CubicTree, &CubicTree, &CubicTree) = (a, b, c);
+            let pair = |x: &CubicTree, y: &CubicTree, z: &CubicTree| {
+                CubicTree::node(CubicTree::node(x.clone(), y.clone()), z.clone())
+            };
+            out.push([pair(a, b, c), pair(b, c, a), pair(c, a, b)]);
+        }
+    }
+    for u in local_triples(l) {
+        out.push(u.map(|v| CubicTree::node(v, (**r).clone())));
+    }
+    for u in local_triples(r) {
+        out.push(u.map(|v| CubicTree::node((**l).clone(), v)));
+    }
+    out
+}
+
+fn collect_propagators(t: &CubicTree, out: &mut Vec<Vec<LegIndex>>) {
+    if let CubicTree::Node(l, r) = t {
+        let mut legs = t.legs();
+        legs.sort();
+        out.push(legs);
+        collect_propagators(l, out);
+        collect_propagators(r, out);
+    }
+}
+
+impl fmt::Display for CubicGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.tree)
+    }
+}
+
+/// The same graph with cyclically ordered neighbours at each vertex.
+struct Unrooted {
+    /// Neighbours `[l, r, parent]` of each internal vertex, in cyclic order.
+    vertices: Vec<[Node; 3]>,
+    /// The internal vertex each leg attaches to.
+    leaf_vertex: Vec<(LegIndex, usize)>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Node {
+    Leg(LegIndex),
+    Vertex(usize),
+}
+
+impl Unrooted {
+    fn from_tree(tree: &CubicTree, root: LegIndex) -> Self {
+        let mut g = Self {
+            vertices: Vec::new(),
+            leaf_vertex: Vec::new(),
+        };
+        // The top node becomes vertex 0, the one `root` attaches to.
+        if let Node::Vertex(v) = g.add(tree, Node::Leg(root)) {
+            g.leaf_vertex.push((root, v));
+        }
+        g
+    }
+
+    fn add(&mut self, tree: &CubicTree, parent: Node) -> Node {
+        match tree {
+            CubicTree::Leaf(l) => {
+                if let Node::Vertex(v) = parent {
+                    self.leaf_vertex.push((*l, v));
+                }
+                Node::Leg(*l)
+            }
+            CubicTree::Node(l, r) => {
+                let id = self.vertices.len();
+                self.vertices.push([parent; 3]);
+                let left = self.add(l, Node::Vertex(id));
+                let right = self.add(r, Node::Vertex(id));
+                self.vertices[id] = [left, right, parent];
+                Node::Vertex(id)
+            }
+        }
+    }
+
+    fn hang_from(&self, leg: LegIndex) -> CubicTree {
+        let &(_, v) = self
+            .leaf_vertex
+            .iter()
+            .find(|(l, _)| *l == leg)
+            .expect("leg attached to the graph");
+        self.grow(Node::Vertex(v), Node::Leg(leg))
+    }
+
+    fn grow(&self, node: Node, parent: Node) -> CubicTree {
+        match node {
+            Node::Leg(l) => CubicTree::Leaf(l),
+            Node::Vertex(v) => {
+                let nb = self.vertices[v];
+                let k = nb
+                    .iter()
+                    .position(|&x| x == parent)
+                    .expect("parent is a neighbour");
+                let (x, y) = (nb[(k + 1) % 3], nb[(k + 2) % 3]);
+                CubicTree::node(self.grow(x, node), self.grow(y, node))
+            }
+        }
+    }
+}
+
+/// All `(2n - 5)!!` cubic tree graphs with legs `1..=n`, canonically oriented.
+///
+/// Built by attaching each new leg to every edge of the graphs on fewer legs.
+pub fn cubic_graphs(n_legs: u8) -> Vec<CubicGraph> {
+    if n_legs < 3 {
+        return Vec::new();
+    }
+    let mut trees = vec![CubicTree::node(
+        CubicTree::Leaf(LegIndex(1)),
+        CubicTree::Leaf(LegIndex(2)),
+    )];
+    for k in 3..n_legs {
+        let mut next = Vec::new();
+        for t in &trees {
+            insert_everywhere(t, LegIndex(k), &mut |u| next.push(u));
+        }
+        trees = next;
+    }
+    let mut out: Vec<CubicGraph> = trees
+        .into_iter()
+        .map(|t| CubicGraph::new(n_legs, t).0)
+        .collect();
+    out.sort();
+    out
+}
+
+/// Call `emit` with every tree obtained by grafting `leg` onto one edge of `t`.
+fn insert_everywhere(t: &CubicTree, leg: LegIndex, emit: &mut dyn FnMut(CubicTree)) {
+    emit(CubicTree::node(t.clone(), CubicTree::Leaf(leg)));
+    if let CubicTree::Node(l, r) = t {
+        insert_everywhere(l, leg, &mut |u| emit(CubicTree::node(u, (**r).clone())));
+        insert_everywhere(r, leg, &mut |u| emit(CubicTree::node((**l).clone(), u)));
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+
+use num_rational::BigRational;
+use num_traits::Zero;
+
+use crate::{
+    bilinear::BilinearFactor,
+    dot_product::ScalarFactor,
+    epsilon::EpsilonFactor,
+    expression::TensorExpression,
+    indexed::{IndexedTensor, strip_polarizations},
+    rational::int,
+    reduction::{expand_field_strengths, finish},
+    tensor_structure::TensorStructure,
+    types::{LegIndex, ScalarKind, Slot},
+};
+
+/// Leg carrying the free index of [`momentum_gradient`]; real legs start at 1.
+const PROBE: LegIndex = LegIndex(0);
+
+/// Directional derivative `v·∂/∂p_i` of `expr`, for `v` a momentum or polarization.
+///
+/// Every momentum is treated as an independent variable, so the result refers
+/// to the expression as written: if momentum conservation has eliminated
+/// `p_n`, the other momenta are the variables, and terms already dropped on
+/// shell do not come back. Field strengths are expanded first. Returns `None`
+/// if a spinor bracket involves leg `i`, as brackets are not functions of the
+/// momentum vectors alone.
+pub fn momentum_derivative(
+    expr: &TensorExpression,
+    leg: LegIndex,
+    v: Slot,
+) -> Option<TensorExpression> {
+    let mut terms = Vec::new();
+    for (c, t) in &expr.terms {
+        for (d, u) in expand_field_strengths(t).terms {
+            for (e, w) in differentiate(&u, leg, v)? {
+                terms.push((c * &d * e, w));
+            }
+        }
+    }
+    Some(finish(terms))
+}
+
+/// The gradient `∂/∂p_i^μ` of `expr`, as tensors with the free index `μ0`.
+///
+/// This is [`momentum_derivative`] along a probe vector on the unused leg 0,
+/// stripped to leave its index open; polarizations of `expr` are stripped
+/// along with it, as in [`strip_polarizations`]. Terms are collected by tensor.
+pub fn momentum_gradient(
+    expr: &TensorExpression,
+    leg: LegIndex,
+) -> Option<Vec<(BigRational, IndexedTensor)>> {
+    let derivative = momentum_derivative(expr, leg, Slot::E(PROBE))?;
+    let mut terms: BTreeMap<IndexedTensor, BigRational> = BTreeMap::new();
+    for (c, t) in &derivative.terms {
+        for (d, u) in strip_polarizations(t) {
+            *terms.entry(u).or_default() += c * d;
+        }
+    }
+    Some(
+        terms
+            .into_iter()
+            .filter(|(_, c)| !c.is_zero())
+            .map(|(u, c)| (c, u))
+            .collect(),
+    )
+}
+
+/// Leibniz rule on one structure without field strengths.
+fn differentiate(
+    t: &TensorStructure,
+    leg: LegIndex,
+    v: Slot,
+) -> Option<Vec<(BigRational, TensorStructure)>> {
+    let mut out = Vec::new();
+    for (x, f) in t.factors.iter().enumerate() {
+        for g in vary_factor(f, leg, v)? {
+            let mut u = t.clone();
+            u.factors[x] = g;
+            out.push((int(1), u));
+        }
+    }
+    if let Some(eps) = &t.epsilon {
+        for k in 0..4 {
+            if eps.slots[k] != Slot::P(leg) {
+                continue;
+            }
+            let mut slots = eps.slots;
+            slots[k] = v;
+            if let Some((e, sign)) = EpsilonFactor::new(slots) {
+                let mut u = t.clone();
+                u.epsilon = Some(e);
+                out.push((int(sign as i64), u));
+            }
+        }
+    }
+    for (x, b) in t.bilinears.iter().enumerate() {
+        if b.gamma == Slot::P(leg) {
+            let mut u = t.clone();
+            u.bilinears[x] = BilinearFactor::new(b.bar, v, b.u);
+            out.push((int(1), u));
+        }
+    }
+    Some(out)
+}
+
+/// Every way of replacing one `p_i` in `f` by `v`.
+fn vary_factor(f: &ScalarFactor, leg: LegIndex, v: Slot) -> Option<Vec<ScalarFactor>> {
+    let mut out = Vec::new();
+    match f.kind {
+        ScalarKind::PP => {
+            if f.a == leg {
+                out.push(ScalarFactor::dot(v, Slot::P(f.b)));
+            }
+            if f.b == leg {
+                out.push(ScalarFactor::dot(Slot::P(f.a), v));
+            }
+        }
+        ScalarKind::PE if f.a == leg => out.push(ScalarFactor::dot(v, Slot::E(f.b))),
+        ScalarKind::LP if f.b == leg => out.push(match v {
+            Slot::P(j) => ScalarFactor::lp(f.a, j),
+            Slot::E(j) => ScalarFactor::le(f.a, j),
+        }),
+        ScalarKind::Angle | ScalarKind::Square if f.a == leg || f.b == leg => return None,
+        _ => {}
+    }
+    Some(out)
+}
@@ -0,0 +1,253 @@
+use std::{
+    fmt,
+    ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+
+use num_rational::BigRational;
+use num_traits::{One, Zero};
+
+/// A polynomial in the spacetime dimension `d` with rational coefficients.
+///
+/// Metric traces `η^μ_μ = d` are kept symbolic, so results derived once in `d`
+/// dimensions can be specialized with [`at`](Self::at) afterwards.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct DimPolynomial {
+    /// Coefficient of `d^k` at index `k`, without trailing zeros.
+    coefficients: Vec<BigRational>,
+}
+
+impl DimPolynomial {
+    pub fn constant(c: BigRational) -> Self {
+        Self::from_coefficients(vec![c])
+    }
+
+    /// The polynomial `d`.
+    pub fn d() -> Self {
+        Self::from_coefficients(vec![BigRational::zero(), BigRational::one()])
+    }
+
+    /// `d^k`.
+    pub fn d_pow(k: usize) -> Self {
+        let mut coefficients = vec![BigRational::zero(); k + 1];
+        coefficients[k] = BigRational::one();
+        Self { coefficients }
+    }
+
+    /// The polynomial `Σ c_k d^k` with `c_k = coefficients[k]`.
+    pub fn from_coefficients(mut coefficients: Vec<BigRational>) -> Self {
+        while coefficients.last().is_some_and(|c| c.is_zero()) {
+            coefficients.pop();
+        }
+        Self { coefficients }
+    }
+
+    /// Coefficients of `1, d, d², …`; empty for the zero polynomial.
+    pub fn coefficients(&self) -> &[BigRational] {
+        &self.coefficients
+    }
+
+    /// Degree in `d`, or `None` for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        self.coefficients.len().checked_sub(1)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coefficients.is_empty()
+    }
+
+    /// `self / rhs` if `rhs` divides `self` exactly, by long division.
+    pub fn div_exact(&self, rhs: &DimPolynomial) -> Option<DimPolynomial> {
+        let m = rhs.degree()?;
+        let lead = &rhs.coefficients[m];
+        let mut rem = self.coefficients.clone();
+        if rem.len() <= m {
+            return self.is_zero().then(DimPolynomial::default);
+        }
+        let mut quotient = vec![BigRational::zero(); rem.len() - m];
+        for k in (0..quotient.len()).rev() {
+            let q = &rem[k + m] / lead;
+            for (j, c) in rhs.coefficients.iter().enumerate() {
+                rem[k + j] -= &q * c;
+            }
+            quotient[k] = q;
+        }
+        rem.iter()
+            .all(Zero::is_zero)
+            .then(|| Self::from_coefficients(quotient))
+    }
+
+    /// Specialize to a fixed dimension.
+    pub fn at(&self, d: BigRational) -> BigRational {
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(BigRational::zero(), |acc, c| acc * &d + c)
+    }
+}
+
+impl From<BigRational> for DimPolynomial {
+    fn from(c: BigRational) -> Self {
+        Self::constant(c)
+    }
+}
+
+impl AddAssign<&DimPolynomial> for DimPolynomial {
+    fn add_assign(&mut self, rhs: &DimPolynomial) {
+        if self.coefficients.len() < rhs.coefficients.len() {
+            self.coefficients
+                .resize(rhs.coefficients.len(), BigRational::zero());
+        }
+        for (x, y) in self.coefficients.iter_mut().zip(&rhs.coefficients) {
+            *x += y;
+        }
+        *self = Self::from_coefficients(std::mem::take(&mut self.coefficients));
+    }
+}
+
+impl SubAssign<&DimPolynomial> for DimPolynomial {
+    fn sub_assign(&mut self, rhs: &DimPolynomial) {
+        *self += &-rhs.clone();
+    }
+}
+
+impl Add for DimPolynomial {
+    type Output = DimPolynomial;
+
+    fn add(mut self, rhs: DimPolynomial) -> DimPolynomial {
+        self += &rhs;
+        self
+    }
+}
+
+impl Sub for DimPolynomial {
+    type Output = DimPolynomial;
+
+    fn sub(mut self, rhs: DimPolynomial) -> DimPolynomial {
+        self -= &rhs;
+        self
+    }
+}
+
+impl Neg for DimPolynomial {
+    type Output = DimPolynomial;
+
+    fn neg(mut self) -> DimPolynomial {
+        for c in &mut self.coefficients {
+            *c = -c.clone();
+        }
+        self
+    }
+}
+
+impl MulAssign<BigRational> for DimPolynomial {
+    fn mul_assign(&mut self, rhs: BigRational) {
+        for c in &mut self.coefficients {
+            *c *= &rhs;
+        }
+        *self = Self::from_coefficients(std::mem::take(&mut self.coefficients));
+    }
+}
+
+impl Mul<BigRational> for DimPolynomial {
+    type Output = DimPolynomial;
+
+    fn mul(mut self, rhs: BigRational) -> DimPolynomial {
+        self *= rhs;
+        self
+    }
+}
+
+impl Mul<&DimPolynomial> for &DimPolynomial {
+    type Output = DimPolynomial;
+
+    fn mul(self, rhs: &DimPolynomial) -> DimPolynomial {
+        if self.is_zero() || rhs.is_zero() {
+            return DimPolynomial::default();
+        }
+        let mut out =
+            vec![BigRational::zero(); self.coefficients.len() + rhs.coefficients.len() - 1];
+        for (i, x) in self.coefficients.iter().enumerate() {
+            for (j, y) in rhs.coefficients.iter().enumerate() {
+                out[i + j] += x * y;
+            }
+        }
+        DimPolynomial::from_coefficients(out)
+    }
+}
+
+impl Mul for DimPolynomial {
+    type Output = DimPolynomial;
+
+    fn mul(self, rhs: DimPolynomial) -> DimPolynomial {
+        &self * &rhs
+    }
+}
+
+/// Printed from the highest power down, e.g. `d^2 - 3 d + 2` or `1/2 d`.
+impl fmt::Display for DimPolynomial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        let mut first = true;
+        for (k, c) in self.coefficients.iter().enumerate().rev() {
+            if c.is_zero() {
+                continue;
+            }
+            let (sign, mag) = if *c < BigRational::zero() {
+                ("-", -c)
+            } else {
+                ("+", c.clone())
+            };
+            if first {
+                if sign == "-" {
+                    write!(f, "-")?;
+                }
+            } else {
+                write!(f, " {} ", sign)?;
+            }
+            first = false;
+            let power = match k {
+                0 => String::new(),
+                1 => "d".to_string(),
+                _ => format!("d^{}", k),
+            };
+            match (mag.is_one(), power.is_empty()) {
+                (true, false) => write!(f, "{}", power)?,
+                (_, true) => write!(f, "{}", mag)?,
+                (false, false) => write!(f, "{} {}", mag, power)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Rank of a matrix over the rational functions of `d`, i.e. for generic `d`.
+///
+/// Fraction-free (Bareiss) elimination keeps every entry a polynomial. The
+/// second value is the nonzero minor of the pivot rows and columns: at a
+/// fixed `d` that is not one of its roots the rank is the same, while at a
+/// root such as `d = 4` it may drop.
+pub fn rank_in_d(mut matrix: Vec<Vec<DimPolynomial>>) -> (usize, DimPolynomial) {
+    let mut previous = DimPolynomial::constant(BigRational::one());
+    let mut rank = 0;
+    let cols = matrix.first().map_or(0, Vec::len);
+    for c in 0..cols {
+        let Some(p) = (rank..matrix.len()).find(|&i| !matrix[i][c].is_zero()) else {
+            continue;
+        };
+        matrix.swap(rank, p);
+        for i in rank + 1..matrix.len() {
+            for j in c + 1..cols {
+                let cross = &matrix[rank][c] * &matrix[i][j] - &matrix[i][c] * &matrix[rank][j];
+                matrix[i][j] = cross
+                    .div_exact(&previous)
+                    .expect("Bareiss steps divide exactly");
+            }
+            matrix[i][c] = DimPolynomial::default();
+        }
+        previous = matrix[rank][c].clone();
+        rank += 1;
+    }
+    (rank, previous)
+}
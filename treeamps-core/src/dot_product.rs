@@ -1,7 +1,10 @@
-use crate::types::{LegIndex, ScalarKind};
+use std::{fmt, str::FromStr};
+
+use crate::types::{LegIndex, ScalarKind, Slot};
 
 /// A single scalar factor (dot product) in the tensor basis.
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ScalarFactor {
     pub kind: ScalarKind,
     pub a: LegIndex,
@@ -18,12 +21,217 @@ impl ScalarFactor {
     pub fn ee(i: LegIndex, j: LegIndex) -> Self {
         Self { kind: ScalarKind::EE, a: i, b: j }
     }
+    pub fn angle(i: LegIndex, j: LegIndex) -> Self {
+        Self { kind: ScalarKind::Angle, a: i, b: j }
+    }
+    pub fn square(i: LegIndex, j: LegIndex) -> Self {
+        Self { kind: ScalarKind::Square, a: i, b: j }
+    }
+    pub fn lp(l: LegIndex, j: LegIndex) -> Self {
+        Self { kind: ScalarKind::LP, a: l, b: j }
+    }
+    pub fn le(l: LegIndex, j: LegIndex) -> Self {
+        Self { kind: ScalarKind::LE, a: l, b: j }
+    }
+    pub fn ll(l: LegIndex, m: LegIndex) -> Self {
+        Self { kind: ScalarKind::LL, a: l.min(m), b: l.max(m) }
+    }
+
+    /// Number of momenta in the factor: PP and the loop products `l·p`, `l·l`
+    /// count 2, PE and `l·e` count 1, EE 0, and a spinor bracket `√p√p` counts 1.
+    pub fn momentum_power(&self) -> u32 {
+        match self.kind {
+            ScalarKind::PP | ScalarKind::LP | ScalarKind::LL => 2,
+            ScalarKind::PE | ScalarKind::LE | ScalarKind::Angle | ScalarKind::Square => 1,
+            ScalarKind::EE => 0,
+        }
+    }
+
+    /// Power of loop momenta carried by the factor.
+    pub fn loop_power(&self) -> u32 {
+        match self.kind {
+            ScalarKind::LP | ScalarKind::LE => 1,
+            ScalarKind::LL => 2,
+            _ => 0,
+        }
+    }
+
+    /// Dot product of two vectors, ordered canonically (`a ≤ b` for PP/EE, momentum first for PE).
+    pub fn dot(x: Slot, y: Slot) -> Self {
+        match (x, y) {
+            (Slot::P(i), Slot::P(j)) => Self::pp(i.min(j), i.max(j)),
+            (Slot::E(i), Slot::E(j)) => Self::ee(i.min(j), i.max(j)),
+            (Slot::P(i), Slot::E(j)) | (Slot::E(j), Slot::P(i)) => Self::pe(i, j),
+        }
+    }
+
+    /// Apply the leg relabeling `sigma` and reorder canonically.
+    ///
+    /// Loop-momentum indices are not legs and stay put. Returns the sign picked up: `-1` when an antisymmetric spinor bracket
+    /// has to be flipped, `0` if it collapses to `⟨ii⟩`, and `1` otherwise.
+    pub fn relabel(&self, sigma: &impl Fn(LegIndex) -> LegIndex) -> (Self, i8) {
+        let (a, b) = (sigma(self.a), sigma(self.b));
+        match self.kind {
+            ScalarKind::PP => (Self::dot(Slot::P(a), Slot::P(b)), 1),
+            ScalarKind::PE => (Self::pe(a, b), 1),
+            ScalarKind::EE => (Self::dot(Slot::E(a), Slot::E(b)), 1),
+            ScalarKind::LP => (Self::lp(self.a, b), 1),
+            ScalarKind::LE => (Self::le(self.a, b), 1),
+            ScalarKind::LL => (self.clone(), 1),
+            ScalarKind::Angle | ScalarKind::Square => {
+                let sign = match a.cmp(&b) {
+                    std::cmp::Ordering::Less => 1,
+                    std::cmp::Ordering::Equal => 0,
+                    std::cmp::Ordering::Greater => -1,
+                };
+                let f = Self {
+                    kind: self.kind,
+                    a: a.min(b),
+                    b: a.max(b),
+                };
+                (f, sign)
+            }
+        }
+    }
+
+    /// LaTeX form: `(p_1\cdot e_2)`, or `\langle 1\,2\rangle` and `[1\,2]`
+    /// for spinor brackets.
+    pub fn to_latex(&self) -> String {
+        let (a, b) = (self.a.latex_subscript(), self.b.latex_subscript());
+        let (x, y) = match self.kind {
+            ScalarKind::Angle => return format!("\\langle {}\\,{}\\rangle", self.a.0, self.b.0),
+            ScalarKind::Square => return format!("[{}\\,{}]", self.a.0, self.b.0),
+            ScalarKind::PP => ('p', 'p'),
+            ScalarKind::PE => ('p', 'e'),
+            ScalarKind::EE => ('e', 'e'),
+            ScalarKind::LP => ('l', 'p'),
+            ScalarKind::LE => ('l', 'e'),
+            ScalarKind::LL => ('l', 'l'),
+        };
+        format!("({}_{}\\cdot {}_{})", x, a, y, b)
+    }
+
+    /// Space between the legs of a bracket once one has two digits, so that
+    /// `⟨1 12⟩` and `⟨11 2⟩` stay apart.
+    fn bracket_sep(&self) -> &'static str {
+        if self.a.0 >= 10 || self.b.0 >= 10 {
+            " "
+        } else {
+            ""
+        }
+    }
+}
+
+/// A [`ScalarFactor`] packed into 16 bits.
+///
+/// The kind takes the top three bits and the two indices six bits each, so
+/// packed factors order exactly like the factors they encode and compare and
+/// hash as plain integers.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PackedFactor(pub u16);
+
+impl PackedFactor {
+    /// Largest leg or loop index that fits.
+    pub const MAX_INDEX: u8 = 63;
+
+    /// Pack `f`, or `None` if an index exceeds [`MAX_INDEX`](Self::MAX_INDEX).
+    pub fn new(f: &ScalarFactor) -> Option<Self> {
+        if f.a.0 > Self::MAX_INDEX || f.b.0 > Self::MAX_INDEX {
+            return None;
+        }
+        Some(Self(
+            (f.kind as u16) << 12 | (f.a.0 as u16) << 6 | f.b.0 as u16,
+        ))
+    }
+
+    pub fn unpack(&self) -> ScalarFactor {
+        ScalarFactor {
+            kind: ScalarKind::ALL[(self.0 >> 12) as usize & 7],
+            a: LegIndex((self.0 >> 6 & 63) as u8),
+            b: LegIndex((self.0 & 63) as u8),
+        }
+    }
+}
+
+impl fmt::Display for PackedFactor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.unpack())
+    }
+}
+
+/// Parses dot products such as `(p1·e2)`, `p1.e2`, `e3*e4` or `l1·p2`, and
+/// the spinor brackets `⟨12⟩` and `[12]` (`⟨1 12⟩` once a leg has two digits).
+impl FromStr for ScalarFactor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        for (open, close, kind) in [
+            ('⟨', '⟩', ScalarKind::Angle),
+            ('[', ']', ScalarKind::Square),
+        ] {
+            let Some(inner) = s
+                .trim()
+                .strip_prefix(open)
+                .and_then(|r| r.strip_suffix(close))
+            else {
+                continue;
+            };
+            let legs: Vec<&str> = if inner.contains([' ', ',']) {
+                inner.split([' ', ',']).filter(|x| !x.is_empty()).collect()
+            } else {
+                inner.split_inclusive(|_| true).collect()
+            };
+            return match legs[..] {
+                [a, b] => Ok(Self {
+                    kind,
+                    a: a.parse()?,
+                    b: b.parse()?,
+                }),
+                _ => Err(format!("expected a bracket like ⟨12⟩, got {:?}", s)),
+            };
+        }
+        let inner = s.trim().trim_start_matches('(').trim_end_matches(')');
+        let (x, y) = inner
+            .split_once(['·', '.', '*'])
+            .ok_or_else(|| format!("expected a dot product like p1·e2, got {:?}", s))?;
+        let (x, y) = (x.trim(), y.trim());
+        let loop_index = |v: &str| {
+            v.strip_prefix('l')
+                .map(|rest| {
+                    rest.parse::<u8>()
+                        .ok()
+                        .filter(|&l| l > 0)
+                        .map(LegIndex)
+                        .ok_or_else(|| format!("invalid loop index in {:?}", s))
+                })
+                .transpose()
+        };
+        match (loop_index(x)?, loop_index(y)?) {
+            (Some(l), Some(m)) => Ok(Self::ll(l, m)),
+            (Some(l), None) | (None, Some(l)) => {
+                let other = if x.starts_with('l') { y } else { x };
+                Ok(match other.parse()? {
+                    Slot::P(j) => Self::lp(l, j),
+                    Slot::E(j) => Self::le(l, j),
+                })
+            }
+            (None, None) => Ok(Self::dot(x.parse()?, y.parse()?)),
+        }
+    }
+}
 
-    pub fn to_string(&self) -> String {
+impl fmt::Display for ScalarFactor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.kind {
-            ScalarKind::PP => format!("(p{}·p{})", self.a.0, self.b.0),
-            ScalarKind::PE => format!("(p{}·e{})", self.a.0, self.b.0),
-            ScalarKind::EE => format!("(e{}·e{})", self.a.0, self.b.0),
+            ScalarKind::PP => write!(f, "(p{}·p{})", self.a.0, self.b.0),
+            ScalarKind::PE => write!(f, "(p{}·e{})", self.a.0, self.b.0),
+            ScalarKind::EE => write!(f, "(e{}·e{})", self.a.0, self.b.0),
+            ScalarKind::Angle => write!(f, "⟨{}{}{}⟩", self.a.0, self.bracket_sep(), self.b.0),
+            ScalarKind::Square => write!(f, "[{}{}{}]", self.a.0, self.bracket_sep(), self.b.0),
+            ScalarKind::LP => write!(f, "(l{}·p{})", self.a.0, self.b.0),
+            ScalarKind::LE => write!(f, "(l{}·e{})", self.a.0, self.b.0),
+            ScalarKind::LL => write!(f, "(l{}·l{})", self.a.0, self.b.0),
         }
     }
 }
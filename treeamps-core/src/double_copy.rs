@@ -0,0 +1,57 @@
+use std::collections::BTreeSet;
+
+use crate::{expression::TensorExpression, reduction::finish, tensor_structure::TensorStructure};
+
+/// Product of two structures with their kinematics merged into one monomial.
+///
+/// Squaring gluon structures this way gives graviton structures in which each
+/// `e_i` appears twice, standing for `e_i^{μν} = e_i^μ e_i^ν`. Returns the
+/// canonical product with the sign picked up by canonicalizing, or `None` if
+/// both carry an ε factor, since two Levi-Civita tensors do not fit in one
+/// structure.
+pub fn double_copy(a: &TensorStructure, b: &TensorStructure) -> Option<(TensorStructure, i8)> {
+    if a.epsilon.is_some() && b.epsilon.is_some() {
+        return None;
+    }
+    let mut t = a.clone();
+    t.factors.extend(b.factors.iter().cloned());
+    t.ee_contractions += b.ee_contractions;
+    t.epsilon = a.epsilon.clone().or_else(|| b.epsilon.clone());
+    t.bilinears.extend(b.bilinears.iter().cloned());
+    t.field_strengths.extend(b.field_strengths.iter().cloned());
+    let sign = t.canonicalize();
+    Some((t, sign))
+}
+
+/// Expand `a · b` term by term with [`double_copy`], dropping ε·ε products.
+///
+/// Use `a * b` instead to keep them, contracted into dot products.
+pub fn double_copy_expression(a: &TensorExpression, b: &TensorExpression) -> TensorExpression {
+    let mut terms = Vec::with_capacity(a.terms.len() * b.terms.len());
+    for (c, s) in &a.terms {
+        for (d, t) in &b.terms {
+            if let Some((u, _)) = double_copy(s, t) {
+                terms.push((c * d, u));
+            }
+        }
+    }
+    finish(terms)
+}
+
+/// All distinct products `T_i · T_j` (`i ≤ j`) of a gluon basis, sorted.
+///
+/// These are the candidate graviton structures reachable by the double copy;
+/// they sit inside the basis generated for spin-2 legs at twice the degree.
+pub fn double_copy_candidates(structures: &[TensorStructure]) -> Vec<TensorStructure> {
+    let mut out = BTreeSet::new();
+    for (i, a) in structures.iter().enumerate() {
+        for b in &structures[i..] {
+            if let Some((t, sign)) = double_copy(a, b)
+                && sign != 0
+            {
+                out.insert(t);
+            }
+        }
+    }
+    out.into_iter().collect()
+}
@@ -0,0 +1,94 @@
+use std::{fmt, str::FromStr};
+
+use crate::types::{LegIndex, Slot};
+
+/// A Levi-Civita contraction `ε(v1, v2, v3, v4)` of four momenta/polarizations.
+///
+/// Stored with its slots in ascending order; the sign picked up while sorting
+/// is reported by the constructors instead of being kept in the factor.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EpsilonFactor {
+    pub slots: [Slot; 4],
+}
+
+impl EpsilonFactor {
+    /// Canonical factor and the sign `±1` relating it to `ε(slots)`.
+    ///
+    /// Returns `None` when a slot repeats, since the contraction then vanishes.
+    pub fn new(slots: [Slot; 4]) -> Option<(Self, i8)> {
+        let mut e = Self { slots };
+        let sign = e.canonicalize();
+        if sign == 0 { None } else { Some((e, sign)) }
+    }
+
+    /// Sort the slots in place and return the permutation sign (`0` if a slot repeats).
+    pub fn canonicalize(&mut self) -> i8 {
+        let mut sign = 1;
+        // Insertion sort, counting transpositions.
+        for i in 1..4 {
+            let mut j = i;
+            while j > 0 && self.slots[j - 1] > self.slots[j] {
+                self.slots.swap(j - 1, j);
+                sign = -sign;
+                j -= 1;
+            }
+        }
+        if self.slots.windows(2).any(|w| w[0] == w[1]) {
+            return 0;
+        }
+        sign
+    }
+
+    /// Apply the leg relabeling `sigma`, returning the reordered factor and its sign.
+    pub fn relabel(&self, sigma: &impl Fn(LegIndex) -> LegIndex) -> (Self, i8) {
+        let mut e = Self {
+            slots: self.slots.map(|s| s.relabel(sigma)),
+        };
+        let sign = e.canonicalize();
+        (e, sign)
+    }
+
+    /// Number of polarization vectors among the slots.
+    pub fn polarization_count(&self) -> u32 {
+        self.slots
+            .iter()
+            .filter(|s| matches!(s, Slot::E(_)))
+            .count() as u32
+    }
+
+    /// LaTeX form, e.g. `\epsilon(p_1,p_2,e_3,e_4)`.
+    pub fn to_latex(&self) -> String {
+        let slots: Vec<String> = self.slots.iter().map(|s| s.to_latex()).collect();
+        format!("\\epsilon({})", slots.join(","))
+    }
+}
+
+/// Parses `ε(p1,p2,e3,e4)` (or `eps(...)`), keeping the slots as written.
+impl FromStr for EpsilonFactor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let inner = s
+            .trim()
+            .strip_prefix("ε(")
+            .or_else(|| s.trim().strip_prefix("eps("))
+            .and_then(|r| r.strip_suffix(')'))
+            .ok_or_else(|| format!("expected ε(v1,v2,v3,v4), got {:?}", s))?;
+        let slots: Vec<Slot> = inner
+            .split(',')
+            .map(|v| v.trim().parse())
+            .collect::<Result<_, _>>()?;
+        let slots = slots
+            .try_into()
+            .map_err(|_| format!("ε takes four vectors, got {:?}", s))?;
+        Ok(Self { slots })
+    }
+}
+
+impl fmt::Display for EpsilonFactor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [a, b, c, d] = self.slots;
+        write!(f, "ε({},{},{},{})", a, b, c, d)
+    }
+}
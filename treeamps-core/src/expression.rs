@@ -0,0 +1,329 @@
+use std::{
+    fmt,
+    ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+
+use num_rational::BigRational;
+use num_traits::One;
+
+use crate::{
+    coefficient::Coefficient, dot_product::ScalarFactor, permutation::Relabeling, rational::int,
+    reduction::finish, symmetry::for_each_permutation, tensor_structure::TensorStructure,
+    types::ScalarKind,
+};
+
+/// A linear combination `Σ c_k T_k` of tensor structures.
+///
+/// Coefficients are exact rationals unless another [`Coefficient`] field is
+/// chosen; on-shell reduction and the products of ε factors work over the
+/// rationals, and [`to_coefficients`](TensorExpression::to_coefficients) maps
+/// the result into e.g. a prime field.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TensorExpression<C = BigRational> {
+    pub terms: Vec<(C, TensorStructure)>,
+}
+
+impl<C: Coefficient> TensorExpression<C> {
+    pub fn new() -> Self {
+        Self { terms: Vec::new() }
+    }
+
+    /// Append `coef · t` without collecting.
+    pub fn push(&mut self, coef: C, t: TensorStructure) {
+        self.terms.push((coef, t));
+    }
+
+    /// Merge terms with equal structures, drop zero coefficients and sort by structure.
+    pub fn collect(&mut self) {
+        self.terms.sort_by(|x, y| x.1.cmp(&y.1));
+        let mut merged: Vec<(C, TensorStructure)> = Vec::with_capacity(self.terms.len());
+        for (c, t) in self.terms.drain(..) {
+            match merged.last_mut() {
+                Some((acc, last)) if *last == t => *acc += c,
+                _ => merged.push((c, t)),
+            }
+        }
+        merged.retain(|(c, _)| !c.is_zero());
+        self.terms = merged;
+    }
+
+    /// The terms as `c*T` joined by ` + ` or ` - `, with each structure as
+    /// `product` writes it, for computer-algebra input; `0` if there are none.
+    pub(crate) fn to_sum(&self, product: impl Fn(&TensorStructure) -> String) -> String {
+        if self.terms.is_empty() {
+            return "0".to_string();
+        }
+        let mut out = String::new();
+        for (k, (c, t)) in self.terms.iter().enumerate() {
+            let (sign, mag) = if c.is_negative() {
+                ("-", -c.clone())
+            } else {
+                ("+", c.clone())
+            };
+            if k == 0 {
+                if sign == "-" {
+                    out.push('-');
+                }
+            } else {
+                out += &format!(" {} ", sign);
+            }
+            if t.degree() == 0 {
+                out += &mag.to_string();
+            } else if mag.is_one() {
+                out += &product(t);
+            } else {
+                out += &format!("{}*{}", mag, product(t));
+            }
+        }
+        out
+    }
+
+    /// Canonicalize every structure, then [`collect`](Self::collect).
+    ///
+    /// Signs from canonicalizing are folded into the coefficients, vanishing
+    /// structures are dropped and EE counts recomputed, so equal expressions
+    /// simplify to identical term lists.
+    pub fn simplify(&mut self) {
+        for (c, t) in &mut self.terms {
+            let sign = t.canonicalize();
+            t.ee_contractions = t
+                .factors
+                .iter()
+                .filter(|f| f.kind == ScalarKind::EE)
+                .count() as u32;
+            if sign < 0 {
+                *c = -c.clone();
+            } else if sign == 0 {
+                *c = C::zero();
+            }
+        }
+        self.collect();
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.terms.iter().all(|(c, _)| c.is_zero())
+    }
+
+    /// Apply the leg relabeling `sigma` to every term, collected.
+    ///
+    /// Signs from [`TensorStructure::relabel`] are folded into the
+    /// coefficients; momentum conservation is not re-imposed.
+    pub fn relabel(&self, sigma: &impl Relabeling) -> Self {
+        let mut out = Self::new();
+        for (c, t) in &self.terms {
+            match t.relabel(sigma) {
+                (_, 0) => {}
+                (u, sign) if sign < 0 => out.push(-c.clone(), u),
+                (u, _) => out.push(c.clone(), u),
+            }
+        }
+        out.collect();
+        out
+    }
+}
+
+impl TensorExpression {
+    /// The same expression over another coefficient field, collected.
+    ///
+    /// Returns `None` if a denominator is not invertible there, e.g. divisible
+    /// by the prime of a finite field.
+    pub fn to_coefficients<C: Coefficient>(&self) -> Option<TensorExpression<C>> {
+        let mut out = TensorExpression::new();
+        for (c, t) in &self.terms {
+            out.push(C::from_rational(c)?, t.clone());
+        }
+        out.collect();
+        Some(out)
+    }
+}
+
+// Sums are collected, so results are always in canonical term order.
+
+impl<C: Coefficient> AddAssign<&TensorExpression<C>> for TensorExpression<C> {
+    fn add_assign(&mut self, rhs: &TensorExpression<C>) {
+        self.terms.extend(rhs.terms.iter().cloned());
+        self.collect();
+    }
+}
+
+impl<C: Coefficient> SubAssign<&TensorExpression<C>> for TensorExpression<C> {
+    fn sub_assign(&mut self, rhs: &TensorExpression<C>) {
+        self.terms
+            .extend(rhs.terms.iter().map(|(c, t)| (-c.clone(), t.clone())));
+        self.collect();
+    }
+}
+
+impl<C: Coefficient> Add<&TensorExpression<C>> for TensorExpression<C> {
+    type Output = TensorExpression<C>;
+
+    fn add(mut self, rhs: &TensorExpression<C>) -> TensorExpression<C> {
+        self += rhs;
+        self
+    }
+}
+
+impl<C: Coefficient> Add for TensorExpression<C> {
+    type Output = TensorExpression<C>;
+
+    fn add(self, rhs: TensorExpression<C>) -> TensorExpression<C> {
+        self + &rhs
+    }
+}
+
+impl<C: Coefficient> Sub<&TensorExpression<C>> for TensorExpression<C> {
+    type Output = TensorExpression<C>;
+
+    fn sub(mut self, rhs: &TensorExpression<C>) -> TensorExpression<C> {
+        self -= rhs;
+        self
+    }
+}
+
+impl<C: Coefficient> Sub for TensorExpression<C> {
+    type Output = TensorExpression<C>;
+
+    fn sub(self, rhs: TensorExpression<C>) -> TensorExpression<C> {
+        self - &rhs
+    }
+}
+
+impl<C: Coefficient> Neg for TensorExpression<C> {
+    type Output = TensorExpression<C>;
+
+    fn neg(mut self) -> TensorExpression<C> {
+        for (c, _) in &mut self.terms {
+            *c = -c.clone();
+        }
+        self
+    }
+}
+
+/// Scalar multiplication; multiplying by zero leaves the empty expression.
+impl<C: Coefficient> MulAssign<C> for TensorExpression<C> {
+    fn mul_assign(&mut self, rhs: C) {
+        if rhs.is_zero() {
+            self.terms.clear();
+            return;
+        }
+        for (c, _) in &mut self.terms {
+            *c *= rhs.clone();
+        }
+    }
+}
+
+impl<C: Coefficient> Mul<C> for TensorExpression<C> {
+    type Output = TensorExpression<C>;
+
+    fn mul(mut self, rhs: C) -> TensorExpression<C> {
+        self *= rhs;
+        self
+    }
+}
+
+/// Product of two structures: factors, bilinears and field strengths are
+/// merged and the EE count is recomputed.
+///
+/// A product of two ε factors is contracted into dot products with
+/// `ε(a,b,c,d) ε(e,f,g,h) = -det[x·y]`, the rows running over `a..d` and the
+/// columns over `e..h` (signature `(+,−,−,−)`, `ε^{0123} = 1`), so the result is
+/// a sum in general. It is canonicalized but not reduced on shell.
+impl Mul<&TensorStructure> for &TensorStructure {
+    type Output = TensorExpression;
+
+    fn mul(self, rhs: &TensorStructure) -> TensorExpression {
+        let mut t = self.clone();
+        t.factors.extend(rhs.factors.iter().cloned());
+        t.bilinears.extend(rhs.bilinears.iter().cloned());
+        t.field_strengths
+            .extend(rhs.field_strengths.iter().cloned());
+        let (x, y) = match (&self.epsilon, &rhs.epsilon) {
+            (Some(x), Some(y)) => (x.slots, y.slots),
+            (x, y) => {
+                t.epsilon = x.clone().or_else(|| y.clone());
+                return finish(vec![(BigRational::one(), t)]);
+            }
+        };
+        t.epsilon = None;
+        let mut terms = Vec::with_capacity(24);
+        for_each_permutation(4, |perm, parity| {
+            let mut u = t.clone();
+            for (k, &j) in perm.iter().enumerate() {
+                u.factors.push(ScalarFactor::dot(x[k], y[j]));
+            }
+            terms.push((int(-parity as i64), u));
+        });
+        finish(terms)
+    }
+}
+
+impl Mul for TensorStructure {
+    type Output = TensorExpression;
+
+    fn mul(self, rhs: TensorStructure) -> TensorExpression {
+        &self * &rhs
+    }
+}
+
+/// Product of two expressions, expanded term by term and collected.
+impl<C: Coefficient> Mul<&TensorExpression<C>> for &TensorExpression<C> {
+    type Output = TensorExpression<C>;
+
+    fn mul(self, rhs: &TensorExpression<C>) -> TensorExpression<C> {
+        let mut out = TensorExpression::new();
+        for (c, s) in &self.terms {
+            for (d, t) in &rhs.terms {
+                for (e, u) in (s * t).terms {
+                    let e = C::from_rational(&e).expect("ε contractions have integer coefficients");
+                    out.push(c.clone() * d.clone() * e, u);
+                }
+            }
+        }
+        out.collect();
+        out
+    }
+}
+
+impl<C: Coefficient> Mul for TensorExpression<C> {
+    type Output = TensorExpression<C>;
+
+    fn mul(self, rhs: TensorExpression<C>) -> TensorExpression<C> {
+        &self * &rhs
+    }
+}
+
+impl<C: Coefficient> From<TensorStructure> for TensorExpression<C> {
+    fn from(t: TensorStructure) -> Self {
+        Self {
+            terms: vec![(C::one(), t)],
+        }
+    }
+}
+
+impl<C: Coefficient> fmt::Display for TensorExpression<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.terms.is_empty() {
+            return write!(f, "0");
+        }
+        for (k, (c, t)) in self.terms.iter().enumerate() {
+            let (sign, mag) = if c.is_negative() {
+                ("-", -c.clone())
+            } else {
+                ("+", c.clone())
+            };
+            if k == 0 {
+                if sign == "-" {
+                    write!(f, "-")?;
+                }
+            } else {
+                write!(f, " {} ", sign)?;
+            }
+            if mag.is_one() {
+                write!(f, "{}", t)?;
+            } else {
+                write!(f, "{} · {}", mag, t)?;
+            }
+        }
+        Ok(())
+    }
+}
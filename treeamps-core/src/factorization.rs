@@ -0,0 +1,193 @@
+use num_rational::BigRational;
+use num_traits::Zero;
+
+use crate::{
+    generator::GenConfig,
+    kinematics::KinematicPoint,
+    mandelstam::Invariant,
+    rational::int,
+    rational_structure::RationalStructure,
+    rng::SplitMix64,
+    types::{LegIndex, Spin},
+};
+
+/// A sum `Σ c_k N_k / (poles_k)` of rational structures.
+pub type RationalTerms = [(BigRational, RationalStructure)];
+
+/// A pole `s_I → 0` with the lower-point expressions on either side.
+///
+/// `left` is the `(|I| + 1)`-point expression on the legs of `I` in
+/// increasing order followed by the internal leg with momentum `−P`, and
+/// `right` the same for the other legs with momentum `P`, where
+/// `P = Σ_{i∈I} p_i`.
+#[derive(Clone, Copy, Debug)]
+pub struct Channel<'a> {
+    pub pole: &'a Invariant,
+    pub left: &'a RationalTerms,
+    pub right: &'a RationalTerms,
+    /// Spin of the exchanged state; tensors are not supported.
+    pub internal: Spin,
+}
+
+/// Outcome of [`check_factorization`] at one point on the pole.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FactorizationCheck {
+    /// `lim s_I A` at the point.
+    pub residue: BigRational,
+    /// `Σ_h A_L A_R` over the states of the internal leg at the same point.
+    pub product: BigRational,
+}
+
+impl FactorizationCheck {
+    pub fn holds(&self) -> bool {
+        self.residue == self.product
+    }
+}
+
+/// Compare the residue of `amplitude` on `s_I = 0` with the product of lower-point expressions.
+///
+/// The amplitude is evaluated at `samples` random points on the pole in `d`
+/// dimensions. Terms with a single pole at `s_I`, written as `s_I` or as
+/// `s_Ī` with `Ī` the other legs, contribute to the residue; a double pole is
+/// an error. For a scalar exchange the product is `A_L A_R`; for a
+/// vector it is `−A_L^μ η_{μν} A_R^ν`, the Feynman-gauge state sum for
+/// `(+,−,…,−)`, which is the physical one when `A_L` and `A_R` satisfy their
+/// Ward identities. Every leg must be massless.
+///
+/// Points are real, so a side with two legs makes them collinear with `P`
+/// and the three-point kinematics degenerate; such channels usually check
+/// only `0 = 0`.
+pub fn check_factorization(
+    cfg: &GenConfig,
+    amplitude: &RationalTerms,
+    channel: &Channel,
+    d: u32,
+    samples: usize,
+) -> Result<Vec<FactorizationCheck>, String> {
+    let Channel {
+        pole,
+        left,
+        right,
+        internal,
+    } = *channel;
+    if internal == Spin::Tensor {
+        return Err("only scalar and vector exchanges are supported".into());
+    }
+    let n = cfg.n_legs as usize;
+    let inside: Vec<LegIndex> = pole.legs.clone();
+    let outside: Vec<LegIndex> = (1..=cfg.n_legs)
+        .map(LegIndex)
+        .filter(|l| !inside.contains(l))
+        .collect();
+    let mut rng = SplitMix64::new(0x7265_7369_6475_6573);
+    let mut out = Vec::with_capacity(samples);
+    for _ in 0..samples {
+        let x = KinematicPoint::on_pole(cfg, pole, d, &mut rng)
+            .ok_or_else(|| format!("no kinematics found on the pole {}", pole))?;
+        let residue = residue(&x, amplitude, pole, n)?;
+
+        let total: Vec<BigRational> = (0..d as usize)
+            .map(|mu| {
+                inside
+                    .iter()
+                    .map(|l| x.momenta[l.0 as usize - 1][mu].clone())
+                    .sum()
+            })
+            .collect();
+        let minus: Vec<BigRational> = total.iter().map(|c| -c).collect();
+        let side = |legs: &[LegIndex], p: &[BigRational], e: Vec<BigRational>| KinematicPoint {
+            momenta: legs
+                .iter()
+                .map(|l| x.momenta[l.0 as usize - 1].clone())
+                .chain([p.to_vec()])
+                .collect(),
+            polarizations: legs
+                .iter()
+                .map(|l| x.polarizations[l.0 as usize - 1].clone())
+                .chain([e])
+                .collect(),
+            loop_momenta: Vec::new(),
+        };
+        let product = match internal {
+            Spin::Scalar => {
+                let zero = vec![BigRational::zero(); d as usize];
+                value(&side(&inside, &minus, zero.clone()), left)?
+                    * value(&side(&outside, &total, zero), right)?
+            }
+            _ => {
+                let mut sum = BigRational::zero();
+                for mu in 0..d as usize {
+                    let mut unit = vec![BigRational::zero(); d as usize];
+                    unit[mu] = int(1);
+                    let a = value(&side(&inside, &minus, unit.clone()), left)?;
+                    let b = value(&side(&outside, &total, unit), right)?;
+                    // −η_{μμ} = −1 for time, +1 for space.
+                    if mu == 0 {
+                        sum -= a * b;
+                    } else {
+                        sum += a * b;
+                    }
+                }
+                sum
+            }
+        };
+        out.push(FactorizationCheck { residue, product });
+    }
+    Ok(out)
+}
+
+/// `lim s_I A` at a point with `s_I = 0`.
+fn residue(
+    x: &KinematicPoint,
+    amplitude: &RationalTerms,
+    channel: &Invariant,
+    n: usize,
+) -> Result<BigRational, String> {
+    let on_channel = |p: &Invariant| {
+        p.legs == channel.legs
+            || (p.legs.len() == n - channel.legs.len()
+                && p.legs.iter().all(|l| !channel.legs.contains(l)))
+    };
+    let mut total = BigRational::zero();
+    for (c, r) in amplitude {
+        let hits = r.poles.iter().filter(|p| on_channel(p)).count();
+        match hits {
+            0 => {}
+            1 => {
+                let mut v = c * numerator(x, r)?;
+                for p in r.poles.iter().filter(|p| !on_channel(p)) {
+                    v /= nonzero(x.invariant(p), r)?;
+                }
+                total += v;
+            }
+            _ => return Err(format!("{} has a double pole at {}", r, channel)),
+        }
+    }
+    Ok(total)
+}
+
+/// Value of `Σ c_k N_k / (poles_k)` at a point, failing on a vanishing pole.
+fn value(x: &KinematicPoint, terms: &RationalTerms) -> Result<BigRational, String> {
+    let mut total = BigRational::zero();
+    for (c, r) in terms {
+        let mut v = c * numerator(x, r)?;
+        for p in &r.poles {
+            v /= nonzero(x.invariant(p), r)?;
+        }
+        total += v;
+    }
+    Ok(total)
+}
+
+fn numerator(x: &KinematicPoint, r: &RationalStructure) -> Result<BigRational, String> {
+    x.evaluate(&r.numerator)
+        .ok_or_else(|| format!("cannot evaluate {} numerically", r.numerator))
+}
+
+fn nonzero(v: BigRational, r: &RationalStructure) -> Result<BigRational, String> {
+    if v.is_zero() {
+        Err(format!("a pole of {} vanishes at the sample point", r))
+    } else {
+        Ok(v)
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::BTreeMap;
+
+use num_rational::BigRational;
+
+use crate::{
+    ansatz::Ansatz,
+    dot_product::ScalarFactor,
+    expression::TensorExpression,
+    generator::GenConfig,
+    mandelstam::Invariant,
+    rational::int,
+    rational_structure::RationalStructure,
+    tensor_structure::TensorStructure,
+    topology::{GraphNode, TreeGraph, tree_graphs},
+    types::{LegIndex, Slot},
+};
+
+/// One color-ordered Yang–Mills Feynman diagram of gluons.
+///
+/// The numerator is the product of the color-ordered vertices of
+/// [`berends_giele_current`](crate::amplitudes::berends_giele_current),
+/// contracted into dot products, so that `Σ_g N_g / Π_{I∈g} s_I` over the
+/// diagrams of [`feynman_diagrams`] is the amplitude of
+/// [`berends_giele_amplitude`](crate::amplitudes::berends_giele_amplitude).
+/// It is simplified but not reduced on shell.
+#[derive(Clone, Debug)]
+pub struct FeynmanDiagram {
+    pub graph: TreeGraph,
+    pub numerator: TensorExpression,
+}
+
+impl FeynmanDiagram {
+    /// The Feynman-gauge numerator of a planar graph with cubic and quartic
+    /// vertices; `None` for other graphs.
+    pub fn new(graph: TreeGraph) -> Option<Self> {
+        if !graph.is_planar() || graph.valences().iter().any(|&v| v > 4) {
+            return None;
+        }
+        let n = LegIndex(graph.n_legs);
+        let vertices = graph.vertices();
+        let j = vertex(&vertices, 0);
+        let mut numerator = dot(&j.0, &leg(Slot::E(n)));
+        numerator.simplify();
+        Some(Self { graph, numerator })
+    }
+
+    /// The propagator poles `s_I` of the graph.
+    pub fn poles(&self) -> Vec<Invariant> {
+        self.graph.poles()
+    }
+}
+
+/// All diagrams of the color-ordered amplitude `A(1, …, n)`: the planar
+/// graphs with cubic and quartic vertices, in the order of [`tree_graphs`].
+pub fn feynman_diagrams(n_legs: u8) -> Vec<FeynmanDiagram> {
+    tree_graphs(n_legs, 4)
+        .into_iter()
+        .filter_map(FeynmanDiagram::new)
+        .collect()
+}
+
+/// Decompose a sum of diagrams onto the tensor structures `basis`.
+///
+/// Each numerator is reduced on shell for `cfg` and matched against the
+/// reduced basis; the result lists `c · T / (s_I1 ⋯)` per basis structure
+/// `T` and pole set of a diagram, with exact coefficients, collected and
+/// sorted. A basis with on-shell relations among its structures gives one of
+/// the possible decompositions. Fails if a numerator is not in its span.
+pub fn project_diagrams(
+    cfg: &GenConfig,
+    diagrams: &[FeynmanDiagram],
+    basis: &[TensorStructure],
+) -> Result<Vec<(BigRational, RationalStructure)>, String> {
+    let ansatz = Ansatz::new(cfg, basis.to_vec());
+    let mut terms: BTreeMap<RationalStructure, BigRational> = BTreeMap::new();
+    for d in diagrams {
+        let solution = ansatz
+            .linear_system(cfg, &d.numerator)
+            .solve()
+            .ok_or_else(|| format!("numerator of {} is not in the span of the basis", d.graph))?;
+        let mut poles = d.poles();
+        for p in &mut poles {
+            p.canonicalize(cfg);
+        }
+        poles.sort();
+        for (t, c) in basis.iter().zip(solution.particular()) {
+            *terms
+                .entry(RationalStructure::new(t.clone(), poles.clone()))
+                .or_default() += c;
+        }
+    }
+    terms.retain(|_, c| *c != int(0));
+    Ok(terms.into_iter().map(|(r, c)| (c, r)).collect())
+}
+
+/// A vector `Σ c_k v_k` over momenta and polarizations, with polynomial
+/// coefficients.
+type Vector = BTreeMap<Slot, TensorExpression>;
+
+/// The vertex sum at `v` from the currents of its children, and the total
+/// momentum flowing in.
+fn vertex(vertices: &[Vec<GraphNode>], v: usize) -> (Vector, Vector) {
+    let children: Vec<(Vector, Vector)> = vertices[v][..vertices[v].len() - 1]
+        .iter()
+        .map(|&c| match c {
+            GraphNode::Leg(l) => (leg(Slot::E(l)), leg(Slot::P(l))),
+            GraphNode::Vertex(u) => vertex(vertices, u),
+        })
+        .collect();
+    let mut momentum = Vector::new();
+    for (_, p) in &children {
+        add(&mut momentum, &constant(1), p);
+    }
+    let mut out = Vector::new();
+    match &children[..] {
+        [(j1, p1), (j2, p2)] => {
+            // (J₁·J₂)(P₁ − P₂) + 2 (P₂·J₁) J₂ − 2 (P₁·J₂) J₁
+            let j12 = dot(j1, j2);
+            add(&mut out, &j12, p1);
+            add(&mut out, &-j12.clone(), p2);
+            add(&mut out, &(dot(p2, j1) * int(2)), j2);
+            add(&mut out, &(dot(p1, j2) * int(-2)), j1);
+        }
+        [(j1, _), (j2, _), (j3, _)] => {
+            // 2 (J₁·J₃) J₂ − (J₁·J₂) J₃ − (J₂·J₃) J₁
+            add(&mut out, &(dot(j1, j3) * int(2)), j2);
+            add(&mut out, &-dot(j1, j2), j3);
+            add(&mut out, &-dot(j2, j3), j1);
+        }
+        _ => unreachable!("only cubic and quartic vertices"),
+    }
+    (out, momentum)
+}
+
+fn constant(c: i64) -> TensorExpression {
+    TensorExpression::from(TensorStructure::new()) * int(c)
+}
+
+fn leg(s: Slot) -> Vector {
+    Vector::from([(s, constant(1))])
+}
+
+/// `out += c v`.
+fn add(out: &mut Vector, c: &TensorExpression, v: &Vector) {
+    for (s, d) in v {
+        *out.entry(*s).or_default() += &(c * d);
+    }
+}
+
+fn dot(a: &Vector, b: &Vector) -> TensorExpression {
+    let mut out = TensorExpression::new();
+    for (s, c) in a {
+        for (t, d) in b {
+            let mut f = TensorStructure::new();
+            f.factors.push(ScalarFactor::dot(*s, *t));
+            out += &(&(c * d) * &TensorExpression::from(f));
+        }
+    }
+    out
+}
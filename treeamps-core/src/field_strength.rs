@@ -0,0 +1,330 @@
+use std::{fmt, str::FromStr};
+
+use crate::{
+    catalog::Catalog,
+    dot_product::ScalarFactor,
+    generator::{GenConfig, dedup_structures},
+    tensor_structure::TensorStructure,
+    types::{LegIndex, ScalarKind},
+};
+
+/// A gauge-invariant contraction of linearized field strengths
+/// `F_i^{μν} = p_i^μ e_i^ν − p_i^ν e_i^μ`.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FieldStrengthFactor {
+    /// Closed chain `tr(F_i1 F_i2 ··· F_ik)` with `k ≥ 2`.
+    Trace(Vec<LegIndex>),
+    /// Open chain `p_a·F_i1 ··· F_ik·p_b` with `k ≥ 1`.
+    Chain {
+        left: LegIndex,
+        legs: Vec<LegIndex>,
+        right: LegIndex,
+    },
+}
+
+impl FieldStrengthFactor {
+    /// Bring the chain to canonical form and return the sign picked up.
+    ///
+    /// Traces are cyclic, and since `F^T = −F` reversing a chain of `k` field
+    /// strengths costs `(−1)^k`. The smallest of the equivalent forms is kept;
+    /// `0` is returned if the factor equals minus itself and so vanishes.
+    pub fn canonicalize(&mut self) -> i8 {
+        match self {
+            Self::Trace(legs) => {
+                let k = legs.len();
+                let flip = if k % 2 == 0 { 1 } else { -1 };
+                let mut reversed = legs.clone();
+                reversed.reverse();
+                let mut forms = Vec::with_capacity(2 * k);
+                for (seq, sign) in [(legs.clone(), 1), (reversed, flip)] {
+                    for r in 0..k {
+                        let mut rot = seq.clone();
+                        rot.rotate_left(r);
+                        forms.push((rot, sign));
+                    }
+                }
+                let best = forms
+                    .iter()
+                    .map(|(f, _)| f)
+                    .min()
+                    .cloned()
+                    .unwrap_or_default();
+                let mut signs = forms.iter().filter(|(f, _)| *f == best).map(|&(_, s)| s);
+                let sign = signs.next().unwrap_or(1);
+                let vanishes = signs.any(|s| s != sign);
+                *legs = best;
+                if vanishes { 0 } else { sign }
+            }
+            Self::Chain { left, legs, right } => {
+                let flip = if legs.len() % 2 == 0 { 1 } else { -1 };
+                let mut rev: Vec<LegIndex> = legs.clone();
+                rev.reverse();
+                let forward = (*left, legs.clone(), *right);
+                let backward = (*right, rev, *left);
+                if forward == backward {
+                    return if flip == 1 { 1 } else { 0 };
+                }
+                if backward < forward {
+                    (*left, *legs, *right) = backward;
+                    return flip;
+                }
+                1
+            }
+        }
+    }
+
+    /// Apply the leg relabeling `sigma` and return the canonical image with its sign.
+    pub fn relabel(&self, sigma: &impl Fn(LegIndex) -> LegIndex) -> (Self, i8) {
+        let mut out = match self {
+            Self::Trace(legs) => Self::Trace(legs.iter().map(|&l| sigma(l)).collect()),
+            Self::Chain { left, legs, right } => Self::Chain {
+                left: sigma(*left),
+                legs: legs.iter().map(|&l| sigma(l)).collect(),
+                right: sigma(*right),
+            },
+        };
+        let sign = out.canonicalize();
+        (out, sign)
+    }
+
+    /// Number of field strengths in the chain.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Trace(legs) | Self::Chain { legs, .. } => legs.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// LaTeX form: `(F_1\cdot F_2)`, `\mathrm{tr}(F_1F_2F_3)` or
+    /// `(p_1\cdot F_2F_3\cdot p_4)`.
+    pub fn to_latex(&self) -> String {
+        let chain = |legs: &[LegIndex]| {
+            legs.iter()
+                .map(|l| format!("F_{}", l.latex_subscript()))
+                .collect::<String>()
+        };
+        match self {
+            Self::Trace(legs) if legs.len() == 2 => format!(
+                "(F_{}\\cdot F_{})",
+                legs[0].latex_subscript(),
+                legs[1].latex_subscript()
+            ),
+            Self::Trace(legs) => format!("\\mathrm{{tr}}({})", chain(legs)),
+            Self::Chain { left, legs, right } => format!(
+                "(p_{}\\cdot {}\\cdot p_{})",
+                left.latex_subscript(),
+                chain(legs),
+                right.latex_subscript()
+            ),
+        }
+    }
+}
+
+/// Parses the `Display` forms `(F1·F2)`, `tr(F1·F2·F3)` and `(p1·F2·F3·p4)`,
+/// keeping the legs as written.
+impl FromStr for FieldStrengthFactor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let t = s.trim();
+        let (trace, inner) = match t.strip_prefix("tr(") {
+            Some(rest) => (true, rest.strip_suffix(')')),
+            None => (false, t.strip_prefix('(').and_then(|r| r.strip_suffix(')'))),
+        };
+        let inner = inner.ok_or_else(|| {
+            format!(
+                "expected a field-strength factor like tr(F1·F2), got {:?}",
+                s
+            )
+        })?;
+        let items: Vec<&str> = inner.split('·').map(str::trim).collect();
+        let legs = |items: &[&str]| -> Result<Vec<LegIndex>, String> {
+            items
+                .iter()
+                .map(|x| {
+                    x.strip_prefix('F')
+                        .ok_or_else(|| format!("expected F<leg>, got {:?}", x))?
+                        .parse()
+                })
+                .collect()
+        };
+        let momentum = |x: &str| {
+            x.strip_prefix('p')
+                .ok_or_else(|| format!("expected p<leg>, got {:?}", x))?
+                .parse()
+        };
+        match items[..] {
+            [first, ref middle @ .., last] if !trace && first.starts_with('p') => Ok(Self::Chain {
+                left: momentum(first)?,
+                legs: legs(middle)?,
+                right: momentum(last)?,
+            }),
+            _ => Ok(Self::Trace(legs(&items)?)),
+        }
+    }
+}
+
+impl fmt::Display for FieldStrengthFactor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let chain = |legs: &[LegIndex]| {
+            legs.iter()
+                .map(|l| format!("F{}", l.0))
+                .collect::<Vec<_>>()
+                .join("·")
+        };
+        match self {
+            Self::Trace(legs) if legs.len() == 2 => write!(f, "({})", chain(legs)),
+            Self::Trace(legs) => write!(f, "tr({})", chain(legs)),
+            Self::Chain { left, legs, right } => {
+                write!(f, "(p{}·{}·p{})", left.0, chain(legs), right.0)
+            }
+        }
+    }
+}
+
+/// Enumerate gauge-invariant structures in which every polarization enters
+/// through a field strength.
+///
+/// Each leg contributes as many `F_i` as its polarization target (one per
+/// gluon, two per graviton). The field strengths are distributed over traces
+/// and momentum-capped chains, and PP factors from the catalog fill up the
+/// remaining momenta. `extra_momenta` counts momenta beyond the one inside
+/// each `F_i`: two per chain and two per PP factor, so odd values yield nothing.
+/// Chain ends avoid the eliminated momentum and `p_i·F_i = 0`.
+pub fn generate_field_strength_structures(
+    cfg: &GenConfig,
+    extra_momenta: u32,
+) -> Vec<TensorStructure> {
+    if !extra_momenta.is_multiple_of(2) {
+        return Vec::new();
+    }
+    let targets = cfg.polarization_targets();
+    let mut strengths = Vec::new();
+    for i in 1..=cfg.n_legs {
+        let leg = LegIndex(i);
+        let count = match &targets {
+            Some(t) => t[i as usize],
+            None => cfg.has_polarization(leg) as u32,
+        };
+        strengths.extend(std::iter::repeat_n(leg, count as usize));
+    }
+
+    let elim = cfg.eliminated_leg();
+    let momenta: Vec<LegIndex> = (1..=cfg.n_legs)
+        .map(LegIndex)
+        .filter(|&l| Some(l) != elim)
+        .collect();
+    let catalog = Catalog::new(cfg);
+    let pp = catalog.of_kind(ScalarKind::PP);
+
+    let mut search = FieldStrengthSearch {
+        momenta: &momenta,
+        pp,
+        cur: Vec::new(),
+        out: Vec::new(),
+    };
+    search.place(&strengths, extra_momenta / 2);
+    dedup_structures(&mut search.out, cfg.deduplication);
+    search.out
+}
+
+struct FieldStrengthSearch<'a> {
+    momenta: &'a [LegIndex],
+    pp: &'a [ScalarFactor],
+    cur: Vec<FieldStrengthFactor>,
+    out: Vec<TensorStructure>,
+}
+
+impl FieldStrengthSearch<'_> {
+    /// Open a new trace or chain at the first remaining field strength.
+    fn place(&mut self, remaining: &[LegIndex], pairs: u32) {
+        let Some((&first, rest)) = remaining.split_first() else {
+            self.fill_pp(pairs, 0, &mut Vec::new());
+            return;
+        };
+        let mut used = vec![false; rest.len()];
+        self.extend(first, &mut vec![first], rest, &mut used, pairs);
+    }
+
+    /// Grow the current sequence by one more field strength, or close it off.
+    fn extend(
+        &mut self,
+        first: LegIndex,
+        seq: &mut Vec<LegIndex>,
+        rest: &[LegIndex],
+        used: &mut [bool],
+        pairs: u32,
+    ) {
+        let left: Vec<LegIndex> = rest
+            .iter()
+            .zip(used.iter())
+            .filter(|&(_, &u)| !u)
+            .map(|(&l, _)| l)
+            .collect();
+
+        if seq.len() >= 2 {
+            self.close(FieldStrengthFactor::Trace(seq.clone()), &left, pairs);
+        }
+        if pairs > 0 {
+            let last = *seq.last().unwrap_or(&first);
+            for &a in self.momenta {
+                for &b in self.momenta {
+                    if a == first || b == last {
+                        continue;
+                    }
+                    let chain = FieldStrengthFactor::Chain {
+                        left: a,
+                        legs: seq.clone(),
+                        right: b,
+                    };
+                    self.close(chain, &left, pairs - 1);
+                }
+            }
+        }
+
+        for k in 0..rest.len() {
+            // Skip repeats of an identical leg so each arrangement is tried once.
+            if used[k] || (k > 0 && rest[k] == rest[k - 1] && !used[k - 1]) {
+                continue;
+            }
+            used[k] = true;
+            seq.push(rest[k]);
+            self.extend(first, seq, rest, used, pairs);
+            seq.pop();
+            used[k] = false;
+        }
+    }
+
+    fn close(&mut self, mut factor: FieldStrengthFactor, left: &[LegIndex], pairs: u32) {
+        if factor.canonicalize() == 0 {
+            return;
+        }
+        self.cur.push(factor);
+        self.place(left, pairs);
+        self.cur.pop();
+    }
+
+    /// Multiply by `pairs` PP factors, in non-decreasing catalog order.
+    fn fill_pp(&mut self, pairs: u32, start: usize, chosen: &mut Vec<ScalarFactor>) {
+        if pairs == 0 {
+            let mut t = TensorStructure {
+                factors: chosen.as_slice().into(),
+                field_strengths: self.cur.clone(),
+                ..TensorStructure::default()
+            };
+            if t.canonicalize() != 0 {
+                self.out.push(t);
+            }
+            return;
+        }
+        for i in start..self.pp.len() {
+            chosen.push(self.pp[i].clone());
+            self.fill_pp(pairs - 1, i, chosen);
+            chosen.pop();
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use std::{
+    fmt,
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::coefficient::Coefficient;
+
+/// An element of the prime field `F_P`, stored as its representative in `0..P`.
+///
+/// `P` must be a prime below `2^63`, so sums fit in a `u64`; products go
+/// through `u128`.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Fp<const P: u64>(u64);
+
+/// The field modulo the Mersenne prime `2^61 − 1`.
+pub type Fp61 = Fp<{ (1 << 61) - 1 }>;
+
+impl<const P: u64> Fp<P> {
+    pub const MODULUS: u64 = P;
+
+    pub fn new(x: u64) -> Self {
+        const { assert!(P > 1 && P < 1 << 63, "modulus must be a prime below 2^63") };
+        Self(x % P)
+    }
+
+    pub fn from_i64(x: i64) -> Self {
+        let r = Self::new(x.unsigned_abs());
+        if x < 0 { -r } else { r }
+    }
+
+    /// The representative in `0..P`.
+    pub fn value(self) -> u64 {
+        self.0
+    }
+
+    pub fn pow(self, mut exp: u64) -> Self {
+        let (mut base, mut acc) = (self, Self::one());
+        while exp > 0 {
+            if exp & 1 == 1 {
+                acc *= base;
+            }
+            base *= base;
+            exp >>= 1;
+        }
+        acc
+    }
+
+    /// Multiplicative inverse by Fermat's little theorem, `None` for zero.
+    pub fn inv(self) -> Option<Self> {
+        (self.0 != 0).then(|| self.pow(P - 2))
+    }
+
+    /// The image of an integer of any size.
+    pub fn from_bigint(x: &BigInt) -> Self {
+        let r = Self::new((x.magnitude() % P).to_u64().expect("reduced below P"));
+        if x.is_negative() { -r } else { r }
+    }
+}
+
+impl<const P: u64> Coefficient for Fp<P> {
+    fn from_rational(c: &BigRational) -> Option<Self> {
+        let den = Self::from_bigint(c.denom()).inv()?;
+        Some(Self::from_bigint(c.numer()) * den)
+    }
+
+    /// A uniform element of `F_P`.
+    fn from_random_bits(bits: u64) -> Self {
+        Self::new(bits)
+    }
+}
+
+impl<const P: u64> Zero for Fp<P> {
+    fn zero() -> Self {
+        Self(0)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl<const P: u64> One for Fp<P> {
+    fn one() -> Self {
+        Self::new(1)
+    }
+}
+
+impl<const P: u64> Add for Fp<P> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self((self.0 + rhs.0) % P)
+    }
+}
+
+impl<const P: u64> Sub for Fp<P> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self((self.0 + P - rhs.0) % P)
+    }
+}
+
+impl<const P: u64> Neg for Fp<P> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self((P - self.0) % P)
+    }
+}
+
+impl<const P: u64> Mul for Fp<P> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self((self.0 as u128 * rhs.0 as u128 % P as u128) as u64)
+    }
+}
+
+/// # Panics
+///
+/// Panics on division by zero.
+impl<const P: u64> Div for Fp<P> {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        Mul::mul(self, rhs.inv().expect("division by zero in F_p"))
+    }
+}
+
+impl<const P: u64> AddAssign for Fp<P> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<const P: u64> SubAssign for Fp<P> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<const P: u64> MulAssign for Fp<P> {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl<const P: u64> DivAssign for Fp<P> {
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs;
+    }
+}
+
+impl<const P: u64> fmt::Display for Fp<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
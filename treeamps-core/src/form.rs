@@ -0,0 +1,223 @@
+//! Structures and expressions as FORM input.
+
+use crate::{
+    bilinear::BilinearFactor,
+    coefficient::Coefficient,
+    dot_product::ScalarFactor,
+    epsilon::EpsilonFactor,
+    expression::TensorExpression,
+    field_strength::FieldStrengthFactor,
+    tensor_structure::TensorStructure,
+    types::{LegIndex, ScalarKind, Slot},
+};
+
+/// Names under which structures are written for FORM.
+///
+/// The defaults use FORM's built-in `d_` and `e_` for dot products and the
+/// Levi-Civita tensor; the other functions are user-defined and listed by
+/// [`declarations`](Self::declarations). A bilinear `ū_i γ·v u_j` becomes
+/// `UB(i)*g_(k,v)*U(j)` with its own spin line `k`, so the gamma matrices
+/// always use the built-in `g_`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FormNames {
+    /// Prefix of the external momenta `p1, p2, ...`.
+    pub momentum: String,
+    /// Prefix of the polarization vectors `e1, e2, ...`.
+    pub polarization: String,
+    /// Prefix of the loop momenta `l1, l2, ...`.
+    pub loop_momentum: String,
+    pub dot: String,
+    pub epsilon: String,
+    /// The angle bracket `⟨ij⟩`.
+    pub angle: String,
+    /// The square bracket `[ij]`.
+    pub square: String,
+    /// A field-strength trace `tr(F_i F_j ...)`, by leg.
+    pub trace: String,
+    /// A chain `p_a·F_i F_j ...·p_b`, with the end momenta first and last.
+    pub chain: String,
+    /// The barred external spinor `ū_i`.
+    pub spinor_bar: String,
+    /// The external spinor `u_j`.
+    pub spinor: String,
+}
+
+impl Default for FormNames {
+    fn default() -> Self {
+        Self {
+            momentum: "p".to_string(),
+            polarization: "e".to_string(),
+            loop_momentum: "l".to_string(),
+            dot: "d_".to_string(),
+            epsilon: "e_".to_string(),
+            angle: "spa".to_string(),
+            square: "spb".to_string(),
+            trace: "trF".to_string(),
+            chain: "chF".to_string(),
+            spinor_bar: "UB".to_string(),
+            spinor: "U".to_string(),
+        }
+    }
+}
+
+impl FormNames {
+    /// Rename one kind of function or vector, e.g. `set("dot", "SP")`; the
+    /// kinds are the field names.
+    pub fn set(&mut self, kind: &str, name: &str) -> Result<(), String> {
+        let valid = name.starts_with(|c: char| c.is_ascii_alphabetic())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(format!("invalid FORM name {:?}", name));
+        }
+        let field = match kind {
+            "momentum" => &mut self.momentum,
+            "polarization" => &mut self.polarization,
+            "loop_momentum" => &mut self.loop_momentum,
+            "dot" => &mut self.dot,
+            "epsilon" => &mut self.epsilon,
+            "angle" => &mut self.angle,
+            "square" => &mut self.square,
+            "trace" => &mut self.trace,
+            "chain" => &mut self.chain,
+            "spinor_bar" => &mut self.spinor_bar,
+            "spinor" => &mut self.spinor,
+            _ => return Err(format!("unknown FORM name kind {:?}", kind)),
+        };
+        *field = name.to_string();
+        Ok(())
+    }
+
+    /// Declarations of the vectors of `n_legs` legs and `n_loops` loop
+    /// momenta and of every function that is not a FORM built-in.
+    ///
+    /// The symmetry of each function is declared with it, so FORM orders
+    /// their arguments itself.
+    pub fn declarations(&self, n_legs: u8, n_loops: u8) -> String {
+        let mut vectors = Vec::new();
+        for prefix in [&self.momentum, &self.polarization] {
+            vectors.extend((1..=n_legs).map(|i| format!("{}{}", prefix, i)));
+        }
+        vectors.extend((1..=n_loops).map(|i| format!("{}{}", self.loop_momentum, i)));
+
+        let user = |name: &String| !name.ends_with('_');
+        let commuting: Vec<String> = [
+            (&self.dot, "symmetric"),
+            (&self.epsilon, "antisymmetric"),
+            (&self.angle, "antisymmetric"),
+            (&self.square, "antisymmetric"),
+            (&self.trace, "cyclic"),
+        ]
+        .into_iter()
+        .filter(|(name, _)| user(name))
+        .map(|(name, symmetry)| format!("{}({})", name, symmetry))
+        .chain(user(&self.chain).then(|| self.chain.clone()))
+        .collect();
+        let spinors: Vec<String> = [&self.spinor_bar, &self.spinor]
+            .into_iter()
+            .filter(|name| user(name))
+            .cloned()
+            .collect();
+
+        let mut out = String::new();
+        for (keyword, names) in [
+            ("Vectors", vectors),
+            ("CFunctions", commuting),
+            ("Functions", spinors),
+        ] {
+            if !names.is_empty() {
+                out += &format!("{} {};\n", keyword, names.join(","));
+            }
+        }
+        out
+    }
+
+    fn slot(&self, s: Slot) -> String {
+        match s {
+            Slot::P(l) => format!("{}{}", self.momentum, l.0),
+            Slot::E(l) => format!("{}{}", self.polarization, l.0),
+        }
+    }
+
+    fn factor(&self, f: &ScalarFactor) -> String {
+        let loop_momentum = |l: LegIndex| format!("{}{}", self.loop_momentum, l.0);
+        let (x, y) = match f.kind {
+            ScalarKind::Angle => return format!("{}({},{})", self.angle, f.a.0, f.b.0),
+            ScalarKind::Square => return format!("{}({},{})", self.square, f.a.0, f.b.0),
+            ScalarKind::PP => (self.slot(Slot::P(f.a)), self.slot(Slot::P(f.b))),
+            ScalarKind::PE => (self.slot(Slot::P(f.a)), self.slot(Slot::E(f.b))),
+            ScalarKind::EE => (self.slot(Slot::E(f.a)), self.slot(Slot::E(f.b))),
+            ScalarKind::LP => (loop_momentum(f.a), self.slot(Slot::P(f.b))),
+            ScalarKind::LE => (loop_momentum(f.a), self.slot(Slot::E(f.b))),
+            ScalarKind::LL => (loop_momentum(f.a), loop_momentum(f.b)),
+        };
+        format!("{}({},{})", self.dot, x, y)
+    }
+
+    fn epsilon(&self, e: &EpsilonFactor) -> String {
+        let slots: Vec<String> = e.slots.iter().map(|&s| self.slot(s)).collect();
+        format!("{}({})", self.epsilon, slots.join(","))
+    }
+
+    /// The bilinear on spin line `line`.
+    fn bilinear(&self, b: &BilinearFactor, line: usize) -> String {
+        format!(
+            "{}({})*g_({},{})*{}({})",
+            self.spinor_bar,
+            b.bar.0,
+            line,
+            self.slot(b.gamma),
+            self.spinor,
+            b.u.0
+        )
+    }
+
+    fn field_strength(&self, f: &FieldStrengthFactor) -> String {
+        let legs = |legs: &[LegIndex]| {
+            legs.iter()
+                .map(|l| l.0.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        match f {
+            FieldStrengthFactor::Trace(ls) => format!("{}({})", self.trace, legs(ls)),
+            FieldStrengthFactor::Chain {
+                left,
+                legs: ls,
+                right,
+            } => format!(
+                "{}({},{},{})",
+                self.chain,
+                self.slot(Slot::P(*left)),
+                legs(ls),
+                self.slot(Slot::P(*right))
+            ),
+        }
+    }
+}
+
+impl TensorStructure {
+    /// FORM product of the factors, e.g. `d_(p1,e2)*d_(e3,e4)`, or `1` for
+    /// the empty product.
+    pub fn to_form(&self, names: &FormNames) -> String {
+        if self.degree() == 0 {
+            return "1".to_string();
+        }
+        self.bilinears
+            .iter()
+            .enumerate()
+            .map(|(k, b)| names.bilinear(b, k + 1))
+            .chain(self.epsilon.iter().map(|e| names.epsilon(e)))
+            .chain(self.field_strengths.iter().map(|x| names.field_strength(x)))
+            .chain(self.factors.iter().map(|x| names.factor(x)))
+            .collect::<Vec<_>>()
+            .join("*")
+    }
+}
+
+impl<C: Coefficient> TensorExpression<C> {
+    /// FORM sum of the terms, e.g. `d_(p1,p2) - 1/2*d_(e1,e2)`, or `0`.
+    pub fn to_form(&self, names: &FormNames) -> String {
+        self.to_sum(|t| t.to_form(names))
+    }
+}
@@ -1,17 +1,110 @@
-use std::collections::BTreeSet;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt, io,
+    ops::RangeInclusive,
+    str::FromStr,
+    sync::Arc,
+};
+
+use rustc_hash::FxHashSet;
 
 use crate::{
+    bilinear::BilinearFactor,
+    catalog::Catalog,
     dot_product::ScalarFactor,
+    epsilon::EpsilonFactor,
+    hilbert::closed_form_count,
+    spill::{SpillConfig, SpilledStructures, sort_to_disk},
     tensor_structure::TensorStructure,
-    types::{LegIndex, PolarizationPattern, ScalarKind, Transversality},
+    types::{
+        Canonicalization, Deduplication, Helicity, LegIndex, Mass, PeExclusion,
+        PolarizationPattern, ScalarKind, Slot, Spin, Statistics, Transversality,
+    },
 };
 
 /// High-level configuration describing which tensors are allowed.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GenConfig {
     pub n_legs: u8,
+    /// Default transversality rule for legs without an entry in `leg_transversality`.
     pub transversality: Transversality,
+    /// Per-leg transversality overrides indexed by `leg - 1`.
+    pub leg_transversality: Vec<Transversality>,
     pub pol_pattern: PolarizationPattern,
+    /// Whether one momentum is eliminated at all; `false` keeps the redundant basis.
+    pub eliminate_momentum: bool,
+    /// Leg whose momentum is removed by momentum conservation; `None` means the last leg.
+    /// Must be `None` without `eliminate_momentum`, see [`GenConfig::validate`].
+    pub eliminated_momentum: Option<LegIndex>,
+    /// Extra `p·e` factor removed by momentum conservation on the eliminated leg.
+    pub pe_exclusion: PeExclusion,
+    /// Per-leg masses indexed by `leg - 1`; legs beyond the end are massless.
+    pub masses: Vec<Mass>,
+    /// Include `p_i·p_i` factors for massive legs.
+    pub include_mass_squares: bool,
+    /// Per-leg spins indexed by `leg - 1`; legs beyond the end are vectors.
+    pub spins: Vec<Spin>,
+    /// Also enumerate parity-odd structures containing one Levi-Civita factor.
+    pub include_parity_odd: bool,
+    /// Per-leg statistics indexed by `leg - 1`; legs beyond the end are bosons.
+    pub statistics: Vec<Statistics>,
+    /// Factors removed from the catalog before the search.
+    pub forbidden_factors: Vec<ScalarFactor>,
+    /// Bounds on how often individual factors may appear in a structure.
+    pub multiplicity_bounds: Vec<MultiplicityBound>,
+    /// Maximum number of PP factors per structure; `None` for no cap.
+    pub max_pp: Option<u32>,
+    /// Which leg relabelings identify structures in the output.
+    pub canonicalization: Canonicalization,
+    /// How structures that canonicalize alike are deduplicated.
+    pub deduplication: Deduplication,
+    /// Off-shell legs such as the operator momentum `q` of a form factor.
+    ///
+    /// They carry no polarization and keep `q·q` in the catalog.
+    pub off_shell_legs: Vec<LegIndex>,
+    /// Number of loop momenta `l_1..l_L` added to the alphabet.
+    pub n_loops: u8,
+    /// Maximum total power of loop momenta per structure; `None` for no cap.
+    pub max_loop_power: Option<u32>,
+    /// Exact total momentum power of every structure (see
+    /// [`TensorStructure::momentum_power`]); `None` for no constraint.
+    pub momentum_power: Option<u32>,
+    /// Per-leg helicities indexed by `leg - 1`, restricting to one helicity
+    /// sector; empty for none. All `+` is the self-dual sector, all `-` the
+    /// anti-self-dual one. See [`GenConfig::helicity_vanishes`].
+    pub helicities: Vec<Helicity>,
+    /// Most structures one generation call may return; `None` for no limit.
+    /// See [`try_generate_tensor_structures`].
+    pub max_structures: Option<u64>,
+}
+
+/// Requires `factor` to appear between `min` and `max` times (inclusive) in every structure.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MultiplicityBound {
+    pub factor: ScalarFactor,
+    pub min: u32,
+    /// Upper bound; `None` leaves the multiplicity unbounded.
+    pub max: Option<u32>,
+}
+
+impl MultiplicityBound {
+    pub fn at_most(factor: ScalarFactor, max: u32) -> Self {
+        Self {
+            factor,
+            min: 0,
+            max: Some(max),
+        }
+    }
+
+    pub fn at_least(factor: ScalarFactor, min: u32) -> Self {
+        Self {
+            factor,
+            min,
+            max: None,
+        }
+    }
 }
 
 impl Default for GenConfig {
@@ -19,205 +112,939 @@ impl Default for GenConfig {
         Self {
             n_legs: 3,
             transversality: Transversality::ForbidPiDotEi,
+            leg_transversality: Vec::new(),
             pol_pattern: PolarizationPattern::OnePerLeg,
+            eliminate_momentum: true,
+            eliminated_momentum: None,
+            pe_exclusion: PeExclusion::Conventional,
+            masses: Vec::new(),
+            include_mass_squares: false,
+            spins: Vec::new(),
+            include_parity_odd: false,
+            statistics: Vec::new(),
+            forbidden_factors: Vec::new(),
+            multiplicity_bounds: Vec::new(),
+            max_pp: None,
+            canonicalization: Canonicalization::Labelled,
+            deduplication: Deduplication::Ordered,
+            off_shell_legs: Vec::new(),
+            n_loops: 0,
+            max_loop_power: None,
+            momentum_power: None,
+            helicities: Vec::new(),
+            max_structures: None,
+        }
+    }
+}
+
+impl GenConfig {
+    /// Mass of a given leg.
+    pub fn mass(&self, leg: LegIndex) -> Mass {
+        self.masses
+            .get(leg.0 as usize - 1)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Spin of a given leg.
+    pub fn spin(&self, leg: LegIndex) -> Spin {
+        self.spins
+            .get(leg.0 as usize - 1)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Statistics of a given leg.
+    pub fn statistics(&self, leg: LegIndex) -> Statistics {
+        self.statistics
+            .get(leg.0 as usize - 1)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn is_fermion(&self, leg: LegIndex) -> bool {
+        self.statistics(leg) == Statistics::Fermion
+    }
+
+    pub fn is_off_shell(&self, leg: LegIndex) -> bool {
+        self.off_shell_legs.contains(&leg)
+    }
+
+    /// Whether `p_i²` may be nonzero: the leg is massive or off shell.
+    pub fn has_virtuality(&self, leg: LegIndex) -> bool {
+        self.mass(leg) == Mass::Massive || self.is_off_shell(leg)
+    }
+
+    /// Whether the leg carries a polarization vector at all.
+    pub fn has_polarization(&self, leg: LegIndex) -> bool {
+        self.spin(leg) != Spin::Scalar && !self.is_fermion(leg) && !self.is_off_shell(leg)
+    }
+
+    /// Required number of polarizations per leg, indexed by leg (slot 0 unused).
+    ///
+    /// `None` when the pattern places no per-leg constraint.
+    pub fn polarization_targets(&self) -> Option<Vec<u32>> {
+        match &self.pol_pattern {
+            PolarizationPattern::Unrestricted => None,
+            PolarizationPattern::ExactlyPerLeg(counts) => Some(
+                std::iter::once(0)
+                    .chain(
+                        (1..=self.n_legs)
+                            .map(|i| counts.get(i as usize - 1).map_or(0, |&k| k as u32)),
+                    )
+                    .collect(),
+            ),
+            PolarizationPattern::OnePerLeg => Some(
+                std::iter::once(0)
+                    .chain((1..=self.n_legs).map(|i| {
+                        if self.has_polarization(LegIndex(i)) {
+                            self.spin(LegIndex(i)).polarization_count()
+                        } else {
+                            0
+                        }
+                    }))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Transversality rule of a given leg, falling back to the global setting.
+    pub fn transversality_of(&self, leg: LegIndex) -> Transversality {
+        self.leg_transversality
+            .get(leg.0 as usize - 1)
+            .copied()
+            .unwrap_or(self.transversality)
+    }
+
+    /// Whether `p_i·e_i` is forbidden for this leg.
+    ///
+    /// Massive and off-shell legs are never treated as transverse.
+    pub fn is_transverse(&self, leg: LegIndex) -> bool {
+        matches!(self.transversality_of(leg), Transversality::ForbidPiDotEi)
+            && !self.has_virtuality(leg)
+    }
+
+    /// Checks that the settings can be combined.
+    ///
+    /// `eliminated_momentum` must name a leg, and only when
+    /// `eliminate_momentum` is set. Cyclic canonicalization rotates the legs,
+    /// which moves the eliminated momentum onto another leg, so it needs
+    /// `eliminate_momentum = false`.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(k) = self.eliminated_momentum {
+            if !self.eliminate_momentum {
+                return Err(format!(
+                    "eliminated_momentum = p{} contradicts eliminate_momentum = false",
+                    k.0
+                ));
+            }
+            if k.0 == 0 || k.0 > self.n_legs {
+                return Err(format!(
+                    "eliminated leg {} is not between 1 and {}",
+                    k.0, self.n_legs
+                ));
+            }
+        }
+        if self.canonicalization == Canonicalization::Cyclic && self.eliminate_momentum {
+            return Err("cyclic canonicalization needs eliminate_momentum = false: \
+                 rotating the legs moves the eliminated momentum"
+                .to_string());
+        }
+        Ok(())
+    }
+
+    /// Leg whose momentum never appears in any factor, if one is eliminated.
+    pub fn eliminated_leg(&self) -> Option<LegIndex> {
+        if !self.eliminate_momentum {
+            return None;
+        }
+        Some(self.eliminated_momentum.unwrap_or(LegIndex(self.n_legs)))
+    }
+
+    /// Helicity of a leg, if a helicity sector is set.
+    pub fn helicity(&self, leg: LegIndex) -> Option<Helicity> {
+        self.helicities.get(leg.0 as usize - 1).copied()
+    }
+
+    /// Reference leg whose momentum is the gauge vector `q` of all legs of helicity `h`.
+    ///
+    /// Legs of one helicity share a reference, the first leg of the opposite
+    /// helicity; `None` if there is none and the reference is a generic vector.
+    pub fn reference_leg(&self, h: Helicity) -> Option<LegIndex> {
+        (1..=self.n_legs)
+            .map(LegIndex)
+            .find(|&l| self.helicity(l).is_some_and(|x| x != h))
+    }
+
+    /// Whether `f` vanishes for the reference choice of the helicity sector.
+    ///
+    /// With `e_i^±` built on the shared references of [`GenConfig::reference_leg`],
+    /// `e_i·e_j` vanishes for equal helicities and whenever one leg is the
+    /// other's reference, and `q·e_i` vanishes for the reference momentum `q`.
+    /// If `q` is the eliminated momentum, `q·e_i = 0` instead becomes a sum
+    /// over the other momenta and removes the first `p_m·e_i` left in it.
+    /// Without a helicity sector nothing vanishes.
+    pub fn helicity_vanishes(&self, f: &ScalarFactor) -> bool {
+        match f.kind {
+            ScalarKind::EE => match (self.helicity(f.a), self.helicity(f.b)) {
+                (Some(x), Some(y)) => {
+                    x == y
+                        || self.reference_leg(x) == Some(f.b)
+                        || self.reference_leg(y) == Some(f.a)
+                }
+                _ => false,
+            },
+            ScalarKind::PE => {
+                let Some(r) = self.helicity(f.b).and_then(|h| self.reference_leg(h)) else {
+                    return false;
+                };
+                if f.a == r {
+                    return true;
+                }
+                let stand_in = (1..=self.n_legs)
+                    .map(LegIndex)
+                    .find(|&m| m != r && !(m == f.b && self.is_transverse(m)));
+                self.eliminated_leg() == Some(r) && stand_in == Some(f.a)
+            }
+            _ => false,
+        }
+    }
+
+    /// The `(momentum, polarization)` legs of the excluded `p·e` factor, if any.
+    ///
+    /// Without momentum elimination there is no relation to exploit, so nothing is excluded.
+    pub fn excluded_pe(&self) -> Option<(LegIndex, LegIndex)> {
+        let k = self.eliminated_leg()?;
+        match self.pe_exclusion {
+            PeExclusion::None => None,
+            PeExclusion::Explicit {
+                momentum,
+                polarization,
+            } => Some((momentum, polarization)),
+            PeExclusion::Conventional => {
+                // The relation `Σ_{i≠k} p_i·e_k = 0` needs `p_k·e_k = 0`: for a
+                // massive or non-transverse eliminated leg `p_k·e_k` survives and
+                // absorbs it, and an off-shell one has no `e_k` to begin with.
+                if !self.is_transverse(k) {
+                    return None;
+                }
+                let m = (1..=self.n_legs).find(|&i| {
+                    i != k.0 && !self.helicity_vanishes(&ScalarFactor::pe(LegIndex(i), k))
+                })?;
+                Some((LegIndex(m), k))
+            }
         }
     }
 }
 
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CatalogCounts {
     pub num_pp: usize,
     pub num_pe: usize,
     pub num_ee: usize,
+    /// Factors involving a loop momentum (`l·p`, `l·e` and `l·l`).
+    pub num_loop: usize,
 }
 
 fn generate_valid_factors(
     cfg: &GenConfig,
 ) -> (Vec<ScalarFactor>, Vec<ScalarFactor>, Vec<ScalarFactor>) {
-    let n = cfg.n_legs as u8;
+    let n = cfg.n_legs;
+    let elim = cfg.eliminated_leg().map(|l| l.0);
+    let excluded_pe = cfg.excluded_pe();
 
     let mut pp = Vec::new();
     let mut pe = Vec::new();
     let mut ee = Vec::new();
 
-    // PP factors: forbid any factor involving p_n (momentum of leg n)
+    // PP factors: forbid any factor involving the eliminated momentum;
+    // p_i·p_i only survives on-shell for massive legs, and always off shell.
     for i in 1..=n {
-        for j in (i + 1)..=n {
-            if i == n || j == n {
+        let square_allowed = (cfg.include_mass_squares && cfg.mass(LegIndex(i)) == Mass::Massive)
+            || cfg.is_off_shell(LegIndex(i));
+        let j_start = if square_allowed { i } else { i + 1 };
+        for j in j_start..=n {
+            if elim == Some(i) || elim == Some(j) {
                 continue;
             }
             pp.push(ScalarFactor::pp(LegIndex(i), LegIndex(j)));
         }
     }
 
-    // PE factors: forbid p_n as momentum, and forbid p_1·e_n
+    // PE factors: forbid the eliminated momentum, and the excluded p·e factor
     for i in 1..=n {
-        if i == n {
+        if elim == Some(i) {
             continue;
         }
         for j in 1..=n {
-            if matches!(cfg.transversality, Transversality::ForbidPiDotEi) && i == j {
+            if !cfg.has_polarization(LegIndex(j)) {
+                continue;
+            }
+            if i == j && cfg.is_transverse(LegIndex(i)) {
                 continue;
             }
-            if j == n && i == 1 {
+            if excluded_pe == Some((LegIndex(i), LegIndex(j))) {
                 continue;
             }
             pe.push(ScalarFactor::pe(LegIndex(i), LegIndex(j)));
         }
     }
 
-    // EE factors
+    // EE factors: only between legs that carry polarizations
     for i in 1..=n {
+        if !cfg.has_polarization(LegIndex(i)) {
+            continue;
+        }
         for j in (i + 1)..=n {
+            if !cfg.has_polarization(LegIndex(j)) {
+                continue;
+            }
             ee.push(ScalarFactor::ee(LegIndex(i), LegIndex(j)));
         }
     }
 
-    pp.sort();
-    pe.sort();
-    ee.sort();
+    for list in [&mut pp, &mut pe, &mut ee] {
+        list.retain(|f| !cfg.forbidden_factors.contains(f) && !cfg.helicity_vanishes(f));
+        list.sort();
+    }
 
     (pp, pe, ee)
 }
 
+/// Loop-momentum factors `l_a·p_j`, `l_a·e_j` and `l_a·l_b`.
+///
+/// The loop momenta are independent of the external ones, so only the
+/// eliminated momentum is dropped; `l·e_j` needs `e_j` to exist.
+fn generate_loop_factors(cfg: &GenConfig) -> Vec<ScalarFactor> {
+    let elim = cfg.eliminated_leg();
+    let mut out = Vec::new();
+    for a in 1..=cfg.n_loops {
+        let l = LegIndex(a);
+        for j in 1..=cfg.n_legs {
+            if Some(LegIndex(j)) != elim {
+                out.push(ScalarFactor::lp(l, LegIndex(j)));
+            }
+            if cfg.has_polarization(LegIndex(j)) {
+                out.push(ScalarFactor::le(l, LegIndex(j)));
+            }
+        }
+        for b in a..=cfg.n_loops {
+            out.push(ScalarFactor::ll(l, LegIndex(b)));
+        }
+    }
+    out.retain(|f| !cfg.forbidden_factors.contains(f));
+    out.sort();
+    out
+}
+
+/// Momenta and polarizations that may fill a contraction slot, in ascending order.
+fn allowed_slots(cfg: &GenConfig) -> Vec<Slot> {
+    let elim = cfg.eliminated_leg();
+    let mut vectors = Vec::new();
+    for i in 1..=cfg.n_legs {
+        if Some(LegIndex(i)) != elim {
+            vectors.push(Slot::P(LegIndex(i)));
+        }
+    }
+    for i in 1..=cfg.n_legs {
+        if cfg.has_polarization(LegIndex(i)) {
+            vectors.push(Slot::E(LegIndex(i)));
+        }
+    }
+    vectors
+}
+
+/// All canonical ε factors built from allowed momenta and polarizations.
+pub(crate) fn generate_epsilon_factors(cfg: &GenConfig) -> Vec<EpsilonFactor> {
+    let vectors = allowed_slots(cfg);
+
+    let m = vectors.len();
+    let mut out = Vec::new();
+    for a in 0..m {
+        for b in (a + 1)..m {
+            for c in (b + 1)..m {
+                for d in (c + 1)..m {
+                    out.push(EpsilonFactor {
+                        slots: [vectors[a], vectors[b], vectors[c], vectors[d]],
+                    });
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Every way of tying the external fermions into bilinears `ū_i γ·v u_j`.
+///
+/// Fermions are paired in all possible ways with the lower leg barred; the
+/// Dirac equation removes `γ·p_i` and `γ·p_j` from a bilinear on legs `i, j`.
+/// Returns a single empty set when there are no fermions and none when their
+/// number is odd.
+pub(crate) fn generate_bilinear_sets(cfg: &GenConfig) -> Vec<Vec<BilinearFactor>> {
+    let fermions: Vec<LegIndex> = (1..=cfg.n_legs)
+        .map(LegIndex)
+        .filter(|&l| cfg.is_fermion(l))
+        .collect();
+    if fermions.len() % 2 == 1 {
+        return Vec::new();
+    }
+    let slots = allowed_slots(cfg);
+
+    fn pair_up(
+        rest: &[LegIndex],
+        slots: &[Slot],
+        cur: &mut Vec<BilinearFactor>,
+        out: &mut Vec<Vec<BilinearFactor>>,
+    ) {
+        let Some((&first, rest)) = rest.split_first() else {
+            let mut set = cur.clone();
+            set.sort();
+            out.push(set);
+            return;
+        };
+        for (k, &partner) in rest.iter().enumerate() {
+            let remaining: Vec<LegIndex> = rest
+                .iter()
+                .enumerate()
+                .filter(|&(m, _)| m != k)
+                .map(|(_, &l)| l)
+                .collect();
+            for &gamma in slots {
+                if gamma == Slot::P(first) || gamma == Slot::P(partner) {
+                    continue;
+                }
+                cur.push(BilinearFactor::new(first, gamma, partner));
+                pair_up(&remaining, slots, cur, out);
+                cur.pop();
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    pair_up(&fermions, &slots, &mut Vec::new(), &mut out);
+    out
+}
+
+/// The dot-product alphabet implied by `cfg`: PP, then PE, then EE factors,
+/// followed by loop-momentum factors when `cfg.n_loops > 0`.
+///
+/// Useful as a starting point for [`generate_tensor_structures_from_catalog`].
+pub fn factor_catalog(cfg: &GenConfig) -> Vec<ScalarFactor> {
+    let (pp, pe, ee) = generate_valid_factors(cfg);
+    let mut catalog = Vec::with_capacity(pp.len() + pe.len() + ee.len());
+    catalog.extend(pp);
+    catalog.extend(pe);
+    catalog.extend(ee);
+    catalog.extend(generate_loop_factors(cfg));
+    catalog
+}
+
 pub fn count_valid_factors(cfg: &GenConfig) -> CatalogCounts {
     let (pp, pe, ee) = generate_valid_factors(cfg);
     CatalogCounts {
         num_pp: pp.len(),
         num_pe: pe.len(),
         num_ee: ee.len(),
+        num_loop: generate_loop_factors(cfg).len(),
     }
 }
 
 #[derive(Clone)]
-struct DfsState<'a> {
+struct DfsState {
     target_deg: u32,
     ee_needed: u32,
-    nlegs: u8,
-    enforce_one_pol: bool,
-    catalog: &'a [ScalarFactor],
+    max_pp: u32,
+    pp_so_far: u32,
+    max_loop_power: u32,
+    loop_power: u32,
+    target_momentum_power: Option<u32>,
+    momentum_power: u32,
+    /// Per-leg polarization targets (slot 0 unused); `None` when unconstrained.
+    pol_target: Option<Vec<u32>>,
+    pol_total: u32,
+    catalog: Arc<[ScalarFactor]>,
+    /// The partial structure, canonical at every node: its prefix is built
+    /// canonical and factors are appended in sorted catalog order.
     cur: TensorStructure,
-    pe_so_far: u32,
+    pol_so_far: u32,
     pol_count: Vec<u32>,
-    // out: BTreeSet<TensorStructure>,
+    /// Multiplicity bookkeeping; `None` when no bounds are configured.
+    mult: Option<MultiplicityState>,
+    /// Output size past which [`dfs_emit`] gives up.
+    max_out: usize,
+}
+
+/// Per-catalog-entry multiplicities and their bounds.
+#[derive(Clone)]
+struct MultiplicityState {
+    count: Vec<u32>,
+    max: Vec<u32>,
+    /// Catalog indices with a nonzero lower bound, and that bound.
+    required: Vec<(usize, u32)>,
+}
+
+impl MultiplicityState {
+    /// Build the bookkeeping for `catalog`; `None` if a required factor is missing.
+    fn new(catalog: &Catalog, bounds: &[MultiplicityBound]) -> Option<Self> {
+        let mut max = vec![u32::MAX; catalog.len()];
+        let mut min = vec![0; catalog.len()];
+        for b in bounds {
+            match catalog.index_of(&b.factor) {
+                Some(i) => {
+                    min[i] = min[i].max(b.min);
+                    max[i] = max[i].min(b.max.unwrap_or(u32::MAX));
+                }
+                None if b.min > 0 => return None,
+                None => {}
+            }
+        }
+        let required = min
+            .iter()
+            .enumerate()
+            .filter(|&(_, &m)| m > 0)
+            .map(|(i, &m)| (i, m))
+            .collect();
+        Some(Self {
+            count: vec![0; catalog.len()],
+            max,
+            required,
+        })
+    }
 }
 
-fn add_polarizations(pc: &mut [u32], f: &ScalarFactor) {
+/// Record the polarizations carried by `f`; returns how many were added.
+fn add_polarizations(pc: &mut [u32], f: &ScalarFactor) -> u32 {
     match f.kind {
-        ScalarKind::PE => {
+        ScalarKind::PE | ScalarKind::LE => {
             pc[f.b.0 as usize] += 1;
+            1
         }
         ScalarKind::EE => {
             pc[f.a.0 as usize] += 1;
             pc[f.b.0 as usize] += 1;
+            2
         }
-        ScalarKind::PP => {}
+        ScalarKind::PP
+        | ScalarKind::Angle
+        | ScalarKind::Square
+        | ScalarKind::LP
+        | ScalarKind::LL => 0,
     }
 }
 
-fn remove_polarizations(pc: &mut [u32], f: &ScalarFactor) {
+/// Undo [`add_polarizations`]; returns how many were removed.
+fn remove_polarizations(pc: &mut [u32], f: &ScalarFactor) -> u32 {
     match f.kind {
-        ScalarKind::PE => {
+        ScalarKind::PE | ScalarKind::LE => {
             pc[f.b.0 as usize] -= 1;
+            1
         }
         ScalarKind::EE => {
             pc[f.a.0 as usize] -= 1;
             pc[f.b.0 as usize] -= 1;
+            2
         }
-        ScalarKind::PP => {}
+        ScalarKind::PP
+        | ScalarKind::Angle
+        | ScalarKind::Square
+        | ScalarKind::LP
+        | ScalarKind::LL => 0,
     }
 }
 
-fn dfs_emit(s: &mut DfsState, idx_start: usize, out: &mut BTreeSet<TensorStructure>) {
-    let deg_so_far = s.cur.factors.len() as u32;
-    let ee_so_far = s.cur.ee_contractions;
+/// What the search finds at one node.
+enum Node {
+    /// Nothing below this node can be emitted; `true` if it is itself a structure.
+    Leaf(bool),
+    /// The structure is incomplete and factors from `idx_start` on may follow.
+    Inner,
+}
 
-    if deg_so_far > s.target_deg || ee_so_far > s.ee_needed {
-        return;
-    }
+impl DfsState {
+    /// Prune, emit or expand the current partial structure.
+    fn visit(&self, idx_start: usize) -> Node {
+        let deg_so_far = self.cur.degree();
+        let ee_so_far = self.cur.ee_contractions;
+
+        if deg_so_far > self.target_deg
+            || ee_so_far > self.ee_needed
+            || self.pp_so_far > self.max_pp
+            || self.loop_power > self.max_loop_power
+            || self
+                .target_momentum_power
+                .is_some_and(|m| self.momentum_power > m)
+        {
+            return Node::Leaf(false);
+        }
 
-    if s.enforce_one_pol {
-        for r in 1..=s.nlegs as usize {
-            if s.pol_count[r] > 1 {
-                return;
+        if let Some(m) = &self.mult {
+            // Entries before `idx_start` can no longer be added, and the
+            // outstanding lower bounds must fit into the remaining degree.
+            let mut outstanding = 0;
+            for &(j, min) in &m.required {
+                let short = min.saturating_sub(m.count[j]);
+                if short > 0 && j < idx_start {
+                    return Node::Leaf(false);
+                }
+                outstanding += short;
+            }
+            if outstanding > self.target_deg - deg_so_far {
+                return Node::Leaf(false);
             }
         }
 
-        let remain = s.target_deg - deg_so_far;
-        let mut missing = 0;
-        for r in 1..=s.nlegs as usize {
-            if s.pol_count[r] == 0 {
-                missing += 1;
+        if let Some(target) = &self.pol_target {
+            if self.pol_count.iter().zip(target).any(|(c, t)| c > t) {
+                return Node::Leaf(false);
+            }
+
+            let remain = self.target_deg - deg_so_far;
+            let missing: u32 = target.iter().zip(&self.pol_count).map(|(t, c)| t - c).sum();
+
+            let max_addable = remain * 2;
+            if max_addable < missing {
+                return Node::Leaf(false);
+            }
+
+            if self.pol_so_far > self.pol_total {
+                return Node::Leaf(false);
             }
         }
 
-        let max_addable = remain * 2;
-        if max_addable < missing {
-            return;
+        if deg_so_far < self.target_deg {
+            return Node::Inner;
         }
+        let complete = ee_so_far == self.ee_needed
+            && self
+                .target_momentum_power
+                .is_none_or(|m| self.momentum_power == m)
+            && self.pol_target.as_ref().is_none_or(|target| {
+                self.pol_so_far == self.pol_total && self.pol_count == *target
+            });
+        Node::Leaf(complete)
+    }
+
+    /// The current structure, which is already canonical.
+    fn emit(&self) -> TensorStructure {
+        debug_assert!({
+            let mut t = self.cur.clone();
+            t.canonicalize() != 0 && t == self.cur
+        });
+        self.cur.clone()
+    }
+}
+
+/// Emit every structure below the current node, in ascending order.
+///
+/// Factors are chosen by nondecreasing catalog index, so each multiset of
+/// factors is reached along exactly one path, and since the catalog is
+/// sorted the factor lists come out sorted and in lexicographic order.
+fn dfs_emit(s: &mut DfsState, idx_start: usize, out: &mut Vec<TensorStructure>) {
+    if let Node::Leaf(complete) = s.visit(idx_start) {
+        if complete {
+            out.push(s.emit());
+        }
+        return;
+    }
 
-        let pol_so_far = 2 * ee_so_far + s.pe_so_far;
-        if pol_so_far > s.nlegs as u32 {
+    for i in idx_start..s.catalog.len() {
+        if out.len() > s.max_out {
             return;
         }
+        if s.push(i) {
+            dfs_emit(s, i, out);
+            s.pop(i);
+        }
     }
+}
 
-    if deg_so_far == s.target_deg {
-        if ee_so_far == s.ee_needed {
-            if !s.enforce_one_pol {
-                let mut t = s.cur.clone();
-                t.canonicalize();
-                out.insert(t);
-            } else {
-                let pol_total = 2 * ee_so_far + s.pe_so_far;
-                if pol_total == s.nlegs as u32 {
-                    let ok = (1..=s.nlegs as usize).all(|r| s.pol_count[r] == 1);
-                    if ok {
-                        let mut t = s.cur.clone();
-                        t.canonicalize();
-                        out.insert(t);
-                    }
-                }
-            }
+/// [`dfs_emit`], counting what the search does for a progress callback.
+fn dfs_emit_reporting(
+    s: &mut DfsState,
+    idx_start: usize,
+    out: &mut Vec<TensorStructure>,
+    progress: &mut Progress,
+) {
+    progress.visit();
+    if let Node::Leaf(complete) = s.visit(idx_start) {
+        if complete {
+            out.push(s.emit());
+            progress.event.found += 1;
+        } else {
+            progress.event.pruned += 1;
         }
         return;
     }
 
     for i in idx_start..s.catalog.len() {
-        let f = &s.catalog[i];
-        s.cur.factors.push(f.clone());
+        if out.len() > s.max_out {
+            return;
+        }
+        if s.push(i) {
+            dfs_emit_reporting(s, i, out, progress);
+            s.pop(i);
+        } else {
+            progress.event.bound_hits += 1;
+        }
+    }
+}
+
+/// Search statistics passed to a progress callback.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ProgressEvent {
+    /// Nodes of the search tree visited so far.
+    pub nodes: u64,
+    /// Complete structures reached, before any cyclic deduplication.
+    pub found: u64,
+    /// Nodes at which the search stopped without reaching a structure.
+    pub pruned: u64,
+    /// Factors not added because their multiplicity bound was reached.
+    pub bound_hits: u64,
+    /// Set on the last event of a run, which reports the final totals.
+    pub finished: bool,
+}
+
+impl ProgressEvent {
+    /// Nodes visited between two reports.
+    pub const INTERVAL: u64 = 1 << 16;
+}
+
+/// The running totals of a search and where to report them.
+struct Progress<'a> {
+    event: ProgressEvent,
+    callback: &'a mut dyn FnMut(ProgressEvent),
+}
+
+impl Progress<'_> {
+    fn visit(&mut self) {
+        self.event.nodes += 1;
+        if self.event.nodes.is_multiple_of(ProgressEvent::INTERVAL) {
+            (self.callback)(self.event);
+        }
+    }
+
+    fn finish(mut self) {
+        self.event.finished = true;
+        (self.callback)(self.event);
+    }
+}
+
+/// Number of structures [`dfs_emit`] would add, without building any.
+///
+/// Only valid for labelled canonicalization, where every complete node is a
+/// different structure.
+fn dfs_count(s: &mut DfsState, idx_start: usize) -> u64 {
+    if let Node::Leaf(complete) = s.visit(idx_start) {
+        return complete as u64;
+    }
 
-        if matches!(f.kind, ScalarKind::EE) {
-            s.cur.ee_contractions += 1;
+    let mut total = 0;
+    for i in idx_start..s.catalog.len() {
+        if s.push(i) {
+            total += dfs_count(s, i);
+            s.pop(i);
         }
+    }
+    total
+}
+
+/// Run `search` from the root with the first factor chosen in parallel.
+///
+/// Each first choice gets its own copy of the state, and the results are
+/// merged at the end. Every pruning test only gets stricter as factors are
+/// added, so a root that would be pruned has no surviving children and the
+/// root itself only needs searching when it is complete.
+#[cfg(feature = "parallel")]
+fn par_first_factor<T: Send>(
+    s: &mut DfsState,
+    search: impl Fn(&mut DfsState, usize) -> T + Sync,
+    merge: impl Fn(T, T) -> T + Sync,
+    empty: impl Fn() -> T + Sync + Send,
+) -> T {
+    use rayon::prelude::*;
 
-        if s.enforce_one_pol {
-            if matches!(f.kind, ScalarKind::PE) {
-                s.pe_so_far += 1;
+    if s.cur.degree() >= s.target_deg {
+        return search(s, 0);
+    }
+    let s = &*s;
+    (0..s.catalog.len())
+        .into_par_iter()
+        .map(|i| {
+            let mut s = s.clone();
+            if s.push(i) {
+                search(&mut s, i)
+            } else {
+                empty()
             }
-            add_polarizations(&mut s.pol_count, f);
+        })
+        .reduce(&empty, &merge)
+}
+
+/// [`dfs_emit`] in parallel; the results of consecutive first factors are
+/// concatenated, which keeps them in order.
+#[cfg(feature = "parallel")]
+fn dfs_emit_parallel(s: &mut DfsState, out: &mut Vec<TensorStructure>) {
+    let found = par_first_factor(
+        s,
+        |s, i| {
+            let mut local = Vec::new();
+            dfs_emit(s, i, &mut local);
+            local
+        },
+        |mut a, mut b| {
+            a.append(&mut b);
+            a
+        },
+        Vec::new,
+    );
+    out.extend(found);
+}
+
+/// [`dfs_emit`] turned inside out: the same search, resumed on every call to
+/// `next` and yielding structures as they are found.
+struct Walk {
+    s: DfsState,
+    /// Catalog index of each factor pushed so far.
+    path: Vec<usize>,
+    /// The next child to try at the current node, or `None` on arrival.
+    next: Option<usize>,
+    done: bool,
+}
+
+impl Walk {
+    fn new(s: DfsState) -> Self {
+        Self {
+            s,
+            path: Vec::new(),
+            next: None,
+            done: false,
         }
+    }
+
+    /// Return to the parent node; `false` at the root.
+    fn up(&mut self) -> bool {
+        match self.path.pop() {
+            Some(i) => {
+                self.s.pop(i);
+                self.next = Some(i + 1);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Walk {
+    /// Descend along `path` from the root of `s`, ready to try `next`.
+    fn at(s: DfsState, path: &[usize], next: Option<usize>) -> Option<Self> {
+        let mut walk = Self::new(s);
+        for &i in path {
+            if i >= walk.s.catalog.len() || walk.path.last().is_some_and(|&j| i < j) {
+                return None;
+            }
+            if !walk.s.push(i) {
+                return None;
+            }
+            walk.path.push(i);
+        }
+        walk.next = next;
+        Some(walk)
+    }
+}
 
-        // let out_after = dfs_emit(s.clone(), i);
-        // s.out = out_after;
+impl Iterator for Walk {
+    type Item = TensorStructure;
 
-        dfs_emit(s, i, out);
+    fn next(&mut self) -> Option<TensorStructure> {
+        while !self.done {
+            let i = match self.next {
+                Some(i) => i,
+                None => {
+                    let start = self.path.last().copied().unwrap_or(0);
+                    match self.s.visit(start) {
+                        Node::Inner => start,
+                        Node::Leaf(complete) => {
+                            let t = complete.then(|| self.s.emit());
+                            self.done = !self.up();
+                            if t.is_some() {
+                                return t;
+                            }
+                            continue;
+                        }
+                    }
+                }
+            };
+            if i >= self.s.catalog.len() {
+                self.done = !self.up();
+            } else if self.s.push(i) {
+                self.path.push(i);
+                self.next = None;
+            } else {
+                self.next = Some(i + 1);
+            }
+        }
+        None
+    }
+}
 
-        if s.enforce_one_pol {
-            remove_polarizations(&mut s.pol_count, f);
-            if matches!(f.kind, ScalarKind::PE) {
-                s.pe_so_far -= 1;
+impl DfsState {
+    /// Append catalog entry `i`; `false` if its multiplicity bound is reached.
+    fn push(&mut self, i: usize) -> bool {
+        if let Some(m) = &mut self.mult {
+            if m.count[i] >= m.max[i] {
+                return false;
             }
+            m.count[i] += 1;
         }
 
-        if matches!(f.kind, ScalarKind::EE) {
-            s.cur.ee_contractions -= 1;
+        let f = &self.catalog[i];
+        self.cur.factors.push(f.clone());
+
+        match f.kind {
+            ScalarKind::EE => self.cur.ee_contractions += 1,
+            ScalarKind::PP => self.pp_so_far += 1,
+            _ => {}
         }
+        self.loop_power += f.loop_power();
+        self.momentum_power += f.momentum_power();
 
-        s.cur.factors.pop();
+        if self.pol_target.is_some() {
+            self.pol_so_far += add_polarizations(&mut self.pol_count, f);
+        }
+        true
+    }
+
+    /// Undo a successful [`push`](Self::push) of entry `i`.
+    fn pop(&mut self, i: usize) {
+        let f = &self.catalog[i];
+        if self.pol_target.is_some() {
+            self.pol_so_far -= remove_polarizations(&mut self.pol_count, f);
+        }
+
+        match f.kind {
+            ScalarKind::EE => self.cur.ee_contractions -= 1,
+            ScalarKind::PP => self.pp_so_far -= 1,
+            _ => {}
+        }
+        self.loop_power -= f.loop_power();
+        self.momentum_power -= f.momentum_power();
+
+        self.cur.factors.pop();
+
+        if let Some(m) = &mut self.mult {
+            m.count[i] -= 1;
+        }
     }
 }
 
+/// Every structure of `target_degree` factors with `ee_contractions` EE
+/// factors allowed by `cfg`, in ascending order.
+///
+/// # Panics
+///
+/// If there are more than `cfg.max_structures` or [`GenConfig::validate`]
+/// rejects `cfg`; use [`try_generate_tensor_structures`] to handle those cases.
 pub fn generate_tensor_structures(
     cfg: &GenConfig,
     target_degree: u32,
@@ -230,25 +1057,541 @@ pub fn generate_tensor_structures(
         return Vec::new();
     }
 
-    let (pp, pe, ee) = generate_valid_factors(cfg);
-    let mut catalog = Vec::with_capacity(pp.len() + pe.len() + ee.len());
-    catalog.extend(pp);
-    catalog.extend(pe);
-    catalog.extend(ee);
+    generate_tensor_structures_with_catalog(cfg, &Catalog::new(cfg), target_degree, ee_contractions)
+}
 
-    let nlegs = cfg.n_legs;
-    let mut s = DfsState {
-        target_deg: target_degree,
-        ee_needed: ee_contractions,
-        nlegs,
-        enforce_one_pol: matches!(cfg.pol_pattern, PolarizationPattern::OnePerLeg),
-        catalog: &catalog,
-        cur: TensorStructure::new(),
-        pe_so_far: 0,
-        pol_count: vec![0; nlegs as usize + 1],
+/// Like [`generate_tensor_structures`], but failing with an error instead of
+/// running out of memory when the output exceeds `cfg.max_structures`, or
+/// when [`GenConfig::validate`] rejects `cfg`.
+///
+/// Where [`predict_structure_count`](crate::predict_structure_count) has a
+/// closed form the search is not started at all; otherwise it stops as soon
+/// as the limit is passed. With [`Canonicalization::Cyclic`] the limit
+/// applies to the structures found before rotations are identified.
+pub fn try_generate_tensor_structures(
+    cfg: &GenConfig,
+    target_degree: u32,
+    ee_contractions: u32,
+) -> Result<Vec<TensorStructure>, String> {
+    if target_degree == 0 || ee_contractions > target_degree {
+        return Ok(Vec::new());
+    }
+    SearchPlan::new(cfg, &Catalog::new(cfg)).run(target_degree, ee_contractions, None)
+}
+
+/// Like [`generate_tensor_structures`], but drawing dot products from an
+/// explicit factor alphabet instead of the catalog implied by `cfg`.
+///
+/// `cfg` still supplies the polarization pattern, fermion bilinears and the
+/// parity-odd toggle. Duplicate entries are ignored, as are factors that refer
+/// to legs beyond `cfg.n_legs` or loop momenta beyond `cfg.n_loops`.
+pub fn generate_tensor_structures_from_catalog(
+    cfg: &GenConfig,
+    catalog: &[ScalarFactor],
+    target_degree: u32,
+    ee_contractions: u32,
+) -> Vec<TensorStructure> {
+    let catalog = Catalog::from_factors(cfg, catalog);
+    generate_tensor_structures_with_catalog(cfg, &catalog, target_degree, ee_contractions)
+}
+
+/// Like [`generate_tensor_structures`], with a [`Catalog`] built beforehand,
+/// e.g. once for a sweep over many `(deg, ee)`.
+///
+/// `catalog` should come from `cfg` or a configuration with the same legs,
+/// as the search takes everything else from `cfg`.
+pub fn generate_tensor_structures_with_catalog(
+    cfg: &GenConfig,
+    catalog: &Catalog,
+    target_degree: u32,
+    ee_contractions: u32,
+) -> Vec<TensorStructure> {
+    SearchPlan::new(cfg, catalog)
+        .run(target_degree, ee_contractions, None)
+        .unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Like [`generate_tensor_structures`], calling `progress` every
+/// [`ProgressEvent::INTERVAL`] search nodes and once more at the end.
+///
+/// The callback is not shared between threads, so the search runs on the
+/// calling thread even with the `parallel` feature. Fails like
+/// [`try_generate_tensor_structures`] past `cfg.max_structures`.
+pub fn generate_tensor_structures_with_progress(
+    cfg: &GenConfig,
+    target_degree: u32,
+    ee_contractions: u32,
+    mut progress: impl FnMut(ProgressEvent),
+) -> Result<Vec<TensorStructure>, String> {
+    if target_degree == 0 || ee_contractions > target_degree {
+        progress(ProgressEvent {
+            finished: true,
+            ..ProgressEvent::default()
+        });
+        return Ok(Vec::new());
+    }
+    SearchPlan::new(cfg, &Catalog::new(cfg)).run(
+        target_degree,
+        ee_contractions,
+        Some(&mut progress),
+    )
+}
+
+/// Like [`generate_tensor_structures`], but yielding structures one at a
+/// time as the search finds them, so memory stays bounded by the search
+/// depth rather than the size of the basis.
+///
+/// Structures come in the same ascending order, each once. With
+/// [`Canonicalization::Cyclic`] the rotated representatives are no longer
+/// sorted and are deduplicated through a set of those already yielded, which
+/// does grow with the output.
+///
+/// # Panics
+///
+/// If [`GenConfig::validate`] rejects `cfg`.
+pub fn generate_tensor_structures_iter(
+    cfg: &GenConfig,
+    target_degree: u32,
+    ee_contractions: u32,
+) -> impl Iterator<Item = TensorStructure> + '_ {
+    if let Err(e) = cfg.validate() {
+        panic!("{}", e);
+    }
+    let catalog = Catalog::new(cfg);
+    let roots = SearchPlan::new(cfg, &catalog).roots(target_degree, ee_contractions);
+    let cyclic = cfg.canonicalization == Canonicalization::Cyclic;
+    let mut seen = StructureSet::new(cfg.deduplication);
+    roots
+        .into_iter()
+        .flat_map(Walk::new)
+        .filter_map(move |mut t| {
+            if !cyclic {
+                return Some(t);
+            }
+            t.canonicalize_cyclic(cfg.n_legs);
+            seen.insert(t.clone()).then_some(t)
+        })
+}
+
+/// The structures seen so far, in the set [`Deduplication`] asks for.
+enum StructureSet {
+    Ordered(BTreeSet<TensorStructure>),
+    Hashed(FxHashSet<TensorStructure>),
+}
+
+impl StructureSet {
+    fn new(dedup: Deduplication) -> Self {
+        match dedup {
+            Deduplication::Ordered => Self::Ordered(BTreeSet::new()),
+            Deduplication::Hashed { .. } => Self::Hashed(FxHashSet::default()),
+        }
+    }
+
+    /// Add `t`, returning whether it was new.
+    fn insert(&mut self, t: TensorStructure) -> bool {
+        match self {
+            Self::Ordered(set) => set.insert(t),
+            Self::Hashed(set) => set.insert(t),
+        }
+    }
+}
+
+/// Drop repeated structures from `out` as `dedup` asks.
+pub(crate) fn dedup_structures(out: &mut Vec<TensorStructure>, dedup: Deduplication) {
+    match dedup {
+        Deduplication::Ordered => {
+            sort_structures(out);
+            out.dedup();
+        }
+        Deduplication::Hashed { sorted } => {
+            // Mark first occurrences, then drop the rest in place.
+            let keep: Vec<bool> = {
+                let mut seen = FxHashSet::default();
+                seen.reserve(out.len());
+                out.iter().map(|t| seen.insert(t)).collect()
+            };
+            let mut keep = keep.into_iter();
+            out.retain(|_| keep.next() == Some(true));
+            if sorted {
+                sort_structures(out);
+            }
+        }
+    }
+}
+
+fn sort_structures(out: &mut [TensorStructure]) {
+    #[cfg(feature = "parallel")]
+    rayon::slice::ParallelSliceMut::par_sort_unstable(out);
+    #[cfg(not(feature = "parallel"))]
+    out.sort_unstable();
+}
+
+/// Position of a [`ResumableSearch`] between two structures.
+///
+/// Written as `root next path…`, with `-` for a `next` of `None`, e.g.
+/// `2 5 0 0 3`, so it can be stored and parsed back.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SearchCursor {
+    /// Index of the ε/bilinear prefix being searched.
+    pub root: usize,
+    /// Catalog indices of the factors on the current branch.
+    pub path: Vec<usize>,
+    /// The next catalog entry to try below the branch, or `None` if the
+    /// branch itself has not been visited yet.
+    pub next: Option<usize>,
+}
+
+impl fmt::Display for SearchCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.root)?;
+        match self.next {
+            Some(i) => write!(f, " {}", i)?,
+            None => write!(f, " -")?,
+        }
+        for i in &self.path {
+            write!(f, " {}", i)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SearchCursor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = || format!("expected a search cursor like `2 5 0 0 3`, got {:?}", s);
+        let mut fields = s.split_whitespace();
+        let root = fields.next().and_then(|r| r.parse().ok()).ok_or_else(bad)?;
+        let next = match fields.next().ok_or_else(bad)? {
+            "-" => None,
+            i => Some(i.parse().map_err(|_| bad())?),
+        };
+        let path = fields
+            .map(|i| i.parse().map_err(|_| bad()))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { root, path, next })
+    }
+}
+
+/// The search behind [`generate_tensor_structures_iter`] as a named
+/// iterator whose position can be saved with [`cursor`](Self::cursor) and
+/// picked up again by [`resume_tensor_structures`], e.g. in another process.
+pub struct ResumableSearch {
+    roots: Vec<DfsState>,
+    root: usize,
+    walk: Option<Walk>,
+}
+
+impl ResumableSearch {
+    /// Where the search stands: resuming here yields exactly the structures
+    /// this iterator has not yielded yet.
+    pub fn cursor(&self) -> SearchCursor {
+        match &self.walk {
+            Some(w) if !w.done => SearchCursor {
+                root: self.root,
+                path: w.path.clone(),
+                next: w.next,
+            },
+            Some(_) => SearchCursor {
+                root: self.root + 1,
+                ..SearchCursor::default()
+            },
+            None => SearchCursor {
+                root: self.root,
+                ..SearchCursor::default()
+            },
+        }
+    }
+}
+
+impl Iterator for ResumableSearch {
+    type Item = TensorStructure;
+
+    fn next(&mut self) -> Option<TensorStructure> {
+        loop {
+            if let Some(t) = self.walk.as_mut()?.next() {
+                return Some(t);
+            }
+            self.root += 1;
+            self.walk = self.roots.get(self.root).cloned().map(Walk::new);
+        }
+    }
+}
+
+/// Continue [`generate_tensor_structures_iter`] from `cursor`, or start it
+/// with [`SearchCursor::default`].
+///
+/// Only labelled canonicalization can be resumed: cyclic deduplication
+/// depends on every structure seen before. Errors if `cursor` does not come
+/// from a search with the same configuration, or if [`GenConfig::validate`]
+/// rejects `cfg`.
+pub fn resume_tensor_structures(
+    cfg: &GenConfig,
+    target_degree: u32,
+    ee_contractions: u32,
+    cursor: &SearchCursor,
+) -> Result<ResumableSearch, String> {
+    if cfg.canonicalization != Canonicalization::Labelled {
+        return Err("only labelled canonicalization can be resumed".to_string());
+    }
+    cfg.validate()?;
+    let catalog = Catalog::new(cfg);
+    let roots = SearchPlan::new(cfg, &catalog).roots(target_degree, ee_contractions);
+    let walk = match roots.get(cursor.root) {
+        Some(s) => Some(
+            Walk::at(s.clone(), &cursor.path, cursor.next)
+                .ok_or_else(|| format!("cursor {} does not fit this search", cursor))?,
+        ),
+        None if cursor.root == roots.len() && cursor.path.is_empty() => None,
+        None => return Err(format!("cursor {} does not fit this search", cursor)),
     };
+    Ok(ResumableSearch {
+        roots,
+        root: cursor.root,
+        walk,
+    })
+}
+
+/// Like [`generate_tensor_structures`], but for bases that do not fit in
+/// memory: the structures are written in sorted chunks to files in
+/// `spill.dir` and merged into a single file on disk.
+///
+/// At most `spill.chunk_size` structures are held at once. The result reads
+/// back the same structures in the same order.
+pub fn generate_tensor_structures_spilled(
+    cfg: &GenConfig,
+    target_degree: u32,
+    ee_contractions: u32,
+    spill: &SpillConfig,
+) -> io::Result<SpilledStructures> {
+    cfg.validate()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let catalog = Catalog::new(cfg);
+    let roots = SearchPlan::new(cfg, &catalog).roots(target_degree, ee_contractions);
+    let cyclic = cfg.canonicalization == Canonicalization::Cyclic;
+    let structures = roots.into_iter().flat_map(Walk::new).map(|mut t| {
+        if cyclic {
+            t.canonicalize_cyclic(cfg.n_legs);
+        }
+        t
+    });
+    sort_to_disk(structures, spill)
+}
+
+/// Number of structures [`generate_tensor_structures`] would return.
+///
+/// Runs the same search but only counts complete structures, so nothing is
+/// allocated per structure. With [`Canonicalization::Cyclic`] the rotated
+/// representatives must be compared, and the structures are generated after all.
+/// Panics like [`generate_tensor_structures`] if [`GenConfig::validate`]
+/// rejects `cfg`.
+pub fn count_tensor_structures(cfg: &GenConfig, target_degree: u32, ee_contractions: u32) -> u64 {
+    if let Err(e) = cfg.validate() {
+        panic!("{}", e);
+    }
+    if cfg.canonicalization == Canonicalization::Cyclic {
+        return generate_tensor_structures(cfg, target_degree, ee_contractions).len() as u64;
+    }
+    let catalog = Catalog::new(cfg);
+    let roots = SearchPlan::new(cfg, &catalog).roots(target_degree, ee_contractions);
+    roots
+        .into_iter()
+        .map(|mut s| {
+            #[cfg(feature = "parallel")]
+            return par_first_factor(&mut s, dfs_count, |a, b| a + b, || 0);
+            #[cfg(not(feature = "parallel"))]
+            dfs_count(&mut s, 0)
+        })
+        .sum()
+}
 
-    let mut out_set = BTreeSet::new();
-    dfs_emit(&mut s, 0, &mut out_set);
-    out_set.into_iter().collect()
+/// Generate every degree in `degrees` at once, grouped by `(deg, ee)`.
+///
+/// The catalog and the fixed bilinear/ε prefixes are built once and shared
+/// across all searches; empty `(deg, ee)` sectors are omitted. Panics if one
+/// sector exceeds `cfg.max_structures`.
+pub fn generate_tensor_structures_range(
+    cfg: &GenConfig,
+    degrees: RangeInclusive<u32>,
+) -> BTreeMap<(u32, u32), Vec<TensorStructure>> {
+    let catalog = Catalog::new(cfg);
+    let plan = SearchPlan::new(cfg, &catalog);
+    let mut out = BTreeMap::new();
+    for deg in degrees {
+        for ee in 0..=deg {
+            let ts = plan.run(deg, ee, None).unwrap_or_else(|e| panic!("{}", e));
+            if !ts.is_empty() {
+                out.insert((deg, ee), ts);
+            }
+        }
+    }
+    out
+}
+
+/// Everything about a search that does not depend on `(deg, ee)`.
+struct SearchPlan<'a> {
+    cfg: &'a GenConfig,
+    catalog: Arc<[ScalarFactor]>,
+    mult: Option<MultiplicityState>,
+    /// False if a required multiplicity bound cannot be met by the catalog.
+    feasible: bool,
+    pol_target: Option<Vec<u32>>,
+    bilinear_sets: Vec<Vec<BilinearFactor>>,
+    epsilons: Vec<Option<EpsilonFactor>>,
+}
+
+impl<'a> SearchPlan<'a> {
+    fn new(cfg: &'a GenConfig, catalog: &Catalog) -> Self {
+        let (mult, feasible) = if cfg.multiplicity_bounds.is_empty() {
+            (None, true)
+        } else {
+            match MultiplicityState::new(catalog, &cfg.multiplicity_bounds) {
+                Some(m) => (Some(m), true),
+                None => (None, false),
+            }
+        };
+
+        // Bilinears and the ε factor are fixed up front and each counts as one of
+        // the target degree's factors; the DFS then fills in the dot products.
+        let mut epsilons = vec![None];
+        if cfg.include_parity_odd {
+            epsilons.extend(generate_epsilon_factors(cfg).into_iter().map(Some));
+        }
+
+        Self {
+            cfg,
+            catalog: catalog.shared(),
+            mult,
+            feasible,
+            pol_target: cfg.polarization_targets(),
+            bilinear_sets: generate_bilinear_sets(cfg),
+            epsilons,
+        }
+    }
+
+    /// One search state per fixed bilinear/ε prefix, ready for the DFS.
+    ///
+    /// The roots are sorted by prefix, which [`TensorStructure`]'s order
+    /// compares before the dot products, so searching them in turn gives
+    /// every structure in ascending order.
+    fn roots(&self, target_degree: u32, ee_contractions: u32) -> Vec<DfsState> {
+        if target_degree == 0 {
+            return Vec::new();
+        }
+        if ee_contractions > target_degree || !self.feasible {
+            return Vec::new();
+        }
+
+        let base = DfsState {
+            target_deg: target_degree,
+            ee_needed: ee_contractions,
+            max_pp: self.cfg.max_pp.unwrap_or(u32::MAX),
+            pp_so_far: 0,
+            max_loop_power: self.cfg.max_loop_power.unwrap_or(u32::MAX),
+            loop_power: 0,
+            target_momentum_power: self.cfg.momentum_power,
+            momentum_power: 0,
+            pol_total: self.pol_target.as_ref().map_or(0, |t| t.iter().sum()),
+            pol_target: self.pol_target.clone(),
+            catalog: self.catalog.clone(),
+            cur: TensorStructure::new(),
+            pol_so_far: 0,
+            pol_count: vec![0; self.cfg.n_legs as usize + 1],
+            mult: self.mult.clone(),
+            max_out: self
+                .cfg
+                .max_structures
+                .map_or(usize::MAX, |m| m.try_into().unwrap_or(usize::MAX)),
+        };
+
+        let mut roots = Vec::new();
+        for bilinears in &self.bilinear_sets {
+            for eps in &self.epsilons {
+                let mut s = base.clone();
+                if s.pol_target.is_some() {
+                    let prefix_pols = bilinears
+                        .iter()
+                        .map(|b| b.gamma)
+                        .chain(eps.iter().flat_map(|e| e.slots))
+                        .filter_map(|slot| match slot {
+                            Slot::E(l) => Some(l.0 as usize),
+                            Slot::P(_) => None,
+                        });
+                    for l in prefix_pols {
+                        s.pol_count[l] += 1;
+                        s.pol_so_far += 1;
+                    }
+                }
+                s.cur.bilinears = bilinears.clone();
+                s.cur.epsilon = eps.clone();
+                s.momentum_power = s.cur.momentum_power();
+                roots.push(s);
+            }
+        }
+        roots.sort_by(|a, b| {
+            (&a.cur.epsilon, &a.cur.bilinears).cmp(&(&b.cur.epsilon, &b.cur.bilinears))
+        });
+        roots
+    }
+
+    fn run(
+        &self,
+        target_degree: u32,
+        ee_contractions: u32,
+        progress: Option<&mut dyn FnMut(ProgressEvent)>,
+    ) -> Result<Vec<TensorStructure>, String> {
+        self.cfg.validate()?;
+        // Where the closed-form count applies it sizes the output up front,
+        // sparing the reallocations of a growing vector, and checks the search.
+        const MAX_RESERVE: u128 = 1 << 24;
+        let expected = (target_degree > 0)
+            .then(|| closed_form_count(self.cfg, &self.catalog, target_degree, ee_contractions))
+            .flatten();
+        let too_many = |found: String| {
+            format!(
+                "{} structures for n={}, deg={}, ee={} exceed the limit of {}",
+                found,
+                self.cfg.n_legs,
+                target_degree,
+                ee_contractions,
+                self.cfg.max_structures.unwrap_or(u64::MAX)
+            )
+        };
+        let max = self.cfg.max_structures.map_or(u128::MAX, u128::from);
+        if let Some(c) = expected.filter(|&c| c > max) {
+            return Err(too_many(c.to_string()));
+        }
+        let mut out = Vec::with_capacity(expected.map_or(0, |c| c.min(MAX_RESERVE) as usize));
+        let roots = self.roots(target_degree, ee_contractions);
+        if let Some(callback) = progress {
+            let mut progress = Progress {
+                event: ProgressEvent::default(),
+                callback,
+            };
+            for mut s in roots {
+                dfs_emit_reporting(&mut s, 0, &mut out, &mut progress);
+            }
+            progress.finish();
+        } else {
+            for mut s in roots {
+                #[cfg(feature = "parallel")]
+                dfs_emit_parallel(&mut s, &mut out);
+                #[cfg(not(feature = "parallel"))]
+                dfs_emit(&mut s, 0, &mut out);
+            }
+        }
+        if let Some(max) = self.cfg.max_structures.filter(|&m| out.len() as u64 > m) {
+            return Err(too_many(format!("more than {}", max)));
+        }
+        debug_assert!(out.windows(2).all(|w| w[0] < w[1]));
+        debug_assert!(expected.is_none_or(|c| c == out.len() as u128));
+
+        if self.cfg.canonicalization == Canonicalization::Cyclic {
+            for t in &mut out {
+                t.canonicalize_cyclic(self.cfg.n_legs);
+            }
+            dedup_structures(&mut out, self.cfg.deduplication);
+        }
+        Ok(out)
+    }
 }
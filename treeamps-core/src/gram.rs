@@ -0,0 +1,451 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive};
+
+use crate::{
+    dot_product::ScalarFactor,
+    expression::TensorExpression,
+    finite_field::Fp,
+    generator::GenConfig,
+    linalg::{rational_reconstruction, row_reduce},
+    reduction::expand_field_strengths,
+    rng::SplitMix64,
+    tensor_structure::TensorStructure,
+    types::{LegIndex, ScalarKind},
+};
+
+/// Outcome of [`gram_reduce`].
+#[derive(Clone, Debug, Default)]
+pub struct GramReduction {
+    /// Structures that survive in the requested dimension, in input order.
+    pub kept: Vec<TensorStructure>,
+    /// Structures that are independent in generic dimension but become
+    /// combinations of the kept ones through Gram-determinant relations.
+    pub eliminated: Vec<TensorStructure>,
+}
+
+/// Remove the dependencies that only appear in `dim` spacetime dimensions.
+///
+/// Every structure is evaluated at random on-shell kinematic points, with
+/// momentum conservation and transversality imposed, once in `dim` dimensions
+/// and once in a dimension large enough for all momenta and polarizations to
+/// be independent. A structure is eliminated if it is new in the generic
+/// dimension but a combination of the earlier ones in `dim`, so relations
+/// that hold in every dimension (momentum conservation, the catalog's own
+/// redundancies) are left alone.
+///
+/// Arithmetic is exact modulo a 61-bit prime; a spurious dependency would need
+/// a random point to hit a zero of a nonzero polynomial. Structures with ε
+/// factors, bilinears or spinor brackets are rejected.
+pub fn gram_reduce(
+    cfg: &GenConfig,
+    structures: &[TensorStructure],
+    dim: u32,
+) -> Result<GramReduction, String> {
+    let expanded = expand_all(structures)?;
+    let n_points = structures.len() + 8;
+    let mut rng = SplitMix64::new(0x7265_6561_6d70_7321);
+    let low = sample(cfg, dim, n_points, &mut rng)?;
+    let high = sample(cfg, generic_dim(cfg), n_points, &mut rng)?;
+
+    let mut generic = Echelon::default();
+    let mut fixed = Echelon::default();
+    let mut out = GramReduction::default();
+    for (t, terms) in structures.iter().zip(&expanded) {
+        let new_generic = generic.insert(evaluate(cfg, terms, &high));
+        let new_fixed = fixed.insert(evaluate(cfg, terms, &low));
+        if new_generic && !new_fixed {
+            out.eliminated.push(t.clone());
+        } else {
+            out.kept.push(t.clone());
+        }
+    }
+    Ok(out)
+}
+
+/// Outcome of [`reduce_to_independent_basis`].
+#[derive(Clone, Debug, Default)]
+pub struct BasisReduction {
+    /// A maximal independent subset, in input order.
+    pub basis: Vec<TensorStructure>,
+    /// Every other structure with its expansion in the basis.
+    pub relations: Vec<(TensorStructure, TensorExpression)>,
+}
+
+/// Split `structures` into a maximal independent subset and relations for the rest.
+///
+/// The structures are evaluated at `samples` random on-shell points in a
+/// dimension large enough for no Gram relations to appear, with momentum
+/// conservation and transversality imposed, and the sample vectors row
+/// reduced modulo a 61-bit prime. Going through the input in order, each
+/// structure independent of the earlier ones joins the basis; the others are
+/// solved for in terms of it, their coefficients recovered as fractions by
+/// rational reconstruction. Only relations with constant coefficients are
+/// found, so pass structures of one mass dimension.
+///
+/// Fails when the basis is as large as `samples`, since more points could
+/// reveal further independent structures, or for structures that cannot be
+/// evaluated: ε factors, bilinears and spinor brackets.
+pub fn reduce_to_independent_basis(
+    cfg: &GenConfig,
+    structures: &[TensorStructure],
+    samples: usize,
+) -> Result<BasisReduction, String> {
+    let expanded = expand_all(structures)?;
+    let mut rng = SplitMix64::new(0x6261_7369_735f_7265);
+    let points = sample(cfg, generic_dim(cfg), samples, &mut rng)?;
+    let columns: Vec<Vec<u64>> = expanded
+        .iter()
+        .map(|terms| evaluate(cfg, terms, &points))
+        .collect();
+
+    let mut echelon = Echelon::default();
+    let (mut basis, mut dependent) = (Vec::new(), Vec::new());
+    for (k, v) in columns.iter().enumerate() {
+        if echelon.insert(v.clone()) {
+            basis.push(k);
+        } else {
+            dependent.push(k);
+        }
+    }
+    if basis.len() >= samples {
+        return Err(format!(
+            "rank reached the {} samples; evaluate at more points",
+            samples
+        ));
+    }
+
+    // Row reduce [basis | dependent]; the basis columns are the pivots, so the
+    // dependent columns hold the expansion coefficients.
+    let order: Vec<usize> = basis.iter().chain(&dependent).copied().collect();
+    let mut matrix: Vec<Vec<Fp<P>>> = (0..samples)
+        .map(|r| order.iter().map(|&k| Fp::new(columns[k][r])).collect())
+        .collect();
+    row_reduce(&mut matrix, order.len());
+
+    let modulus = BigInt::from(P);
+    let mut out = BasisReduction {
+        basis: basis.iter().map(|&k| structures[k].clone()).collect(),
+        relations: Vec::with_capacity(dependent.len()),
+    };
+    for (j, &k) in dependent.iter().enumerate() {
+        let col = basis.len() + j;
+        let mut expr = TensorExpression::new();
+        for (row, t) in out.basis.iter().enumerate() {
+            let x = matrix[row][col].value();
+            if x != 0 {
+                let c = rational_reconstruction(&BigInt::from(x), &modulus).ok_or_else(|| {
+                    format!(
+                        "could not reconstruct the coefficients of {}",
+                        structures[k]
+                    )
+                })?;
+                expr.push(c, t.clone());
+            }
+        }
+        out.relations.push((structures[k].clone(), expr));
+    }
+    Ok(out)
+}
+
+/// All linear relations among `structures` with constant coefficients.
+///
+/// There is one relation `Σ c_k T_k = 0` per structure dependent on earlier
+/// ones, read off from [`reduce_to_independent_basis`], so together they span
+/// the null space of the evaluation matrix. Each starts with its dependent
+/// structure, followed by the basis structures it involves in input order,
+/// with coprime integer coefficients and the first one positive.
+pub fn find_relations(
+    cfg: &GenConfig,
+    structures: &[TensorStructure],
+) -> Result<Vec<TensorExpression>, String> {
+    let reduction = reduce_to_independent_basis(cfg, structures, structures.len() + 8)?;
+    Ok(reduction
+        .relations
+        .into_iter()
+        .map(|(t, e)| {
+            let lcm = e
+                .terms
+                .iter()
+                .fold(BigInt::one(), |acc, (c, _)| acc.lcm(c.denom()));
+            let ints: Vec<BigInt> = e
+                .terms
+                .iter()
+                .map(|(c, _)| (c * BigRational::from_integer(lcm.clone())).to_integer())
+                .collect();
+            let gcd = ints.iter().fold(lcm.clone(), |acc, c| acc.gcd(c));
+            let mut relation = TensorExpression::new();
+            relation.push(BigRational::from_integer(&lcm / &gcd), t);
+            for (c, (_, u)) in ints.into_iter().zip(e.terms) {
+                relation.push(BigRational::from_integer(-c / &gcd), u);
+            }
+            relation
+        })
+        .collect())
+}
+
+/// Expand field strengths and reject what cannot be evaluated numerically.
+fn expand_all(
+    structures: &[TensorStructure],
+) -> Result<Vec<Vec<(BigRational, TensorStructure)>>, String> {
+    let mut expanded = Vec::with_capacity(structures.len());
+    for t in structures {
+        if t.epsilon.is_some() || !t.bilinears.is_empty() {
+            return Err(format!("cannot evaluate {} numerically", t));
+        }
+        let e = expand_field_strengths(t);
+        let bracket = e
+            .terms
+            .iter()
+            .flat_map(|(_, u)| &u.factors)
+            .any(|f| matches!(f.kind, ScalarKind::Angle | ScalarKind::Square));
+        if bracket {
+            return Err(format!("cannot evaluate spinor brackets in {}", t));
+        }
+        expanded.push(e.terms);
+    }
+    Ok(expanded)
+}
+
+/// A dimension in which the momenta, polarizations and loop momenta of a
+/// point are generic, so no Gram relations hold.
+fn generic_dim(cfg: &GenConfig) -> u32 {
+    2 * (cfg.n_legs as u32 + cfg.n_loops as u32) + 2
+}
+
+fn sample(
+    cfg: &GenConfig,
+    d: u32,
+    n_points: usize,
+    rng: &mut SplitMix64,
+) -> Result<Vec<Vec<u64>>, String> {
+    (0..n_points)
+        .map(|_| point(cfg, d, rng))
+        .collect::<Option<_>>()
+        .ok_or_else(|| format!("no on-shell kinematics found in {} dimensions", d))
+}
+
+/// Values of an expanded structure at each point, modulo `P`.
+fn evaluate(
+    cfg: &GenConfig,
+    terms: &[(BigRational, TensorStructure)],
+    points: &[Vec<u64>],
+) -> Vec<u64> {
+    points
+        .iter()
+        .map(|dots| {
+            terms.iter().fold(0, |acc, (c, u)| {
+                let c = from_rational(c);
+                let v = u
+                    .factors
+                    .iter()
+                    .fold(c, |v, f| mul(v, dots[dot_index(cfg, f)]));
+                add(acc, v)
+            })
+        })
+        .collect()
+}
+
+pub(crate) const P: u64 = (1 << 61) - 1;
+
+pub(crate) fn add(a: u64, b: u64) -> u64 {
+    (a + b) % P
+}
+
+fn sub(a: u64, b: u64) -> u64 {
+    (a + P - b) % P
+}
+
+pub(crate) fn mul(a: u64, b: u64) -> u64 {
+    ((a as u128 * b as u128) % P as u128) as u64
+}
+
+fn inv(a: u64) -> u64 {
+    // Fermat: a^(P-2).
+    let (mut base, mut exp, mut acc) = (a, P - 2, 1);
+    while exp > 0 {
+        if exp & 1 == 1 {
+            acc = mul(acc, base);
+        }
+        base = mul(base, base);
+        exp >>= 1;
+    }
+    acc
+}
+
+pub(crate) fn from_rational(c: &BigRational) -> u64 {
+    let reduce = |x: &BigInt| {
+        let r = (x.magnitude() % P).to_u64().expect("reduced below P");
+        if x.is_negative() { sub(0, r) } else { r }
+    };
+    mul(reduce(c.numer()), inv(reduce(c.denom())))
+}
+
+/// Minkowski product with signature `(+, −, …, −)`.
+fn minkowski(a: &[u64], b: &[u64]) -> u64 {
+    let mut acc = mul(a[0], b[0]);
+    for (x, y) in a[1..].iter().zip(&b[1..]) {
+        acc = sub(acc, mul(*x, *y));
+    }
+    acc
+}
+
+/// A random null vector `(1 + |u|², 2u, 1 − |u|²)` scaled by a random factor.
+fn null_vector(d: u32, rng: &mut SplitMix64) -> Vec<u64> {
+    let u: Vec<u64> = (0..d.saturating_sub(2))
+        .map(|_| rng.next_u64() % P)
+        .collect();
+    let u2 = u.iter().fold(0, |acc, &x| add(acc, mul(x, x)));
+    let scale = rng.next_u64() % P;
+    let mut v = vec![mul(scale, add(1, u2))];
+    v.extend(u.iter().map(|&x| mul(scale, add(x, x))));
+    if d >= 2 {
+        v.push(mul(scale, sub(1, u2)));
+    }
+    v
+}
+
+fn random_vector(d: u32, rng: &mut SplitMix64) -> Vec<u64> {
+    (0..d).map(|_| rng.next_u64() % P).collect()
+}
+
+/// All vectors of one kinematic point: momenta, then polarizations, then loop momenta.
+///
+/// Returned as the table of pairwise Minkowski products, indexed by [`dot_index`],
+/// or `None` if no valid point turned up, as happens when `d` is too small for
+/// the kinematics to exist at all.
+fn point(cfg: &GenConfig, d: u32, rng: &mut SplitMix64) -> Option<Vec<u64>> {
+    const ATTEMPTS: usize = 64;
+    let n = cfg.n_legs;
+    'retry: for _ in 0..ATTEMPTS {
+        let mut p: Vec<Vec<u64>> = Vec::with_capacity(n as usize);
+        for i in 1..=n {
+            if cfg.has_virtuality(LegIndex(i)) {
+                p.push(random_vector(d, rng));
+            } else {
+                p.push(null_vector(d, rng));
+            }
+        }
+        // Momentum conservation: fix the eliminated (or last) leg, rescaling a
+        // massless helper leg so that the fixed one stays on shell if needed.
+        let t = cfg.eliminated_leg().unwrap_or(LegIndex(n)).0 as usize - 1;
+        let helper = (0..n as usize)
+            .rev()
+            .find(|&j| j != t && !cfg.has_virtuality(LegIndex(j as u8 + 1)));
+        let mut rest = vec![0; d as usize];
+        for (j, v) in p.iter().enumerate() {
+            if j != t && Some(j) != helper {
+                for (r, x) in rest.iter_mut().zip(v) {
+                    *r = sub(*r, *x);
+                }
+            }
+        }
+        match helper {
+            Some(h) if !cfg.has_virtuality(LegIndex(t as u8 + 1)) => {
+                let q = p[h].clone();
+                let pq = minkowski(&rest, &q);
+                if pq == 0 {
+                    continue 'retry;
+                }
+                let lambda = mul(minkowski(&rest, &rest), inv(add(pq, pq)));
+                p[h] = q.iter().map(|&x| mul(lambda, x)).collect();
+            }
+            _ => {}
+        }
+        let mut last = rest;
+        if let Some(h) = helper {
+            for (r, x) in last.iter_mut().zip(&p[h]) {
+                *r = sub(*r, *x);
+            }
+        }
+        p[t] = last;
+
+        let reference = null_vector(d, rng);
+        let mut vectors = p.clone();
+        for i in 1..=n {
+            let mut e = random_vector(d, rng);
+            if cfg.has_polarization(LegIndex(i)) && cfg.is_transverse(LegIndex(i)) {
+                let pi = &p[i as usize - 1];
+                let qp = minkowski(&reference, pi);
+                if qp == 0 {
+                    continue 'retry;
+                }
+                let c = mul(minkowski(&e, pi), inv(qp));
+                for (x, r) in e.iter_mut().zip(&reference) {
+                    *x = sub(*x, mul(c, *r));
+                }
+            }
+            vectors.push(e);
+        }
+        for _ in 0..cfg.n_loops {
+            vectors.push(random_vector(d, rng));
+        }
+
+        let m = vectors.len();
+        let mut dots = vec![0; m * m];
+        for a in 0..m {
+            for b in a..m {
+                let v = minkowski(&vectors[a], &vectors[b]);
+                dots[a * m + b] = v;
+                dots[b * m + a] = v;
+            }
+        }
+        return Some(dots);
+    }
+    None
+}
+
+/// Position of a dot product in the table built by [`point`].
+fn dot_index(cfg: &GenConfig, f: &ScalarFactor) -> usize {
+    let n = cfg.n_legs as usize;
+    let m = 2 * n + cfg.n_loops as usize;
+    let p = |l: LegIndex| l.0 as usize - 1;
+    let e = |l: LegIndex| n + l.0 as usize - 1;
+    let l = |l: LegIndex| 2 * n + l.0 as usize - 1;
+    let (x, y) = match f.kind {
+        ScalarKind::PP => (p(f.a), p(f.b)),
+        ScalarKind::PE => (p(f.a), e(f.b)),
+        ScalarKind::EE => (e(f.a), e(f.b)),
+        ScalarKind::LP => (l(f.a), p(f.b)),
+        ScalarKind::LE => (l(f.a), e(f.b)),
+        ScalarKind::LL => (l(f.a), l(f.b)),
+        ScalarKind::Angle | ScalarKind::Square => unreachable!("rejected by gram_reduce"),
+    };
+    x * m + y
+}
+
+/// Incremental row echelon form over `F_P` on dense sample vectors.
+#[derive(Default)]
+pub(crate) struct Echelon {
+    rows: Vec<(usize, Vec<u64>)>,
+}
+
+impl Echelon {
+    /// Add a row of samples; returns `false` if it is a combination of the existing rows.
+    pub(crate) fn insert(&mut self, mut v: Vec<u64>) -> bool {
+        for (pivot, row) in &self.rows {
+            let factor = v[*pivot];
+            if factor == 0 {
+                continue;
+            }
+            for (x, &c) in v.iter_mut().zip(row) {
+                *x = sub(*x, mul(factor, c));
+            }
+        }
+        let Some(pivot) = v.iter().position(|&x| x != 0) else {
+            return false;
+        };
+        let scale = inv(v[pivot]);
+        for x in &mut v {
+            *x = mul(*x, scale);
+        }
+        self.rows.push((pivot, v));
+        true
+    }
+
+    /// Number of independent rows inserted so far.
+    pub(crate) fn rank(&self) -> usize {
+        self.rows.len()
+    }
+}
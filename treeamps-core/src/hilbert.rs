@@ -0,0 +1,413 @@
+use std::collections::{BTreeMap, HashMap};
+
+use smallvec::{SmallVec, smallvec};
+
+use crate::{
+    dot_product::ScalarFactor,
+    generator::{GenConfig, factor_catalog, generate_bilinear_sets, generate_epsilon_factors},
+    types::{Canonicalization, LegIndex, ScalarKind, Slot},
+};
+
+/// Grading of a monomial: degree, EE count, PP count, loop power, momentum
+/// power and the polarizations per leg (slot 0 unused).
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+struct Grade {
+    deg: u32,
+    ee: u32,
+    pp: u32,
+    loop_power: u32,
+    momentum: u32,
+    pols: SmallVec<[u32; 16]>,
+}
+
+impl Grade {
+    fn zero(n_legs: u8) -> Self {
+        Self {
+            deg: 0,
+            ee: 0,
+            pp: 0,
+            loop_power: 0,
+            momentum: 0,
+            pols: smallvec![0; n_legs as usize + 1],
+        }
+    }
+
+    fn of_factor(f: &ScalarFactor, n_legs: u8) -> Self {
+        let mut g = Self::zero(n_legs);
+        g.deg = 1;
+        g.loop_power = f.loop_power();
+        g.momentum = f.momentum_power();
+        match f.kind {
+            ScalarKind::PP => g.pp = 1,
+            ScalarKind::PE | ScalarKind::LE => g.pols[f.b.0 as usize] += 1,
+            ScalarKind::EE => {
+                g.ee = 1;
+                g.pols[f.a.0 as usize] += 1;
+                g.pols[f.b.0 as usize] += 1;
+            }
+            _ => {}
+        }
+        g
+    }
+
+    fn add_scaled(&self, other: &Self, k: u32) -> Self {
+        Self {
+            deg: self.deg + k * other.deg,
+            ee: self.ee + k * other.ee,
+            pp: self.pp + k * other.pp,
+            loop_power: self.loop_power + k * other.loop_power,
+            momentum: self.momentum + k * other.momentum,
+            pols: self
+                .pols
+                .iter()
+                .zip(&other.pols)
+                .map(|(a, b)| a + k * b)
+                .collect(),
+        }
+    }
+}
+
+/// Truncation of the series to the grades a search for `(deg, ee)` can reach.
+struct Bounds {
+    deg: u32,
+    ee: u32,
+    pp: u32,
+    loop_power: u32,
+    momentum: u32,
+    pols: Option<Vec<u32>>,
+}
+
+impl Bounds {
+    fn admits(&self, g: &Grade) -> bool {
+        g.deg <= self.deg
+            && g.ee <= self.ee
+            && g.pp <= self.pp
+            && g.loop_power <= self.loop_power
+            && g.momentum <= self.momentum
+            && self
+                .pols
+                .as_ref()
+                .is_none_or(|t| g.pols.iter().zip(t).all(|(c, t)| c <= t))
+    }
+}
+
+impl Bounds {
+    /// `g` as the series tracks it: without polarizations when nothing
+    /// constrains them, which keeps the number of distinct grades small.
+    fn project(&self, mut g: Grade) -> Grade {
+        if self.pols.is_none() {
+            g.pols.clear();
+        }
+        g
+    }
+}
+
+/// Truncated Hilbert series of the free polynomial ring on a set of generators.
+type Series = HashMap<Grade, u64>;
+
+/// Multiply `series` by `Σ_{k=min}^{max} t^{k·w}` for a generator of weight `w`.
+fn multiply_geometric(series: &Series, w: &Grade, min: u32, max: u32, bounds: &Bounds) -> Series {
+    let mut out = Series::new();
+    for (g, &c) in series {
+        let mut k = min;
+        while k <= max {
+            let h = g.add_scaled(w, k);
+            if !bounds.admits(&h) {
+                break;
+            }
+            *out.entry(h).or_default() += c;
+            k += 1;
+        }
+    }
+    out
+}
+
+/// Multiply `series` by `1/(1 − t^w)^m = Σ_k C(k+m−1, k) t^{k·w}` for `m`
+/// unbounded generators sharing the weight `w`.
+fn multiply_power(series: &Series, w: &Grade, m: u64, bounds: &Bounds) -> Series {
+    let mut out = Series::new();
+    for (g, &c) in series {
+        let mut binomial = 1;
+        let mut k = 0;
+        loop {
+            let h = g.add_scaled(w, k);
+            if !bounds.admits(&h) {
+                break;
+            }
+            *out.entry(h).or_default() += c * binomial;
+            k += 1;
+            binomial = binomial * (k as u64 + m - 1) / k as u64;
+        }
+    }
+    out
+}
+
+/// Multiply `series` by the unbounded geometric factors of `weights`, one
+/// factor per distinct weight.
+fn multiply_free(
+    series: Series,
+    weights: impl IntoIterator<Item = Grade>,
+    bounds: &Bounds,
+) -> Series {
+    let mut groups = BTreeMap::<Grade, u64>::new();
+    for w in weights {
+        *groups.entry(w).or_default() += 1;
+    }
+    groups.iter().fold(series, |series, (w, &m)| {
+        multiply_power(&series, w, m, bounds)
+    })
+}
+
+/// Predict how many structures [`generate_tensor_structures`] returns for `(deg, ee)`.
+///
+/// The count is read off the Hilbert series `Π_f 1/(1 − t^{w(f)})` of the
+/// catalog, graded by degree, EE count and polarizations per leg, with the
+/// fermion bilinears and ε factor as fixed prefixes and multiplicity bounds
+/// truncating each geometric factor. No search is involved, so this gives an
+/// independent check of the enumeration. For plain dot products with at most
+/// one polarization per leg, such as gluon amplitudes, the coefficient has a
+/// closed form that is evaluated directly, whatever the multiplicity.
+///
+/// With [`Canonicalization::Cyclic`] the orbits are counted via Burnside's
+/// lemma (Molien's formula for the cyclic group); `None` is returned when the
+/// catalog is not closed under rotations or ε factors, bilinears or
+/// multiplicity bounds are present.
+///
+/// [`generate_tensor_structures`]: crate::generator::generate_tensor_structures
+pub fn predict_structure_count(cfg: &GenConfig, deg: u32, ee: u32) -> Option<u64> {
+    if deg == 0 || ee > deg {
+        return Some(0);
+    }
+    let catalog = factor_catalog(cfg);
+    let targets = cfg.polarization_targets();
+    let bounds = Bounds {
+        deg,
+        ee,
+        pp: cfg.max_pp.unwrap_or(u32::MAX),
+        loop_power: cfg.max_loop_power.unwrap_or(u32::MAX),
+        momentum: cfg.momentum_power.unwrap_or(u32::MAX),
+        pols: targets.clone(),
+    };
+    let accept = |g: &Grade| {
+        g.deg == deg
+            && g.ee == ee
+            && cfg.momentum_power.is_none_or(|m| g.momentum == m)
+            && targets.as_ref().is_none_or(|t| g.pols[..] == t[..])
+    };
+
+    match cfg.canonicalization {
+        Canonicalization::Labelled => {
+            if let Some(count) = closed_form_count(cfg, &catalog, deg, ee) {
+                return count.try_into().ok();
+            }
+            let series = labelled_series(cfg, &catalog, &bounds);
+            Some(
+                series
+                    .iter()
+                    .filter(|(g, _)| accept(g))
+                    .map(|(_, c)| c)
+                    .sum(),
+            )
+        }
+        Canonicalization::Cyclic => {
+            if cfg.include_parity_odd
+                || !cfg.multiplicity_bounds.is_empty()
+                || (1..=cfg.n_legs).any(|i| cfg.is_fermion(LegIndex(i)))
+            {
+                return None;
+            }
+            cyclic_count(cfg, &catalog, &bounds, &accept)
+        }
+    }
+}
+
+/// Closed-form count for dot products with at most one polarization per leg,
+/// in `O(n²)` instead of expanding the series.
+///
+/// The EE factors then form a matching on the `L` polarized legs. If every
+/// pair of them may be contracted and leg `i` has `c_i` factors `p·e_i`, the
+/// structures number `e_{L−2ee}(c) · (2ee−1)!! · C(P+pp−1, pp)`, with `e_k`
+/// the elementary symmetric polynomial in the `c_i`, `P` the PP factors in
+/// the catalog and `pp = deg + ee − L` of them in each structure. `None`
+/// when the configuration is outside this case; counts beyond `u128`
+/// saturate.
+pub(crate) fn closed_form_count(
+    cfg: &GenConfig,
+    catalog: &[ScalarFactor],
+    deg: u32,
+    ee: u32,
+) -> Option<u128> {
+    if cfg.canonicalization != Canonicalization::Labelled
+        || cfg.n_loops > 0
+        || cfg.include_parity_odd
+        || cfg.momentum_power.is_some()
+        || !cfg.multiplicity_bounds.is_empty()
+        || (1..=cfg.n_legs).any(|i| cfg.is_fermion(LegIndex(i)))
+    {
+        return None;
+    }
+    let targets = cfg.polarization_targets()?;
+    if targets.iter().any(|&t| t > 1) {
+        return None;
+    }
+    let polarized = |l: LegIndex| targets.get(l.0 as usize) == Some(&1);
+    let legs = targets.iter().filter(|&&t| t == 1).count() as u32;
+
+    let mut pe = vec![0u128; targets.len()];
+    let mut n_ee = 0u32;
+    let mut n_pp = 0u128;
+    for f in catalog {
+        match f.kind {
+            ScalarKind::PP => n_pp += 1,
+            ScalarKind::PE if polarized(f.b) => pe[f.b.0 as usize] += 1,
+            ScalarKind::EE if polarized(f.a) && polarized(f.b) && f.a != f.b => n_ee += 1,
+            ScalarKind::PE | ScalarKind::EE => {}
+            _ => return None,
+        }
+    }
+    if n_ee != legs * legs.saturating_sub(1) / 2 {
+        return None;
+    }
+
+    let Some(n_pe) = legs.checked_sub(2 * ee) else {
+        return Some(0);
+    };
+    let pp = match (deg + ee).checked_sub(legs) {
+        Some(pp) if pp <= cfg.max_pp.unwrap_or(u32::MAX) => pp,
+        _ => return Some(0),
+    };
+
+    let mut elementary = vec![0u128; n_pe as usize + 1];
+    elementary[0] = 1;
+    for (i, &c) in pe.iter().enumerate() {
+        if targets[i] == 1 {
+            for k in (1..elementary.len()).rev() {
+                elementary[k] = elementary[k].saturating_add(elementary[k - 1].saturating_mul(c));
+            }
+        }
+    }
+    let matchings = (1..=ee).fold(1u128, |acc, k| acc.saturating_mul(2 * k as u128 - 1));
+    let pp_choices = (1..=pp as u128).fold(1u128, |acc, k| acc.saturating_mul(n_pp + k - 1) / k);
+    Some(
+        elementary[n_pe as usize]
+            .saturating_mul(matchings)
+            .saturating_mul(pp_choices),
+    )
+}
+
+fn labelled_series(cfg: &GenConfig, catalog: &[ScalarFactor], bounds: &Bounds) -> Series {
+    let n = cfg.n_legs;
+    let mut series = Series::new();
+    for prefix in prefix_grades(cfg) {
+        if bounds.admits(&prefix) {
+            *series.entry(bounds.project(prefix)).or_default() += 1;
+        }
+    }
+    // Factors of equal weight, such as every p_j·e_i for one leg i, enter
+    // together as a power of one geometric series unless they are bounded.
+    let mut free = Vec::new();
+    for f in catalog {
+        let w = bounds.project(Grade::of_factor(f, n));
+        let (min, max) = cfg
+            .multiplicity_bounds
+            .iter()
+            .filter(|b| b.factor == *f)
+            .fold((0, u32::MAX), |(lo, hi), b| {
+                (lo.max(b.min), hi.min(b.max.unwrap_or(u32::MAX)))
+            });
+        if (min, max) == (0, u32::MAX) {
+            free.push(w);
+        } else {
+            series = multiply_geometric(&series, &w, min, max, bounds);
+        }
+    }
+    series = multiply_free(series, free, bounds);
+    // A required factor missing from the catalog can never be met.
+    if cfg
+        .multiplicity_bounds
+        .iter()
+        .any(|b| b.min > 0 && !catalog.contains(&b.factor))
+    {
+        series.clear();
+    }
+    series
+}
+
+/// Grades of the fixed bilinear/ε prefixes the search starts from.
+fn prefix_grades(cfg: &GenConfig) -> Vec<Grade> {
+    let n = cfg.n_legs;
+    let pol_grade = |slots: &mut dyn Iterator<Item = Slot>, factors: u32| {
+        let mut g = Grade::zero(n);
+        g.deg = factors;
+        for s in slots {
+            match s {
+                Slot::E(l) => g.pols[l.0 as usize] += 1,
+                Slot::P(_) => g.momentum += 1,
+            }
+        }
+        g
+    };
+    let mut out = Vec::new();
+    for bilinears in generate_bilinear_sets(cfg) {
+        let base = pol_grade(
+            &mut bilinears.iter().map(|b| b.gamma),
+            bilinears.len() as u32,
+        );
+        out.push(base.clone());
+        if cfg.include_parity_odd {
+            for e in generate_epsilon_factors(cfg) {
+                let g = pol_grade(&mut e.slots.into_iter(), 1);
+                out.push(base.add_scaled(&g, 1));
+            }
+        }
+    }
+    out
+}
+
+/// Burnside count of rotation orbits among the labelled structures.
+fn cyclic_count(
+    cfg: &GenConfig,
+    catalog: &[ScalarFactor],
+    bounds: &Bounds,
+    accept: &impl Fn(&Grade) -> bool,
+) -> Option<u64> {
+    let n = cfg.n_legs;
+    if n == 0 {
+        return Some(0);
+    }
+    let index: BTreeMap<&ScalarFactor, usize> = catalog.iter().zip(0..).collect();
+    let mut total = 0u64;
+    for r in 0..n as u16 {
+        let rotate = |l: LegIndex| LegIndex(((l.0 as u16 - 1 + r) % n as u16 + 1) as u8);
+        let image: Vec<usize> = catalog
+            .iter()
+            .map(|f| index.get(&f.relabel(&rotate).0).copied())
+            .collect::<Option<_>>()?;
+        // A fixed monomial is constant on every cycle of the rotation, so each
+        // cycle acts as one generator carrying the summed weight of its factors.
+        let mut seen = vec![false; catalog.len()];
+        let mut series = Series::new();
+        series.insert(bounds.project(Grade::zero(n)), 1);
+        let mut cycles = Vec::new();
+        for start in 0..catalog.len() {
+            if seen[start] {
+                continue;
+            }
+            let mut w = Grade::zero(n);
+            let mut i = start;
+            while !seen[i] {
+                seen[i] = true;
+                w = w.add_scaled(&Grade::of_factor(&catalog[i], n), 1);
+                i = image[i];
+            }
+            cycles.push(bounds.project(w));
+        }
+        series = multiply_free(series, cycles, bounds);
+        total += series
+            .iter()
+            .filter(|(g, _)| accept(g))
+            .map(|(_, c)| c)
+            .sum::<u64>();
+    }
+    Some(total / n as u64)
+}
@@ -0,0 +1,454 @@
+use std::{collections::BTreeMap, fmt};
+
+use num_rational::BigRational;
+use num_traits::One;
+
+use crate::{
+    dimension::{DimPolynomial, rank_in_d},
+    dot_product::ScalarFactor,
+    linalg::rank,
+    reduction::expand_field_strengths,
+    tensor_structure::TensorStructure,
+    types::{LegIndex, ScalarKind, Slot},
+};
+
+/// Free Lorentz index left behind by a stripped polarization.
+///
+/// The `k`-th polarization of leg `i` (counting from 0) becomes the `k`-th
+/// index of that leg, printed `μi`, `νi`, `ρi`, `σi`, then `μi_4`, ….
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct LorentzIndex {
+    pub leg: LegIndex,
+    pub position: u8,
+}
+
+impl fmt::Display for LorentzIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const NAMES: [&str; 4] = ["μ", "ν", "ρ", "σ"];
+        match NAMES.get(self.position as usize) {
+            Some(name) => write!(f, "{}{}", name, self.leg.0),
+            None => write!(f, "μ{}_{}", self.leg.0, self.position),
+        }
+    }
+}
+
+/// An ε slot after stripping: a contracted momentum or a free index.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum IndexedSlot {
+    Momentum(LegIndex),
+    Free(LorentzIndex),
+}
+
+impl fmt::Display for IndexedSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Momentum(l) => write!(f, "p{}", l.0),
+            Self::Free(i) => write!(f, "{}", i),
+        }
+    }
+}
+
+/// One factor of a tensor with free Lorentz indices.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum IndexedFactor {
+    /// `η^{μν}` from a stripped `e_i·e_j`.
+    Metric(LorentzIndex, LorentzIndex),
+    /// `p_a^μ` from a stripped `p_a·e_i`.
+    Momentum(LegIndex, LorentzIndex),
+    /// `l_a^μ` from a stripped `l_a·e_i`.
+    LoopMomentum(LegIndex, LorentzIndex),
+    /// `ε^{…}` with its polarization slots turned into free indices.
+    Epsilon([IndexedSlot; 4]),
+    /// `ū_i γ^μ u_j`, or `ū_i γ·p u_j` when nothing is stripped.
+    Bilinear {
+        bar: LegIndex,
+        gamma: IndexedSlot,
+        u: LegIndex,
+    },
+    /// A factor without polarizations, kept as it is.
+    Scalar(ScalarFactor),
+}
+
+impl fmt::Display for IndexedFactor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Metric(a, b) => write!(f, "η^{{{}{}}}", a, b),
+            Self::Momentum(l, i) => write!(f, "p_{}^{{{}}}", l.0, i),
+            Self::LoopMomentum(l, i) => write!(f, "l_{}^{{{}}}", l.0, i),
+            Self::Epsilon([a, b, c, d]) => write!(f, "ε({},{},{},{})", a, b, c, d),
+            Self::Bilinear { bar, gamma, u } => match gamma {
+                IndexedSlot::Free(i) => write!(f, "(ū{} γ^{{{}}} u{})", bar.0, i, u.0),
+                IndexedSlot::Momentum(l) => write!(f, "(ū{} γ·p{} u{})", bar.0, l.0, u.0),
+            },
+            Self::Scalar(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// A tensor structure with its polarizations stripped off.
+///
+/// This is the form needed to build projectors: the structure is recovered by
+/// contracting each free index with the matching polarization vector.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct IndexedTensor {
+    pub factors: Vec<IndexedFactor>,
+}
+
+impl IndexedTensor {
+    /// All free indices, sorted by leg and position.
+    ///
+    /// Dummy indices left by [`contract`](Self::contract) appear twice.
+    pub fn free_indices(&self) -> Vec<LorentzIndex> {
+        let mut out: Vec<LorentzIndex> = self.factors.iter().flat_map(indices).collect();
+        out.sort();
+        out
+    }
+
+    /// Sum over every index that appears twice, in `d` dimensions.
+    ///
+    /// Metrics are eaten by renaming, and each closed trace `η^μ_μ` gives a
+    /// factor `d`. Momenta contracted with each other become dot products,
+    /// and a momentum contracted into an ε or a bilinear fills that slot. Pairs
+    /// between ε's, bilinears and loop momenta in ε slots cannot be written as
+    /// factors and stay as dummy indices. The coefficient is `d^k`, or zero
+    /// when an ε ends up with two equal slots.
+    pub fn contract(&self) -> (DimPolynomial, IndexedTensor) {
+        let mut factors = self.factors.clone();
+        let mut traces = 0;
+        let mut dummies: Vec<LorentzIndex> = Vec::new();
+        loop {
+            let all: Vec<LorentzIndex> = factors.iter().flat_map(indices).collect();
+            let Some(&mu) = all
+                .iter()
+                .enumerate()
+                .find(|&(k, i)| all[k + 1..].contains(i) && !dummies.contains(i))
+                .map(|(_, i)| i)
+            else {
+                break;
+            };
+            let at: Vec<usize> = (0..factors.len())
+                .filter(|&k| indices(&factors[k]).contains(&mu))
+                .collect();
+            if let Some(k) = at
+                .iter()
+                .copied()
+                .find(|&k| matches!(factors[k], IndexedFactor::Metric(..)))
+            {
+                let IndexedFactor::Metric(a, b) = factors.remove(k) else {
+                    unreachable!()
+                };
+                if a == b {
+                    traces += 1;
+                } else {
+                    let nu = if a == mu { b } else { a };
+                    for f in &mut factors {
+                        rename(f, mu, nu);
+                    }
+                }
+                continue;
+            }
+            let &[x, y] = at.as_slice() else {
+                // An ε holding the index twice vanishes.
+                return (DimPolynomial::default(), IndexedTensor::default());
+            };
+            let (x, y) = match (&factors[x], &factors[y]) {
+                (IndexedFactor::Epsilon(_) | IndexedFactor::Bilinear { .. }, _) => (y, x),
+                _ => (x, y),
+            };
+            let momentum = |l: LegIndex| IndexedSlot::Momentum(l);
+            let fill = |slot: &mut IndexedSlot, p: LegIndex| {
+                if *slot == IndexedSlot::Free(mu) {
+                    *slot = momentum(p);
+                }
+            };
+            let merged = match (&factors[x], &factors[y]) {
+                (IndexedFactor::Momentum(p, _), IndexedFactor::Momentum(q, _)) => Some(
+                    IndexedFactor::Scalar(ScalarFactor::dot(Slot::P(*p), Slot::P(*q))),
+                ),
+                (IndexedFactor::LoopMomentum(l, _), IndexedFactor::Momentum(p, _))
+                | (IndexedFactor::Momentum(p, _), IndexedFactor::LoopMomentum(l, _)) => {
+                    Some(IndexedFactor::Scalar(ScalarFactor::lp(*l, *p)))
+                }
+                (IndexedFactor::LoopMomentum(l, _), IndexedFactor::LoopMomentum(m, _)) => {
+                    Some(IndexedFactor::Scalar(ScalarFactor::ll(*l, *m)))
+                }
+                (IndexedFactor::Momentum(p, _), IndexedFactor::Epsilon(slots)) => {
+                    let mut slots = *slots;
+                    slots.iter_mut().for_each(|s| fill(s, *p));
+                    if slots.iter().filter(|&&s| s == momentum(*p)).count() > 1 {
+                        return (DimPolynomial::default(), IndexedTensor::default());
+                    }
+                    Some(IndexedFactor::Epsilon(slots))
+                }
+                (IndexedFactor::Momentum(p, _), IndexedFactor::Bilinear { bar, gamma, u }) => {
+                    let mut gamma = *gamma;
+                    fill(&mut gamma, *p);
+                    Some(IndexedFactor::Bilinear {
+                        bar: *bar,
+                        gamma,
+                        u: *u,
+                    })
+                }
+                _ => None,
+            };
+            match merged {
+                Some(f) => {
+                    factors[y] = f;
+                    factors.remove(x);
+                }
+                None => dummies.push(mu),
+            }
+        }
+        factors.sort();
+        (DimPolynomial::d_pow(traces), IndexedTensor { factors })
+    }
+}
+
+/// Indices carried by one factor, in slot order.
+fn indices(f: &IndexedFactor) -> Vec<LorentzIndex> {
+    match f {
+        IndexedFactor::Metric(a, b) => vec![*a, *b],
+        IndexedFactor::Momentum(_, i) | IndexedFactor::LoopMomentum(_, i) => vec![*i],
+        IndexedFactor::Epsilon(slots) => slots
+            .iter()
+            .filter_map(|s| match s {
+                IndexedSlot::Free(i) => Some(*i),
+                IndexedSlot::Momentum(_) => None,
+            })
+            .collect(),
+        IndexedFactor::Bilinear {
+            gamma: IndexedSlot::Free(i),
+            ..
+        } => vec![*i],
+        IndexedFactor::Bilinear { .. } | IndexedFactor::Scalar(_) => Vec::new(),
+    }
+}
+
+/// Replace the index `from` by `to` wherever it appears in `f`.
+fn rename(f: &mut IndexedFactor, from: LorentzIndex, to: LorentzIndex) {
+    let swap = |i: &mut LorentzIndex| {
+        if *i == from {
+            *i = to;
+        }
+    };
+    let swap_slot = |s: &mut IndexedSlot| {
+        if let IndexedSlot::Free(i) = s {
+            swap(i);
+        }
+    };
+    match f {
+        IndexedFactor::Metric(a, b) => {
+            swap(a);
+            swap(b);
+        }
+        IndexedFactor::Momentum(_, i) | IndexedFactor::LoopMomentum(_, i) => swap(i),
+        IndexedFactor::Epsilon(slots) => slots.iter_mut().for_each(swap_slot),
+        IndexedFactor::Bilinear { gamma, .. } => swap_slot(gamma),
+        IndexedFactor::Scalar(_) => {}
+    }
+}
+
+impl fmt::Display for IndexedTensor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.factors.is_empty() {
+            return write!(f, "1");
+        }
+        let joined: Vec<String> = self.factors.iter().map(|x| x.to_string()).collect();
+        write!(f, "{}", joined.join(" "))
+    }
+}
+
+/// Strip the polarizations of `t`, leaving one free Lorentz index per `e_i`.
+///
+/// Field strengths are expanded first, so the result is a sum; for other
+/// structures it is a single term with coefficient `1`. Indices are handed out
+/// per leg in order of appearance: bilinears, then ε, then the scalar factors.
+pub fn strip_polarizations(t: &TensorStructure) -> Vec<(BigRational, IndexedTensor)> {
+    expand_field_strengths(t)
+        .terms
+        .iter()
+        .map(|(c, u)| (c.clone(), strip(u)))
+        .collect()
+}
+
+fn strip(t: &TensorStructure) -> IndexedTensor {
+    let mut used: Vec<u8> = Vec::new();
+    let mut next = |leg: LegIndex| {
+        let k = leg.0 as usize;
+        if used.len() <= k {
+            used.resize(k + 1, 0);
+        }
+        used[k] += 1;
+        LorentzIndex {
+            leg,
+            position: used[k] - 1,
+        }
+    };
+    let mut slot = |s: Slot| match s {
+        Slot::P(l) => IndexedSlot::Momentum(l),
+        Slot::E(l) => IndexedSlot::Free(next(l)),
+    };
+
+    let mut out = IndexedTensor::default();
+    for b in &t.bilinears {
+        out.factors.push(IndexedFactor::Bilinear {
+            bar: b.bar,
+            gamma: slot(b.gamma),
+            u: b.u,
+        });
+    }
+    if let Some(e) = &t.epsilon {
+        out.factors
+            .push(IndexedFactor::Epsilon(e.slots.map(&mut slot)));
+    }
+    for f in &t.factors {
+        let free = |s: IndexedSlot| match s {
+            IndexedSlot::Free(i) => i,
+            IndexedSlot::Momentum(_) => unreachable!("polarization slot"),
+        };
+        out.factors.push(match f.kind {
+            ScalarKind::EE => {
+                let a = free(slot(Slot::E(f.a)));
+                IndexedFactor::Metric(a, free(slot(Slot::E(f.b))))
+            }
+            ScalarKind::PE => IndexedFactor::Momentum(f.a, free(slot(Slot::E(f.b)))),
+            ScalarKind::LE => IndexedFactor::LoopMomentum(f.a, free(slot(Slot::E(f.b)))),
+            _ => IndexedFactor::Scalar(f.clone()),
+        });
+    }
+    out
+}
+
+/// Sum over the polarizations shared by `a` and `b`, keeping `d` symbolic.
+///
+/// Both structures are stripped, and each pair of matching free indices is
+/// contracted with `η^{μν}`, which is the Feynman-gauge sum
+/// `Σ e^μ e^{*ν}` up to the overall sign of the metric convention; gauge
+/// terms drop out when either side is gauge invariant. Indices are matched by
+/// leg and position, so with one polarization per leg the result is a sum of
+/// products of dot products with coefficients polynomial in `d`, collected by
+/// tensor. Polarizations present on one side only are left as free indices.
+pub fn polarization_sum(
+    a: &TensorStructure,
+    b: &TensorStructure,
+) -> Vec<(DimPolynomial, IndexedTensor)> {
+    let mut terms: BTreeMap<IndexedTensor, DimPolynomial> = BTreeMap::new();
+    for (x, s) in strip_polarizations(a) {
+        for (y, t) in strip_polarizations(b) {
+            let mut product = s.clone();
+            product.factors.extend(t.factors.iter().cloned());
+            let (c, u) = product.contract();
+            if !c.is_zero() {
+                *terms.entry(u).or_default() += &(c * (&x * &y));
+            }
+        }
+    }
+    terms
+        .into_iter()
+        .filter(|(_, c)| !c.is_zero())
+        .map(|(u, c)| (c, u))
+        .collect()
+}
+
+/// Gram matrix `G_ij = Σ_pol T_i T_j` of a list of structures.
+///
+/// Entries come from [`polarization_sum`] and keep `d` symbolic; with every
+/// polarization summed they are sums of products of momentum dot products.
+/// Specializing `d` and the kinematics gives an exact rational matrix; at a
+/// generic point its rank is the number of structures independent over
+/// functions of the Mandelstams, e.g. 3 + 24 + 16 = 43 for four gluons.
+#[derive(Clone, Debug, Default)]
+pub struct GramMatrix {
+    /// `entries[i][j]`, symmetric in `i` and `j`.
+    pub entries: Vec<Vec<Vec<(DimPolynomial, IndexedTensor)>>>,
+}
+
+impl GramMatrix {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The matrix in dimension `d`, with `value` giving each remaining dot product.
+    ///
+    /// Returns `None` if `value` does, or if an entry still holds free or
+    /// dummy indices, e.g. from ε or bilinear factors.
+    pub fn evaluate(
+        &self,
+        d: &BigRational,
+        value: impl Fn(&ScalarFactor) -> Option<BigRational>,
+    ) -> Option<Vec<Vec<BigRational>>> {
+        let matrix = self.evaluate_in_d(value)?;
+        Some(
+            matrix
+                .iter()
+                .map(|row| row.iter().map(|c| c.at(d.clone())).collect())
+                .collect(),
+        )
+    }
+
+    /// As [`evaluate`](Self::evaluate), keeping `d` symbolic so entries are
+    /// polynomials in `d`.
+    pub fn evaluate_in_d(
+        &self,
+        value: impl Fn(&ScalarFactor) -> Option<BigRational>,
+    ) -> Option<Vec<Vec<DimPolynomial>>> {
+        let product = |u: &IndexedTensor| {
+            u.factors
+                .iter()
+                .try_fold(BigRational::one(), |v, f| match f {
+                    IndexedFactor::Scalar(s) => Some(v * value(s)?),
+                    _ => None,
+                })
+        };
+        self.entries
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|entry| {
+                        entry
+                            .iter()
+                            .try_fold(DimPolynomial::default(), |acc, (c, u)| {
+                                Some(acc + c.clone() * product(u)?)
+                            })
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Rank of [`evaluate`](Self::evaluate) at one point.
+    pub fn rank(
+        &self,
+        d: &BigRational,
+        value: impl Fn(&ScalarFactor) -> Option<BigRational>,
+    ) -> Option<usize> {
+        Some(rank(self.evaluate(d, value)?, self.len()))
+    }
+
+    /// Rank at one point for generic `d`, with the minor whose roots are the
+    /// dimensions where it may drop; see [`rank_in_d`].
+    pub fn rank_in_d(
+        &self,
+        value: impl Fn(&ScalarFactor) -> Option<BigRational>,
+    ) -> Option<(usize, DimPolynomial)> {
+        Some(rank_in_d(self.evaluate_in_d(value)?))
+    }
+}
+
+/// The Gram matrix of `structures` under the polarization sum.
+pub fn gram_matrix(structures: &[TensorStructure]) -> GramMatrix {
+    let n = structures.len();
+    let mut entries = vec![vec![Vec::new(); n]; n];
+    for i in 0..n {
+        for j in i..n {
+            let entry = polarization_sum(&structures[i], &structures[j]);
+            entries[j][i] = entry.clone();
+            entries[i][j] = entry;
+        }
+    }
+    GramMatrix { entries }
+}
@@ -0,0 +1,797 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
+
+use num_rational::BigRational;
+
+use crate::{
+    coefficient::Coefficient,
+    dot_product::ScalarFactor,
+    epsilon::EpsilonFactor,
+    expression::TensorExpression,
+    finite_field::Fp,
+    generator::GenConfig,
+    linalg::{SparseMatrix, rank},
+    mandelstam::Invariant,
+    rational::int,
+    reduction::expand_field_strengths,
+    rng::SplitMix64,
+    tensor_structure::TensorStructure,
+    types::{LegIndex, ScalarKind, Slot},
+};
+
+/// One kinematic point with explicit vectors in `d` dimensions.
+///
+/// Components are exact rationals, or elements of a prime field `F_p` for
+/// fast modular evaluation, in the metric `(+,−,…,−)`. Massless momenta are
+/// null, momentum is conserved, and transverse polarizations are orthogonal to
+/// their momenta; legs without polarizations still get a vector, which no
+/// structure refers to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KinematicPoint<C = BigRational> {
+    /// `p_i` at index `i − 1`.
+    pub momenta: Vec<Vec<C>>,
+    /// `e_i` at index `i − 1`.
+    pub polarizations: Vec<Vec<C>>,
+    /// `l_a` at index `a − 1`.
+    pub loop_momenta: Vec<Vec<C>>,
+}
+
+impl<C: Coefficient> KinematicPoint<C> {
+    pub fn dimension(&self) -> usize {
+        self.momenta.first().map_or(0, Vec::len)
+    }
+
+    /// Value of a dot-product factor, or `None` for spinor brackets.
+    pub fn dot(&self, f: &ScalarFactor) -> Option<C> {
+        let p = |l: LegIndex| &self.momenta[l.0 as usize - 1];
+        let e = |l: LegIndex| &self.polarizations[l.0 as usize - 1];
+        let l = |l: LegIndex| &self.loop_momenta[l.0 as usize - 1];
+        let (a, b) = match f.kind {
+            ScalarKind::PP => (p(f.a), p(f.b)),
+            ScalarKind::PE => (p(f.a), e(f.b)),
+            ScalarKind::EE => (e(f.a), e(f.b)),
+            ScalarKind::LP => (l(f.a), p(f.b)),
+            ScalarKind::LE => (l(f.a), e(f.b)),
+            ScalarKind::LL => (l(f.a), l(f.b)),
+            ScalarKind::Angle | ScalarKind::Square => return None,
+        };
+        Some(minkowski(a, b))
+    }
+
+    /// `s_I = (Σ_{i∈I} p_i)²` at this point.
+    pub fn invariant(&self, s: &Invariant) -> C {
+        let total: Vec<C> = (0..self.dimension())
+            .map(|mu| {
+                s.legs.iter().fold(C::zero(), |acc, l| {
+                    acc + self.momenta[l.0 as usize - 1][mu].clone()
+                })
+            })
+            .collect();
+        minkowski(&total, &total)
+    }
+
+    /// Value of `t` at this point, with field strengths expanded.
+    ///
+    /// The ε factor is `ε_{μνρσ} a^μ b^ν c^ρ d^σ` with `ε^{0123} = 1`, as in
+    /// products of structures, and needs four dimensions. Returns `None` for
+    /// bilinears, spinor brackets, ε in other dimensions and, over `F_p`,
+    /// field-strength coefficients whose denominators vanish mod `p`.
+    pub fn evaluate(&self, t: &TensorStructure) -> Option<C> {
+        if !t.bilinears.is_empty() {
+            return None;
+        }
+        let epsilon = match &t.epsilon {
+            Some(e) => self.epsilon(e)?,
+            None => C::one(),
+        };
+        let mut total = C::zero();
+        for (c, u) in expand_field_strengths(t).terms {
+            let mut v = C::from_rational(&c)?;
+            for f in &u.factors {
+                v *= self.dot(f)?;
+            }
+            total += v;
+        }
+        Some(total * epsilon)
+    }
+
+    /// Value of `expr` at this point; see [`evaluate`](Self::evaluate).
+    pub fn evaluate_expression(&self, expr: &TensorExpression) -> Option<C> {
+        let mut total = C::zero();
+        for (c, t) in &expr.terms {
+            total += C::from_rational(c)? * self.evaluate(t)?;
+        }
+        Some(total)
+    }
+
+    /// `ε_{μνρσ} a^μ b^ν c^ρ d^σ`, or `None` outside four dimensions.
+    fn epsilon(&self, e: &EpsilonFactor) -> Option<C> {
+        if self.dimension() != 4 {
+            return None;
+        }
+        let rows: Vec<&[C]> = e.slots.iter().map(|&s| self.vector(s)).collect();
+        Some(-det4(&rows))
+    }
+
+    /// Many structures at this point; see [`PointBatch`].
+    pub fn evaluate_all(&self, structures: &[TensorStructure]) -> Option<Vec<C>> {
+        let batch = PointBatch::new(std::slice::from_ref(self));
+        structures
+            .iter()
+            .map(|t| Some(batch.evaluate(t)?.remove(0)))
+            .collect()
+    }
+
+    fn vector(&self, s: Slot) -> &[C] {
+        match s {
+            Slot::P(l) => &self.momenta[l.0 as usize - 1],
+            Slot::E(l) => &self.polarizations[l.0 as usize - 1],
+        }
+    }
+
+    /// A random point for `cfg` in `d` dimensions, or `None` if none turned up,
+    /// as happens when `d` is too small for the kinematics to exist.
+    ///
+    /// Massless momenta come from the rational parametrization
+    /// `s (1 + t², 2t, 1 − t²)` of the light cone, with `s` and `t` drawn by
+    /// [`Coefficient::from_random_bits`]; momenta of massive and off-shell legs are arbitrary. The
+    /// eliminated (or last) momentum is fixed by conservation, rescaling one
+    /// massless helper leg to keep it on shell, and polarizations are made
+    /// transverse with a shared null reference vector.
+    pub(crate) fn random(cfg: &GenConfig, d: u32, rng: &mut SplitMix64) -> Option<Self> {
+        const ATTEMPTS: usize = 64;
+        if d < 2 {
+            return None;
+        }
+        let n = cfg.n_legs as usize;
+        'retry: for _ in 0..ATTEMPTS {
+            let mut p: Vec<Vec<C>> = (1..=n)
+                .map(|i| {
+                    if cfg.has_virtuality(LegIndex(i as u8)) {
+                        random_vector(d, rng)
+                    } else {
+                        null_vector(d, rng)
+                    }
+                })
+                .collect();
+            let t = cfg.eliminated_leg().unwrap_or(LegIndex(n as u8)).0 as usize - 1;
+            let helper = (0..n)
+                .rev()
+                .find(|&j| j != t && !cfg.has_virtuality(LegIndex(j as u8 + 1)));
+            let mut rest = vec![C::zero(); d as usize];
+            for (j, v) in p.iter().enumerate() {
+                if j != t && Some(j) != helper {
+                    subtract(&mut rest, v);
+                }
+            }
+            match helper {
+                Some(h) if !cfg.has_virtuality(LegIndex(t as u8 + 1)) => {
+                    let pq = minkowski(&rest, &p[h]);
+                    if pq.is_zero() {
+                        continue 'retry;
+                    }
+                    let lambda = minkowski(&rest, &rest) / (pq.clone() + pq);
+                    for x in &mut p[h] {
+                        *x *= lambda.clone();
+                    }
+                }
+                _ => {}
+            }
+            if let Some(h) = helper {
+                subtract(&mut rest, &p[h]);
+            }
+            p[t] = rest;
+            if let Some(point) = Self::complete(cfg, p, d, rng) {
+                return Some(point);
+            }
+        }
+        None
+    }
+
+    /// A random point for massless legs on the pole `s_I = 0` of `channel`.
+    ///
+    /// The legs of `I` sum to a random null momentum `P`, and the others to
+    /// `−P`, each side filled in with the same rescaling as [`random`](Self::random).
+    /// Returns `None` for massive or off-shell legs, channels with fewer than
+    /// two legs on either side, or when no point turned up.
+    pub(crate) fn on_pole(
+        cfg: &GenConfig,
+        channel: &Invariant,
+        d: u32,
+        rng: &mut SplitMix64,
+    ) -> Option<Self> {
+        const ATTEMPTS: usize = 64;
+        let legs: Vec<LegIndex> = (1..=cfg.n_legs).map(LegIndex).collect();
+        let (inside, outside): (Vec<LegIndex>, Vec<LegIndex>) =
+            legs.iter().partition(|l| channel.legs.contains(l));
+        if d < 2
+            || inside.len() < 2
+            || outside.len() < 2
+            || legs.iter().any(|&l| cfg.has_virtuality(l))
+        {
+            return None;
+        }
+        for _ in 0..ATTEMPTS {
+            let total: Vec<C> = null_vector(d, rng);
+            let minus: Vec<C> = total.iter().map(|x| -x.clone()).collect();
+            let (Some(left), Some(right)) = (
+                null_momenta(inside.len(), &total, d, rng),
+                null_momenta(outside.len(), &minus, d, rng),
+            ) else {
+                continue;
+            };
+            let mut p = vec![Vec::new(); legs.len()];
+            for (l, v) in inside.iter().zip(left).chain(outside.iter().zip(right)) {
+                p[l.0 as usize - 1] = v;
+            }
+            if let Some(point) = Self::complete(cfg, p, d, rng) {
+                return Some(point);
+            }
+        }
+        None
+    }
+
+    /// Add polarizations and loop momenta to the momenta `p`.
+    ///
+    /// Transverse polarizations are projected with a shared null reference;
+    /// `None` if it happens to be orthogonal to a momentum.
+    fn complete(cfg: &GenConfig, p: Vec<Vec<C>>, d: u32, rng: &mut SplitMix64) -> Option<Self> {
+        let reference = null_vector(d, rng);
+        let mut polarizations = Vec::with_capacity(p.len());
+        for (i, pi) in p.iter().enumerate() {
+            let leg = LegIndex(i as u8 + 1);
+            let mut e = random_vector(d, rng);
+            if cfg.has_polarization(leg) && cfg.is_transverse(leg) {
+                let qp = minkowski(&reference, pi);
+                if qp.is_zero() {
+                    return None;
+                }
+                let c = minkowski(&e, pi) / qp;
+                for (x, r) in e.iter_mut().zip(&reference) {
+                    *x -= c.clone() * r.clone();
+                }
+            }
+            polarizations.push(e);
+        }
+        let loop_momenta = (0..cfg.n_loops).map(|_| random_vector(d, rng)).collect();
+        Some(Self {
+            momenta: p,
+            polarizations,
+            loop_momenta,
+        })
+    }
+}
+
+/// A BCFW shift `p_i → p_i + z q`, `p_j → p_j − z q`.
+///
+/// `q` is null and orthogonal to `p_i` and `p_j`, so both shifted momenta stay
+/// on shell and momentum is conserved for every `z`; in real Minkowski space
+/// such a `q` is complex. Polarizations are not shifted, so the shifted point
+/// stays transverse only if `e_i·q = e_j·q = 0`, see
+/// [`is_transverse`](Self::is_transverse).
+#[derive(Clone, Debug, PartialEq)]
+pub struct BcfwShift<C = BigRational> {
+    pub i: LegIndex,
+    pub j: LegIndex,
+    pub q: Vec<C>,
+}
+
+impl<C: Coefficient> BcfwShift<C> {
+    /// The shift of legs `i` and `j` of `x` by `q`, checking that it is valid.
+    pub fn new(x: &KinematicPoint<C>, i: LegIndex, j: LegIndex, q: Vec<C>) -> Result<Self, String> {
+        let n = x.momenta.len() as u8;
+        if i == j || !(1..=n).contains(&i.0) || !(1..=n).contains(&j.0) {
+            return Err(format!(
+                "cannot shift legs {} and {} of {} legs",
+                i.0, j.0, n
+            ));
+        }
+        if q.len() != x.dimension() {
+            return Err(format!(
+                "q has {} components in {} dimensions",
+                q.len(),
+                x.dimension()
+            ));
+        }
+        let p = |l: LegIndex| &x.momenta[l.0 as usize - 1];
+        if !minkowski(&q, &q).is_zero()
+            || !minkowski(&q, p(i)).is_zero()
+            || !minkowski(&q, p(j)).is_zero()
+        {
+            return Err(format!(
+                "q must be null and orthogonal to p{} and p{}",
+                i.0, j.0
+            ));
+        }
+        Ok(Self { i, j, q })
+    }
+
+    /// Whether `e_i·q = e_j·q = 0`, so the shifted polarizations stay transverse.
+    pub fn is_transverse(&self, x: &KinematicPoint<C>) -> bool {
+        [self.i, self.j]
+            .iter()
+            .all(|l| minkowski(&self.q, &x.polarizations[l.0 as usize - 1]).is_zero())
+    }
+
+    /// The point at a fixed value of `z`.
+    pub fn apply(&self, x: &KinematicPoint<C>, z: &C) -> KinematicPoint<C> {
+        let mut out = x.clone();
+        for (l, sign) in [(self.i, C::one()), (self.j, -C::one())] {
+            for (v, q) in out.momenta[l.0 as usize - 1].iter_mut().zip(&self.q) {
+                *v += sign.clone() * z.clone() * q.clone();
+            }
+        }
+        out
+    }
+
+    /// The `z`-dependent part of a vector: `±q` for the shifted momenta, else `None`.
+    fn slope(&self, s: Slot) -> Option<Vec<C>> {
+        match s {
+            Slot::P(l) if l == self.i => Some(self.q.clone()),
+            Slot::P(l) if l == self.j => Some(self.q.iter().map(|x| -x.clone()).collect()),
+            _ => None,
+        }
+    }
+}
+
+impl<C: Coefficient> KinematicPoint<C> {
+    /// Value of `t` at the shifted point as a polynomial in `z`, the
+    /// coefficient of `z^k` at index `k`, with trailing zeros dropped.
+    ///
+    /// Exact in `z`: dot products are at most quadratic and an ε factor
+    /// multilinear in the shifted momenta. Returns `None` where
+    /// [`evaluate`](Self::evaluate) does.
+    pub fn evaluate_shifted(&self, shift: &BcfwShift<C>, t: &TensorStructure) -> Option<Vec<C>> {
+        if !t.bilinears.is_empty() {
+            return None;
+        }
+        let epsilon = match &t.epsilon {
+            Some(e) if self.dimension() == 4 => self.shifted_epsilon(shift, &e.slots),
+            Some(_) => return None,
+            None => vec![C::one()],
+        };
+        let mut total = vec![C::zero()];
+        for (c, u) in expand_field_strengths(t).terms {
+            let mut v = vec![C::from_rational(&c)?];
+            for f in &u.factors {
+                v = poly_mul(&v, &self.shifted_dot(shift, f)?);
+            }
+            poly_add(&mut total, &v);
+        }
+        Some(trim(poly_mul(&total, &epsilon)))
+    }
+
+    /// Value of `expr` at the shifted point as a polynomial in `z`; see
+    /// [`evaluate_shifted`](Self::evaluate_shifted).
+    pub fn evaluate_expression_shifted(
+        &self,
+        shift: &BcfwShift<C>,
+        expr: &TensorExpression,
+    ) -> Option<Vec<C>> {
+        let mut total = vec![C::zero()];
+        for (c, t) in &expr.terms {
+            let v = self.evaluate_shifted(shift, t)?;
+            poly_add(&mut total, &poly_mul(&[C::from_rational(c)?], &v));
+        }
+        Some(trim(total))
+    }
+
+    /// `f` at the shifted point: `[a·b, a·q' + q·b', q·q']` for vectors `a + z q`, `b + z q'`.
+    fn shifted_dot(&self, shift: &BcfwShift<C>, f: &ScalarFactor) -> Option<Vec<C>> {
+        let base = self.dot(f)?;
+        let p = |l: LegIndex| (self.vector(Slot::P(l)), shift.slope(Slot::P(l)));
+        let ((x, sx), (y, sy)) = match f.kind {
+            ScalarKind::PP => (p(f.a), p(f.b)),
+            ScalarKind::PE => (p(f.a), (self.vector(Slot::E(f.b)), None)),
+            ScalarKind::LP => ((&self.loop_momenta[f.a.0 as usize - 1][..], None), p(f.b)),
+            _ => return Some(vec![base]),
+        };
+        let mut out = vec![base, C::zero(), C::zero()];
+        if let Some(q) = &sx {
+            out[1] += minkowski(q, y);
+        }
+        if let Some(q) = &sy {
+            out[1] += minkowski(x, q);
+        }
+        if let (Some(a), Some(b)) = (&sx, &sy) {
+            out[2] += minkowski(a, b);
+        }
+        Some(out)
+    }
+
+    /// `ε(v1, v2, v3, v4)` at the shifted point, expanded over which slots take `z q`.
+    fn shifted_epsilon(&self, shift: &BcfwShift<C>, slots: &[Slot; 4]) -> Vec<C> {
+        let slopes: Vec<Option<Vec<C>>> = slots.iter().map(|&s| shift.slope(s)).collect();
+        let mut out = vec![C::zero(); 5];
+        for mask in 0..16usize {
+            if (0..4).any(|k| mask >> k & 1 == 1 && slopes[k].is_none()) {
+                continue;
+            }
+            let rows: Vec<&[C]> = (0..4)
+                .map(|k| match &slopes[k] {
+                    Some(q) if mask >> k & 1 == 1 => &q[..],
+                    _ => self.vector(slots[k]),
+                })
+                .collect();
+            out[mask.count_ones() as usize] -= det4(&rows);
+        }
+        out
+    }
+}
+
+impl KinematicPoint {
+    /// A reproducible random point for `cfg` in `d` dimensions, as in
+    /// [`random`](Self::random), drawn from `seed`.
+    ///
+    /// The same seed always gives the same point; [`random_seed`] draws a
+    /// fresh one to record alongside a run.
+    pub fn sample(cfg: &GenConfig, d: u32, seed: u64) -> Option<Self> {
+        Self::random(cfg, d, &mut SplitMix64::new(seed))
+    }
+
+    /// A reproducible point with `n` massless gluons in `d` dimensions.
+    ///
+    /// Momenta are null and sum to zero, with transverse polarizations; this
+    /// is [`sample`](Self::sample) for the default configuration on `n` legs.
+    pub fn massless(n: u8, d: u32, seed: u64) -> Option<Self> {
+        let cfg = GenConfig {
+            n_legs: n,
+            ..GenConfig::default()
+        };
+        Self::sample(&cfg, d, seed)
+    }
+}
+
+impl<const P: u64> KinematicPoint<Fp<P>> {
+    /// A reproducible random point over `F_P`, as in [`random`](Self::random),
+    /// drawn from `seed`.
+    ///
+    /// Components are uniform in `F_P`, so an accidental zero of a nonzero
+    /// polynomial has probability of order `1/P`; the point satisfies
+    /// momentum conservation, on-shell conditions and transversality mod `P`.
+    pub fn sample_mod_p(cfg: &GenConfig, d: u32, seed: u64) -> Option<Self> {
+        Self::random(cfg, d, &mut SplitMix64::new(seed))
+    }
+
+    /// The image of a rational point, or `None` if a denominator vanishes mod `P`.
+    pub fn reduce(x: &KinematicPoint) -> Option<Self> {
+        let reduce = |vs: &[Vec<BigRational>]| -> Option<Vec<Vec<Fp<P>>>> {
+            vs.iter()
+                .map(|v| v.iter().map(Fp::from_rational).collect())
+                .collect()
+        };
+        Some(Self {
+            momenta: reduce(&x.momenta)?,
+            polarizations: reduce(&x.polarizations)?,
+            loop_momenta: reduce(&x.loop_momenta)?,
+        })
+    }
+}
+
+impl TensorStructure {
+    /// Exact value at a rational point; see [`KinematicPoint::evaluate`].
+    pub fn eval_rational(&self, x: &KinematicPoint) -> Option<BigRational> {
+        x.evaluate(self)
+    }
+}
+
+impl TensorExpression {
+    /// Exact value at a rational point; see [`KinematicPoint::evaluate`].
+    pub fn eval_rational(&self, x: &KinematicPoint) -> Option<BigRational> {
+        x.evaluate_expression(self)
+    }
+}
+
+/// Many kinematic points prepared for evaluating structures in bulk.
+///
+/// Every dot product of every point is computed once and stored in one
+/// contiguous table, so a structure is expanded and resolved to table offsets
+/// once and then evaluated at each point by lookups and products alone. The
+/// points must all have the same legs and loop momenta.
+#[derive(Clone, Debug)]
+pub struct PointBatch<'a, C = BigRational> {
+    points: &'a [KinematicPoint<C>],
+    legs: usize,
+    /// Vectors per point: momenta, polarizations, then loop momenta.
+    vectors: usize,
+    /// `a·b` of point `k` at `k vectors² + a vectors + b`.
+    dots: Vec<C>,
+}
+
+impl<'a, C: Coefficient> PointBatch<'a, C> {
+    pub fn new(points: &'a [KinematicPoint<C>]) -> Self {
+        let legs = points.first().map_or(0, |x| x.momenta.len());
+        let loops = points.first().map_or(0, |x| x.loop_momenta.len());
+        let vectors = 2 * legs + loops;
+        let mut dots: Vec<C> = Vec::with_capacity(points.len() * vectors * vectors);
+        for x in points {
+            let all: Vec<&[C]> = x
+                .momenta
+                .iter()
+                .chain(&x.polarizations)
+                .chain(&x.loop_momenta)
+                .map(Vec::as_slice)
+                .collect();
+            let start = dots.len();
+            for a in 0..vectors {
+                for b in 0..vectors {
+                    dots.push(if b < a {
+                        dots[start + b * vectors + a].clone()
+                    } else {
+                        minkowski(all[a], all[b])
+                    });
+                }
+            }
+        }
+        Self {
+            points,
+            legs,
+            vectors,
+            dots,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Value of `t` at every point, in order.
+    ///
+    /// Returns `None` where [`KinematicPoint::evaluate`] would at any point.
+    pub fn evaluate(&self, t: &TensorStructure) -> Option<Vec<C>> {
+        if !t.bilinears.is_empty() {
+            return None;
+        }
+        let terms = expand_field_strengths(t)
+            .terms
+            .iter()
+            .map(|(c, u)| {
+                let offsets = u
+                    .factors
+                    .iter()
+                    .map(|f| self.offset(f))
+                    .collect::<Option<Vec<_>>>()?;
+                Some((C::from_rational(c)?, offsets))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let width = self.vectors * self.vectors;
+        self.points
+            .iter()
+            .zip(self.dots.chunks_exact(width.max(1)))
+            .map(|(x, dots)| {
+                let mut total = C::zero();
+                for (c, offsets) in &terms {
+                    let mut v = c.clone();
+                    for &o in offsets {
+                        v *= dots[o].clone();
+                    }
+                    total += v;
+                }
+                match &t.epsilon {
+                    Some(e) => Some(total * x.epsilon(e)?),
+                    None => Some(total),
+                }
+            })
+            .collect()
+    }
+
+    /// Values of `structures` at every point, one row per structure.
+    pub fn evaluate_all(&self, structures: &[TensorStructure]) -> Option<Vec<Vec<C>>> {
+        structures.iter().map(|t| self.evaluate(t)).collect()
+    }
+
+    /// Position of `f` within one point's block of the table.
+    fn offset(&self, f: &ScalarFactor) -> Option<usize> {
+        let p = |l: LegIndex| l.0 as usize - 1;
+        let e = |l: LegIndex| self.legs + l.0 as usize - 1;
+        let l = |l: LegIndex| 2 * self.legs + l.0 as usize - 1;
+        let (a, b) = match f.kind {
+            ScalarKind::PP => (p(f.a), p(f.b)),
+            ScalarKind::PE => (p(f.a), e(f.b)),
+            ScalarKind::EE => (e(f.a), e(f.b)),
+            ScalarKind::LP => (l(f.a), p(f.b)),
+            ScalarKind::LE => (l(f.a), e(f.b)),
+            ScalarKind::LL => (l(f.a), l(f.b)),
+            ScalarKind::Angle | ScalarKind::Square => return None,
+        };
+        Some(a * self.vectors + b)
+    }
+}
+
+/// Values of `structures` at `points`, one row per point and one column per structure.
+///
+/// The matrix is exact, so its [`rank`](SparseMatrix::rank) bounds the number
+/// of independent structures from below and its
+/// [`null_space`](SparseMatrix::null_space) holds every linear relation among
+/// them, together with spurious ones if there are too few points. Fails if a
+/// structure cannot be evaluated.
+pub fn evaluation_matrix(
+    structures: &[TensorStructure],
+    points: &[KinematicPoint],
+) -> Result<SparseMatrix, String> {
+    let batch = PointBatch::new(points);
+    let columns = columns(&batch, structures, "exactly")?;
+    let mut m = SparseMatrix::new(structures.len());
+    for k in 0..points.len() {
+        m.push_row(columns.iter().map(|c| c[k].clone()).enumerate().collect());
+    }
+    Ok(m)
+}
+
+/// Rank of the values of `structures` at points over `F_p`, one row per point.
+///
+/// The modular counterpart of [`evaluation_matrix`]; it agrees with the
+/// rational rank unless a random point or the prime is unlucky.
+pub fn evaluation_rank<const P: u64>(
+    structures: &[TensorStructure],
+    points: &[KinematicPoint<Fp<P>>],
+) -> Result<usize, String> {
+    let batch = PointBatch::new(points);
+    let columns = columns(&batch, structures, &format!("mod {}", P))?;
+    let matrix = (0..points.len())
+        .map(|k| columns.iter().map(|c| c[k]).collect())
+        .collect();
+    Ok(rank(matrix, structures.len()))
+}
+
+/// Values of each structure at every point of `batch`, one column per structure.
+fn columns<C: Coefficient>(
+    batch: &PointBatch<C>,
+    structures: &[TensorStructure],
+    how: &str,
+) -> Result<Vec<Vec<C>>, String> {
+    structures
+        .iter()
+        .map(|t| {
+            batch
+                .evaluate(t)
+                .ok_or_else(|| format!("cannot evaluate {} {}", t, how))
+        })
+        .collect()
+}
+
+/// A fresh seed for [`KinematicPoint::sample`], different on every call.
+///
+/// In the browser there is no system clock to mix in (`SystemTime::now`
+/// panics on `wasm32-unknown-unknown`), so only the hasher keys vary; pass a
+/// seed from JavaScript there instead.
+pub fn random_seed() -> u64 {
+    let mut h = RandomState::new().build_hasher();
+    #[cfg(not(all(target_family = "wasm", target_os = "unknown")))]
+    h.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |t| t.as_nanos()),
+    );
+    h.finish()
+}
+
+/// `count ≥ 2` random null vectors summing to `total`.
+///
+/// All but the last two are random; the second to last is rescaled so that
+/// the remainder is null as well. Two real null vectors summing to a null
+/// `total` are collinear with it, so for `count = 2` they are `z total` and
+/// `(1 − z) total`.
+fn null_momenta<C: Coefficient>(
+    count: usize,
+    total: &[C],
+    d: u32,
+    rng: &mut SplitMix64,
+) -> Option<Vec<Vec<C>>> {
+    if count == 2 && minkowski(total, total).is_zero() {
+        let z = draw::<C>(rng) / C::from_rational(&int(10))?;
+        if z.is_one() {
+            return None;
+        }
+        let rest = C::one() - z.clone();
+        return Some(vec![
+            total.iter().map(|x| x.clone() * z.clone()).collect(),
+            total.iter().map(|x| x.clone() * rest.clone()).collect(),
+        ]);
+    }
+    let mut out: Vec<Vec<C>> = (0..count - 1).map(|_| null_vector(d, rng)).collect();
+    let mut rest = total.to_vec();
+    for v in &out[..count - 2] {
+        subtract(&mut rest, v);
+    }
+    let q = &out[count - 2];
+    let rq = minkowski(&rest, q);
+    if rq.is_zero() {
+        return None;
+    }
+    let lambda = minkowski(&rest, &rest) / (rq.clone() + rq);
+    let q: Vec<C> = q.iter().map(|x| x.clone() * lambda.clone()).collect();
+    subtract(&mut rest, &q);
+    out[count - 2] = q;
+    out.push(rest);
+    Some(out)
+}
+
+/// `a += b` for polynomials in `z`, coefficients by ascending power.
+fn poly_add<C: Coefficient>(a: &mut Vec<C>, b: &[C]) {
+    if a.len() < b.len() {
+        a.resize(b.len(), C::zero());
+    }
+    for (x, y) in a.iter_mut().zip(b) {
+        *x += y.clone();
+    }
+}
+
+/// Drop trailing zero coefficients, keeping at least the constant term.
+fn trim<C: Coefficient>(mut a: Vec<C>) -> Vec<C> {
+    while a.len() > 1 && a.last().is_some_and(C::is_zero) {
+        a.pop();
+    }
+    a
+}
+
+fn poly_mul<C: Coefficient>(a: &[C], b: &[C]) -> Vec<C> {
+    let mut out = vec![C::zero(); a.len() + b.len() - 1];
+    for (k, x) in a.iter().enumerate() {
+        for (l, y) in b.iter().enumerate() {
+            out[k + l] += x.clone() * y.clone();
+        }
+    }
+    out
+}
+
+/// `a·b` with the metric `(+,−,…,−)`.
+pub fn minkowski<C: Coefficient>(a: &[C], b: &[C]) -> C {
+    let mut out = C::zero();
+    for (k, (x, y)) in a.iter().zip(b).enumerate() {
+        if k == 0 {
+            out += x.clone() * y.clone();
+        } else {
+            out -= x.clone() * y.clone();
+        }
+    }
+    out
+}
+
+fn subtract<C: Coefficient>(a: &mut [C], b: &[C]) {
+    for (x, y) in a.iter_mut().zip(b) {
+        *x -= y.clone();
+    }
+}
+
+/// Determinant of four 4-vectors stacked as rows, by Laplace expansion.
+fn det4<C: Coefficient>(rows: &[&[C]]) -> C {
+    let minor = |skip: usize| {
+        let cols: Vec<usize> = (0..4).filter(|&c| c != skip).collect();
+        let m = |r: usize, c: usize| rows[r][cols[c]].clone();
+        m(1, 0) * (m(2, 1) * m(3, 2) - m(2, 2) * m(3, 1))
+            - m(1, 1) * (m(2, 0) * m(3, 2) - m(2, 2) * m(3, 0))
+            + m(1, 2) * (m(2, 0) * m(3, 1) - m(2, 1) * m(3, 0))
+    };
+    (0..4).fold(C::zero(), |acc, c| {
+        let term = rows[0][c].clone() * minor(c);
+        if c % 2 == 0 { acc + term } else { acc - term }
+    })
+}
+
+fn draw<C: Coefficient>(rng: &mut SplitMix64) -> C {
+    C::from_random_bits(rng.next_u64())
+}
+
+fn random_vector<C: Coefficient>(d: u32, rng: &mut SplitMix64) -> Vec<C> {
+    (0..d).map(|_| draw(rng)).collect()
+}
+
+fn null_vector<C: Coefficient>(d: u32, rng: &mut SplitMix64) -> Vec<C> {
+    let t: Vec<C> = (0..d - 2).map(|_| draw(rng)).collect();
+    let t2 = t
+        .iter()
+        .fold(C::zero(), |acc, x| acc + x.clone() * x.clone());
+    let s: C = draw(rng);
+    let mut v = Vec::with_capacity(d as usize);
+    v.push(s.clone() * (C::one() + t2.clone()));
+    v.extend(t.iter().map(|x| s.clone() * (x.clone() + x.clone())));
+    v.push(s * (C::one() - t2));
+    v
+}
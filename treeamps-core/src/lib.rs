@@ -1,11 +1,131 @@
 // Tensor-structure (TS) subsystem: combinatorics and canonical representation
+pub mod amplitude_relations;
+pub mod amplitudes;
+pub mod ansatz;
+pub mod bcj;
+pub mod bilinear;
+pub mod catalog;
+pub mod coefficient;
+pub mod color;
+pub mod cubic_graph;
+pub mod derivative;
+pub mod dimension;
 pub mod dot_product;
+pub mod double_copy;
+pub mod epsilon;
+pub mod expression;
+pub mod factorization;
+pub mod feynman;
+pub mod field_strength;
+pub mod finite_field;
+pub mod form;
 pub mod generator;
+pub mod gram;
+pub mod hilbert;
+pub mod indexed;
+pub mod kinematics;
+pub mod limits;
+pub mod linalg;
+pub mod mandelstam;
+pub mod momentum_twistor;
+pub mod numeric;
+pub mod operator_basis;
+pub mod orbit;
+pub mod permutation;
+pub mod rational;
+pub mod rational_structure;
+mod reduction;
+mod rng;
+pub mod spill;
+pub mod spinor;
+pub mod spinor_kinematics;
+#[cfg(feature = "symbolica")]
+pub mod symbolica;
+pub mod symmetry;
 pub mod tensor_structure;
+pub mod topology;
 pub mod types;
+pub mod ward;
+pub mod wolfram;
 
 // Public TS API only
-pub use crate::dot_product::ScalarFactor;
-pub use crate::generator::{CatalogCounts, GenConfig, generate_tensor_structures};
-pub use crate::tensor_structure::TensorStructure;
-pub use crate::types::{LegIndex, PolarizationPattern, ScalarKind, Transversality};
+pub use crate::amplitude_relations::{
+    AmplitudeRelation, ColorOrdering, bcj_relations, color_orderings, independent_orderings,
+    kk_relations,
+};
+pub use crate::amplitudes::{
+    bcfw_gluon_amplitude, berends_giele_amplitude, berends_giele_current, parke_taylor,
+};
+pub use crate::ansatz::{Ansatz, LinearEquation, LinearSystem, Solution};
+pub use crate::bcj::{BcjAnsatz, bcj_ansatz};
+pub use crate::bilinear::BilinearFactor;
+pub use crate::catalog::Catalog;
+pub use crate::coefficient::Coefficient;
+pub use crate::color::{
+    ColorStructure, HalfLadder, Trace, color_dressed_basis, color_factor_traces, color_graph_basis,
+    ddm_basis, ddm_decomposition, single_trace_structures, trace_structures,
+};
+pub use crate::cubic_graph::{CubicGraph, CubicTree, cubic_graphs};
+pub use crate::derivative::{momentum_derivative, momentum_gradient};
+pub use crate::dimension::{DimPolynomial, rank_in_d};
+pub use crate::dot_product::{PackedFactor, ScalarFactor};
+pub use crate::double_copy::{double_copy, double_copy_candidates, double_copy_expression};
+pub use crate::epsilon::EpsilonFactor;
+pub use crate::expression::TensorExpression;
+pub use crate::factorization::{Channel, FactorizationCheck, RationalTerms, check_factorization};
+pub use crate::feynman::{FeynmanDiagram, feynman_diagrams, project_diagrams};
+pub use crate::field_strength::{FieldStrengthFactor, generate_field_strength_structures};
+pub use crate::finite_field::{Fp, Fp61};
+pub use crate::form::FormNames;
+pub use crate::generator::{
+    CatalogCounts, GenConfig, MultiplicityBound, ProgressEvent, ResumableSearch, SearchCursor,
+    count_tensor_structures, factor_catalog, generate_tensor_structures,
+    generate_tensor_structures_from_catalog, generate_tensor_structures_iter,
+    generate_tensor_structures_range, generate_tensor_structures_spilled,
+    generate_tensor_structures_with_catalog, generate_tensor_structures_with_progress,
+    resume_tensor_structures, try_generate_tensor_structures,
+};
+pub use crate::gram::{
+    BasisReduction, GramReduction, find_relations, gram_reduce, reduce_to_independent_basis,
+};
+pub use crate::hilbert::predict_structure_count;
+pub use crate::indexed::{
+    GramMatrix, IndexedFactor, IndexedSlot, IndexedTensor, LorentzIndex, gram_matrix,
+    polarization_sum, strip_polarizations,
+};
+pub use crate::kinematics::{
+    BcfwShift, KinematicPoint, PointBatch, evaluation_matrix, evaluation_rank, minkowski,
+    random_seed,
+};
+pub use crate::limits::{
+    CollinearLimit, SoftExpansion, collinear_limit, collinear_pole_order, soft_expand,
+};
+pub use crate::linalg::{SparseMatrix, chinese_remainder, rational_reconstruction};
+pub use crate::mandelstam::{
+    Invariant, Mandelstam, MandelstamTerm, generate_invariants, independent_invariants,
+    pp_to_mandelstam,
+};
+pub use crate::momentum_twistor::MomentumTwistors;
+pub use crate::numeric::{Kinematics, ReferenceCheck, reference_independence};
+pub use crate::operator_basis::{OperatorBasis, operator_basis};
+pub use crate::orbit::{Orbit, group_by_permutation_orbit};
+pub use crate::permutation::{Permutation, Relabeling};
+pub use crate::rational::{approximate_rational, rational_from_f64, rational_to_f64};
+pub use crate::rational_structure::RationalStructure;
+pub use crate::reduction::{canonical_form, reduce_expression, schouten_reduce};
+pub use crate::spill::{
+    STRUCTURE_FILE_MAGIC, SpillConfig, SpillReader, SpilledStructures, read_structure_file,
+    write_structure_header, write_structure_record,
+};
+pub use crate::spinor::{SpinorConfig, generate_spinor_structures};
+pub use crate::spinor_kinematics::{FourVector, SpinorPoint};
+pub use crate::symmetry::{antisymmetrize, symmetrize};
+pub use crate::tensor_structure::{PackedStructure, TensorStructure};
+pub use crate::topology::{GraphNode, TreeGraph, tree_graphs, tree_topologies};
+pub use crate::types::{
+    Canonicalization, Deduplication, Helicity, LegIndex, Mass, PeExclusion, PolarizationPattern,
+    ScalarKind, Slot, Spin, Statistics, Transversality,
+};
+pub use crate::ward::{
+    WardReport, gauge_invariant_combinations, gauge_legs, ward_filter, ward_variation,
+};
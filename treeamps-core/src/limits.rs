@@ -0,0 +1,237 @@
+use num_rational::BigRational;
+use num_traits::One;
+
+use crate::{
+    bilinear::BilinearFactor,
+    dot_product::ScalarFactor,
+    expression::TensorExpression,
+    generator::GenConfig,
+    rational::int,
+    rational_structure::RationalStructure,
+    reduction::canonical_form,
+    tensor_structure::TensorStructure,
+    types::{LegIndex, ScalarKind, Slot},
+};
+
+/// Expansion of an expression under `p_s → λ p_s`, by powers of `λ`.
+#[derive(Clone, Debug, Default)]
+pub struct SoftExpansion {
+    /// The coefficient of `λ^k` at index `k`, up to the requested order.
+    pub pieces: Vec<TensorExpression>,
+}
+
+impl SoftExpansion {
+    /// The lowest nonzero order and its coefficient.
+    pub fn leading(&self) -> Option<(usize, &TensorExpression)> {
+        self.pieces.iter().enumerate().find(|(_, e)| !e.is_zero())
+    }
+
+    /// The order after the leading one and its coefficient, which may vanish.
+    pub fn subleading(&self) -> Option<(usize, &TensorExpression)> {
+        let (k, _) = self.leading()?;
+        self.pieces.get(k + 1).map(|e| (k + 1, e))
+    }
+}
+
+/// Expand `expr` in the momentum of `soft_leg`, keeping orders up to `λ^order`.
+///
+/// The expression is first brought to [`canonical_form`], eliminating a
+/// momentum other than `p_s` so that the soft momentum appears explicitly; the
+/// eliminated momentum absorbs `−λ p_s` and the other dot products are held
+/// fixed. Each structure is then homogeneous in `p_s`, and its degree is its
+/// order. The polarization `e_s` is not scaled. Fails for spinor brackets on
+/// the soft leg, whose scaling depends on a spinor convention.
+pub fn soft_expand(
+    cfg: &GenConfig,
+    expr: &TensorExpression,
+    soft_leg: LegIndex,
+    order: usize,
+) -> Result<SoftExpansion, String> {
+    let mut cfg = cfg.clone();
+    if cfg.eliminated_leg() == Some(soft_leg) {
+        cfg.eliminated_momentum = (1..=cfg.n_legs)
+            .rev()
+            .map(LegIndex)
+            .find(|&l| l != soft_leg);
+    }
+    let mut pieces = vec![TensorExpression::new(); order + 1];
+    for (c, t) in canonical_form(&cfg, expr).terms {
+        let k = soft_degree(&t, soft_leg)
+            .ok_or_else(|| format!("cannot scale the spinor brackets of {}", t))?;
+        if let Some(piece) = pieces.get_mut(k) {
+            piece.push(c, t);
+        }
+    }
+    Ok(SoftExpansion { pieces })
+}
+
+/// Number of times `p_s` appears in `t`, or `None` if a bracket involves leg `s`.
+fn soft_degree(t: &TensorStructure, s: LegIndex) -> Option<usize> {
+    let mut k = 0;
+    for f in &t.factors {
+        k += match f.kind {
+            ScalarKind::PP => (f.a == s) as usize + (f.b == s) as usize,
+            ScalarKind::PE => (f.a == s) as usize,
+            ScalarKind::LP => (f.b == s) as usize,
+            ScalarKind::Angle | ScalarKind::Square if f.a == s || f.b == s => return None,
+            _ => 0,
+        };
+    }
+    if let Some(e) = &t.epsilon {
+        k += e.slots.iter().filter(|&&x| x == Slot::P(s)).count();
+    }
+    k += t.bilinears.iter().filter(|b| b.gamma == Slot::P(s)).count();
+    Some(k)
+}
+
+/// Strict collinear limit of an expression, by powers of the momentum fraction `z`.
+#[derive(Clone, Debug, Default)]
+pub struct CollinearLimit {
+    /// The coefficient of `z^k` at index `k`, with the common momentum `P`
+    /// written as `p_i`.
+    pub pieces: Vec<TensorExpression>,
+}
+
+impl CollinearLimit {
+    /// Whether the expression vanishes in the limit.
+    pub fn is_zero(&self) -> bool {
+        self.pieces.iter().all(TensorExpression::is_zero)
+    }
+}
+
+/// Take `expr` to the limit `p_i = z P`, `p_j = (1 − z) P` of two massless legs.
+///
+/// The expression is first brought to [`canonical_form`] with a momentum other
+/// than `p_i` and `p_j` eliminated. Then `P² = 0`, and `P·e_i = P·e_j = 0` by
+/// transversality, so such factors vanish; the rest is expanded in `z` and
+/// `P` is relabeled `p_i`, leaving `p_j` nowhere. Fails for massive or
+/// off-shell legs and for spinor brackets on either leg.
+///
+/// A nonzero limit means the expression is regular there, as polynomials
+/// always are; the singular behaviour of a pole structure follows from
+/// [`collinear_pole_order`].
+pub fn collinear_limit(
+    cfg: &GenConfig,
+    expr: &TensorExpression,
+    i: LegIndex,
+    j: LegIndex,
+) -> Result<CollinearLimit, String> {
+    if i == j || cfg.has_virtuality(i) || cfg.has_virtuality(j) {
+        return Err(format!("legs {} and {} cannot become collinear", i.0, j.0));
+    }
+    let mut cfg = cfg.clone();
+    if cfg.eliminated_leg().is_some_and(|k| k == i || k == j) {
+        cfg.eliminated_momentum = (1..=cfg.n_legs)
+            .rev()
+            .map(LegIndex)
+            .find(|&l| l != i && l != j);
+    }
+    let mut terms: Vec<Vec<(BigRational, TensorStructure)>> = Vec::new();
+    for (c, t) in canonical_form(&cfg, expr).terms {
+        let Some((mut u, a, b)) = collinear_structure(&t, i, j)? else {
+            continue;
+        };
+        let c = match u.canonicalize() {
+            0 => continue,
+            sign => c * int(sign as i64),
+        };
+        // z^a (1 − z)^b = Σ_k C(b, k) (−1)^k z^{a+k}
+        let mut binomial = BigRational::one();
+        for k in 0..=b {
+            if terms.len() <= a + k {
+                terms.resize(a + k + 1, Vec::new());
+            }
+            let sign = if k % 2 == 0 { int(1) } else { int(-1) };
+            terms[a + k].push((&c * &binomial * sign, u.clone()));
+            binomial = binomial * int((b - k) as i64) / int(k as i64 + 1);
+        }
+    }
+    let pieces = terms
+        .into_iter()
+        .map(|t| {
+            let mut e = TensorExpression { terms: t };
+            e.simplify();
+            e
+        })
+        .collect();
+    Ok(CollinearLimit { pieces })
+}
+
+/// Substitute `p_i → z P`, `p_j → (1 − z) P` with `P` written as `p_i`.
+///
+/// Returns the structure, not yet canonicalized, with the powers of `z` and
+/// `1 − z`, or `None` if a factor vanishes in the limit.
+fn collinear_structure(
+    t: &TensorStructure,
+    i: LegIndex,
+    j: LegIndex,
+) -> Result<Option<(TensorStructure, usize, usize)>, String> {
+    let (mut a, mut b) = (0, 0);
+    let mut momentum = |l: LegIndex| {
+        if l == i {
+            a += 1;
+        } else if l == j {
+            b += 1;
+        } else {
+            return l;
+        }
+        i
+    };
+    let is_pair = |l: LegIndex| l == i || l == j;
+    let mut u = t.clone();
+    for f in &mut u.factors {
+        *f = match f.kind {
+            ScalarKind::PP => {
+                if is_pair(f.a) && is_pair(f.b) {
+                    return Ok(None);
+                }
+                ScalarFactor::pp(momentum(f.a), momentum(f.b))
+            }
+            ScalarKind::PE => {
+                if is_pair(f.a) && is_pair(f.b) {
+                    return Ok(None);
+                }
+                ScalarFactor::pe(momentum(f.a), f.b)
+            }
+            ScalarKind::LP => ScalarFactor::lp(f.a, momentum(f.b)),
+            ScalarKind::Angle | ScalarKind::Square if is_pair(f.a) || is_pair(f.b) => {
+                return Err(format!("cannot take the collinear limit of {}", t));
+            }
+            _ => continue,
+        };
+    }
+    if let Some(e) = &mut u.epsilon {
+        for x in &mut e.slots {
+            if let Slot::P(l) = *x {
+                *x = Slot::P(momentum(l));
+            }
+        }
+    }
+    for x in &mut u.bilinears {
+        if let Slot::P(l) = x.gamma {
+            *x = BilinearFactor::new(x.bar, Slot::P(momentum(l)), x.u);
+        }
+    }
+    Ok(Some((u, a, b)))
+}
+
+/// Number of poles of `r` that vanish as `p_i ∥ p_j`, with multiplicity.
+///
+/// For massless legs only `s_ij` vanishes, or `s_Ī` with `Ī` the other legs
+/// by momentum conservation, so `r` blows up as `s_ij^{-k}` times the
+/// [`collinear_limit`] of its numerator whenever that limit is nonzero.
+pub fn collinear_pole_order(
+    cfg: &GenConfig,
+    r: &RationalStructure,
+    i: LegIndex,
+    j: LegIndex,
+) -> usize {
+    let rest = cfg.n_legs as usize - 2;
+    r.poles
+        .iter()
+        .filter(|p| {
+            let (has_i, has_j) = (p.legs.contains(&i), p.legs.contains(&j));
+            (p.legs.len() == 2 && has_i && has_j) || (p.legs.len() == rest && !has_i && !has_j)
+        })
+        .count()
+}
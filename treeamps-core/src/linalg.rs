@@ -0,0 +1,549 @@
+//! Exact linear algebra for the solvers.
+//!
+//! Basis reduction, Ward identities and BCJ constraints all end in a sparse
+//! linear system whose rows are labeled by the independent structures of some
+//! expressions. [`SparseMatrix`] collects such systems with integer entries
+//! and eliminates them fraction-free: rows are combined as `a·r − b·s` and
+//! divided by their content, so no denominators ever appear and entries stay
+//! as small as the data allows.
+
+use std::collections::BTreeMap;
+
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Signed, Zero};
+
+use crate::{
+    coefficient::Coefficient, expression::TensorExpression, finite_field::Fp,
+    tensor_structure::TensorStructure,
+};
+
+/// One row: `(column, entry)` pairs with nonzero entries, sorted by column.
+type SparseRow = Vec<(usize, BigInt)>;
+
+/// A sparse integer matrix, stored by rows.
+///
+/// Rational rows are scaled to coprime integers on the way in, which changes
+/// neither the rank nor the null space.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SparseMatrix {
+    n_cols: usize,
+    rows: Vec<SparseRow>,
+}
+
+impl SparseMatrix {
+    pub fn new(n_cols: usize) -> Self {
+        Self {
+            n_cols,
+            rows: Vec::new(),
+        }
+    }
+
+    /// The system `Σ_j x_j E_j = 0` for unknowns `x_j` multiplying expressions.
+    ///
+    /// Each entry `(label, j, E)` says that unknown `j` contributes `E` to the
+    /// equations labeled `label`; there is one row per label and structure,
+    /// holding the coefficients of that structure. Pass expressions in a
+    /// canonical form (e.g. reduced on shell) so equal structures match.
+    pub fn from_expressions<'a, K: Ord + Clone>(
+        n_cols: usize,
+        entries: impl IntoIterator<Item = (K, usize, &'a TensorExpression)>,
+    ) -> Self {
+        let mut rows: BTreeMap<(K, &TensorStructure), BTreeMap<usize, BigRational>> =
+            BTreeMap::new();
+        for (label, col, e) in entries {
+            for (c, t) in &e.terms {
+                *rows
+                    .entry((label.clone(), t))
+                    .or_default()
+                    .entry(col)
+                    .or_default() += c;
+            }
+        }
+        let mut out = Self::new(n_cols);
+        for row in rows.into_values() {
+            out.push_row(row.into_iter().collect());
+        }
+        out
+    }
+
+    /// Append a row given as `(column, entry)` pairs; repeated columns add up.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a column is out of range.
+    pub fn push_row(&mut self, entries: Vec<(usize, BigRational)>) {
+        let mut merged: BTreeMap<usize, BigRational> = BTreeMap::new();
+        for (col, c) in entries {
+            assert!(col < self.n_cols, "column {} out of range", col);
+            *merged.entry(col).or_default() += c;
+        }
+        merged.retain(|_, c| !c.is_zero());
+        let lcm = merged
+            .values()
+            .fold(BigInt::one(), |acc, c| acc.lcm(c.denom()));
+        let row: SparseRow = merged
+            .into_iter()
+            .map(|(col, c)| {
+                (
+                    col,
+                    (c * BigRational::from_integer(lcm.clone())).to_integer(),
+                )
+            })
+            .collect();
+        if let Some(row) = primitive(row) {
+            self.rows.push(row);
+        }
+    }
+
+    pub fn n_rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn n_cols(&self) -> usize {
+        self.n_cols
+    }
+
+    pub fn rank(&self) -> usize {
+        self.echelon().len()
+    }
+
+    /// Basis of the right null space, over the integers.
+    ///
+    /// There is one vector per non-pivot column, nonzero there and zero at
+    /// the other non-pivot columns, with coprime entries and a positive
+    /// leading entry.
+    pub fn null_space(&self) -> Vec<Vec<BigInt>> {
+        let mut pivots = self.echelon();
+        // Back substitution, from the last pivot up, gives the reduced form.
+        let cols: Vec<usize> = pivots.keys().copied().collect();
+        for (k, &col) in cols.iter().enumerate().rev() {
+            let pivot = pivots[&col].clone();
+            for &above in &cols[..k] {
+                let row = pivots.get_mut(&above).expect("pivot row");
+                if let Some(b) = entry(row, col) {
+                    *row = primitive(combine(&pivot[0].1, row, &b, &pivot))
+                        .expect("pivot rows are independent");
+                }
+            }
+        }
+
+        let mut out = Vec::new();
+        for free in (0..self.n_cols).filter(|c| !pivots.contains_key(c)) {
+            // x_pivot = −(b/a) x_free for each row `a x_pivot + b x_free + …`.
+            let scale = pivots
+                .values()
+                .filter(|row| entry(row, free).is_some())
+                .fold(BigInt::one(), |acc, row| acc.lcm(&row[0].1));
+            let mut v = vec![BigInt::zero(); self.n_cols];
+            v[free] = scale.clone();
+            for (&col, row) in &pivots {
+                if let Some(b) = entry(row, free) {
+                    v[col] = -(&scale / &row[0].1) * b;
+                }
+            }
+            let content = v.iter().fold(BigInt::zero(), |acc, c| acc.gcd(c));
+            let negative = v
+                .iter()
+                .find(|c| !c.is_zero())
+                .is_some_and(|c| c.is_negative());
+            let content = if negative { -content } else { content };
+            out.push(v.into_iter().map(|c| c / &content).collect());
+        }
+        out
+    }
+
+    /// Row echelon form, as primitive rows keyed by their pivot column.
+    fn echelon(&self) -> BTreeMap<usize, SparseRow> {
+        let mut pivots: BTreeMap<usize, SparseRow> = BTreeMap::new();
+        for row in &self.rows {
+            let mut row = row.clone();
+            // Entries left of the pivot being cleared never change, so one
+            // left-to-right scan suffices.
+            let mut k = 0;
+            while k < row.len() {
+                let col = row[k].0;
+                let Some(pivot) = pivots.get(&col) else {
+                    k += 1;
+                    continue;
+                };
+                let b = row[k].1.clone();
+                match primitive(combine(&pivot[0].1, &row, &b, pivot)) {
+                    Some(r) => row = r,
+                    None => {
+                        row.clear();
+                        break;
+                    }
+                }
+            }
+            if let Some(&(col, _)) = row.first() {
+                pivots.insert(col, row);
+            }
+        }
+        pivots
+    }
+}
+
+/// `a·row − b·pivot`, which clears the pivot column of `row` when `b` is its entry there.
+fn combine(a: &BigInt, row: &SparseRow, b: &BigInt, pivot: &SparseRow) -> SparseRow {
+    let mut out = Vec::with_capacity(row.len() + pivot.len());
+    let (mut x, mut y) = (row.iter().peekable(), pivot.iter().peekable());
+    loop {
+        let next = match (x.peek(), y.peek()) {
+            (Some((i, c)), Some((j, d))) if i == j => {
+                let v = a * c - b * d;
+                let i = *i;
+                x.next();
+                y.next();
+                (i, v)
+            }
+            (Some((i, c)), Some((j, _))) if i < j => {
+                let v = (*i, a * c);
+                x.next();
+                v
+            }
+            (Some((i, c)), None) => {
+                let v = (*i, a * c);
+                x.next();
+                v
+            }
+            (_, Some((j, d))) => {
+                let v = (*j, -(b * d));
+                y.next();
+                v
+            }
+            (None, None) => break,
+        };
+        if !next.1.is_zero() {
+            out.push(next);
+        }
+    }
+    out
+}
+
+/// The row divided by the gcd of its entries, with a positive leading entry;
+/// `None` for a zero row.
+fn primitive(row: SparseRow) -> Option<SparseRow> {
+    let (_, lead) = row.first()?;
+    let content = row.iter().fold(BigInt::zero(), |acc, (_, c)| acc.gcd(c));
+    let content = if lead.is_negative() {
+        -content
+    } else {
+        content
+    };
+    Some(
+        row.into_iter()
+            .map(|(col, c)| (col, c / &content))
+            .collect(),
+    )
+}
+
+/// The entry of `row` in column `col`, if nonzero.
+fn entry(row: &SparseRow, col: usize) -> Option<BigInt> {
+    row.binary_search_by_key(&col, |(c, _)| *c)
+        .ok()
+        .map(|k| row[k].1.clone())
+}
+
+/// Rank of `matrix`, whose rows have `n_cols` entries, over the field `C`.
+pub(crate) fn rank<C: Coefficient>(mut matrix: Vec<Vec<C>>, n_cols: usize) -> usize {
+    row_reduce(&mut matrix, n_cols).len()
+}
+
+/// Bring `matrix` to reduced row echelon form, returning the pivot column of each nonzero row.
+///
+/// Rows past the rank are left zero and can be ignored.
+pub(crate) fn row_reduce<C: Coefficient>(matrix: &mut [Vec<C>], n_cols: usize) -> Vec<usize> {
+    let mut pivots = Vec::new();
+    for col in 0..n_cols {
+        let rank = pivots.len();
+        let Some(found) = (rank..matrix.len()).find(|&r| !matrix[r][col].is_zero()) else {
+            continue;
+        };
+        matrix.swap(rank, found);
+        let lead = matrix[rank][col].clone();
+        for x in &mut matrix[rank] {
+            *x /= lead.clone();
+        }
+        let pivot_row = matrix[rank].clone();
+        for (r, row) in matrix.iter_mut().enumerate() {
+            if r == rank || row[col].is_zero() {
+                continue;
+            }
+            let factor = row[col].clone();
+            for (x, p) in row.iter_mut().zip(&pivot_row) {
+                if !p.is_zero() {
+                    *x -= factor.clone() * p.clone();
+                }
+            }
+        }
+        pivots.push(col);
+    }
+    pivots
+}
+
+/// Primes below `2^62` for modular elimination, largest first.
+const PRIMES: [u64; 8] = [
+    (1 << 62) - 57,
+    (1 << 62) - 87,
+    (1 << 62) - 117,
+    (1 << 62) - 143,
+    (1 << 62) - 153,
+    (1 << 62) - 167,
+    (1 << 62) - 171,
+    (1 << 62) - 195,
+];
+
+/// The reduced row echelon form of a matrix modulo one prime.
+struct ModularImage {
+    modulus: u64,
+    pivots: Vec<usize>,
+    /// Per non-pivot column `f`, the null vector with `x_f = 1` and zero at the
+    /// other non-pivot columns, as its nonzero `(pivot column, value)` entries.
+    basis: Vec<(usize, Vec<(usize, u64)>)>,
+}
+
+const IMAGES: [fn(&[SparseRow], usize) -> ModularImage; 8] = [
+    modular_image::<{ PRIMES[0] }>,
+    modular_image::<{ PRIMES[1] }>,
+    modular_image::<{ PRIMES[2] }>,
+    modular_image::<{ PRIMES[3] }>,
+    modular_image::<{ PRIMES[4] }>,
+    modular_image::<{ PRIMES[5] }>,
+    modular_image::<{ PRIMES[6] }>,
+    modular_image::<{ PRIMES[7] }>,
+];
+
+impl SparseMatrix {
+    /// Same result as [`null_space`](Self::null_space), computed modulo primes.
+    ///
+    /// The matrix is row reduced over `F_p` for one 62-bit prime after another,
+    /// the null vectors in the normalization `x_f = 1` are combined by the
+    /// Chinese remainder theorem, and their entries recovered as fractions by
+    /// rational reconstruction. The candidate is checked exactly against every
+    /// row, so the result is never wrong: primes whose pivots disagree with
+    /// the best ones seen are discarded, and if reconstruction has not
+    /// succeeded after all primes the exact elimination is used instead.
+    /// Intermediate numbers stay word-sized, which pays off for systems with
+    /// thousands of unknowns.
+    pub fn null_space_modular(&self) -> Vec<Vec<BigInt>> {
+        let mut best: Option<Vec<usize>> = None;
+        // Residues of every basis entry modulo `product`, keyed by free column.
+        let mut residues: BTreeMap<usize, BTreeMap<usize, BigInt>> = BTreeMap::new();
+        let mut product = BigInt::one();
+        for image in IMAGES {
+            let image = image(&self.rows, self.n_cols);
+            let better = match &best {
+                None => true,
+                Some(pivots) => {
+                    image.pivots.len() > pivots.len()
+                        || (image.pivots.len() == pivots.len() && image.pivots < *pivots)
+                }
+            };
+            if better {
+                best = Some(image.pivots.clone());
+                residues.clear();
+                product = BigInt::one();
+            } else if best.as_ref() != Some(&image.pivots) {
+                continue;
+            }
+            for (free, entries) in &image.basis {
+                let acc = residues.entry(*free).or_default();
+                let cols: Vec<usize> = acc
+                    .keys()
+                    .copied()
+                    .chain(entries.iter().map(|(c, _)| *c))
+                    .collect();
+                for col in cols {
+                    let r = entries
+                        .binary_search_by_key(&col, |(c, _)| *c)
+                        .map_or(0, |k| entries[k].1);
+                    let x = acc.entry(col).or_default();
+                    *x = chinese_remainder(x, &product, r, image.modulus);
+                }
+            }
+            product *= image.modulus;
+
+            if let Some(out) = self.reconstruct(&residues, &product) {
+                return out;
+            }
+        }
+        self.null_space()
+    }
+
+    /// Lift the residues to integer null vectors, if every entry reconstructs
+    /// and every vector passes the exact check.
+    fn reconstruct(
+        &self,
+        residues: &BTreeMap<usize, BTreeMap<usize, BigInt>>,
+        modulus: &BigInt,
+    ) -> Option<Vec<Vec<BigInt>>> {
+        // Sparse candidates, sorted by column, with `x_free = 1`.
+        let mut candidates = Vec::with_capacity(residues.len());
+        for (&free, entries) in residues {
+            let mut v = vec![(free, BigRational::one())];
+            for (&col, x) in entries {
+                if !x.is_zero() {
+                    v.push((col, rational_reconstruction(x, modulus)?));
+                }
+            }
+            v.sort_by_key(|(c, _)| *c);
+            candidates.push(v);
+        }
+        let mut out = Vec::with_capacity(candidates.len());
+        for v in candidates {
+            let vanishes = self.rows.iter().all(|row| {
+                row.iter()
+                    .filter_map(|(c, a)| {
+                        let k = v.binary_search_by_key(c, |(d, _)| *d).ok()?;
+                        Some(&v[k].1 * a)
+                    })
+                    .fold(BigRational::zero(), |acc, t| acc + t)
+                    .is_zero()
+            });
+            if !vanishes {
+                return None;
+            }
+            let lcm = v
+                .iter()
+                .fold(BigInt::one(), |acc, (_, c)| acc.lcm(c.denom()));
+            let ints: SparseRow = v
+                .into_iter()
+                .map(|(c, x)| (c, (x * BigRational::from_integer(lcm.clone())).to_integer()))
+                .collect();
+            let mut dense = vec![BigInt::zero(); self.n_cols];
+            for (c, x) in primitive(ints).expect("x_free = 1") {
+                dense[c] = x;
+            }
+            out.push(dense);
+        }
+        Some(out)
+    }
+}
+
+/// Reduced row echelon form over `F_P`, with the normalized null-space basis.
+fn modular_image<const P: u64>(rows: &[SparseRow], n_cols: usize) -> ModularImage {
+    type Row<const P: u64> = Vec<(usize, Fp<P>)>;
+    let mut pivots: BTreeMap<usize, Row<P>> = BTreeMap::new();
+    for row in rows {
+        let mut row: Row<P> = row
+            .iter()
+            .map(|(c, x)| (*c, Fp::from_bigint(x)))
+            .filter(|(_, x)| !x.is_zero())
+            .collect();
+        let mut k = 0;
+        while k < row.len() {
+            let (col, b) = row[k];
+            let Some(pivot) = pivots.get(&col) else {
+                k += 1;
+                continue;
+            };
+            row = axpy(-b, pivot, &row);
+        }
+        let Some(&(col, lead)) = row.first() else {
+            continue;
+        };
+        let inv = lead.inv().expect("nonzero lead");
+        for (_, x) in &mut row {
+            *x *= inv;
+        }
+        pivots.insert(col, row);
+    }
+    let cols: Vec<usize> = pivots.keys().copied().collect();
+    for (k, &col) in cols.iter().enumerate().rev() {
+        let pivot = pivots[&col].clone();
+        for &above in &cols[..k] {
+            let row = &pivots[&above];
+            if let Ok(j) = row.binary_search_by_key(&col, |(c, _)| *c) {
+                let updated = axpy(-row[j].1, &pivot, row);
+                pivots.insert(above, updated);
+            }
+        }
+    }
+
+    let mut basis: Vec<(usize, Vec<(usize, u64)>)> = (0..n_cols)
+        .filter(|c| !pivots.contains_key(c))
+        .map(|f| (f, Vec::new()))
+        .collect();
+    for (&col, row) in &pivots {
+        for &(c, x) in &row[1..] {
+            let k = basis
+                .binary_search_by_key(&c, |(f, _)| *f)
+                .expect("entries right of a pivot are in free columns");
+            basis[k].1.push((col, (-x).value()));
+        }
+    }
+    ModularImage {
+        modulus: P,
+        pivots: cols,
+        basis,
+    }
+}
+
+/// `row + a·pivot`, dropping zeros.
+fn axpy<const P: u64>(
+    a: Fp<P>,
+    pivot: &[(usize, Fp<P>)],
+    row: &[(usize, Fp<P>)],
+) -> Vec<(usize, Fp<P>)> {
+    let mut out = Vec::with_capacity(row.len() + pivot.len());
+    let (mut i, mut j) = (0, 0);
+    while i < row.len() || j < pivot.len() {
+        let (col, x) = match (row.get(i), pivot.get(j)) {
+            (Some(&(c, x)), Some(&(d, y))) if c == d => {
+                i += 1;
+                j += 1;
+                (c, x + a * y)
+            }
+            (Some(&(c, x)), Some(&(d, _))) if c < d => {
+                i += 1;
+                (c, x)
+            }
+            (Some(&(c, x)), None) => {
+                i += 1;
+                (c, x)
+            }
+            (_, Some(&(d, y))) => {
+                j += 1;
+                (d, a * y)
+            }
+            (None, None) => unreachable!(),
+        };
+        if !x.is_zero() {
+            out.push((col, x));
+        }
+    }
+    out
+}
+
+/// The `x` in `0..m·p` with `x ≡ a (mod m)` and `x ≡ b (mod p)`.
+///
+/// `a` must lie in `0..m` and `p` be a prime not dividing `m`.
+pub fn chinese_remainder(a: &BigInt, m: &BigInt, b: u64, p: u64) -> BigInt {
+    let p = BigInt::from(p);
+    // m^{-1} mod p by Fermat.
+    let inv = m.mod_floor(&p).modpow(&(&p - 2), &p);
+    let t = ((BigInt::from(b) - a) * inv).mod_floor(&p);
+    a + m * t
+}
+
+/// The fraction `r/s ≡ a (mod m)` with `|r|, s ≤ √(m/2)`, if there is one.
+///
+/// Wang's algorithm: the extended Euclidean algorithm on `(m, a)` stopped at
+/// the first remainder below the bound. The result is unique when it exists,
+/// so agreeing images modulo a large enough `m` determine the fraction.
+pub fn rational_reconstruction(a: &BigInt, m: &BigInt) -> Option<BigRational> {
+    let bound = (m / BigInt::from(2)).sqrt();
+    let (mut r0, mut r1) = (m.clone(), a.mod_floor(m));
+    let (mut s0, mut s1) = (BigInt::zero(), BigInt::one());
+    while r1 > bound {
+        let q = &r0 / &r1;
+        (r0, r1) = (r1.clone(), &r0 - &q * &r1);
+        (s0, s1) = (s1.clone(), &s0 - &q * &s1);
+    }
+    if s1.is_zero() || s1.abs() > bound || !r1.gcd(&s1).is_one() {
+        return None;
+    }
+    Some(BigRational::new(r1, s1))
+}
@@ -0,0 +1,307 @@
+use std::fmt;
+
+use num_rational::Rational32;
+
+use crate::{
+    dot_product::ScalarFactor,
+    generator::GenConfig,
+    tensor_structure::TensorStructure,
+    types::{LegIndex, ScalarKind},
+};
+
+/// Two-particle invariant `s_ij = (p_i + p_j)²`, stored with `i < j`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Mandelstam {
+    pub i: LegIndex,
+    pub j: LegIndex,
+}
+
+/// A term in the Mandelstam form of a PP factor: `s_ij` or `m_i² = p_i²`.
+///
+/// For an off-shell leg `m_i²` stands for its virtuality.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum MandelstamTerm {
+    S(Mandelstam),
+    MassSquare(LegIndex),
+}
+
+impl Mandelstam {
+    /// `s_ij` with the legs ordered canonically; `None` for `i == j`.
+    pub fn new(a: LegIndex, b: LegIndex) -> Option<Self> {
+        (a != b).then(|| Self {
+            i: a.min(b),
+            j: a.max(b),
+        })
+    }
+
+    /// Expand `s_ij = p_i² + p_j² + 2 p_i·p_j` in dot products.
+    ///
+    /// On shell `p_k² = 0` for massless legs, so `p_k·p_k` only appears for
+    /// massive or off-shell ones.
+    pub fn to_dot_products(self, cfg: &GenConfig) -> Vec<(i32, ScalarFactor)> {
+        let mut out = Vec::new();
+        for k in [self.i, self.j] {
+            if cfg.has_virtuality(k) {
+                out.push((1, ScalarFactor::pp(k, k)));
+            }
+        }
+        out.push((2, ScalarFactor::pp(self.i, self.j)));
+        out.sort_by(|x, y| x.1.cmp(&y.1));
+        out
+    }
+}
+
+/// Rewrite a PP factor in Mandelstam form: `p_i·p_j = ½ (s_ij − m_i² − m_j²)`.
+///
+/// Masses of massless legs vanish on shell and `p_i·p_i` becomes `m_i²`. Returns
+/// `None` for factors that are not PP.
+pub fn pp_to_mandelstam(
+    f: &ScalarFactor,
+    cfg: &GenConfig,
+) -> Option<Vec<(Rational32, MandelstamTerm)>> {
+    if f.kind != ScalarKind::PP {
+        return None;
+    }
+    let Some(s) = Mandelstam::new(f.a, f.b) else {
+        return Some(vec![(Rational32::from(1), MandelstamTerm::MassSquare(f.a))]);
+    };
+    let half = Rational32::new(1, 2);
+    let mut out = vec![(half, MandelstamTerm::S(s))];
+    for k in [s.i, s.j] {
+        if cfg.has_virtuality(k) {
+            out.push((-half, MandelstamTerm::MassSquare(k)));
+        }
+    }
+    Some(out)
+}
+
+impl TensorStructure {
+    /// Render the structure with PP factors written as Mandelstam invariants.
+    ///
+    /// Each `p_i·p_j` contributes a factor `½`, collected into an overall prefactor.
+    pub fn to_mandelstam_string(&self, cfg: &GenConfig) -> String {
+        let mut halves = 0u32;
+        let mut parts = Vec::new();
+        for b in &self.bilinears {
+            parts.push(b.to_string());
+        }
+        if let Some(e) = &self.epsilon {
+            parts.push(e.to_string());
+        }
+        for x in &self.field_strengths {
+            parts.push(x.to_string());
+        }
+        for f in &self.factors {
+            match pp_to_mandelstam(f, cfg) {
+                Some(terms) if terms.len() == 1 && terms[0].0 == Rational32::from(1) => {
+                    parts.push(terms[0].1.to_string());
+                }
+                Some(terms) => {
+                    halves += 1;
+                    let joined = terms
+                        .iter()
+                        .map(|(_, t)| t.to_string())
+                        .collect::<Vec<_>>()
+                        .join(" − ");
+                    if terms.len() == 1 {
+                        parts.push(joined);
+                    } else {
+                        parts.push(format!("({})", joined));
+                    }
+                }
+                None => parts.push(f.to_string()),
+            }
+        }
+        if parts.is_empty() {
+            return "1".to_string();
+        }
+        let body = parts.join(" · ");
+        if halves == 0 {
+            body
+        } else {
+            format!("1/{} · {}", 1u64 << halves, body)
+        }
+    }
+}
+
+impl fmt::Display for Mandelstam {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "s{}{}", self.i.0, self.j.0)
+    }
+}
+
+impl fmt::Display for MandelstamTerm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MandelstamTerm::S(s) => write!(f, "{}", s),
+            MandelstamTerm::MassSquare(k) => write!(f, "m{}²", k.0),
+        }
+    }
+}
+
+/// Multi-particle invariant `s_I = (Σ_{i∈I} p_i)²` over a sorted set of at least two legs.
+///
+/// Two-leg sets coincide with [`Mandelstam`]. Invariants order by the number
+/// of legs first, then lexicographically, so `s12 < s34 < s123`.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Invariant {
+    pub legs: Vec<LegIndex>,
+}
+
+impl Invariant {
+    /// `s_I` with the legs sorted; `None` if fewer than two distinct legs are given.
+    pub fn new(legs: impl IntoIterator<Item = LegIndex>) -> Option<Self> {
+        let mut legs: Vec<LegIndex> = legs.into_iter().collect();
+        legs.sort();
+        legs.dedup();
+        (legs.len() >= 2).then_some(Self { legs })
+    }
+
+    /// Use momentum conservation `s_I = s_Ī` to avoid the eliminated leg.
+    ///
+    /// The complement is taken only while it still has at least two legs, so
+    /// `s_I` with `|Ī| = 1` (an on-shell mass) is left alone.
+    pub fn canonicalize(&mut self, cfg: &GenConfig) {
+        if let Some(k) = cfg.eliminated_leg()
+            && self.legs.contains(&k)
+        {
+            let complement = self.complement(cfg.n_legs);
+            if complement.len() >= 2 {
+                self.legs = complement;
+            }
+        }
+    }
+
+    fn complement(&self, n_legs: u8) -> Vec<LegIndex> {
+        (1..=n_legs)
+            .map(LegIndex)
+            .filter(|l| !self.legs.contains(l))
+            .collect()
+    }
+
+    /// Expand `s_I = Σ_{i∈I} p_i² + 2 Σ_{i<j∈I} p_i·p_j` in the catalog's PP factors.
+    ///
+    /// Sets containing the eliminated leg are complemented first, so the result
+    /// never mentions its momentum. Masses of massless legs vanish on shell.
+    pub fn to_dot_products(&self, cfg: &GenConfig) -> Vec<(i32, ScalarFactor)> {
+        let legs = match cfg.eliminated_leg() {
+            Some(k) if self.legs.contains(&k) => self.complement(cfg.n_legs),
+            _ => self.legs.clone(),
+        };
+        let mut out = Vec::new();
+        for (x, &i) in legs.iter().enumerate() {
+            if cfg.has_virtuality(i) {
+                out.push((1, ScalarFactor::pp(i, i)));
+            }
+            for &j in &legs[x + 1..] {
+                out.push((2, ScalarFactor::pp(i, j)));
+            }
+        }
+        out.sort_by(|x, y| x.1.cmp(&y.1));
+        out
+    }
+
+    /// Expand `s_I = Σ_{i<j∈I} s_ij − (|I| − 2) Σ_{i∈I} m_i²` in two-particle invariants.
+    pub fn to_mandelstams(&self, cfg: &GenConfig) -> Vec<(i32, MandelstamTerm)> {
+        let mut out = Vec::new();
+        let shift = self.legs.len() as i32 - 2;
+        for (x, &i) in self.legs.iter().enumerate() {
+            if shift != 0 && cfg.has_virtuality(i) {
+                out.push((-shift, MandelstamTerm::MassSquare(i)));
+            }
+            for &j in &self.legs[x + 1..] {
+                out.push((1, MandelstamTerm::S(Mandelstam { i, j })));
+            }
+        }
+        out.sort_by_key(|x| x.1);
+        out
+    }
+}
+
+impl From<Mandelstam> for Invariant {
+    fn from(s: Mandelstam) -> Self {
+        Self {
+            legs: vec![s.i, s.j],
+        }
+    }
+}
+
+/// All distinct invariants `s_I` with `2 ≤ |I| ≤ n − 2`, in canonical order.
+///
+/// With momentum elimination `s_I = s_Ī`, so only sets avoiding the eliminated
+/// leg are kept; without it every subset is listed, mirroring the redundant
+/// `--no-elim` basis. `|I| = n − 1` is an on-shell mass and is never generated.
+pub fn generate_invariants(cfg: &GenConfig) -> Vec<Invariant> {
+    let n = cfg.n_legs as usize;
+    let elim = cfg.eliminated_leg();
+    let legs: Vec<LegIndex> = (1..=cfg.n_legs)
+        .map(LegIndex)
+        .filter(|&l| Some(l) != elim)
+        .collect();
+    let mut out = Vec::new();
+    for mask in 1u64..(1u64 << legs.len()) {
+        let size = mask.count_ones() as usize;
+        if size < 2 || size + 2 > n {
+            continue;
+        }
+        let subset = legs
+            .iter()
+            .enumerate()
+            .filter(|(b, _)| mask >> b & 1 == 1)
+            .map(|(_, &l)| l);
+        out.extend(Invariant::new(subset));
+    }
+    out.sort();
+    out
+}
+
+/// A basis of linearly independent two-particle invariants.
+///
+/// These are the `s_ij` with `i, j` distinct from the eliminated leg `k`,
+/// less the lexicographically last one, which momentum conservation
+/// `(Σ_{i≠k} p_i)² = m_k²` fixes in terms of the rest. Every multi-particle
+/// invariant is a combination of these (see [`Invariant::to_mandelstams`]).
+/// Without momentum elimination all `s_ij` are returned.
+pub fn independent_invariants(cfg: &GenConfig) -> Vec<Mandelstam> {
+    let elim = cfg.eliminated_leg();
+    let mut out = Vec::new();
+    for i in 1..=cfg.n_legs {
+        for j in i + 1..=cfg.n_legs {
+            if elim != Some(LegIndex(i)) && elim != Some(LegIndex(j)) {
+                out.push(Mandelstam {
+                    i: LegIndex(i),
+                    j: LegIndex(j),
+                });
+            }
+        }
+    }
+    if elim.is_some() {
+        out.pop();
+    }
+    out
+}
+
+impl Ord for Invariant {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.legs
+            .len()
+            .cmp(&other.legs.len())
+            .then_with(|| self.legs.cmp(&other.legs))
+    }
+}
+
+impl PartialOrd for Invariant {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Invariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "s")?;
+        for l in &self.legs {
+            write!(f, "{}", l.0)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+use num_rational::BigRational;
+use num_traits::Zero;
+
+use crate::{
+    coefficient::Coefficient, rng::SplitMix64, spinor_kinematics::SpinorPoint, types::LegIndex,
+};
+
+/// Planar massless 4D kinematics given by momentum twistors `Z_i = (λ_i, μ_i)`.
+///
+/// The region momenta `x_i` with `p_i = x_i − x_{i+1}` are the lines
+/// `(Z_{i−1} Z_i)`, so any `n` twistors in general position give momenta that
+/// are null and conserved by construction, with every component rational:
+///
+/// `λ̃_i = (⟨i i+1⟩ μ_{i−1} + ⟨i+1 i−1⟩ μ_i + ⟨i−1 i⟩ μ_{i+1}) / (⟨i−1 i⟩⟨i i+1⟩)`,
+///
+/// with labels cyclic. Unlike [`SpinorPoint::sample`], no leg is special, and
+/// planar invariants are four-brackets, e.g.
+/// `s_{i i+1} = ⟨i−1 i i+1 i+2⟩ / (⟨i−1 i⟩⟨i+1 i+2⟩)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MomentumTwistors {
+    /// `Z_i` at index `i − 1`, as `(λ¹, λ², μ¹, μ²)`.
+    pub z: Vec<[BigRational; 4]>,
+}
+
+impl MomentumTwistors {
+    /// Reproducible random twistors for `n ≥ 4` legs drawn from `seed`.
+    ///
+    /// Components are small random integers, redrawn until every cyclically
+    /// adjacent `⟨i i+1⟩` is nonzero. Returns `None` for `n < 4` or if no such
+    /// twistors turned up.
+    pub fn sample(n: u8, seed: u64) -> Option<Self> {
+        const ATTEMPTS: usize = 64;
+        if n < 4 {
+            return None;
+        }
+        let mut rng = SplitMix64::new(seed);
+        for _ in 0..ATTEMPTS {
+            let z = (0..n)
+                .map(|_| std::array::from_fn(|_| BigRational::from_random_bits(rng.next_u64())))
+                .collect();
+            let twistors = Self { z };
+            if twistors.spinors().is_some() {
+                return Some(twistors);
+            }
+        }
+        None
+    }
+
+    pub fn n_legs(&self) -> usize {
+        self.z.len()
+    }
+
+    /// `⟨ij⟩` of the `λ` parts.
+    pub fn angle(&self, i: LegIndex, j: LegIndex) -> BigRational {
+        let (a, b) = (&self.z[i.0 as usize - 1], &self.z[j.0 as usize - 1]);
+        &a[0] * &b[1] - &a[1] * &b[0]
+    }
+
+    /// `⟨ijkl⟩ = det(Z_i, Z_j, Z_k, Z_l)`.
+    pub fn four_bracket(&self, i: LegIndex, j: LegIndex, k: LegIndex, l: LegIndex) -> BigRational {
+        let rows = [i, j, k, l].map(|m| &self.z[m.0 as usize - 1]);
+        let det2 = |r: [usize; 2], c: [usize; 2]| {
+            &rows[r[0]][c[0]] * &rows[r[1]][c[1]] - &rows[r[0]][c[1]] * &rows[r[1]][c[0]]
+        };
+        // Laplace expansion along the first two rows, with sign (−1)^{1+c₀+c₁}.
+        let pairs = [[0, 1], [0, 2], [0, 3], [1, 2], [1, 3], [2, 3]];
+        pairs
+            .iter()
+            .enumerate()
+            .fold(BigRational::zero(), |acc, (k, &c)| {
+                let rest = pairs[5 - k];
+                let term = det2([0, 1], c) * det2([2, 3], rest);
+                if (c[0] + c[1]) % 2 == 1 {
+                    acc + term
+                } else {
+                    acc - term
+                }
+            })
+    }
+
+    /// The spinors of these twistors, or `None` if some `⟨i i+1⟩` vanishes.
+    pub fn spinors(&self) -> Option<SpinorPoint> {
+        let n = self.n_legs();
+        let leg = |k: usize| LegIndex((k % n) as u8 + 1);
+        let mut lambda_tilde = Vec::with_capacity(n);
+        for i in 0..n {
+            let (prev, this, next) = (leg(i + n - 1), leg(i), leg(i + 1));
+            let norm = self.angle(prev, this) * self.angle(this, next);
+            if norm.is_zero() {
+                return None;
+            }
+            let weights = [
+                (prev, self.angle(this, next)),
+                (this, self.angle(next, prev)),
+                (next, self.angle(prev, this)),
+            ];
+            lambda_tilde.push(std::array::from_fn(|a| {
+                weights.iter().fold(BigRational::zero(), |acc, (m, w)| {
+                    acc + w * &self.z[m.0 as usize - 1][a + 2]
+                }) / &norm
+            }));
+        }
+        Some(SpinorPoint {
+            lambda: self
+                .z
+                .iter()
+                .map(|z| [z[0].clone(), z[1].clone()])
+                .collect(),
+            lambda_tilde,
+        })
+    }
+}
@@ -0,0 +1,297 @@
+use num_complex::Complex64;
+use num_rational::BigRational;
+
+use crate::{
+    dot_product::ScalarFactor,
+    expression::TensorExpression,
+    kinematics::KinematicPoint,
+    rational::rational_to_f64,
+    reduction::expand_field_strengths,
+    rng::SplitMix64,
+    spinor_kinematics::SpinorPoint,
+    tensor_structure::TensorStructure,
+    types::{Helicity, LegIndex, ScalarKind, Slot},
+};
+
+/// Explicit floating-point vectors for numerical evaluation.
+///
+/// Components are complex in the metric `(+,−,…,−)`; dot products are the
+/// bilinear `a·b`, not a hermitian product, so complex momenta and helicity
+/// polarizations work as they do analytically. Spinor brackets need the
+/// spinors, which only points built [`from_spinors`](Self::from_spinors) have.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Kinematics {
+    /// `p_i` at index `i − 1`.
+    pub momenta: Vec<Vec<Complex64>>,
+    /// `e_i` at index `i − 1`.
+    pub polarizations: Vec<Vec<Complex64>>,
+    /// `l_a` at index `a − 1`.
+    pub loop_momenta: Vec<Vec<Complex64>>,
+    /// `(λ_i, λ̃_i)` at index `i − 1`, in the conventions of [`SpinorPoint`].
+    pub spinors: Vec<[[Complex64; 2]; 2]>,
+}
+
+impl Kinematics {
+    pub fn dimension(&self) -> usize {
+        self.momenta.first().map_or(0, Vec::len)
+    }
+
+    /// A 4D point with the momenta of `point` and helicity polarizations.
+    ///
+    /// Leg `i` gets the polarization of helicity `helicities[i − 1]` with the
+    /// next leg as reference, see [`polarization`](Self::polarization); legs
+    /// beyond the list get a zero vector. Returns `None` if a reference bracket
+    /// vanishes.
+    pub fn from_spinors(point: &SpinorPoint, helicities: &[Helicity]) -> Option<Self> {
+        let n = point.n_legs();
+        let c = |x: &BigRational| Complex64::new(rational_to_f64(x), 0.0);
+        let mut out = Self {
+            momenta: point
+                .momenta()
+                .iter()
+                .map(|p| {
+                    p.iter()
+                        .map(|x| Complex64::new(rational_to_f64(&x.re), rational_to_f64(&x.im)))
+                        .collect()
+                })
+                .collect(),
+            polarizations: vec![vec![Complex64::new(0.0, 0.0); 4]; n],
+            loop_momenta: Vec::new(),
+            spinors: point
+                .lambda
+                .iter()
+                .zip(&point.lambda_tilde)
+                .map(|(l, t)| [[c(&l[0]), c(&l[1])], [c(&t[0]), c(&t[1])]])
+                .collect(),
+        };
+        for (i, &h) in helicities.iter().enumerate().take(n) {
+            let leg = LegIndex(i as u8 + 1);
+            let reference = LegIndex((i + 1) as u8 % n as u8 + 1);
+            out.polarizations[i] = out.polarization(leg, h, reference)?;
+        }
+        Some(out)
+    }
+
+    /// `⟨ij⟩`, or `None` without spinors.
+    pub fn angle(&self, i: LegIndex, j: LegIndex) -> Option<Complex64> {
+        let (a, b) = (self.spinor(i)?[0], self.spinor(j)?[0]);
+        Some(det(a, b))
+    }
+
+    /// `[ij]`, or `None` without spinors.
+    pub fn square(&self, i: LegIndex, j: LegIndex) -> Option<Complex64> {
+        let (a, b) = (self.spinor(i)?[1], self.spinor(j)?[1]);
+        Some(det(b, a))
+    }
+
+    fn spinor(&self, i: LegIndex) -> Option<&[[Complex64; 2]; 2]> {
+        self.spinors.get(i.0 as usize - 1)
+    }
+
+    /// Polarization of helicity `h` for leg `i` with reference leg `q`.
+    ///
+    /// `e_+^{αα̇} = √2 λ_q^α λ̃_i^α̇ / ⟨qi⟩` and
+    /// `e_−^{αα̇} = √2 λ_i^α λ̃_q^α̇ / [iq]`, so that `e_±·p_i = e_±·p_q = 0`
+    /// and `e_+·e_− = −1`. Returns `None` without spinors or when the bracket
+    /// vanishes.
+    pub fn polarization(&self, i: LegIndex, h: Helicity, q: LegIndex) -> Option<Vec<Complex64>> {
+        self.polarization_with(i, h, *self.spinor(q)?)
+    }
+
+    /// As [`polarization`](Self::polarization), with arbitrary reference
+    /// spinors `(λ_q, λ̃_q)` instead of those of a leg.
+    pub fn polarization_with(
+        &self,
+        i: LegIndex,
+        h: Helicity,
+        [lq, tq]: [[Complex64; 2]; 2],
+    ) -> Option<Vec<Complex64>> {
+        let [li, ti] = *self.spinor(i)?;
+        let (a, b, norm) = match h {
+            Helicity::Plus => (lq, ti, det(lq, li)),
+            Helicity::Minus => (li, tq, det(tq, ti)),
+        };
+        if norm.norm() == 0.0 {
+            return None;
+        }
+        let s = std::f64::consts::SQRT_2 / norm;
+        let m = |x: usize, y: usize| a[x] * b[y] * s;
+        let i = Complex64::new(0.0, 1.0);
+        Some(vec![
+            (m(0, 0) + m(1, 1)) / 2.0,
+            (m(0, 1) + m(1, 0)) / 2.0,
+            i * (m(0, 1) - m(1, 0)) / 2.0,
+            (m(0, 0) - m(1, 1)) / 2.0,
+        ])
+    }
+
+    /// Value of a scalar factor, or `None` for brackets without spinors.
+    pub fn dot(&self, f: &ScalarFactor) -> Option<Complex64> {
+        let p = |l: LegIndex| &self.momenta[l.0 as usize - 1];
+        let e = |l: LegIndex| &self.polarizations[l.0 as usize - 1];
+        let l = |l: LegIndex| &self.loop_momenta[l.0 as usize - 1];
+        let (a, b) = match f.kind {
+            ScalarKind::PP => (p(f.a), p(f.b)),
+            ScalarKind::PE => (p(f.a), e(f.b)),
+            ScalarKind::EE => (e(f.a), e(f.b)),
+            ScalarKind::LP => (l(f.a), p(f.b)),
+            ScalarKind::LE => (l(f.a), e(f.b)),
+            ScalarKind::LL => (l(f.a), l(f.b)),
+            ScalarKind::Angle => return self.angle(f.a, f.b),
+            ScalarKind::Square => return self.square(f.a, f.b),
+        };
+        Some(minkowski(a, b))
+    }
+
+    fn vector(&self, s: Slot) -> &[Complex64] {
+        match s {
+            Slot::P(l) => &self.momenta[l.0 as usize - 1],
+            Slot::E(l) => &self.polarizations[l.0 as usize - 1],
+        }
+    }
+}
+
+impl From<&KinematicPoint> for Kinematics {
+    fn from(x: &KinematicPoint) -> Self {
+        let convert = |vs: &[Vec<BigRational>]| -> Vec<Vec<Complex64>> {
+            vs.iter()
+                .map(|v| {
+                    v.iter()
+                        .map(|c| Complex64::new(rational_to_f64(c), 0.0))
+                        .collect()
+                })
+                .collect()
+        };
+        Self {
+            momenta: convert(&x.momenta),
+            polarizations: convert(&x.polarizations),
+            loop_momenta: convert(&x.loop_momenta),
+            spinors: Vec::new(),
+        }
+    }
+}
+
+impl TensorStructure {
+    /// Numerical value at `k`, with field strengths expanded.
+    ///
+    /// The ε factor is `ε_{μνρσ} a^μ b^ν c^ρ d^σ` with `ε^{0123} = 1` and needs
+    /// four dimensions. Returns `None` for bilinears, for ε in other
+    /// dimensions and for brackets without spinors.
+    pub fn eval_f64(&self, k: &Kinematics) -> Option<Complex64> {
+        if !self.bilinears.is_empty() {
+            return None;
+        }
+        let epsilon = match &self.epsilon {
+            Some(e) if k.dimension() == 4 => {
+                let rows: Vec<&[Complex64]> = e.slots.iter().map(|&s| k.vector(s)).collect();
+                -det4(&rows)
+            }
+            Some(_) => return None,
+            None => Complex64::new(1.0, 0.0),
+        };
+        let mut total = Complex64::new(0.0, 0.0);
+        for (c, u) in expand_field_strengths(self).terms {
+            let mut v = Complex64::new(rational_to_f64(&c), 0.0);
+            for f in &u.factors {
+                v *= k.dot(f)?;
+            }
+            total += v;
+        }
+        Some(total * epsilon)
+    }
+}
+
+impl TensorExpression {
+    /// Numerical value at `k`; see [`TensorStructure::eval_f64`].
+    pub fn eval_f64(&self, k: &Kinematics) -> Option<Complex64> {
+        let mut total = Complex64::new(0.0, 0.0);
+        for (c, t) in &self.terms {
+            total += t.eval_f64(k)? * rational_to_f64(c);
+        }
+        Some(total)
+    }
+}
+
+/// Outcome of [`reference_independence`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReferenceCheck {
+    /// Value with the references of the given point.
+    pub value: Complex64,
+    /// Largest `|f − value|` over the random references.
+    pub max_deviation: f64,
+}
+
+impl ReferenceCheck {
+    /// [`max_deviation`](Self::max_deviation) relative to `|value|`.
+    pub fn relative_deviation(&self) -> f64 {
+        self.max_deviation / self.value.norm().max(f64::MIN_POSITIVE)
+    }
+}
+
+/// Check numerically that `f` does not depend on the reference spinors of
+/// the helicity polarizations.
+///
+/// `k` must carry spinors, as from [`Kinematics::from_spinors`]. Leg `i`
+/// gets `samples` times a polarization of helicity `helicities[i − 1]` with
+/// fresh random complex reference spinors drawn from `seed`, and `f` is
+/// compared with its value at `k`; pass e.g. `|k| expr.eval_f64(k)` for a
+/// candidate gauge-invariant combination. Returns `None` if `f` or a
+/// polarization cannot be evaluated.
+pub fn reference_independence(
+    k: &Kinematics,
+    helicities: &[Helicity],
+    samples: usize,
+    seed: u64,
+    mut f: impl FnMut(&Kinematics) -> Option<Complex64>,
+) -> Option<ReferenceCheck> {
+    let value = f(k)?;
+    let mut rng = SplitMix64::new(seed);
+    let mut draw = || Complex64::new(2.0 * rng.next_f64() - 1.0, 2.0 * rng.next_f64() - 1.0);
+    let mut max_deviation: f64 = 0.0;
+    for _ in 0..samples {
+        let mut shifted = k.clone();
+        for (i, &h) in helicities.iter().enumerate() {
+            let q = [[draw(), draw()], [draw(), draw()]];
+            shifted.polarizations[i] = k.polarization_with(LegIndex(i as u8 + 1), h, q)?;
+        }
+        max_deviation = max_deviation.max((f(&shifted)? - value).norm());
+    }
+    Some(ReferenceCheck {
+        value,
+        max_deviation,
+    })
+}
+
+/// `a·b` with the metric `(+,−,…,−)`.
+fn minkowski(a: &[Complex64], b: &[Complex64]) -> Complex64 {
+    let mut out = Complex64::new(0.0, 0.0);
+    for (k, (x, y)) in a.iter().zip(b).enumerate() {
+        if k == 0 {
+            out += x * y;
+        } else {
+            out -= x * y;
+        }
+    }
+    out
+}
+
+fn det(a: [Complex64; 2], b: [Complex64; 2]) -> Complex64 {
+    a[0] * b[1] - a[1] * b[0]
+}
+
+/// Determinant of four 4-vectors stacked as rows, by Laplace expansion.
+fn det4(rows: &[&[Complex64]]) -> Complex64 {
+    let minor = |skip: usize| {
+        let cols: Vec<usize> = (0..4).filter(|&c| c != skip).collect();
+        let m = |r: usize, c: usize| rows[r][cols[c]];
+        m(1, 0) * (m(2, 1) * m(3, 2) - m(2, 2) * m(3, 1))
+            - m(1, 1) * (m(2, 0) * m(3, 2) - m(2, 2) * m(3, 0))
+            + m(1, 2) * (m(2, 0) * m(3, 1) - m(2, 1) * m(3, 0))
+    };
+    (0..4)
+        .map(|c| {
+            let term = rows[0][c] * minor(c);
+            if c % 2 == 0 { term } else { -term }
+        })
+        .sum()
+}
@@ -0,0 +1,101 @@
+use std::collections::BTreeMap;
+
+use num_rational::BigRational;
+use num_traits::Zero;
+
+use crate::{
+    expression::TensorExpression,
+    field_strength::generate_field_strength_structures,
+    generator::GenConfig,
+    reduction::{expand_field_strengths, reduce_expression},
+    tensor_structure::TensorStructure,
+    types::LegIndex,
+};
+
+/// An independent set of local operators at fixed mass dimension.
+#[derive(Clone, Debug, Default)]
+pub struct OperatorBasis {
+    /// Field-strength structures that are linearly independent on shell.
+    pub basis: Vec<TensorStructure>,
+    /// Candidates removed as combinations of earlier ones.
+    pub redundant: Vec<TensorStructure>,
+}
+
+/// Build a basis of gauge-invariant operators of the given mass dimension.
+///
+/// Candidates come from [`generate_field_strength_structures`], counting two
+/// units for each `F_i`, one for each scalar leg and one per extra momentum.
+/// Integration by parts corresponds to momentum conservation and the equations
+/// of motion to the on-shell conditions, so each candidate is expanded into
+/// dot products, reduced with both, and kept only if it is not a linear
+/// combination of the candidates kept before it. Fermion legs are not supported
+/// and give an empty basis.
+pub fn operator_basis(cfg: &GenConfig, mass_dimension: u32) -> OperatorBasis {
+    if (1..=cfg.n_legs).any(|i| cfg.is_fermion(LegIndex(i))) {
+        return OperatorBasis::default();
+    }
+    let targets = cfg.polarization_targets();
+    let mut base_dimension = 0;
+    for i in 1..=cfg.n_legs {
+        let strengths = match &targets {
+            Some(t) => t[i as usize],
+            None => cfg.has_polarization(LegIndex(i)) as u32,
+        };
+        base_dimension += if strengths == 0 { 1 } else { 2 * strengths };
+    }
+    let Some(extra) = mass_dimension.checked_sub(base_dimension) else {
+        return OperatorBasis::default();
+    };
+
+    let mut echelon = Echelon::default();
+    let mut out = OperatorBasis::default();
+    for t in generate_field_strength_structures(cfg, extra) {
+        let reduced = reduce_expression(cfg, &expand_field_strengths(&t));
+        if echelon.insert(&reduced) {
+            out.basis.push(t);
+        } else {
+            out.redundant.push(t);
+        }
+    }
+    out
+}
+
+/// Rows in reduced echelon form, each normalized to `1` at its pivot.
+#[derive(Default)]
+pub(crate) struct Echelon {
+    rows: Vec<(TensorStructure, BTreeMap<TensorStructure, BigRational>)>,
+}
+
+impl Echelon {
+    /// Add `expr` as a new row; returns `false` if it is a combination of the existing rows.
+    pub(crate) fn insert(&mut self, expr: &TensorExpression) -> bool {
+        let mut v: BTreeMap<TensorStructure, BigRational> = BTreeMap::new();
+        for (c, t) in &expr.terms {
+            *v.entry(t.clone()).or_default() += c;
+        }
+        v.retain(|_, c| !c.is_zero());
+
+        for (pivot, row) in &self.rows {
+            let Some(factor) = v.get(pivot).cloned() else {
+                continue;
+            };
+            for (key, c) in row {
+                let entry = v.entry(key.clone()).or_default();
+                *entry -= &factor * c;
+                if entry.is_zero() {
+                    v.remove(key);
+                }
+            }
+        }
+
+        let Some((pivot, lead)) = v.iter().next() else {
+            return false;
+        };
+        let (pivot, lead) = (pivot.clone(), lead.clone());
+        for c in v.values_mut() {
+            *c /= &lead;
+        }
+        self.rows.push((pivot, v));
+        true
+    }
+}
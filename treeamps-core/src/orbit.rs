@@ -0,0 +1,62 @@
+use std::collections::BTreeMap;
+
+use crate::{symmetry::for_each_permutation, tensor_structure::TensorStructure, types::LegIndex};
+
+/// Structures from one input set that are related by a relabeling of the legs.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Orbit {
+    /// Smallest member of the orbit in canonical order.
+    pub representative: TensorStructure,
+    /// Number of input structures in the orbit.
+    pub size: usize,
+    /// Positions of the members in the input slice, ascending.
+    pub members: Vec<usize>,
+}
+
+/// Partition `structures` into orbits under all relabelings of legs `1..=n`.
+///
+/// Structures are compared up to sign, through the smallest image over all
+/// `n!` permutations, so this is meant for the small `n` where inspecting the
+/// output by hand is still feasible. Orbits are returned in order of their
+/// representatives.
+pub fn group_by_permutation_orbit(structures: &[TensorStructure], n: u8) -> Vec<Orbit> {
+    let mut by_key: BTreeMap<TensorStructure, Vec<usize>> = BTreeMap::new();
+    for (idx, t) in structures.iter().enumerate() {
+        by_key.entry(orbit_key(t, n)).or_default().push(idx);
+    }
+    let mut orbits: Vec<Orbit> = by_key
+        .into_values()
+        .map(|members| {
+            let representative = members
+                .iter()
+                .map(|&i| &structures[i])
+                .min()
+                .cloned()
+                .unwrap_or_default();
+            Orbit {
+                representative,
+                size: members.len(),
+                members,
+            }
+        })
+        .collect();
+    orbits.sort_by(|a, b| a.representative.cmp(&b.representative));
+    orbits
+}
+
+/// Smallest canonical image of `t` over all permutations of `1..=n`.
+fn orbit_key(t: &TensorStructure, n: u8) -> TensorStructure {
+    let mut best = t.clone();
+    best.canonicalize();
+    for_each_permutation(n as usize, |perm, _| {
+        let sigma = |l: LegIndex| match perm.get(l.0 as usize - 1) {
+            Some(&m) => LegIndex(m as u8 + 1),
+            None => l,
+        };
+        let (candidate, _) = t.relabel(&sigma);
+        if candidate < best {
+            best = candidate;
+        }
+    });
+    best
+}
@@ -0,0 +1,215 @@
+use std::fmt;
+
+use crate::{symmetry::for_each_permutation, types::LegIndex};
+
+/// A map from legs to legs, as taken by the `relabel` methods.
+///
+/// Implemented by [`Permutation`] and by any `Fn(LegIndex) -> LegIndex`.
+pub trait Relabeling {
+    fn image(&self, leg: LegIndex) -> LegIndex;
+}
+
+impl<F: Fn(LegIndex) -> LegIndex> Relabeling for F {
+    fn image(&self, leg: LegIndex) -> LegIndex {
+        self(leg)
+    }
+}
+
+/// A permutation `σ` of the legs `1..=n`; legs beyond `n` are fixed.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Permutation {
+    /// `σ(i)` at index `i − 1`.
+    images: Vec<LegIndex>,
+}
+
+impl Permutation {
+    pub fn identity(n: u8) -> Self {
+        Self {
+            images: (1..=n).map(LegIndex).collect(),
+        }
+    }
+
+    /// The permutation `i ↦ images[i − 1]`, which must be a rearrangement of `1..=n`.
+    pub fn new(images: Vec<LegIndex>) -> Result<Self, String> {
+        let n = images.len();
+        let mut seen = vec![false; n];
+        for l in &images {
+            match (l.0 as usize).checked_sub(1).and_then(|k| seen.get_mut(k)) {
+                Some(s) if !*s => *s = true,
+                _ => {
+                    return Err(format!(
+                        "{:?} is not a permutation of 1..={}",
+                        images.iter().map(|l| l.0).collect::<Vec<_>>(),
+                        n
+                    ));
+                }
+            }
+        }
+        Ok(Self { images })
+    }
+
+    /// The exchange `a ↔ b` on `n` legs, the identity for `a == b`.
+    pub fn transposition(n: u8, a: LegIndex, b: LegIndex) -> Result<Self, String> {
+        let cycle = if a == b { vec![a] } else { vec![a, b] };
+        Self::from_cycles(n, &[cycle])
+    }
+
+    /// The cyclic shift `i ↦ i + r (mod n)`.
+    pub fn cyclic(n: u8, r: u8) -> Self {
+        let n = n as u16;
+        Self {
+            images: (0..n)
+                .map(|i| LegIndex(((i + r as u16) % n.max(1) + 1) as u8))
+                .collect(),
+        }
+    }
+
+    /// The product of disjoint cycles on `n` legs, each `(a b c)` sending `a ↦ b ↦ c ↦ a`.
+    pub fn from_cycles(n: u8, cycles: &[Vec<LegIndex>]) -> Result<Self, String> {
+        let mut images: Vec<LegIndex> = (1..=n).map(LegIndex).collect();
+        let mut moved = vec![false; n as usize];
+        for cycle in cycles {
+            for (k, &l) in cycle.iter().enumerate() {
+                let idx = (l.0 as usize)
+                    .checked_sub(1)
+                    .filter(|&i| i < n as usize)
+                    .ok_or_else(|| format!("leg {} is outside 1..={}", l.0, n))?;
+                if moved[idx] {
+                    return Err(format!("leg {} appears in more than one cycle", l.0));
+                }
+                moved[idx] = true;
+                images[idx] = cycle[(k + 1) % cycle.len()];
+            }
+        }
+        Ok(Self { images })
+    }
+
+    /// All `n!` permutations of `n` legs, the identity first.
+    pub fn all(n: u8) -> Vec<Self> {
+        let mut out = Vec::new();
+        for_each_permutation(n as usize, |perm, _| {
+            out.push(Self {
+                images: perm.iter().map(|&k| LegIndex(k as u8 + 1)).collect(),
+            });
+        });
+        out
+    }
+
+    /// Number of legs `n` the permutation acts on.
+    pub fn n_legs(&self) -> u8 {
+        self.images.len() as u8
+    }
+
+    /// `σ(i)` at index `i − 1`.
+    pub fn images(&self) -> &[LegIndex] {
+        &self.images
+    }
+
+    /// `σ(leg)`, with legs beyond `n` fixed.
+    pub fn apply(&self, leg: LegIndex) -> LegIndex {
+        match (leg.0 as usize).checked_sub(1) {
+            Some(k) if k < self.images.len() => self.images[k],
+            _ => leg,
+        }
+    }
+
+    /// `self ∘ other`, applying `other` first, on the larger of the two leg counts.
+    pub fn compose(&self, other: &Self) -> Self {
+        let n = self.n_legs().max(other.n_legs());
+        Self {
+            images: (1..=n)
+                .map(|l| self.apply(other.apply(LegIndex(l))))
+                .collect(),
+        }
+    }
+
+    pub fn inverse(&self) -> Self {
+        let mut images = self.images.clone();
+        for (k, l) in self.images.iter().enumerate() {
+            images[l.0 as usize - 1] = LegIndex(k as u8 + 1);
+        }
+        Self { images }
+    }
+
+    /// Sign `±1` of the permutation.
+    pub fn parity(&self) -> i8 {
+        let even = self.cycles().iter().filter(|c| c.len() % 2 == 0).count();
+        if even % 2 == 0 { 1 } else { -1 }
+    }
+
+    /// Disjoint cycles of length at least two, each starting at its smallest leg.
+    pub fn cycles(&self) -> Vec<Vec<LegIndex>> {
+        let mut seen = vec![false; self.images.len()];
+        let mut out = Vec::new();
+        for start in 0..self.images.len() {
+            if seen[start] {
+                continue;
+            }
+            let mut cycle = Vec::new();
+            let mut k = start;
+            while !seen[k] {
+                seen[k] = true;
+                cycle.push(LegIndex(k as u8 + 1));
+                k = self.images[k].0 as usize - 1;
+            }
+            if cycle.len() > 1 {
+                out.push(cycle);
+            }
+        }
+        out
+    }
+}
+
+impl Relabeling for Permutation {
+    fn image(&self, leg: LegIndex) -> LegIndex {
+        self.apply(leg)
+    }
+}
+
+/// Cycle notation such as `(1 2 3)(4 5)`; the identity prints as `()`.
+impl fmt::Display for Permutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cycles = self.cycles();
+        if cycles.is_empty() {
+            return write!(f, "()");
+        }
+        for c in cycles {
+            let legs: Vec<String> = c.iter().map(|l| l.0.to_string()).collect();
+            write!(f, "({})", legs.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses cycle notation such as `(1 2 3)(4 5)` or `(1,2)`, acting on legs up
+/// to the largest one mentioned.
+impl std::str::FromStr for Permutation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut cycles = Vec::new();
+        let mut rest = s.trim();
+        while !rest.is_empty() {
+            let body = rest
+                .strip_prefix('(')
+                .and_then(|r| r.split_once(')'))
+                .ok_or_else(|| format!("expected cycles like (1 2), got {:?}", s))?;
+            let cycle = body
+                .0
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|x| !x.is_empty())
+                .map(|x| {
+                    x.parse::<u8>()
+                        .ok()
+                        .filter(|&l| l > 0)
+                        .map(LegIndex)
+                        .ok_or_else(|| format!("invalid leg {:?} in {:?}", x, s))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            cycles.push(cycle);
+            rest = body.1.trim_start();
+        }
+        let n = cycles.iter().flatten().map(|l| l.0).max().unwrap_or(0);
+        Self::from_cycles(n, &cycles)
+    }
+}
@@ -0,0 +1,49 @@
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{FromPrimitive, Signed, ToPrimitive, Zero};
+
+/// The integer `n` as an exact coefficient.
+pub(crate) fn int(n: i64) -> BigRational {
+    BigRational::from_integer(BigInt::from(n))
+}
+
+/// Nearest `f64` to `c`, for quick numeric checks; huge values saturate to `±∞`.
+pub fn rational_to_f64(c: &BigRational) -> f64 {
+    c.to_f64().unwrap_or(if c.is_negative() {
+        f64::NEG_INFINITY
+    } else {
+        f64::INFINITY
+    })
+}
+
+/// The exact binary value of `x`, or `None` for NaN and infinities.
+pub fn rational_from_f64(x: f64) -> Option<BigRational> {
+    BigRational::from_f64(x)
+}
+
+/// The simplest fraction within `tolerance` of `x`, by continued fractions.
+///
+/// Meant for reading back numeric results, e.g. `0.333333333` → `1/3`;
+/// returns `None` for NaN and infinities.
+pub fn approximate_rational(x: f64, tolerance: f64) -> Option<BigRational> {
+    if !x.is_finite() {
+        return None;
+    }
+    let target = BigRational::from_f64(x)?;
+    let tolerance = BigRational::from_f64(tolerance.abs())?;
+    // Convergents h/k of the continued fraction of x.
+    let (mut h0, mut h1) = (BigInt::zero(), BigInt::from(1));
+    let (mut k0, mut k1) = (BigInt::from(1), BigInt::zero());
+    let mut rest = target.clone();
+    loop {
+        let a = rest.floor().to_integer();
+        (h0, h1) = (h1.clone(), &a * &h1 + h0);
+        (k0, k1) = (k1.clone(), &a * &k1 + k0);
+        let approx = BigRational::new(h1.clone(), k1.clone());
+        let frac = &rest - BigRational::from_integer(a);
+        if (&approx - &target).abs() <= tolerance || frac.is_zero() {
+            return Some(approx);
+        }
+        rest = frac.recip();
+    }
+}
@@ -0,0 +1,70 @@
+use std::fmt;
+
+use crate::{generator::GenConfig, mandelstam::Invariant, tensor_structure::TensorStructure};
+
+/// A tensor structure over a product of propagator poles, `N / (s_I1 · s_I2 · …)`.
+///
+/// The poles form a multiset, so repeated invariants describe higher-order poles.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct RationalStructure {
+    pub numerator: TensorStructure,
+    pub poles: Vec<Invariant>,
+}
+
+impl RationalStructure {
+    pub fn new(numerator: TensorStructure, poles: Vec<Invariant>) -> Self {
+        Self { numerator, poles }
+    }
+
+    /// Bring numerator and poles to canonical form and return the numerator's sign.
+    ///
+    /// Poles are rewritten with `s_I = s_Ī` to avoid the eliminated leg, then sorted.
+    pub fn canonicalize(&mut self, cfg: &GenConfig) -> i8 {
+        for p in &mut self.poles {
+            p.canonicalize(cfg);
+        }
+        self.poles.sort();
+        self.numerator.canonicalize()
+    }
+
+    /// Number of poles, counted with multiplicity.
+    pub fn pole_count(&self) -> u32 {
+        self.poles.len() as u32
+    }
+}
+
+impl From<TensorStructure> for RationalStructure {
+    fn from(numerator: TensorStructure) -> Self {
+        Self::new(numerator, Vec::new())
+    }
+}
+
+impl fmt::Display for RationalStructure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.poles.is_empty() {
+            return write!(f, "{}", self.numerator);
+        }
+        let joined = self
+            .poles
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<_>>()
+            .join(" · ");
+        write!(f, "{} / ({})", self.numerator, joined)
+    }
+}
+
+impl Ord for RationalStructure {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        // Group by pole structure first, then by numerator.
+        self.poles
+            .cmp(&other.poles)
+            .then_with(|| self.numerator.cmp(&other.numerator))
+    }
+}
+
+impl PartialOrd for RationalStructure {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
@@ -0,0 +1,433 @@
+use num_rational::BigRational;
+use num_traits::{One, Zero};
+
+use crate::{
+    bilinear::BilinearFactor,
+    dot_product::ScalarFactor,
+    epsilon::EpsilonFactor,
+    expression::TensorExpression,
+    field_strength::FieldStrengthFactor,
+    generator::GenConfig,
+    rational::int,
+    tensor_structure::TensorStructure,
+    types::{LegIndex, ScalarKind, Slot},
+};
+
+/// Expand every field-strength trace or chain into dot products.
+///
+/// Each `F_i` contributes `p_i ⊗ e_i − e_i ⊗ p_i`, so a chain of `k` field
+/// strengths becomes `2^k` products of adjacent contractions.
+pub(crate) fn expand_field_strengths(t: &TensorStructure) -> TensorExpression {
+    let mut base = t.clone();
+    base.field_strengths.clear();
+    let mut terms = vec![(BigRational::one(), base)];
+    for fs in &t.field_strengths {
+        let expansion = expand_chain(fs);
+        let mut next = Vec::with_capacity(terms.len() * expansion.len());
+        for (c, t) in &terms {
+            for (d, factors) in &expansion {
+                let mut u = t.clone();
+                u.factors.extend(factors.iter().cloned());
+                next.push((c * d, u));
+            }
+        }
+        terms = next;
+    }
+    finish(terms)
+}
+
+/// Products of dot products making up one field-strength chain.
+fn expand_chain(fs: &FieldStrengthFactor) -> Vec<(BigRational, Vec<ScalarFactor>)> {
+    let (legs, ends) = match fs {
+        FieldStrengthFactor::Trace(legs) => (legs, None),
+        FieldStrengthFactor::Chain { left, legs, right } => (legs, Some((*left, *right))),
+    };
+    let k = legs.len();
+    let mut out = Vec::with_capacity(1 << k);
+    for mask in 0u64..(1u64 << k) {
+        // Bit set: F_i enters as −e_i ⊗ p_i instead of p_i ⊗ e_i.
+        let mut sign = 1i64;
+        let sides: Vec<(Slot, Slot)> = legs
+            .iter()
+            .enumerate()
+            .map(|(b, &l)| {
+                if mask >> b & 1 == 1 {
+                    sign = -sign;
+                    (Slot::E(l), Slot::P(l))
+                } else {
+                    (Slot::P(l), Slot::E(l))
+                }
+            })
+            .collect();
+        let mut factors = Vec::with_capacity(k + 1);
+        for w in sides.windows(2) {
+            factors.push(ScalarFactor::dot(w[0].1, w[1].0));
+        }
+        match ends {
+            None => factors.push(ScalarFactor::dot(sides[k - 1].1, sides[0].0)),
+            Some((a, b)) => {
+                factors.push(ScalarFactor::dot(Slot::P(a), sides[0].0));
+                factors.push(ScalarFactor::dot(sides[k - 1].1, Slot::P(b)));
+            }
+        }
+        out.push((int(sign), factors));
+    }
+    out
+}
+
+/// Rewrite `expr` in the reduced basis used by the generator.
+///
+/// Any expression can be passed, including ones mentioning the eliminated
+/// momentum or vanishing factors; structures are canonicalized and like terms
+/// collected, so the result can be compared term by term with generated
+/// structures. Field strengths are kept; see [`canonical_form`] to expand them.
+///
+/// The eliminated momentum `p_k` is replaced by `−Σ_{i≠k} p_i`; `p_i² = 0`,
+/// `e_i² = 0` and `p_i·e_i = 0` are imposed on shell for massless transverse
+/// legs; the excluded `p_m·e_k` is traded for the other `p·e_k` through
+/// `Σ_{i≠k} p_i·e_k = 0`; and the last `p_a·p_b` among the remaining legs is
+/// fixed by `(Σ_{i≠k} p_i)² = m_k²`. Masses stay as `p_i·p_i` symbols.
+pub fn reduce_expression(cfg: &GenConfig, expr: &TensorExpression) -> TensorExpression {
+    let mut terms = Vec::new();
+    for (c, t) in &expr.terms {
+        for (d, u) in reduce_structure(cfg, t) {
+            terms.push((c * d, u));
+        }
+    }
+    finish(terms)
+}
+
+/// Canonical form of `expr` in the generator's dot-product basis.
+///
+/// Expands field strengths and then applies [`reduce_expression`]: the
+/// eliminated momentum is removed, on-shell conditions and the conditions of
+/// a helicity sector imposed. In generic dimension two expressions are equal on
+/// shell exactly when their canonical forms agree; fixed dimensions add the
+/// Gram relations of [`crate::gram::gram_reduce`].
+pub fn canonical_form(cfg: &GenConfig, expr: &TensorExpression) -> TensorExpression {
+    let mut expanded = TensorExpression::new();
+    for (c, t) in &expr.terms {
+        for (d, u) in expand_field_strengths(t).terms {
+            expanded.push(c * d, u);
+        }
+    }
+    reduce_expression(cfg, &expanded)
+}
+
+fn reduce_structure(cfg: &GenConfig, t: &TensorStructure) -> Vec<(BigRational, TensorStructure)> {
+    let mut base = TensorStructure::new();
+    base.field_strengths = t.field_strengths.clone();
+    let mut terms = vec![(BigRational::one(), base)];
+
+    for f in &t.factors {
+        let combo = reduce_factor(cfg, f);
+        terms = multiply(terms, &combo, |u, g| u.factors.push(g.clone()));
+    }
+    for b in &t.bilinears {
+        let combo = reduce_bilinear(cfg, b);
+        terms = multiply(terms, &combo, |u, g| u.bilinears.push(g.clone()));
+    }
+    if let Some(e) = &t.epsilon {
+        let combo = reduce_epsilon(cfg, e);
+        terms = multiply(terms, &combo, |u, g| u.epsilon = Some(g.clone()));
+    }
+    terms
+}
+
+fn multiply<T>(
+    terms: Vec<(BigRational, TensorStructure)>,
+    combo: &[(BigRational, T)],
+    attach: impl Fn(&mut TensorStructure, &T),
+) -> Vec<(BigRational, TensorStructure)> {
+    let mut out = Vec::with_capacity(terms.len() * combo.len());
+    for (c, t) in &terms {
+        for (d, g) in combo {
+            let mut u = t.clone();
+            attach(&mut u, g);
+            out.push((c * d, u));
+        }
+    }
+    out
+}
+
+/// Build an expression from `terms` and [`simplify`](TensorExpression::simplify) it.
+pub(crate) fn finish(terms: Vec<(BigRational, TensorStructure)>) -> TensorExpression {
+    let mut out = TensorExpression { terms };
+    out.simplify();
+    out
+}
+
+/// `p_k → −Σ_{i≠k} p_i` for the eliminated leg; every other slot is kept.
+fn substitute(cfg: &GenConfig, slot: Slot) -> Vec<(i64, Slot)> {
+    match (slot, cfg.eliminated_leg()) {
+        (Slot::P(l), Some(k)) if l == k => (1..=cfg.n_legs)
+            .map(LegIndex)
+            .filter(|&i| i != k)
+            .map(|i| (-1, Slot::P(i)))
+            .collect(),
+        _ => vec![(1, slot)],
+    }
+}
+
+fn reduce_factor(cfg: &GenConfig, f: &ScalarFactor) -> Vec<(BigRational, ScalarFactor)> {
+    let slots = match f.kind {
+        ScalarKind::PP => (Slot::P(f.a), Slot::P(f.b)),
+        ScalarKind::PE => (Slot::P(f.a), Slot::E(f.b)),
+        ScalarKind::EE => (Slot::E(f.a), Slot::E(f.b)),
+        ScalarKind::LP => {
+            return substitute(cfg, Slot::P(f.b))
+                .into_iter()
+                .map(|(c, s)| (int(c), ScalarFactor::lp(f.a, s.leg())))
+                .collect();
+        }
+        _ => return vec![(BigRational::one(), f.clone())],
+    };
+    // Massive and off-shell `p_i·p_i` are kept as mass symbols, even for `p_k`.
+    if slots.0 == slots.1 && f.kind == ScalarKind::PP {
+        return if cfg.has_virtuality(f.a) {
+            vec![(BigRational::one(), f.clone())]
+        } else {
+            Vec::new()
+        };
+    }
+    let mut out = Vec::new();
+    for (cx, x) in substitute(cfg, slots.0) {
+        for (cy, y) in substitute(cfg, slots.1) {
+            for (c, g) in on_shell(cfg, x, y) {
+                out.push((c * int(cx * cy), g));
+            }
+        }
+    }
+    merge(out)
+}
+
+/// Impose the on-shell relations on a dot product free of the eliminated momentum.
+fn on_shell(cfg: &GenConfig, x: Slot, y: Slot) -> Vec<(BigRational, ScalarFactor)> {
+    let f = ScalarFactor::dot(x, y);
+    let one = BigRational::one();
+    match f.kind {
+        ScalarKind::PP if f.a == f.b => {
+            if cfg.has_virtuality(f.a) {
+                vec![(one, f)]
+            } else {
+                Vec::new()
+            }
+        }
+        ScalarKind::PP if Some((f.a, f.b)) == dropped_pp(cfg) => pp_relation(cfg, f.a, f.b),
+        ScalarKind::PE if f.a == f.b && cfg.is_transverse(f.a) => Vec::new(),
+        ScalarKind::EE | ScalarKind::PE if cfg.helicity_vanishes(&f) => helicity_relation(cfg, &f),
+        ScalarKind::PE if Some((f.a, f.b)) == pe_relation_target(cfg) => (1..=cfg.n_legs)
+            .map(LegIndex)
+            .filter(|&i| i != f.a && i != f.b && Some(i) != cfg.eliminated_leg())
+            .map(|i| ScalarFactor::pe(i, f.b))
+            .filter(|g| !cfg.helicity_vanishes(g))
+            .map(|g| (-one.clone(), g))
+            .collect(),
+        ScalarKind::EE if f.a == f.b && !cfg.has_virtuality(f.a) => Vec::new(),
+        _ => vec![(one, f)],
+    }
+}
+
+/// A factor removed by the reference choice of the helicity sector.
+///
+/// It vanishes outright unless it is the stand-in for `q·e_i` with `q` the
+/// eliminated momentum, which `Σ_{j≠q} p_j·e_i = 0` trades for the other `p_j·e_i`.
+fn helicity_relation(cfg: &GenConfig, f: &ScalarFactor) -> Vec<(BigRational, ScalarFactor)> {
+    let reference = cfg.helicity(f.b).and_then(|h| cfg.reference_leg(h));
+    if f.kind == ScalarKind::EE || reference == Some(f.a) {
+        return Vec::new();
+    }
+    (1..=cfg.n_legs)
+        .map(LegIndex)
+        .filter(|&j| j != f.a && Some(j) != reference)
+        .filter(|&j| !(j == f.b && cfg.is_transverse(j)))
+        .map(|j| (-BigRational::one(), ScalarFactor::pe(j, f.b)))
+        .collect()
+}
+
+/// The `p_m·e_k` traded away by `Σ_{i≠k} p_i·e_k = 0`, if that relation holds.
+///
+/// It needs a transverse eliminated leg `k`, whatever exclusion rule picked `m`.
+fn pe_relation_target(cfg: &GenConfig) -> Option<(LegIndex, LegIndex)> {
+    let k = cfg.eliminated_leg()?;
+    let (m, j) = cfg.excluded_pe()?;
+    (j == k && m != k && cfg.is_transverse(k)).then_some((m, k))
+}
+
+/// The last pair `(a, b)` of remaining legs, fixed by `(Σ_{i≠k} p_i)² = m_k²`.
+pub(crate) fn dropped_pp(cfg: &GenConfig) -> Option<(LegIndex, LegIndex)> {
+    let k = cfg.eliminated_leg()?;
+    let rest: Vec<LegIndex> = (1..=cfg.n_legs).map(LegIndex).filter(|&i| i != k).collect();
+    match rest.as_slice() {
+        [.., a, b] => Some((*a, *b)),
+        _ => None,
+    }
+}
+
+/// `p_a·p_b = ½ (m_k² − Σ_{i≠k} m_i²) − Σ_{other pairs} p_i·p_j`.
+fn pp_relation(cfg: &GenConfig, a: LegIndex, b: LegIndex) -> Vec<(BigRational, ScalarFactor)> {
+    let Some(k) = cfg.eliminated_leg() else {
+        return vec![(BigRational::one(), ScalarFactor::pp(a, b))];
+    };
+    let half = int(1) / int(2);
+    let mut out = Vec::new();
+    if cfg.has_virtuality(k) {
+        out.push((half.clone(), ScalarFactor::pp(k, k)));
+    }
+    let rest: Vec<LegIndex> = (1..=cfg.n_legs).map(LegIndex).filter(|&i| i != k).collect();
+    for (x, &i) in rest.iter().enumerate() {
+        if cfg.has_virtuality(i) {
+            out.push((-half.clone(), ScalarFactor::pp(i, i)));
+        }
+        for &j in &rest[x + 1..] {
+            if (i, j) != (a, b) {
+                out.push((-BigRational::one(), ScalarFactor::pp(i, j)));
+            }
+        }
+    }
+    out
+}
+
+fn reduce_bilinear(cfg: &GenConfig, b: &BilinearFactor) -> Vec<(BigRational, BilinearFactor)> {
+    let mut out = Vec::new();
+    for (c, gamma) in substitute(cfg, b.gamma) {
+        // Dirac equation: ū_i p̸_i = 0 and p̸_j u_j = 0 for massless fermions.
+        if let Slot::P(l) = gamma
+            && (l == b.bar || l == b.u)
+            && !cfg.has_virtuality(l)
+        {
+            continue;
+        }
+        out.push((int(c), BilinearFactor::new(b.bar, gamma, b.u)));
+    }
+    merge(out)
+}
+
+fn reduce_epsilon(cfg: &GenConfig, e: &EpsilonFactor) -> Vec<(BigRational, EpsilonFactor)> {
+    let mut partial: Vec<(i64, Vec<Slot>)> = vec![(1, Vec::new())];
+    for &slot in &e.slots {
+        let mut next = Vec::new();
+        for (c, prefix) in &partial {
+            for (d, s) in substitute(cfg, slot) {
+                let mut p = prefix.clone();
+                p.push(s);
+                next.push((c * d, p));
+            }
+        }
+        partial = next;
+    }
+    let mut out = Vec::new();
+    for (c, slots) in partial {
+        if let Some((eps, sign)) = EpsilonFactor::new([slots[0], slots[1], slots[2], slots[3]]) {
+            out.push((int(c * sign as i64), eps));
+        }
+    }
+    merge(out)
+}
+
+/// Bring the spinor brackets and the ε factor of `expr` to a Schouten normal form.
+///
+/// Angle and square brackets obey `⟨ik⟩⟨jl⟩ = ⟨ij⟩⟨kl⟩ + ⟨il⟩⟨jk⟩` for
+/// `i < j < k < l`; applying it until no two brackets of the same kind cross
+/// leaves non-crossing monomials, which are linearly independent. In four
+/// dimensions no five vectors can be antisymmetrized, so
+/// `(x·y) ε(a,b,c,d) = (x·a) ε(y,b,c,d) + (x·b) ε(a,y,c,d) + (x·c) ε(a,b,y,d) + (x·d) ε(a,b,c,y)`;
+/// it is applied whenever a dot product contains a vector ordered before every
+/// slot of ε, moving that vector into ε. Only these identities are used;
+/// momentum conservation and on-shell conditions are left to
+/// [`reduce_expression`].
+pub fn schouten_reduce(expr: &TensorExpression) -> TensorExpression {
+    let mut pending = expr.terms.clone();
+    let mut done = Vec::new();
+    while let Some((c, t)) = pending.pop() {
+        match schouten_step(&t) {
+            Some(rewrite) => pending.extend(rewrite.into_iter().map(|(d, u)| (&c * d, u))),
+            None => done.push((c, t)),
+        }
+    }
+    finish(done)
+}
+
+/// One Schouten rewrite of `t`, or `None` if it is already in normal form.
+fn schouten_step(t: &TensorStructure) -> Option<Vec<(BigRational, TensorStructure)>> {
+    for (x, f) in t.factors.iter().enumerate() {
+        if !matches!(f.kind, ScalarKind::Angle | ScalarKind::Square) {
+            continue;
+        }
+        for (y, g) in t.factors.iter().enumerate() {
+            // `f = ⟨ik⟩`, `g = ⟨jl⟩` with `i < j < k < l`.
+            if g.kind != f.kind || !(f.a < g.a && g.a < f.b && f.b < g.b) {
+                continue;
+            }
+            let (i, j, k, l) = (f.a, g.a, f.b, g.b);
+            let bracket = |a, b| ScalarFactor { kind: f.kind, a, b };
+            let rest: Vec<ScalarFactor> = t
+                .factors
+                .iter()
+                .enumerate()
+                .filter(|&(z, _)| z != x && z != y)
+                .map(|(_, h)| h.clone())
+                .collect();
+            let with = |p: ScalarFactor, q: ScalarFactor| {
+                let mut u = t.clone();
+                u.factors = rest.as_slice().into();
+                u.factors.push(p);
+                u.factors.push(q);
+                (BigRational::one(), u)
+            };
+            return Some(vec![
+                with(bracket(i, j), bracket(k, l)),
+                with(bracket(i, l), bracket(j, k)),
+            ]);
+        }
+    }
+
+    let eps = t.epsilon.as_ref()?;
+    for (x, f) in t.factors.iter().enumerate() {
+        let Some((u, v)) = dot_slots(f) else {
+            continue;
+        };
+        let (low, other) = if u <= v { (u, v) } else { (v, u) };
+        if low >= eps.slots[0] {
+            continue;
+        }
+        let mut out = Vec::new();
+        for k in 0..4 {
+            let mut slots = eps.slots;
+            let moved = std::mem::replace(&mut slots[k], low);
+            let Some((e, sign)) = EpsilonFactor::new(slots) else {
+                continue;
+            };
+            let mut w = t.clone();
+            w.factors[x] = ScalarFactor::dot(other, moved);
+            w.epsilon = Some(e);
+            out.push((int(sign as i64), w));
+        }
+        return Some(out);
+    }
+    None
+}
+
+/// The two vectors contracted by a PP, PE or EE factor.
+fn dot_slots(f: &ScalarFactor) -> Option<(Slot, Slot)> {
+    match f.kind {
+        ScalarKind::PP => Some((Slot::P(f.a), Slot::P(f.b))),
+        ScalarKind::PE => Some((Slot::P(f.a), Slot::E(f.b))),
+        ScalarKind::EE => Some((Slot::E(f.a), Slot::E(f.b))),
+        _ => None,
+    }
+}
+
+/// Combine equal entries of a linear combination and drop zeros.
+fn merge<T: Ord>(mut v: Vec<(BigRational, T)>) -> Vec<(BigRational, T)> {
+    v.sort_by(|x, y| x.1.cmp(&y.1));
+    let mut out: Vec<(BigRational, T)> = Vec::with_capacity(v.len());
+    for (c, g) in v {
+        match out.last_mut() {
+            Some((acc, last)) if *last == g => *acc += c,
+            _ => out.push((c, g)),
+        }
+    }
+    out.retain(|(c, _)| !c.is_zero());
+    out
+}
@@ -0,0 +1,348 @@
+//! Disk-backed external sort for structure sets larger than memory.
+//!
+//! Structures are buffered in chunks, each chunk is sorted and written to a
+//! temporary file in a compact binary encoding, and the chunks are then merged
+//! into one sorted, duplicate-free file that is read back lazily.
+
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::{
+    bilinear::BilinearFactor,
+    dot_product::ScalarFactor,
+    epsilon::EpsilonFactor,
+    field_strength::FieldStrengthFactor,
+    tensor_structure::TensorStructure,
+    types::{LegIndex, ScalarKind, Slot},
+};
+
+/// Where and how often to spill structures to disk.
+#[derive(Clone, Debug)]
+pub struct SpillConfig {
+    /// Directory for the temporary files; it must exist.
+    pub dir: PathBuf,
+    /// Structures held in memory before a chunk is written out.
+    pub chunk_size: usize,
+}
+
+impl SpillConfig {
+    pub const DEFAULT_CHUNK_SIZE: usize = 1 << 20;
+
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            chunk_size: Self::DEFAULT_CHUNK_SIZE,
+        }
+    }
+}
+
+/// A sorted, duplicate-free set of structures stored in a temporary file,
+/// which is removed when this is dropped.
+#[derive(Debug)]
+pub struct SpilledStructures {
+    path: PathBuf,
+    len: u64,
+}
+
+impl SpilledStructures {
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read the structures back in ascending order.
+    pub fn iter(&self) -> io::Result<SpillReader> {
+        SpillReader::open(&self.path)
+    }
+}
+
+impl Drop for SpilledStructures {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Streams structures out of a spill file.
+pub struct SpillReader {
+    reader: BufReader<File>,
+}
+
+impl SpillReader {
+    fn open(path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            reader: BufReader::new(File::open(path)?),
+        })
+    }
+}
+
+impl Iterator for SpillReader {
+    type Item = io::Result<TensorStructure>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.reader.fill_buf() {
+            Ok([]) => None,
+            Ok(_) => Some(read_structure(&mut self.reader)),
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// First bytes of a saved structure file, which holds the records of a
+/// spill file after it.
+pub const STRUCTURE_FILE_MAGIC: &[u8; 8] = b"TAMPSv1\n";
+
+/// Start a structure file: write [`STRUCTURE_FILE_MAGIC`], after which each
+/// structure follows with [`write_structure_record`].
+pub fn write_structure_header(w: &mut impl Write) -> io::Result<()> {
+    w.write_all(STRUCTURE_FILE_MAGIC)
+}
+
+/// Append one structure to a file started by [`write_structure_header`].
+pub fn write_structure_record(w: &mut impl Write, t: &TensorStructure) -> io::Result<()> {
+    write_structure(w, t)
+}
+
+/// Stream the structures of a saved structure file, in the order written.
+pub fn read_structure_file(path: &Path) -> io::Result<SpillReader> {
+    let mut reader = SpillReader::open(path)?;
+    let magic: [u8; 8] = read_bytes(&mut reader.reader)?;
+    if &magic != STRUCTURE_FILE_MAGIC {
+        return Err(invalid("not a treeamps structure file"));
+    }
+    Ok(reader)
+}
+
+/// Sort `structures` through files in `spill.dir`, dropping duplicates.
+pub(crate) fn sort_to_disk(
+    structures: impl Iterator<Item = TensorStructure>,
+    spill: &SpillConfig,
+) -> io::Result<SpilledStructures> {
+    let mut chunks = Vec::new();
+    let mut buffer = Vec::with_capacity(spill.chunk_size.min(SpillConfig::DEFAULT_CHUNK_SIZE));
+    // Chunk files are removed on drop, also when a later step fails.
+    let write_chunk = |buffer: &mut Vec<TensorStructure>, chunks: &mut Vec<SpilledStructures>| {
+        buffer.sort_unstable();
+        buffer.dedup();
+        let chunk = write_file(&spill.dir, buffer.drain(..).map(Ok))?;
+        chunks.push(chunk);
+        io::Result::Ok(())
+    };
+    for t in structures {
+        buffer.push(t);
+        if buffer.len() >= spill.chunk_size.max(1) {
+            write_chunk(&mut buffer, &mut chunks)?;
+        }
+    }
+    if !buffer.is_empty() || chunks.is_empty() {
+        write_chunk(&mut buffer, &mut chunks)?;
+    }
+    // Merge a bounded number of files at a time to stay within the limit on
+    // open files.
+    const FAN_IN: usize = 64;
+    while chunks.len() > 1 {
+        let rest = chunks.split_off(FAN_IN.min(chunks.len()));
+        let merged = merge(&spill.dir, &chunks)?;
+        chunks = rest;
+        chunks.push(merged);
+    }
+    Ok(chunks.pop().expect("at least one chunk"))
+}
+
+/// Merge sorted files into one, dropping duplicates.
+fn merge(dir: &Path, chunks: &[SpilledStructures]) -> io::Result<SpilledStructures> {
+    let mut readers = chunks
+        .iter()
+        .map(|c| c.iter())
+        .collect::<io::Result<Vec<_>>>()?;
+    let mut heap = BinaryHeap::new();
+    for (k, r) in readers.iter_mut().enumerate() {
+        if let Some(t) = r.next() {
+            heap.push(Reverse((t?, k)));
+        }
+    }
+    let mut last: Option<TensorStructure> = None;
+    let merged = std::iter::from_fn(|| {
+        while let Some(Reverse((t, k))) = heap.pop() {
+            match readers[k].next() {
+                Some(Ok(next)) => heap.push(Reverse((next, k))),
+                Some(Err(e)) => return Some(Err(e)),
+                None => {}
+            }
+            if last.as_ref() != Some(&t) {
+                last = Some(t.clone());
+                return Some(Ok(t));
+            }
+        }
+        None
+    });
+    write_file(dir, merged)
+}
+
+/// Write `structures` to a fresh file in `dir`.
+fn write_file(
+    dir: &Path,
+    structures: impl Iterator<Item = io::Result<TensorStructure>>,
+) -> io::Result<SpilledStructures> {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    let name = format!(
+        "treeamps-{}-{}.spill",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    );
+    let mut out = SpilledStructures {
+        path: dir.join(name),
+        len: 0,
+    };
+    let mut w = BufWriter::new(File::create(&out.path)?);
+    for t in structures {
+        write_structure(&mut w, &t?)?;
+        out.len += 1;
+    }
+    w.flush()?;
+    Ok(out)
+}
+
+// Records are length-prefixed lists of single-byte fields: the dot products
+// as (kind, a, b), the EE count, an optional ε, the bilinears and the
+// field-strength chains. Slots are a tag byte (0 for p, 1 for e) and a leg.
+
+fn write_structure(w: &mut impl Write, t: &TensorStructure) -> io::Result<()> {
+    write_len(w, t.factors.len())?;
+    for f in &t.factors {
+        w.write_all(&[f.kind as u8, f.a.0, f.b.0])?;
+    }
+    w.write_all(&t.ee_contractions.to_le_bytes())?;
+    match &t.epsilon {
+        Some(e) => {
+            w.write_all(&[1])?;
+            for &s in &e.slots {
+                write_slot(w, s)?;
+            }
+        }
+        None => w.write_all(&[0])?,
+    }
+    write_len(w, t.bilinears.len())?;
+    for b in &t.bilinears {
+        w.write_all(&[b.bar.0])?;
+        write_slot(w, b.gamma)?;
+        w.write_all(&[b.u.0])?;
+    }
+    write_len(w, t.field_strengths.len())?;
+    for f in &t.field_strengths {
+        match f {
+            FieldStrengthFactor::Trace(legs) => {
+                w.write_all(&[0])?;
+                write_legs(w, legs)?;
+            }
+            FieldStrengthFactor::Chain { left, legs, right } => {
+                w.write_all(&[1, left.0])?;
+                write_legs(w, legs)?;
+                w.write_all(&[right.0])?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_structure(r: &mut impl Read) -> io::Result<TensorStructure> {
+    let mut t = TensorStructure::new();
+    for _ in 0..read_len(r)? {
+        let [kind, a, b] = read_bytes(r)?;
+        let kind = *ScalarKind::ALL
+            .get(kind as usize)
+            .ok_or_else(|| invalid("unknown factor kind"))?;
+        t.factors.push(ScalarFactor {
+            kind,
+            a: LegIndex(a),
+            b: LegIndex(b),
+        });
+    }
+    t.ee_contractions = u32::from_le_bytes(read_bytes(r)?);
+    if read_bytes::<1>(r)? == [1] {
+        t.epsilon = Some(EpsilonFactor {
+            slots: [read_slot(r)?, read_slot(r)?, read_slot(r)?, read_slot(r)?],
+        });
+    }
+    for _ in 0..read_len(r)? {
+        let [bar] = read_bytes(r)?;
+        let gamma = read_slot(r)?;
+        let [u] = read_bytes(r)?;
+        t.bilinears
+            .push(BilinearFactor::new(LegIndex(bar), gamma, LegIndex(u)));
+    }
+    for _ in 0..read_len(r)? {
+        let f = match read_bytes(r)? {
+            [0] => FieldStrengthFactor::Trace(read_legs(r)?),
+            _ => {
+                let [left] = read_bytes(r)?;
+                let legs = read_legs(r)?;
+                let [right] = read_bytes(r)?;
+                FieldStrengthFactor::Chain {
+                    left: LegIndex(left),
+                    legs,
+                    right: LegIndex(right),
+                }
+            }
+        };
+        t.field_strengths.push(f);
+    }
+    Ok(t)
+}
+
+fn write_len(w: &mut impl Write, len: usize) -> io::Result<()> {
+    let len = u32::try_from(len).map_err(|_| invalid("list too long to spill"))?;
+    w.write_all(&len.to_le_bytes())
+}
+
+fn read_len(r: &mut impl Read) -> io::Result<u32> {
+    Ok(u32::from_le_bytes(read_bytes(r)?))
+}
+
+fn write_slot(w: &mut impl Write, s: Slot) -> io::Result<()> {
+    match s {
+        Slot::P(l) => w.write_all(&[0, l.0]),
+        Slot::E(l) => w.write_all(&[1, l.0]),
+    }
+}
+
+fn read_slot(r: &mut impl Read) -> io::Result<Slot> {
+    match read_bytes(r)? {
+        [0, l] => Ok(Slot::P(LegIndex(l))),
+        [1, l] => Ok(Slot::E(LegIndex(l))),
+        _ => Err(invalid("unknown slot tag")),
+    }
+}
+
+fn write_legs(w: &mut impl Write, legs: &[LegIndex]) -> io::Result<()> {
+    write_len(w, legs.len())?;
+    legs.iter().try_for_each(|l| w.write_all(&[l.0]))
+}
+
+fn read_legs(r: &mut impl Read) -> io::Result<Vec<LegIndex>> {
+    (0..read_len(r)?)
+        .map(|_| read_bytes(r).map(|[l]| LegIndex(l)))
+        .collect()
+}
+
+fn read_bytes<const N: usize>(r: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut buf = [0; N];
+    r.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
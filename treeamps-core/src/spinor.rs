@@ -0,0 +1,139 @@
+//! Tensor structures in spinor-helicity variables.
+//!
+//! For 4D massless kinematics a structure can be written directly as a
+//! monomial in angle brackets `⟨ij⟩` and square brackets `[ij]`. Under the
+//! little group of leg `i`, `|i⟩ → t|i⟩` and `|i] → t⁻¹|i]`, so a monomial
+//! describing helicity `h_i` must satisfy `#[i] - #⟨i⟩ = 2 h_i`.
+
+use crate::{
+    dot_product::ScalarFactor,
+    tensor_structure::TensorStructure,
+    types::{LegIndex, ScalarKind},
+};
+
+/// Configuration of a spinor-helicity enumeration.
+#[derive(Clone, Debug)]
+pub struct SpinorConfig {
+    pub n_legs: u8,
+    /// Twice the helicity of each leg, indexed by `leg - 1` (gluon `±` is `±2`).
+    pub helicity_weights: Vec<i32>,
+}
+
+impl SpinorConfig {
+    /// Required little-group weight `#[i] - #⟨i⟩` of a leg.
+    pub fn weight(&self, leg: LegIndex) -> i32 {
+        self.helicity_weights
+            .get(leg.0 as usize - 1)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Whether the little-group weights of `t` match this helicity configuration.
+    pub fn admits(&self, t: &TensorStructure) -> bool {
+        let w = t.weights();
+        w.len() <= self.n_legs as usize
+            && (1..=self.n_legs)
+                .all(|i| w.get(i as usize - 1).copied().unwrap_or(0) == self.weight(LegIndex(i)))
+    }
+}
+
+/// All brackets `⟨ij⟩` followed by all `[ij]`, with `i < j`.
+fn generate_bracket_catalog(n: u8) -> Vec<ScalarFactor> {
+    let mut out = Vec::new();
+    for i in 1..=n {
+        for j in (i + 1)..=n {
+            out.push(ScalarFactor::angle(LegIndex(i), LegIndex(j)));
+        }
+    }
+    for i in 1..=n {
+        for j in (i + 1)..=n {
+            out.push(ScalarFactor::square(LegIndex(i), LegIndex(j)));
+        }
+    }
+    out
+}
+
+struct SpinorDfs<'a> {
+    catalog: &'a [ScalarFactor],
+    targets: Vec<i32>,
+    angles_left: u32,
+    squares_left: u32,
+    /// Current `#[i] - #⟨i⟩` per leg (slot 0 unused).
+    weights: Vec<i32>,
+    cur: TensorStructure,
+}
+
+/// Brackets are added in nondecreasing order of the sorted catalog, so every
+/// monomial is reached once, already canonical, and in ascending order.
+fn spinor_dfs(s: &mut SpinorDfs, idx_start: usize, out: &mut Vec<TensorStructure>) {
+    // Each remaining bracket moves the weight of two legs by one unit.
+    let mut need_square = 0;
+    let mut need_angle = 0;
+    for (w, t) in s.weights.iter().zip(&s.targets) {
+        if w < t {
+            need_square += (t - w) as u32;
+        } else {
+            need_angle += (w - t) as u32;
+        }
+    }
+    if need_square > 2 * s.squares_left || need_angle > 2 * s.angles_left {
+        return;
+    }
+
+    if s.angles_left == 0 && s.squares_left == 0 {
+        if s.weights == s.targets {
+            out.push(s.cur.clone());
+        }
+        return;
+    }
+
+    for i in idx_start..s.catalog.len() {
+        let f = &s.catalog[i];
+        let (left, step) = match f.kind {
+            ScalarKind::Angle => (&mut s.angles_left, -1),
+            _ => (&mut s.squares_left, 1),
+        };
+        if *left == 0 {
+            continue;
+        }
+        *left -= 1;
+        s.weights[f.a.0 as usize] += step;
+        s.weights[f.b.0 as usize] += step;
+        s.cur.factors.push(f.clone());
+
+        spinor_dfs(s, i, out);
+
+        s.cur.factors.pop();
+        s.weights[f.a.0 as usize] -= step;
+        s.weights[f.b.0 as usize] -= step;
+        match f.kind {
+            ScalarKind::Angle => s.angles_left += 1,
+            _ => s.squares_left += 1,
+        }
+    }
+}
+
+/// Enumerate bracket monomials with `n_angle` angle and `n_square` square
+/// brackets whose little-group weights match `cfg.helicity_weights`.
+pub fn generate_spinor_structures(
+    cfg: &SpinorConfig,
+    n_angle: u32,
+    n_square: u32,
+) -> Vec<TensorStructure> {
+    let catalog = generate_bracket_catalog(cfg.n_legs);
+    let targets = std::iter::once(0)
+        .chain((1..=cfg.n_legs).map(|i| cfg.weight(LegIndex(i))))
+        .collect();
+    let mut s = SpinorDfs {
+        catalog: &catalog,
+        targets,
+        angles_left: n_angle,
+        squares_left: n_square,
+        weights: vec![0; cfg.n_legs as usize + 1],
+        cur: TensorStructure::new(),
+    };
+    let mut out = Vec::new();
+    spinor_dfs(&mut s, 0, &mut out);
+    debug_assert!(out.windows(2).all(|w| w[0] < w[1]));
+    out
+}
@@ -0,0 +1,251 @@
+use num_complex::Complex;
+use num_rational::BigRational;
+use num_traits::Zero;
+
+use crate::{
+    coefficient::Coefficient,
+    dot_product::ScalarFactor,
+    kinematics::{BcfwShift, KinematicPoint},
+    rational::int,
+    rng::SplitMix64,
+    tensor_structure::TensorStructure,
+    types::{Helicity, LegIndex, ScalarKind},
+};
+
+/// A complex 4-vector `v^μ` over the Gaussian rationals.
+pub type FourVector = [Complex<BigRational>; 4];
+
+/// A massless 4D point given by rational spinors `λ_i` and `λ̃_i`.
+///
+/// The two spinors are independent, so the momenta `p_i^{αα̇} = λ_i^α λ̃_i^α̇`
+/// are complex; everything else is rational. Brackets are
+/// `⟨ij⟩ = λ_i^1 λ_j^2 − λ_i^2 λ_j^1` and `[ij] = λ̃_i^2 λ̃_j^1 − λ̃_i^1 λ̃_j^2`,
+/// so that `2 p_i·p_j = ⟨ij⟩[ji]` in the metric `(+,−,−,−)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpinorPoint {
+    /// `λ_i` at index `i − 1`.
+    pub lambda: Vec<[BigRational; 2]>,
+    /// `λ̃_i` at index `i − 1`.
+    pub lambda_tilde: Vec<[BigRational; 2]>,
+}
+
+impl SpinorPoint {
+    /// A reproducible random point with `n ≥ 4` legs drawn from `seed`.
+    ///
+    /// All `λ_i` and the first `n − 2` of the `λ̃_i` are small random integers;
+    /// the last two `λ̃` are solved from `Σ_i λ_i λ̃_i = 0`. Attempts where some
+    /// `⟨ij⟩` or `[ij]` vanishes are drawn again, so every `s_ij` is nonzero
+    /// too; three legs always have one kind of bracket vanishing. Returns
+    /// `None` if no such point turned up.
+    pub fn sample(n: u8, seed: u64) -> Option<Self> {
+        const ATTEMPTS: usize = 64;
+        if n < 4 {
+            return None;
+        }
+        let n = n as usize;
+        let mut rng = SplitMix64::new(seed);
+        for _ in 0..ATTEMPTS {
+            let lambda: Vec<[BigRational; 2]> =
+                (0..n).map(|_| [small(&mut rng), small(&mut rng)]).collect();
+            let mut lambda_tilde: Vec<[BigRational; 2]> = (0..n - 2)
+                .map(|_| [small(&mut rng), small(&mut rng)])
+                .collect();
+            let (a, b) = (&lambda[n - 2], &lambda[n - 1]);
+            let ab = det(a, b);
+            if ab.is_zero() {
+                continue;
+            }
+            // Columns of R = −Σ_{i<n−1} λ_i λ̃_iᵀ, split as λ_{n−1} x + λ_n y.
+            let column = |k: usize| -> [BigRational; 2] {
+                let mut r = [BigRational::zero(), BigRational::zero()];
+                for (l, t) in lambda.iter().zip(&lambda_tilde) {
+                    r[0] -= &l[0] * &t[k];
+                    r[1] -= &l[1] * &t[k];
+                }
+                r
+            };
+            let r = [column(0), column(1)];
+            let x = [det(&r[0], b) / &ab, det(&r[1], b) / &ab];
+            let y = [det(a, &r[0]) / &ab, det(a, &r[1]) / &ab];
+            lambda_tilde.push(x);
+            lambda_tilde.push(y);
+            let point = Self {
+                lambda,
+                lambda_tilde,
+            };
+            let degenerate = (1..=n as u8).any(|i| {
+                (i + 1..=n as u8).any(|j| {
+                    let (i, j) = (LegIndex(i), LegIndex(j));
+                    point.angle(i, j).is_zero() || point.square(i, j).is_zero()
+                })
+            });
+            if !degenerate {
+                return Some(point);
+            }
+        }
+        None
+    }
+
+    pub fn n_legs(&self) -> usize {
+        self.lambda.len()
+    }
+
+    /// `⟨ij⟩`.
+    pub fn angle(&self, i: LegIndex, j: LegIndex) -> BigRational {
+        det(
+            &self.lambda[i.0 as usize - 1],
+            &self.lambda[j.0 as usize - 1],
+        )
+    }
+
+    /// `[ij]`.
+    pub fn square(&self, i: LegIndex, j: LegIndex) -> BigRational {
+        det(
+            &self.lambda_tilde[j.0 as usize - 1],
+            &self.lambda_tilde[i.0 as usize - 1],
+        )
+    }
+
+    /// `p_i·p_j = ½ ⟨ij⟩[ji]`.
+    pub fn dot(&self, i: LegIndex, j: LegIndex) -> BigRational {
+        self.angle(i, j) * self.square(j, i) / int(2)
+    }
+
+    /// The 4-vector `p_i^μ`; see [`vector`].
+    pub fn momentum(&self, i: LegIndex) -> FourVector {
+        let k = i.0 as usize - 1;
+        vector(&self.lambda[k], &self.lambda_tilde[k])
+    }
+
+    /// All momenta, `p_i` at index `i − 1`.
+    pub fn momenta(&self) -> Vec<FourVector> {
+        (1..=self.n_legs() as u8)
+            .map(|i| self.momentum(LegIndex(i)))
+            .collect()
+    }
+
+    /// The momenta as a [`KinematicPoint`] over the Gaussian rationals.
+    ///
+    /// Polarizations are left zero; see [`helicity_point`](Self::helicity_point).
+    pub fn kinematic_point(&self) -> KinematicPoint<Complex<BigRational>> {
+        let momenta: Vec<Vec<_>> = self.momenta().into_iter().map(Vec::from).collect();
+        KinematicPoint {
+            polarizations: vec![vec![Complex::zero(); 4]; momenta.len()],
+            momenta,
+            loop_momenta: Vec::new(),
+        }
+    }
+
+    /// Polarization of helicity `h` for leg `i` with reference leg `q`.
+    ///
+    /// `e_+ = λ_q λ̃_i / ⟨qi⟩` and `e_− = λ_i λ̃_q / [iq]`, transverse to `p_i`
+    /// and `p_q` with `e_+·e_− = −1/2`. These are the polarizations of
+    /// [`Kinematics::polarization`](crate::numeric::Kinematics::polarization)
+    /// divided by `√2`, which keeps them exact; an amplitude linear in all `n`
+    /// polarizations comes out `2^{−n/2}` times its usual value. Returns `None`
+    /// when the bracket vanishes.
+    pub fn polarization(&self, i: LegIndex, h: Helicity, q: LegIndex) -> Option<FourVector> {
+        let (i, q) = (i.0 as usize - 1, q.0 as usize - 1);
+        let (l, t, norm) = match h {
+            Helicity::Plus => (
+                &self.lambda[q],
+                &self.lambda_tilde[i],
+                det(&self.lambda[q], &self.lambda[i]),
+            ),
+            Helicity::Minus => (
+                &self.lambda[i],
+                &self.lambda_tilde[q],
+                det(&self.lambda_tilde[q], &self.lambda_tilde[i]),
+            ),
+        };
+        if norm.is_zero() {
+            return None;
+        }
+        Some(vector(l, t).map(|c| c / norm.clone()))
+    }
+
+    /// [`kinematic_point`](Self::kinematic_point) with the polarization of
+    /// helicity `helicities[i − 1]` on each leg `i`, with the next leg as
+    /// reference, ready for [`KinematicPoint::evaluate`].
+    ///
+    /// Returns `None` unless there is one helicity per leg and every reference
+    /// bracket is nonzero.
+    pub fn helicity_point(
+        &self,
+        helicities: &[Helicity],
+    ) -> Option<KinematicPoint<Complex<BigRational>>> {
+        let n = self.n_legs();
+        if helicities.len() != n {
+            return None;
+        }
+        let mut x = self.kinematic_point();
+        for (i, &h) in helicities.iter().enumerate() {
+            let reference = LegIndex(((i + 1) % n) as u8 + 1);
+            x.polarizations[i] = self
+                .polarization(LegIndex(i as u8 + 1), h, reference)?
+                .to_vec();
+        }
+        Some(x)
+    }
+
+    /// The `[i, j⟩` shift `λ̃_i → λ̃_i + z λ̃_j`, `λ_j → λ_j − z λ_i` of
+    /// [`kinematic_point`](Self::kinematic_point), with `q = λ_i λ̃_j`.
+    pub fn bcfw_shift(&self, i: LegIndex, j: LegIndex) -> BcfwShift<Complex<BigRational>> {
+        let q = vector(
+            &self.lambda[i.0 as usize - 1],
+            &self.lambda_tilde[j.0 as usize - 1],
+        );
+        BcfwShift {
+            i,
+            j,
+            q: q.to_vec(),
+        }
+    }
+
+    /// Value of a bracket or a momentum dot product, or `None` for other factors.
+    pub fn value(&self, f: &ScalarFactor) -> Option<BigRational> {
+        match f.kind {
+            ScalarKind::Angle => Some(self.angle(f.a, f.b)),
+            ScalarKind::Square => Some(self.square(f.a, f.b)),
+            ScalarKind::PP => Some(self.dot(f.a, f.b)),
+            _ => None,
+        }
+    }
+
+    /// Value of a structure built from brackets and `p_i·p_j`, or `None` if it
+    /// involves polarizations, loop momenta, ε, bilinears or field strengths.
+    pub fn evaluate(&self, t: &TensorStructure) -> Option<BigRational> {
+        if t.epsilon.is_some() || !t.bilinears.is_empty() || !t.field_strengths.is_empty() {
+            return None;
+        }
+        let mut v = int(1);
+        for f in &t.factors {
+            v *= self.value(f)?;
+        }
+        Some(v)
+    }
+}
+
+/// The 4-vector of `λ λ̃`, read off from `p^{αα̇} = p_μ σ^μ` with
+/// `p^{11} = p⁰ + p³`, `p^{12} = p¹ − i p²`, `p^{21} = p¹ + i p²` and
+/// `p^{22} = p⁰ − p³`.
+fn vector(l: &[BigRational; 2], t: &[BigRational; 2]) -> FourVector {
+    let m = |a: usize, b: usize| &l[a] * &t[b];
+    let half = |x: BigRational| x / int(2);
+    let real = |x: BigRational| Complex::new(x, BigRational::zero());
+    [
+        real(half(m(0, 0) + m(1, 1))),
+        real(half(m(0, 1) + m(1, 0))),
+        Complex::new(BigRational::zero(), half(m(0, 1) - m(1, 0))),
+        real(half(m(0, 0) - m(1, 1))),
+    ]
+}
+
+/// `a¹ b² − a² b¹`.
+fn det(a: &[BigRational; 2], b: &[BigRational; 2]) -> BigRational {
+    &a[0] * &b[1] - &a[1] * &b[0]
+}
+
+fn small(rng: &mut SplitMix64) -> BigRational {
+    BigRational::from_random_bits(rng.next_u64())
+}
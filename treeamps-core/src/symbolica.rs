@@ -0,0 +1,150 @@
+//! Structures and expressions as Symbolica atoms.
+//!
+//! Vectors are the functions `p(i)`, `e(i)` and `l(a)` and a dot product is
+//! `dot(x, y)`, declared symmetric so Symbolica orders its arguments itself.
+//! The other factors follow the Wolfram export: `spa(i, j)` and `spb(i, j)`
+//! (antisymmetric) for the spinor brackets, `eps(...)` (antisymmetric) for ε,
+//! `bilinear(i, v, j)` for `ū_i γ·v u_j`, `trF(i, j, ...)` (cyclic) for
+//! field-strength traces and `chF(p(a), i, ..., p(b))` for chains. Every
+//! symbol lives in the `treeamps` namespace, e.g. `symbol!("treeamps::dot")`.
+
+use ::symbolica::{
+    atom::{Atom, FunctionBuilder, Symbol},
+    coefficient::Coefficient,
+    domains::integer::Integer,
+    symbol,
+};
+use num_rational::BigRational;
+
+use crate::{
+    bilinear::BilinearFactor,
+    dot_product::ScalarFactor,
+    epsilon::EpsilonFactor,
+    expression::TensorExpression,
+    field_strength::FieldStrengthFactor,
+    tensor_structure::TensorStructure,
+    types::{LegIndex, ScalarKind, Slot},
+};
+
+/// The function symbols, declared with their symmetries.
+struct Symbols {
+    p: Symbol,
+    e: Symbol,
+    l: Symbol,
+    dot: Symbol,
+    eps: Symbol,
+    spa: Symbol,
+    spb: Symbol,
+    bilinear: Symbol,
+    tr_f: Symbol,
+    ch_f: Symbol,
+}
+
+impl Symbols {
+    fn get() -> Self {
+        Self {
+            p: symbol!("treeamps::p"),
+            e: symbol!("treeamps::e"),
+            l: symbol!("treeamps::l"),
+            dot: symbol!("treeamps::dot"; Symmetric),
+            eps: symbol!("treeamps::eps"; Antisymmetric),
+            spa: symbol!("treeamps::spa"; Antisymmetric),
+            spb: symbol!("treeamps::spb"; Antisymmetric),
+            bilinear: symbol!("treeamps::bilinear"),
+            tr_f: symbol!("treeamps::trF"; Cyclesymmetric),
+            ch_f: symbol!("treeamps::chF"),
+        }
+    }
+
+    fn call(&self, f: Symbol, args: impl IntoIterator<Item = Atom>) -> Atom {
+        FunctionBuilder::new(f).add_args(args).finish()
+    }
+
+    fn leg(l: LegIndex) -> Atom {
+        Atom::num(l.0 as i64)
+    }
+
+    fn slot(&self, s: Slot) -> Atom {
+        match s {
+            Slot::P(l) => self.call(self.p, [Self::leg(l)]),
+            Slot::E(l) => self.call(self.e, [Self::leg(l)]),
+        }
+    }
+
+    fn factor(&self, f: &ScalarFactor) -> Atom {
+        let l = |l: LegIndex| self.call(self.l, [Self::leg(l)]);
+        let (x, y) = match f.kind {
+            ScalarKind::Angle => return self.call(self.spa, [Self::leg(f.a), Self::leg(f.b)]),
+            ScalarKind::Square => return self.call(self.spb, [Self::leg(f.a), Self::leg(f.b)]),
+            ScalarKind::PP => (self.slot(Slot::P(f.a)), self.slot(Slot::P(f.b))),
+            ScalarKind::PE => (self.slot(Slot::P(f.a)), self.slot(Slot::E(f.b))),
+            ScalarKind::EE => (self.slot(Slot::E(f.a)), self.slot(Slot::E(f.b))),
+            ScalarKind::LP => (l(f.a), self.slot(Slot::P(f.b))),
+            ScalarKind::LE => (l(f.a), self.slot(Slot::E(f.b))),
+            ScalarKind::LL => (l(f.a), l(f.b)),
+        };
+        self.call(self.dot, [x, y])
+    }
+
+    fn epsilon(&self, e: &EpsilonFactor) -> Atom {
+        self.call(self.eps, e.slots.iter().map(|&s| self.slot(s)))
+    }
+
+    fn bilinear(&self, b: &BilinearFactor) -> Atom {
+        self.call(
+            self.bilinear,
+            [Self::leg(b.bar), self.slot(b.gamma), Self::leg(b.u)],
+        )
+    }
+
+    fn field_strength(&self, f: &FieldStrengthFactor) -> Atom {
+        match f {
+            FieldStrengthFactor::Trace(ls) => {
+                self.call(self.tr_f, ls.iter().map(|&l| Self::leg(l)))
+            }
+            FieldStrengthFactor::Chain { left, legs, right } => self.call(
+                self.ch_f,
+                std::iter::once(self.slot(Slot::P(*left)))
+                    .chain(legs.iter().map(|&l| Self::leg(l)))
+                    .chain(std::iter::once(self.slot(Slot::P(*right)))),
+            ),
+        }
+    }
+
+    fn structure(&self, t: &TensorStructure) -> Atom {
+        t.bilinears
+            .iter()
+            .map(|b| self.bilinear(b))
+            .chain(t.epsilon.iter().map(|e| self.epsilon(e)))
+            .chain(t.field_strengths.iter().map(|f| self.field_strength(f)))
+            .chain(t.factors.iter().map(|f| self.factor(f)))
+            .fold(Atom::num(1), |product, f| product * f)
+    }
+}
+
+fn rational(c: &BigRational) -> Atom {
+    let integer = |n: &num_bigint::BigInt| -> Integer {
+        n.to_string()
+            .parse()
+            .unwrap_or_else(|_| unreachable!("a BigInt prints in decimal"))
+    };
+    Atom::num(Coefficient::from((integer(c.numer()), integer(c.denom()))))
+}
+
+impl TensorStructure {
+    /// Product of the factors, e.g. `dot(p(1),e(2))*dot(e(3),e(4))`, or `1`
+    /// for the empty product.
+    pub fn to_symbolica(&self) -> Atom {
+        Symbols::get().structure(self)
+    }
+}
+
+impl TensorExpression<BigRational> {
+    /// Sum of the terms with their exact coefficients; `0` when empty.
+    pub fn to_symbolica(&self) -> Atom {
+        let symbols = Symbols::get();
+        self.terms.iter().fold(Atom::num(0), |sum, (c, t)| {
+            sum + rational(c) * symbols.structure(t)
+        })
+    }
+}
@@ -0,0 +1,68 @@
+use crate::{
+    expression::TensorExpression, rational::int, tensor_structure::TensorStructure, types::LegIndex,
+};
+
+/// Bose-symmetric projection `(1/k!) Σ_σ σ(t)` over all exchanges of `legs`.
+///
+/// Signs from reordering spinor brackets or ε slots under each relabeling are
+/// kept; repeated legs are ignored.
+pub fn symmetrize(t: &TensorStructure, legs: &[LegIndex]) -> TensorExpression {
+    project(t, legs, false)
+}
+
+/// Antisymmetric projection `(1/k!) Σ_σ sgn(σ) σ(t)` over all exchanges of `legs`.
+pub fn antisymmetrize(t: &TensorStructure, legs: &[LegIndex]) -> TensorExpression {
+    project(t, legs, true)
+}
+
+fn project(t: &TensorStructure, legs: &[LegIndex], alternating: bool) -> TensorExpression {
+    let mut legs = legs.to_vec();
+    legs.sort();
+    legs.dedup();
+
+    let mut out = TensorExpression::new();
+    let mut count = 0i64;
+    for_each_permutation(legs.len(), |perm, parity| {
+        let sigma = |l: LegIndex| match legs.iter().position(|&m| m == l) {
+            Some(k) => legs[perm[k]],
+            None => l,
+        };
+        let (image, sign) = t.relabel(&sigma);
+        let sign = if alternating { sign * parity } else { sign };
+        out.push(int(sign as i64), image);
+        count += 1;
+    });
+    for (c, _) in &mut out.terms {
+        *c /= int(count);
+    }
+    out.collect();
+    out
+}
+
+/// Visit every permutation of `0..len`, together with its parity `±1`.
+///
+/// Uses Heap's algorithm, so consecutive permutations differ by one
+/// transposition; the identity comes first.
+pub(crate) fn for_each_permutation(len: usize, mut visit: impl FnMut(&[usize], i8)) {
+    let mut perm: Vec<usize> = (0..len).collect();
+    let mut parity = 1;
+    visit(&perm, parity);
+    let mut c = vec![0usize; len];
+    let mut i = 1;
+    while i < len {
+        if c[i] < i {
+            if i % 2 == 0 {
+                perm.swap(0, i);
+            } else {
+                perm.swap(c[i], i);
+            }
+            parity = -parity;
+            visit(&perm, parity);
+            c[i] += 1;
+            i = 1;
+        } else {
+            c[i] = 0;
+            i += 1;
+        }
+    }
+}
@@ -1,40 +1,323 @@
-use crate::dot_product::ScalarFactor;
+use std::{fmt, str::FromStr};
 
+use smallvec::SmallVec;
+
+use crate::{
+    bilinear::BilinearFactor,
+    dot_product::{PackedFactor, ScalarFactor},
+    epsilon::EpsilonFactor,
+    field_strength::FieldStrengthFactor,
+    permutation::Relabeling,
+    types::{LegIndex, ScalarKind, Slot},
+};
+
+/// Dot products a [`TensorStructure`] holds without allocating; most
+/// generated structures have no more.
+pub const INLINE_FACTORS: usize = 8;
+
+/// A product of dot products, with optional ε, bilinear and field-strength factors.
+///
+/// The dot products are [`ScalarFactor`]s by default; [`PackedStructure`]
+/// stores them as two-byte [`PackedFactor`]s instead, for holding very many
+/// structures at once.
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
-pub struct TensorStructure {
-    pub factors: Vec<ScalarFactor>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TensorStructure<F = ScalarFactor> {
+    /// The dot products, stored inline up to [`INLINE_FACTORS`] of them.
+    pub factors: SmallVec<[F; INLINE_FACTORS]>,
     pub ee_contractions: u32,
+    /// Levi-Civita factor of a parity-odd structure; `None` for parity-even ones.
+    pub epsilon: Option<EpsilonFactor>,
+    /// Fermion bilinears, one per pair of external fermions.
+    pub bilinears: Vec<BilinearFactor>,
+    /// Traces and chains of field strengths in the gauge-invariant operator basis.
+    pub field_strengths: Vec<FieldStrengthFactor>,
 }
 
 impl TensorStructure {
     pub fn new() -> Self {
-        Self { factors: Vec::new(), ee_contractions: 0 }
+        Self {
+            factors: SmallVec::new(),
+            ee_contractions: 0,
+            epsilon: None,
+            bilinears: Vec::new(),
+            field_strengths: Vec::new(),
+        }
     }
 
-    pub fn canonicalize(&mut self) {
+    /// Bring the structure to canonical form and return the sign picked up.
+    ///
+    /// The sign is `±1` from reordering the ε slots and field-strength chains,
+    /// or `0` if the structure vanishes.
+    pub fn canonicalize(&mut self) -> i8 {
         self.factors.sort();
+        self.bilinears.sort();
+        let mut sign = 1;
+        for f in &mut self.field_strengths {
+            sign *= f.canonicalize();
+        }
+        self.field_strengths.sort();
+        match &mut self.epsilon {
+            Some(e) => sign * e.canonicalize(),
+            None => sign,
+        }
+    }
+
+    /// Apply the leg relabeling `sigma` and return the canonical image with its sign.
+    ///
+    /// The image is compared factor by factor, without re-imposing momentum
+    /// conservation, so it may mention the eliminated momentum. `sigma` is a
+    /// [`Permutation`](crate::permutation::Permutation) or any map of legs.
+    pub fn relabel(&self, sigma: &impl Relabeling) -> (Self, i8) {
+        let sigma = &|l: LegIndex| sigma.image(l);
+        let mut sign = 1;
+        let mut out = Self {
+            factors: SmallVec::with_capacity(self.factors.len()),
+            ee_contractions: self.ee_contractions,
+            epsilon: None,
+            bilinears: self.bilinears.iter().map(|b| b.relabel(sigma)).collect(),
+            field_strengths: Vec::with_capacity(self.field_strengths.len()),
+        };
+        for f in &self.field_strengths {
+            let (g, s) = f.relabel(sigma);
+            out.field_strengths.push(g);
+            sign *= s;
+        }
+        for f in &self.factors {
+            let (g, s) = f.relabel(sigma);
+            out.factors.push(g);
+            sign *= s;
+        }
+        if let Some(e) = &self.epsilon {
+            let (e, s) = e.relabel(sigma);
+            out.epsilon = Some(e);
+            sign *= s;
+        }
+        sign *= out.canonicalize();
+        (out, sign)
+    }
+
+    /// Canonicalize up to cyclic shifts `i → i + r (mod n)` of the legs.
+    ///
+    /// Picks the smallest image over all `n` rotations and returns the sign
+    /// relating it to the original structure (`0` if it vanishes). Rotations
+    /// do not re-impose momentum conservation, so `self` should come from a
+    /// basis without an eliminated momentum.
+    pub fn canonicalize_cyclic(&mut self, n_legs: u8) -> i8 {
+        let base_sign = self.canonicalize();
+        let base = self.clone();
+        let mut best_sign = base_sign;
+        for r in 1..n_legs as u16 {
+            let rotate = |l: LegIndex| LegIndex(((l.0 as u16 - 1 + r) % n_legs as u16 + 1) as u8);
+            let (image, sign) = base.relabel(&rotate);
+            if image < *self {
+                *self = image;
+                best_sign = base_sign * sign;
+            }
+        }
+        best_sign
     }
 
-    pub fn to_string(&self) -> String {
-        if self.factors.is_empty() {
+    /// Number of factors, counting the ε factor, each bilinear and each
+    /// field-strength chain as one.
+    pub fn degree(&self) -> u32 {
+        self.factors.len() as u32
+            + self.epsilon.is_some() as u32
+            + self.bilinears.len() as u32
+            + self.field_strengths.len() as u32
+    }
+
+    /// Total number of momenta, the EFT ordering parameter.
+    ///
+    /// Dot products count as in [`ScalarFactor::momentum_power`]; ε slots and
+    /// bilinear `γ·p` contribute one per momentum, and each field strength `F_i`
+    /// one plus the two end momenta of a chain.
+    pub fn momentum_power(&self) -> u32 {
+        let slot = |s: &Slot| matches!(s, Slot::P(_)) as u32;
+        self.factors.iter().map(|f| f.momentum_power()).sum::<u32>()
+            + self
+                .epsilon
+                .as_ref()
+                .map_or(0, |e| e.slots.iter().map(slot).sum())
+            + self.bilinears.iter().map(|b| slot(&b.gamma)).sum::<u32>()
+            + self
+                .field_strengths
+                .iter()
+                .map(|f| match f {
+                    FieldStrengthFactor::Trace(legs) => legs.len() as u32,
+                    FieldStrengthFactor::Chain { legs, .. } => legs.len() as u32 + 2,
+                })
+                .sum::<u32>()
+    }
+
+    /// Mass dimension: the momentum power plus one per bilinear for its two
+    /// external spinors. Polarization vectors are dimensionless.
+    pub fn mass_dimension(&self) -> u32 {
+        self.momentum_power() + self.bilinears.len() as u32
+    }
+
+    pub fn is_parity_odd(&self) -> bool {
+        self.epsilon.is_some()
+    }
+
+    /// Little-group weight `#[i] - #⟨i⟩` of each leg, indexed by `leg - 1`.
+    ///
+    /// Only spinor brackets carry weight, so the list runs up to the highest leg
+    /// in a bracket; see [`SpinorConfig::admits`] to match a helicity configuration.
+    ///
+    /// [`SpinorConfig::admits`]: crate::spinor::SpinorConfig::admits
+    pub fn weights(&self) -> Vec<i32> {
+        let mut w = Vec::new();
+        for f in &self.factors {
+            let step = match f.kind {
+                ScalarKind::Angle => -1,
+                ScalarKind::Square => 1,
+                _ => continue,
+            };
+            let top = f.a.max(f.b).0 as usize;
+            if w.len() < top {
+                w.resize(top, 0);
+            }
+            w[f.a.0 as usize - 1] += step;
+            w[f.b.0 as usize - 1] += step;
+        }
+        w
+    }
+
+    /// LaTeX form, the factors juxtaposed in the order of `Display`:
+    /// `(p_1\cdot e_2)(e_3\cdot e_4)`, or `1` for the empty product.
+    pub fn to_latex(&self) -> String {
+        if self.degree() == 0 {
             return "1".to_string();
         }
-        self.factors
+        self.bilinears
             .iter()
-            .map(|f| f.to_string())
+            .map(|b| b.to_latex())
+            .chain(self.epsilon.iter().map(|e| e.to_latex()))
+            .chain(self.field_strengths.iter().map(|x| x.to_latex()))
+            .chain(self.factors.iter().map(|x| x.to_latex()))
+            .collect()
+    }
+}
+
+impl fmt::Display for TensorStructure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.degree() == 0 {
+            return write!(f, "1");
+        }
+        let joined = self
+            .bilinears
+            .iter()
+            .map(|b| b.to_string())
+            .chain(self.epsilon.iter().map(|e| e.to_string()))
+            .chain(self.field_strengths.iter().map(|x| x.to_string()))
+            .chain(self.factors.iter().map(|x| x.to_string()))
             .collect::<Vec<_>>()
-            .join(" · ")
+            .join(" · ");
+        write!(f, "{}", joined)
+    }
+}
+
+/// Parses the `Display` form, e.g. `(p1·e2) · (e3·e4)` or `1`, keeping the
+/// factors in the order written.
+impl FromStr for TensorStructure {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut t = Self::new();
+        if s.trim() == "1" {
+            return Ok(t);
+        }
+        for part in s.split(" · ").map(str::trim) {
+            if part.starts_with("ε(") || part.starts_with("eps(") {
+                if t.epsilon.is_some() {
+                    return Err(format!("more than one ε factor in {:?}", s));
+                }
+                t.epsilon = Some(part.parse()?);
+            } else if part.starts_with("(ū") {
+                t.bilinears.push(part.parse()?);
+            } else if part.contains('F') {
+                t.field_strengths.push(part.parse()?);
+            } else {
+                t.factors.push(part.parse()?);
+            }
+        }
+        t.ee_contractions = t
+            .factors
+            .iter()
+            .filter(|f| f.kind == ScalarKind::EE)
+            .count() as u32;
+        Ok(t)
+    }
+}
+
+impl<F> Default for TensorStructure<F> {
+    fn default() -> Self {
+        Self {
+            factors: SmallVec::new(),
+            ee_contractions: 0,
+            epsilon: None,
+            bilinears: Vec::new(),
+            field_strengths: Vec::new(),
+        }
     }
 }
 
-impl Ord for TensorStructure {
+impl<F: Ord> Ord for TensorStructure<F> {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.factors.cmp(&other.factors)
+        // Parity-even structures (no ε) sort before parity-odd ones.
+        self.epsilon
+            .cmp(&other.epsilon)
+            .then_with(|| self.bilinears.cmp(&other.bilinears))
+            .then_with(|| self.field_strengths.cmp(&other.field_strengths))
+            .then_with(|| self.factors.cmp(&other.factors))
     }
 }
 
-impl PartialOrd for TensorStructure {
+impl<F: Ord> PartialOrd for TensorStructure<F> {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         Some(self.cmp(other))
     }
 }
+
+/// A [`TensorStructure`] with bit-packed dot products.
+///
+/// Packing preserves order and equality, but each factor takes two bytes and
+/// compares and hashes as one integer.
+pub type PackedStructure = TensorStructure<PackedFactor>;
+
+impl TensorStructure {
+    /// The same structure with packed factors, or `None` if a leg or loop
+    /// index exceeds [`PackedFactor::MAX_INDEX`].
+    pub fn pack(&self) -> Option<PackedStructure> {
+        Some(TensorStructure {
+            factors: self
+                .factors
+                .iter()
+                .map(PackedFactor::new)
+                .collect::<Option<_>>()?,
+            ee_contractions: self.ee_contractions,
+            epsilon: self.epsilon.clone(),
+            bilinears: self.bilinears.clone(),
+            field_strengths: self.field_strengths.clone(),
+        })
+    }
+}
+
+impl PackedStructure {
+    pub fn unpack(&self) -> TensorStructure {
+        TensorStructure {
+            factors: self.factors.iter().map(PackedFactor::unpack).collect(),
+            ee_contractions: self.ee_contractions,
+            epsilon: self.epsilon.clone(),
+            bilinears: self.bilinears.clone(),
+            field_strengths: self.field_strengths.clone(),
+        }
+    }
+}
+
+impl fmt::Display for PackedStructure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.unpack())
+    }
+}
@@ -0,0 +1,323 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use crate::{
+    cubic_graph::{CubicGraph, cubic_graphs},
+    mandelstam::Invariant,
+    permutation::Relabeling,
+    types::LegIndex,
+};
+
+/// A tree graph with `n` labeled external legs and internal vertices of
+/// valence three or more.
+///
+/// Stored by its propagators: each internal edge is the sorted set of legs on
+/// its side away from leg `n`, and the edges are sorted, so equal graphs
+/// compare equal. Unlike [`CubicGraph`] the vertices are unoriented, and they
+/// may be quartic or of higher valence.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct TreeGraph {
+    pub n_legs: u8,
+    pub propagators: Vec<Vec<LegIndex>>,
+}
+
+/// One end of an edge of a [`TreeGraph`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum GraphNode {
+    Leg(LegIndex),
+    /// Vertex `0` carries leg `n`, vertex `k ≥ 1` sits below propagator `k − 1`.
+    Vertex(usize),
+}
+
+impl TreeGraph {
+    /// The graph with the given internal edges, each given by the legs on
+    /// either of its sides.
+    ///
+    /// Fails unless every edge splits off at least two legs on each side and
+    /// the edges are distinct and pairwise compatible, i.e. nested or
+    /// disjoint once oriented away from leg `n`.
+    pub fn new(n_legs: u8, propagators: Vec<Vec<LegIndex>>) -> Result<Self, String> {
+        let mut sides = BTreeSet::new();
+        for legs in propagators {
+            let mut legs: BTreeSet<LegIndex> = legs.into_iter().collect();
+            if legs.iter().any(|l| l.0 == 0 || l.0 > n_legs) {
+                return Err(format!(
+                    "propagator {:?} has legs outside 1..={}",
+                    numbers(&legs),
+                    n_legs
+                ));
+            }
+            if legs.contains(&LegIndex(n_legs)) {
+                legs = (1..=n_legs)
+                    .map(LegIndex)
+                    .filter(|l| !legs.contains(l))
+                    .collect();
+            }
+            if legs.len() < 2 || legs.len() + 2 > n_legs as usize {
+                return Err(format!("propagator {:?} is not internal", numbers(&legs)));
+            }
+            if !sides.insert(legs.into_iter().collect::<Vec<_>>()) {
+                return Err("repeated propagator".into());
+            }
+        }
+        let propagators: Vec<Vec<LegIndex>> = sides.into_iter().collect();
+        for (k, a) in propagators.iter().enumerate() {
+            for b in &propagators[k + 1..] {
+                let shared = a.iter().filter(|l| b.contains(l)).count();
+                if shared != 0 && shared != a.len() && shared != b.len() {
+                    return Err(format!(
+                        "propagators {:?} and {:?} cross",
+                        numbers(a),
+                        numbers(b)
+                    ));
+                }
+            }
+        }
+        Ok(Self {
+            n_legs,
+            propagators,
+        })
+    }
+
+    /// The graph with the single vertex joining all legs.
+    pub fn star(n_legs: u8) -> Self {
+        Self {
+            n_legs,
+            propagators: Vec::new(),
+        }
+    }
+
+    /// Whether every vertex is cubic, i.e. there are `n − 3` propagators.
+    pub fn is_cubic(&self) -> bool {
+        self.propagators.len() + 3 == self.n_legs as usize
+    }
+
+    /// Whether the graph can be drawn with the legs in the cyclic order
+    /// `1, …, n`, i.e. every propagator is a run of consecutive legs.
+    pub fn is_planar(&self) -> bool {
+        self.propagators
+            .iter()
+            .all(|p| p.windows(2).all(|w| w[1].0 == w[0].0 + 1))
+    }
+
+    /// Neighbours of every vertex, children by smallest leg and then the
+    /// parent on the way to leg `n`.
+    pub fn vertices(&self) -> Vec<Vec<GraphNode>> {
+        let sets = self.vertex_legs();
+        let parent = |v: usize| -> usize {
+            (0..sets.len())
+                .filter(|&u| {
+                    sets[u].len() > sets[v].len() && sets[v].iter().all(|l| sets[u].contains(l))
+                })
+                .min_by_key(|&u| sets[u].len())
+                .expect("the root vertex contains every leg")
+        };
+        let mut children: Vec<Vec<(LegIndex, GraphNode)>> = vec![Vec::new(); sets.len()];
+        for v in 1..sets.len() {
+            children[parent(v)].push((sets[v][0], GraphNode::Vertex(v)));
+        }
+        for l in 1..self.n_legs {
+            let l = LegIndex(l);
+            let v = (0..sets.len())
+                .filter(|&u| sets[u].contains(&l))
+                .min_by_key(|&u| sets[u].len())
+                .expect("the root vertex contains every leg");
+            children[v].push((l, GraphNode::Leg(l)));
+        }
+        children
+            .into_iter()
+            .enumerate()
+            .map(|(v, mut c)| {
+                c.sort();
+                let mut out: Vec<GraphNode> = c.into_iter().map(|(_, x)| x).collect();
+                out.push(if v == 0 {
+                    GraphNode::Leg(LegIndex(self.n_legs))
+                } else {
+                    GraphNode::Vertex(parent(v))
+                });
+                out
+            })
+            .collect()
+    }
+
+    /// Number of neighbours of each vertex.
+    pub fn valences(&self) -> Vec<usize> {
+        self.vertices().iter().map(Vec::len).collect()
+    }
+
+    /// All edges, each once and oriented towards leg `n`: external legs by
+    /// leg, then the propagators in order.
+    pub fn edges(&self) -> Vec<(GraphNode, GraphNode)> {
+        let vertices = self.vertices();
+        let mut legs = Vec::new();
+        let mut internal = Vec::new();
+        for (v, nb) in vertices.iter().enumerate() {
+            for &x in nb {
+                if let GraphNode::Leg(_) = x {
+                    legs.push((x, GraphNode::Vertex(v)));
+                }
+            }
+            if v > 0 {
+                internal.push((GraphNode::Vertex(v), nb[nb.len() - 1]));
+            }
+        }
+        legs.sort();
+        legs.extend(internal);
+        legs
+    }
+
+    /// The pole `s_I` of each propagator.
+    pub fn poles(&self) -> Vec<Invariant> {
+        self.propagators
+            .iter()
+            .map(|p| Invariant::new(p.iter().copied()).expect("propagators have two legs"))
+            .collect()
+    }
+
+    /// The graph with propagator `k` contracted, merging its two vertices.
+    pub fn contract(&self, k: usize) -> Self {
+        let mut propagators = self.propagators.clone();
+        propagators.remove(k);
+        Self {
+            n_legs: self.n_legs,
+            propagators,
+        }
+    }
+
+    /// The image under the leg relabeling `sigma`, which must permute `1..=n`.
+    pub fn relabel(&self, sigma: &impl Relabeling) -> Self {
+        let propagators = self
+            .propagators
+            .iter()
+            .map(|p| p.iter().map(|&l| sigma.image(l)).collect())
+            .collect();
+        Self::new(self.n_legs, propagators).expect("relabeling keeps propagators compatible")
+    }
+
+    /// Whether `other` is the same graph up to leg labels.
+    pub fn is_isomorphic(&self, other: &Self) -> bool {
+        self.n_legs == other.n_legs && self.shape() == other.shape()
+    }
+
+    /// Legs below each vertex: all but leg `n` for vertex `0`, the
+    /// propagator's legs for the others.
+    fn vertex_legs(&self) -> Vec<Vec<LegIndex>> {
+        let all: Vec<LegIndex> = (1..self.n_legs).map(LegIndex).collect();
+        std::iter::once(all)
+            .chain(self.propagators.iter().cloned())
+            .collect()
+    }
+
+    /// A label-free code of the graph, the smallest nested-bracket encoding
+    /// over all choices of internal root vertex.
+    fn shape(&self) -> String {
+        fn encode(vertices: &[Vec<GraphNode>], v: usize, from: Option<usize>) -> String {
+            let mut parts: Vec<String> = vertices[v]
+                .iter()
+                .filter_map(|&x| match x {
+                    GraphNode::Leg(_) => Some("l".to_string()),
+                    GraphNode::Vertex(u) if Some(u) != from => Some(encode(vertices, u, Some(v))),
+                    GraphNode::Vertex(_) => None,
+                })
+                .collect();
+            parts.sort();
+            format!("({})", parts.concat())
+        }
+        let vertices = self.vertices();
+        (0..vertices.len())
+            .map(|r| encode(&vertices, r, None))
+            .min()
+            .unwrap_or_default()
+    }
+}
+
+impl From<&CubicGraph> for TreeGraph {
+    fn from(g: &CubicGraph) -> Self {
+        let mut propagators = g.propagators();
+        propagators.sort();
+        Self {
+            n_legs: g.n_legs,
+            propagators,
+        }
+    }
+}
+
+impl fmt::Display for TreeGraph {
+    /// Nested brackets hanging off leg `n`, as for [`CubicGraph`]; `[1,2,3]`
+    /// is a quartic vertex with legs 1, 2, 3 and 4.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn write_vertex(
+            f: &mut fmt::Formatter<'_>,
+            vertices: &[Vec<GraphNode>],
+            v: usize,
+        ) -> fmt::Result {
+            let children = &vertices[v][..vertices[v].len() - 1];
+            write!(f, "[")?;
+            for (k, &c) in children.iter().enumerate() {
+                if k > 0 {
+                    write!(f, ",")?;
+                }
+                match c {
+                    GraphNode::Leg(l) => write!(f, "{}", l.0)?,
+                    GraphNode::Vertex(u) => write_vertex(f, vertices, u)?,
+                }
+            }
+            write!(f, "]")
+        }
+        if self.n_legs < 3 {
+            return write!(f, "[]");
+        }
+        write_vertex(f, &self.vertices(), 0)
+    }
+}
+
+fn numbers<'a>(legs: impl IntoIterator<Item = &'a LegIndex>) -> Vec<u8> {
+    legs.into_iter().map(|l| l.0).collect()
+}
+
+/// All tree graphs with legs `1..=n` and vertex valences in
+/// `3..=max_valence`, sorted; `max_valence = 3` gives the `(2n − 5)!!` cubic
+/// graphs and `4` adds quartic vertices.
+///
+/// Built by contracting sets of propagators of the cubic graphs.
+pub fn tree_graphs(n_legs: u8, max_valence: usize) -> Vec<TreeGraph> {
+    if n_legs < 3 || max_valence < 3 {
+        return Vec::new();
+    }
+    let mut out = BTreeSet::new();
+    for g in cubic_graphs(n_legs) {
+        let g = TreeGraph::from(&g);
+        let k = g.propagators.len();
+        for mask in 0u32..1 << k {
+            let propagators = (0..k)
+                .filter(|&i| mask & (1 << i) == 0)
+                .map(|i| g.propagators[i].clone())
+                .collect();
+            let h = TreeGraph {
+                n_legs,
+                propagators,
+            };
+            if h.valences().iter().all(|&v| v <= max_valence) {
+                out.insert(h);
+            }
+        }
+    }
+    out.into_iter().collect()
+}
+
+/// One graph per topology, i.e. per class of [`tree_graphs`] up to leg
+/// labels, with the smallest labeling of its class, and the class size.
+pub fn tree_topologies(n_legs: u8, max_valence: usize) -> Vec<(TreeGraph, usize)> {
+    let mut classes: BTreeMap<String, (TreeGraph, usize)> = BTreeMap::new();
+    for g in tree_graphs(n_legs, max_valence) {
+        classes
+            .entry(g.shape())
+            .and_modify(|(_, count)| *count += 1)
+            .or_insert((g, 1));
+    }
+    let mut out: Vec<(TreeGraph, usize)> = classes.into_values().collect();
+    out.sort();
+    out
+}
@@ -1,24 +1,247 @@
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LegIndex(pub u8); // 1-based external leg index
 
+impl LegIndex {
+    /// The index as a LaTeX subscript: `1`, or `{12}` with several digits.
+    pub(crate) fn latex_subscript(self) -> String {
+        if self.0 < 10 {
+            self.0.to_string()
+        } else {
+            format!("{{{}}}", self.0)
+        }
+    }
+}
+
 /// Kind of scalar factor: momentum-momentum, momentum-polarization, or polarization-polarization.
+///
+/// `Angle` and `Square` are the spinor-helicity brackets `⟨ij⟩` and `[ij]` of
+/// 4D massless kinematics, kept as a separate alphabet. The loop kinds hold
+/// the loop-momentum index `l_a` in the factor's `a` field.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ScalarKind {
     PP,
     PE,
     EE,
+    Angle,
+    Square,
+    /// Loop momentum with an external momentum, `l_a·p_j`.
+    LP,
+    /// Loop momentum with a polarization, `l_a·e_j`.
+    LE,
+    /// Two loop momenta, `l_a·l_b` with `a ≤ b`.
+    LL,
+}
+
+impl ScalarKind {
+    /// Every kind, in declaration (and sort) order.
+    pub const ALL: [ScalarKind; 8] = [
+        ScalarKind::PP,
+        ScalarKind::PE,
+        ScalarKind::EE,
+        ScalarKind::Angle,
+        ScalarKind::Square,
+        ScalarKind::LP,
+        ScalarKind::LE,
+        ScalarKind::LL,
+    ];
 }
 
 /// Transversality / p·e rules.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Transversality {
     None,
     ForbidPiDotEi,
 }
 
 /// How polarizations are allowed to appear per leg.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PolarizationPattern {
     Unrestricted,
+    /// As many polarizations as each leg's spin demands (one per gluon).
     OnePerLeg,
+    /// Exactly `k_i` polarization insertions on leg `i`, indexed by `leg - 1`;
+    /// legs beyond the end get none.
+    ExactlyPerLeg(Vec<u8>),
+}
+
+/// Which `p·e` factor is dropped on top of momentum elimination.
+///
+/// Momentum conservation plus transversality of the eliminated leg `k` give
+/// `Σ_{i≠k} p_i·e_k = 0`, so one of these factors is redundant.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PeExclusion {
+    /// Drop `p_m·e_k`, where `k` is the eliminated leg and `m` the lowest other leg.
+    /// Nothing is dropped unless `k` is transverse, since the relation needs `p_k·e_k = 0`.
+    Conventional,
+    /// Keep every `p·e` factor.
+    None,
+    /// Drop an explicit `p_i·e_j`.
+    Explicit {
+        momentum: LegIndex,
+        polarization: LegIndex,
+    },
+}
+
+/// Mass of an external leg.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Mass {
+    #[default]
+    Massless,
+    Massive,
+}
+
+/// Spin of an external leg, which fixes how many polarization vectors it carries.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Spin {
+    /// No polarization vector.
+    Scalar,
+    /// One polarization vector `e_i` (gluons, photons).
+    #[default]
+    Vector,
+    /// Two copies of `e_i`, i.e. `e_i^{μν} = e_i^μ e_i^ν` (gravitons).
+    Tensor,
+}
+
+impl Spin {
+    /// Number of polarization vectors carried by a leg of this spin.
+    pub fn polarization_count(self) -> u32 {
+        match self {
+            Spin::Scalar => 0,
+            Spin::Vector => 1,
+            Spin::Tensor => 2,
+        }
+    }
+}
+
+/// Helicity of a massless leg in a fixed helicity sector.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Helicity {
+    Plus,
+    Minus,
+}
+
+/// A single Lorentz vector that can fill a slot of a contraction: `p_i` or `e_i`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Slot {
+    P(LegIndex),
+    E(LegIndex),
+}
+
+impl Slot {
+    pub fn leg(self) -> LegIndex {
+        match self {
+            Slot::P(l) | Slot::E(l) => l,
+        }
+    }
+
+    /// Same kind of vector on the relabeled leg `sigma(leg)`.
+    pub fn relabel(self, sigma: &impl Fn(LegIndex) -> LegIndex) -> Self {
+        match self {
+            Slot::P(l) => Slot::P(sigma(l)),
+            Slot::E(l) => Slot::E(sigma(l)),
+        }
+    }
+
+    /// `p_3` or `e_3`.
+    pub fn to_latex(self) -> String {
+        match self {
+            Slot::P(l) => format!("p_{}", l.latex_subscript()),
+            Slot::E(l) => format!("e_{}", l.latex_subscript()),
+        }
+    }
+}
+
+/// Parses a 1-based leg index such as `3`.
+impl std::str::FromStr for LegIndex {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.trim()
+            .parse::<u8>()
+            .ok()
+            .filter(|&l| l > 0)
+            .map(LegIndex)
+            .ok_or_else(|| format!("invalid leg index {:?}", s))
+    }
+}
+
+/// Parses `p3` or `e3`.
+impl std::str::FromStr for Slot {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let leg = |rest: &str| {
+            rest.parse::<u8>()
+                .ok()
+                .filter(|&l| l > 0)
+                .map(LegIndex)
+                .ok_or_else(|| format!("invalid leg index in {:?}", s))
+        };
+        if let Some(rest) = s.strip_prefix('p') {
+            Ok(Slot::P(leg(rest)?))
+        } else if let Some(rest) = s.strip_prefix('e') {
+            Ok(Slot::E(leg(rest)?))
+        } else {
+            Err(format!("expected p<leg> or e<leg>, got {:?}", s))
+        }
+    }
+}
+
+impl std::fmt::Display for Slot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Slot::P(l) => write!(f, "p{}", l.0),
+            Slot::E(l) => write!(f, "e{}", l.0),
+        }
+    }
+}
+
+/// Quantum statistics of an external leg.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Statistics {
+    #[default]
+    Boson,
+    /// Spin-1/2 leg; enters only through a spinor bilinear and carries no `e_i`.
+    Fermion,
+}
+
+/// Which leg relabelings are treated as the same structure.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Canonicalization {
+    /// Legs are distinguishable; every labeling is its own structure.
+    #[default]
+    Labelled,
+    /// Structures related by a cyclic shift of the legs are identified, as in
+    /// color-ordered partial amplitudes. Rotations do not preserve the choice
+    /// of eliminated momentum, so it requires the redundant basis
+    /// (`eliminate_momentum = false`); [`GenConfig::validate`] rejects it
+    /// otherwise.
+    ///
+    /// [`GenConfig::validate`]: crate::GenConfig::validate
+    Cyclic,
+}
+
+/// How structures identified by canonicalization are deduplicated.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Deduplication {
+    /// Through an ordered set, or sorting and dropping adjacent repeats; the
+    /// output is sorted.
+    #[default]
+    Ordered,
+    /// Through an `FxHashSet`, which is faster for large collections. The
+    /// output keeps the order structures were found in, and is sorted at the
+    /// end only if `sorted` is set.
+    Hashed { sorted: bool },
 }
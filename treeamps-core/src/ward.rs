@@ -0,0 +1,172 @@
+use num_rational::BigRational;
+use num_traits::{One, Signed, Zero};
+
+use crate::{
+    bilinear::BilinearFactor,
+    dot_product::ScalarFactor,
+    epsilon::EpsilonFactor,
+    expression::TensorExpression,
+    generator::GenConfig,
+    linalg::SparseMatrix,
+    operator_basis::Echelon,
+    rational::int,
+    reduction::{finish, reduce_expression},
+    tensor_structure::TensorStructure,
+    types::{LegIndex, ScalarKind, Slot},
+};
+
+/// Gauge variation of `t` under `e_i → p_i` for the given leg.
+///
+/// The substitution acts as a derivation: each occurrence of `e_i` is replaced
+/// in turn and the results are summed, which for a vector leg is the plain
+/// substitution and for a tensor leg the linearized `e^μ e^ν → p^μ e^ν + e^μ p^ν`.
+/// Field strengths are invariant and contribute nothing. The result is
+/// canonicalized but not reduced on shell; see [`ward_filter`] for that.
+pub fn ward_variation(t: &TensorStructure, leg: LegIndex) -> TensorExpression {
+    let mut terms = Vec::new();
+    for (x, f) in t.factors.iter().enumerate() {
+        for g in vary_factor(f, leg) {
+            let mut u = t.clone();
+            u.factors[x] = g;
+            terms.push((BigRational::one(), u));
+        }
+    }
+    if let Some(eps) = &t.epsilon {
+        for k in 0..4 {
+            if eps.slots[k] != Slot::E(leg) {
+                continue;
+            }
+            let mut slots = eps.slots;
+            slots[k] = Slot::P(leg);
+            if let Some((e, sign)) = EpsilonFactor::new(slots) {
+                let mut u = t.clone();
+                u.epsilon = Some(e);
+                terms.push((int(sign as i64), u));
+            }
+        }
+    }
+    for (x, b) in t.bilinears.iter().enumerate() {
+        if b.gamma == Slot::E(leg) {
+            let mut u = t.clone();
+            u.bilinears[x] = BilinearFactor::new(b.bar, Slot::P(leg), b.u);
+            terms.push((BigRational::one(), u));
+        }
+    }
+    finish(terms)
+}
+
+/// Every way of turning one `e_i` in `f` into `p_i`.
+fn vary_factor(f: &ScalarFactor, leg: LegIndex) -> Vec<ScalarFactor> {
+    match f.kind {
+        ScalarKind::PE if f.b == leg => vec![ScalarFactor::dot(Slot::P(f.a), Slot::P(leg))],
+        ScalarKind::EE => {
+            let mut out = Vec::new();
+            if f.a == leg {
+                out.push(ScalarFactor::pe(leg, f.b));
+            }
+            if f.b == leg {
+                out.push(ScalarFactor::pe(leg, f.a));
+            }
+            out
+        }
+        ScalarKind::LE if f.b == leg => vec![ScalarFactor::lp(f.a, leg)],
+        _ => Vec::new(),
+    }
+}
+
+/// Outcome of [`ward_filter`].
+#[derive(Clone, Debug, Default)]
+pub struct WardReport {
+    /// Structures whose variation vanishes on shell for every gauge leg.
+    pub invariant: Vec<TensorStructure>,
+    /// The remaining structures, each with the legs whose Ward identity fails.
+    pub violating: Vec<(TensorStructure, Vec<LegIndex>)>,
+}
+
+/// Massless legs with polarizations, for which `e_i → p_i` is a gauge transformation.
+pub fn gauge_legs(cfg: &GenConfig) -> Vec<LegIndex> {
+    (1..=cfg.n_legs)
+        .map(LegIndex)
+        .filter(|&i| cfg.has_polarization(i) && !cfg.has_virtuality(i))
+        .collect()
+}
+
+/// Split `structures` by whether they satisfy the Ward identity on every gauge leg.
+///
+/// Each variation is reduced with momentum conservation and the on-shell
+/// conditions before testing for zero. Individual structures are rarely
+/// invariant on their own; gauge-invariant combinations come from the null
+/// space of the variation map instead.
+pub fn ward_filter(cfg: &GenConfig, structures: &[TensorStructure]) -> WardReport {
+    let legs = gauge_legs(cfg);
+    let mut out = WardReport::default();
+    for t in structures {
+        let failing: Vec<LegIndex> = legs
+            .iter()
+            .copied()
+            .filter(|&i| !reduce_expression(cfg, &ward_variation(t, i)).is_zero())
+            .collect();
+        if failing.is_empty() {
+            out.invariant.push(t.clone());
+        } else {
+            out.violating.push((t.clone(), failing));
+        }
+    }
+    out
+}
+
+/// A basis of the gauge-invariant linear combinations of `structures`.
+///
+/// This is the null space of the map sending a structure to its on-shell
+/// Ward variations on all gauge legs, solved exactly, keeping only
+/// combinations that stay independent once reduced on shell (the generator's
+/// PP factors are not independent, so many null vectors vanish). Coefficients are
+/// rational numbers, so invariants whose coefficients would depend on the
+/// Mandelstam invariants only appear when the matching PP factors are part of
+/// the input: pass all structures of one degree, which fixes the momentum
+/// power, with enough PP factors allowed. Each combination is returned
+/// reduced on shell, with the coefficients it had over `structures` scaled to
+/// coprime integers.
+///
+/// For four gluons this gives 6, 16 and 26 invariants at degrees 4, 5 and 6:
+/// six generators at four momenta and four more at six, times polynomials in
+/// `s` and `t`, the ten independent gauge-invariant structures.
+pub fn gauge_invariant_combinations(
+    cfg: &GenConfig,
+    structures: &[TensorStructure],
+) -> Vec<TensorExpression> {
+    let legs = gauge_legs(cfg);
+    let variations: Vec<(LegIndex, usize, TensorExpression)> = structures
+        .iter()
+        .enumerate()
+        .flat_map(|(col, t)| {
+            legs.iter()
+                .map(move |&i| (i, col, reduce_expression(cfg, &ward_variation(t, i))))
+        })
+        .collect();
+    let matrix = SparseMatrix::from_expressions(
+        structures.len(),
+        variations.iter().map(|(i, col, e)| (*i, *col, e)),
+    );
+
+    let mut independent = Echelon::default();
+    let mut out = Vec::new();
+    for v in matrix.null_space() {
+        let mut combo = TensorExpression::new();
+        for (t, c) in structures.iter().zip(v) {
+            if !c.is_zero() {
+                combo.push(BigRational::from_integer(c), t.clone());
+            }
+        }
+        let expr = reduce_expression(cfg, &combo);
+        if !independent.insert(&expr) {
+            continue;
+        }
+        if expr.terms.first().is_some_and(|(c, _)| c.is_negative()) {
+            out.push(-expr);
+        } else {
+            out.push(expr);
+        }
+    }
+    out
+}
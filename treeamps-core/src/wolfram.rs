@@ -0,0 +1,121 @@
+//! Structures and expressions as Wolfram Language input.
+//!
+//! Vectors are written `p[i]`, `e[i]` and `l[a]`, dot products with `Dot`,
+//! which Mathematica leaves unevaluated for symbols. The other factors use
+//! inert heads: `Spa[i, j]` and `Spb[i, j]` for the spinor brackets,
+//! `Eps[...]` for ε, `Bilinear[i, v, j]` for `ū_i γ·v u_j`, `TrF[i, j, ...]`
+//! for field-strength traces and `ChF[p[a], i, ..., p[b]]` for chains.
+
+use crate::{
+    bilinear::BilinearFactor,
+    coefficient::Coefficient,
+    dot_product::ScalarFactor,
+    epsilon::EpsilonFactor,
+    expression::TensorExpression,
+    field_strength::FieldStrengthFactor,
+    tensor_structure::TensorStructure,
+    types::{LegIndex, ScalarKind, Slot},
+};
+
+fn slot(s: Slot) -> String {
+    match s {
+        Slot::P(l) => format!("p[{}]", l.0),
+        Slot::E(l) => format!("e[{}]", l.0),
+    }
+}
+
+/// The two vectors of a dot product, or `None` for a spinor bracket.
+fn vectors(f: &ScalarFactor) -> Option<[String; 2]> {
+    let l = |l: LegIndex| format!("l[{}]", l.0);
+    Some(match f.kind {
+        ScalarKind::PP => [slot(Slot::P(f.a)), slot(Slot::P(f.b))],
+        ScalarKind::PE => [slot(Slot::P(f.a)), slot(Slot::E(f.b))],
+        ScalarKind::EE => [slot(Slot::E(f.a)), slot(Slot::E(f.b))],
+        ScalarKind::LP => [l(f.a), slot(Slot::P(f.b))],
+        ScalarKind::LE => [l(f.a), slot(Slot::E(f.b))],
+        ScalarKind::LL => [l(f.a), l(f.b)],
+        ScalarKind::Angle | ScalarKind::Square => return None,
+    })
+}
+
+fn bracket(f: &ScalarFactor) -> String {
+    let head = if f.kind == ScalarKind::Angle {
+        "Spa"
+    } else {
+        "Spb"
+    };
+    format!("{}[{}, {}]", head, f.a.0, f.b.0)
+}
+
+fn epsilon(e: &EpsilonFactor) -> String {
+    let slots: Vec<String> = e.slots.iter().map(|&s| slot(s)).collect();
+    format!("Eps[{}]", slots.join(", "))
+}
+
+fn bilinear(b: &BilinearFactor) -> String {
+    format!("Bilinear[{}, {}, {}]", b.bar.0, slot(b.gamma), b.u.0)
+}
+
+fn field_strength(f: &FieldStrengthFactor) -> String {
+    let legs = |legs: &[LegIndex]| {
+        legs.iter()
+            .map(|l| l.0.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    match f {
+        FieldStrengthFactor::Trace(ls) => format!("TrF[{}]", legs(ls)),
+        FieldStrengthFactor::Chain {
+            left,
+            legs: ls,
+            right,
+        } => format!(
+            "ChF[{}, {}, {}]",
+            slot(Slot::P(*left)),
+            legs(ls),
+            slot(Slot::P(*right))
+        ),
+    }
+}
+
+impl TensorStructure {
+    /// Product of the factors, e.g. `Dot[p[1], e[2]]*Dot[e[3], e[4]]`, or `1`
+    /// for the empty product.
+    pub fn to_wolfram(&self) -> String {
+        if self.degree() == 0 {
+            return "1".to_string();
+        }
+        self.wolfram_factors(|f| match vectors(f) {
+            Some([x, y]) => format!("Dot[{}, {}]", x, y),
+            None => bracket(f),
+        })
+        .join("*")
+    }
+
+    /// The factors as a list, with each dot product as the pair of its
+    /// vectors: `{{p[1], e[2]}, {e[3], e[4]}}`.
+    pub fn to_wolfram_list(&self) -> String {
+        let factors = self.wolfram_factors(|f| match vectors(f) {
+            Some([x, y]) => format!("{{{}, {}}}", x, y),
+            None => bracket(f),
+        });
+        format!("{{{}}}", factors.join(", "))
+    }
+
+    fn wolfram_factors(&self, factor: impl Fn(&ScalarFactor) -> String) -> Vec<String> {
+        self.bilinears
+            .iter()
+            .map(bilinear)
+            .chain(self.epsilon.iter().map(epsilon))
+            .chain(self.field_strengths.iter().map(field_strength))
+            .chain(self.factors.iter().map(factor))
+            .collect()
+    }
+}
+
+impl<C: Coefficient> TensorExpression<C> {
+    /// Sum of the terms, e.g. `Dot[p[1], p[2]] - 1/2*Dot[e[1], e[2]]`, or `0`.
+    pub fn to_wolfram(&self) -> String {
+        self.to_sum(|t| t.to_wolfram())
+    }
+}
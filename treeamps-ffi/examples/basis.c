@@ -0,0 +1,63 @@
+/*
+ * Print the 4-gluon basis with one EE factor, then the start of a larger one.
+ *
+ *   cargo build --release -p treeamps-ffi
+ *   cc -Itreeamps-ffi/include treeamps-ffi/examples/basis.c \
+ *      -Ltarget/release -ltreeamps_ffi -o basis
+ *   LD_LIBRARY_PATH=target/release ./basis
+ */
+#include <stdio.h>
+
+#include "treeamps.h"
+
+/* Print structures until `*left` runs out. */
+static bool print_some(const TreeampsStructure *t, void *user_data) {
+    size_t *left = (size_t *)user_data;
+    char *text = treeamps_structure_to_string(t);
+    printf("     %s\n", text);
+    treeamps_string_free(text);
+    return --*left > 0;
+}
+
+int main(void) {
+    TreeampsConfig *cfg = treeamps_config_new(4);
+    TreeampsStructures *basis = treeamps_generate(cfg, 3, 1);
+    if (basis == NULL) {
+        fprintf(stderr, "%s\n", treeamps_last_error());
+        return 1;
+    }
+
+    uint64_t predicted = 0;
+    if (treeamps_predict_count(cfg, 3, 1, &predicted)) {
+        printf("count=%zu predicted=%llu\n", treeamps_structures_len(basis),
+               (unsigned long long)predicted);
+    }
+    for (size_t i = 0; i < treeamps_structures_len(basis); ++i) {
+        const TreeampsStructure *t = treeamps_structures_get(basis, i);
+        char *text = treeamps_structure_to_string(t);
+        char *form = treeamps_structure_to_form(t, "dot=SP");
+        printf("%3zu) %s    %s\n", i + 1, text, form);
+        treeamps_string_free(form);
+        treeamps_string_free(text);
+
+        TreeampsFactor f;
+        for (size_t k = 0; treeamps_structure_factor(t, k, &f); ++k) {
+            if (f.kind == TREEAMPS_FACTOR_KIND_EE) {
+                printf("     e%u.e%u\n", f.a, f.b);
+            }
+        }
+    }
+    treeamps_structures_free(basis);
+    treeamps_config_free(cfg);
+
+    cfg = treeamps_config_new(7);
+    printf("n=7 deg=5 ee=2 of %llu:\n", (unsigned long long)treeamps_count(cfg, 5, 2));
+    size_t left = 3;
+    treeamps_for_each(cfg, 5, 2, print_some, &left);
+    treeamps_config_free(cfg);
+
+    if (treeamps_structure_parse("(p1·x2)") == NULL) {
+        printf("parse error: %s\n", treeamps_last_error());
+    }
+    return 0;
+}
@@ -0,0 +1,198 @@
+#ifndef TREEAMPS_H
+#define TREEAMPS_H
+
+/* Generated by cbindgen from treeamps-ffi/src/lib.rs; do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// The kind of a [`TreeampsFactor`], as in `ScalarKind`.
+typedef enum TreeampsFactorKind {
+  TREEAMPS_FACTOR_KIND_PP,
+  TREEAMPS_FACTOR_KIND_PE,
+  TREEAMPS_FACTOR_KIND_EE,
+  TREEAMPS_FACTOR_KIND_ANGLE,
+  TREEAMPS_FACTOR_KIND_SQUARE,
+  TREEAMPS_FACTOR_KIND_LP,
+  TREEAMPS_FACTOR_KIND_LE,
+  TREEAMPS_FACTOR_KIND_LL,
+} TreeampsFactorKind;
+
+// Which structures to generate.
+typedef struct TreeampsConfig TreeampsConfig;
+
+// One tensor structure.
+typedef struct TreeampsStructure TreeampsStructure;
+
+// A generated basis, in canonical order.
+typedef struct TreeampsStructures TreeampsStructures;
+
+// A dot product or spinor bracket: `p_a·e_b` is `{PE, a, b}`, and for the
+// loop-momentum kinds `a` is the loop index.
+typedef struct TreeampsFactor {
+  enum TreeampsFactorKind kind;
+  uint8_t a;
+  uint8_t b;
+} TreeampsFactor;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// The message of the last failure on this thread, or `NULL` if there was
+// none. It stays valid until the next failing call on the thread.
+const char *treeamps_last_error(void);
+
+// Release a string returned by this library.
+//
+// # Safety
+//
+// `s` is `NULL` or a string returned by this library, not yet released.
+void treeamps_string_free(char *s);
+
+// The default configuration for `n_legs` gluons, with the last momentum
+// eliminated.
+struct TreeampsConfig *treeamps_config_new(uint8_t n_legs);
+
+// A configuration from the `config` object of `gen-ts --format json`, or
+// `NULL` if it does not parse.
+//
+// # Safety
+//
+// `json` is `NULL` or a NUL-terminated string.
+struct TreeampsConfig *treeamps_config_from_json(const char *json);
+
+// The configuration as a JSON object, or `NULL` for a `NULL` handle.
+char *treeamps_config_to_json(const struct TreeampsConfig *cfg);
+
+void treeamps_config_free(struct TreeampsConfig *cfg);
+
+// Set one of the switches `eliminate_momentum`, `parity_odd`,
+// `mass_squares` and `cyclic`.
+//
+// Returns 0, or -1 for an unknown name. `cyclic` together with
+// `eliminate_momentum` is only rejected once the configuration is used.
+//
+// # Safety
+//
+// `name` is `NULL` or a NUL-terminated string.
+int32_t treeamps_config_set_flag(struct TreeampsConfig *cfg, const char *name, bool on);
+
+// Set one of the bounds `max_pp`, `loops`, `momentum_power` and
+// `max_structures`; a negative `value` removes the bound.
+//
+// Returns 0, or -1 for an unknown name or a value out of range.
+//
+// # Safety
+//
+// `name` is `NULL` or a NUL-terminated string.
+int32_t treeamps_config_set_limit(struct TreeampsConfig *cfg, const char *name, int64_t value);
+
+// Make leg `leg` (1-based) massive or massless.
+//
+// Returns 0, or -1 for a leg out of range.
+int32_t treeamps_config_set_massive(struct TreeampsConfig *cfg, uint8_t leg, bool massive);
+
+// Set the spin of leg `leg` (1-based): 0 for a scalar, 1 for a vector and
+// 2 for a tensor.
+//
+// Returns 0, or -1 for a leg or spin out of range.
+int32_t treeamps_config_set_spin(struct TreeampsConfig *cfg, uint8_t leg, uint8_t spin);
+
+// The basis of structures with `deg` factors, `ee` of them EE, or `NULL`
+// when the count exceeds `max_structures`.
+struct TreeampsStructures *treeamps_generate(const struct TreeampsConfig *cfg,
+                                             uint32_t deg,
+                                             uint32_t ee);
+
+// Call `visit` on each structure of the basis as the search finds it,
+// without holding the basis, until it returns `false`.
+//
+// The structure handle is valid only during the call. Returns the number
+// of structures visited, or -1 for a `NULL` handle or a configuration
+// combining `cyclic` with `eliminate_momentum`.
+//
+// # Safety
+//
+// `visit` is safe to call with `user_data`.
+int64_t treeamps_for_each(const struct TreeampsConfig *cfg,
+                          uint32_t deg,
+                          uint32_t ee,
+                          bool (*visit)(const struct TreeampsStructure *structure, void *user_data),
+                          void *user_data);
+
+// The number of structures [`treeamps_generate`] returns, without holding
+// them; 0 for a `NULL` handle or a configuration combining `cyclic` with
+// `eliminate_momentum`.
+uint64_t treeamps_count(const struct TreeampsConfig *cfg, uint32_t deg, uint32_t ee);
+
+// Store the count predicted by the Hilbert series in `*count` and return
+// `true`, or return `false` where the prediction does not apply.
+bool treeamps_predict_count(const struct TreeampsConfig *cfg,
+                            uint32_t deg,
+                            uint32_t ee,
+                            uint64_t *count);
+
+size_t treeamps_structures_len(const struct TreeampsStructures *set);
+
+// The structure at `index` (0-based), borrowed from `set`, or `NULL` past
+// the end.
+const struct TreeampsStructure *treeamps_structures_get(const struct TreeampsStructures *set,
+                                                        size_t index);
+
+void treeamps_structures_free(struct TreeampsStructures *set);
+
+// Parse the printed form of a structure, e.g. `(p1·e2) · (e3·e4)`, or
+// return `NULL`.
+//
+// # Safety
+//
+// `s` is `NULL` or a NUL-terminated string.
+struct TreeampsStructure *treeamps_structure_parse(const char *s);
+
+// Release a structure from [`treeamps_structure_parse`]; never one
+// borrowed from a set.
+void treeamps_structure_free(struct TreeampsStructure *structure);
+
+// Number of factors.
+uint32_t treeamps_structure_degree(const struct TreeampsStructure *structure);
+
+// Number of EE factors.
+uint32_t treeamps_structure_ee(const struct TreeampsStructure *structure);
+
+// Number of dot products and spinor brackets, the factors
+// [`treeamps_structure_factor`] reads.
+size_t treeamps_structure_factor_count(const struct TreeampsStructure *structure);
+
+// Store factor `index` (0-based) in `*factor` and return `true`, or return
+// `false` past the end.
+bool treeamps_structure_factor(const struct TreeampsStructure *structure,
+                               size_t index,
+                               struct TreeampsFactor *factor);
+
+// The printed form, e.g. `(p1·e2) · (e3·e4)`.
+char *treeamps_structure_to_string(const struct TreeampsStructure *structure);
+
+char *treeamps_structure_to_latex(const struct TreeampsStructure *structure);
+
+char *treeamps_structure_to_wolfram(const struct TreeampsStructure *structure);
+
+// FORM input. `names` is `NULL` for the defaults or renames functions and
+// vectors as `--form-name` does, comma-separated: `"dot=SP,momentum=k"`.
+//
+// # Safety
+//
+// `names` is `NULL` or a NUL-terminated string.
+char *treeamps_structure_to_form(const struct TreeampsStructure *structure, const char *names);
+
+// The structure as JSON, as in `--format json`.
+char *treeamps_structure_to_json(const struct TreeampsStructure *structure);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* TREEAMPS_H */
@@ -0,0 +1,546 @@
+//! C ABI over the generator, for amplitude codes in C and C++.
+//!
+//! Configurations, result sets and parsed structures are opaque handles,
+//! created by the `_new`/`_from_*`/`treeamps_generate` functions and released
+//! with the matching `_free`. A structure handle from
+//! [`treeamps_structures_get`] or [`treeamps_for_each`] is borrowed and must
+//! not be freed. Strings returned to the caller are owned and released with
+//! [`treeamps_string_free`].
+//!
+//! Functions that can fail return `NULL` or a negative status and leave a
+//! message for [`treeamps_last_error`]. The header `include/treeamps.h` is
+//! generated from this file by cbindgen (see `cbindgen.toml`).
+
+use std::{
+    cell::RefCell,
+    ffi::{CStr, CString, c_char, c_void},
+    ptr,
+};
+
+use treeamps_core::{
+    Canonicalization, FormNames, GenConfig, LegIndex, Mass, ScalarKind, Spin, TensorStructure,
+    count_tensor_structures, generate_tensor_structures_iter, predict_structure_count,
+    try_generate_tensor_structures,
+};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_error(e: impl ToString) {
+    let message = CString::new(e.to_string().replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Record a failure and return `err`.
+fn fail<T>(e: impl ToString, err: T) -> T {
+    set_error(e);
+    err
+}
+
+fn to_c_string(s: String) -> *mut c_char {
+    CString::new(s).map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// The argument `s`, or `None` after recording why it is not a string.
+///
+/// # Safety
+///
+/// `s` is `NULL` or a NUL-terminated string.
+unsafe fn from_c_str<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return fail("null string", None);
+    }
+    // SAFETY: non-null and NUL-terminated by the caller's contract.
+    match unsafe { CStr::from_ptr(s) }.to_str() {
+        Ok(s) => Some(s),
+        Err(e) => fail(e, None),
+    }
+}
+
+/// The message of the last failure on this thread, or `NULL` if there was
+/// none. It stays valid until the next failing call on the thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn treeamps_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |e| e.as_ptr()))
+}
+
+/// Release a string returned by this library.
+///
+/// # Safety
+///
+/// `s` is `NULL` or a string returned by this library, not yet released.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn treeamps_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        // SAFETY: `s` came from `CString::into_raw` by the caller's contract.
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+/// Which structures to generate.
+pub struct TreeampsConfig {
+    inner: GenConfig,
+}
+
+/// A generated basis, in canonical order.
+pub struct TreeampsStructures {
+    items: Vec<TreeampsStructure>,
+}
+
+/// One tensor structure.
+pub struct TreeampsStructure {
+    inner: TensorStructure,
+}
+
+/// The kind of a [`TreeampsFactor`], as in `ScalarKind`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub enum TreeampsFactorKind {
+    PP,
+    PE,
+    EE,
+    Angle,
+    Square,
+    LP,
+    LE,
+    LL,
+}
+
+/// A dot product or spinor bracket: `p_a·e_b` is `{PE, a, b}`, and for the
+/// loop-momentum kinds `a` is the loop index.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct TreeampsFactor {
+    pub kind: TreeampsFactorKind,
+    pub a: u8,
+    pub b: u8,
+}
+
+/// The default configuration for `n_legs` gluons, with the last momentum
+/// eliminated.
+#[unsafe(no_mangle)]
+pub extern "C" fn treeamps_config_new(n_legs: u8) -> Box<TreeampsConfig> {
+    Box::new(TreeampsConfig {
+        inner: GenConfig {
+            n_legs,
+            masses: vec![Mass::Massless; n_legs as usize],
+            spins: vec![Spin::Vector; n_legs as usize],
+            ..GenConfig::default()
+        },
+    })
+}
+
+/// A configuration from the `config` object of `gen-ts --format json`, or
+/// `NULL` if it does not parse.
+///
+/// # Safety
+///
+/// `json` is `NULL` or a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn treeamps_config_from_json(
+    json: *const c_char,
+) -> Option<Box<TreeampsConfig>> {
+    // SAFETY: forwarded from the caller.
+    let json = unsafe { from_c_str(json) }?;
+    match serde_json::from_str(json) {
+        Ok(inner) => Some(Box::new(TreeampsConfig { inner })),
+        Err(e) => fail(e, None),
+    }
+}
+
+/// The configuration as a JSON object, or `NULL` for a `NULL` handle.
+#[unsafe(no_mangle)]
+pub extern "C" fn treeamps_config_to_json(cfg: Option<&TreeampsConfig>) -> *mut c_char {
+    let Some(cfg) = cfg else {
+        return fail("null config", ptr::null_mut());
+    };
+    match serde_json::to_string(&cfg.inner) {
+        Ok(json) => to_c_string(json),
+        Err(e) => fail(e, ptr::null_mut()),
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn treeamps_config_free(cfg: Option<Box<TreeampsConfig>>) {
+    drop(cfg);
+}
+
+/// Set one of the switches `eliminate_momentum`, `parity_odd`,
+/// `mass_squares` and `cyclic`.
+///
+/// Returns 0, or -1 for an unknown name. `cyclic` together with
+/// `eliminate_momentum` is only rejected once the configuration is used.
+///
+/// # Safety
+///
+/// `name` is `NULL` or a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn treeamps_config_set_flag(
+    cfg: Option<&mut TreeampsConfig>,
+    name: *const c_char,
+    on: bool,
+) -> i32 {
+    let Some(cfg) = cfg else {
+        return fail("null config", -1);
+    };
+    // SAFETY: forwarded from the caller.
+    let Some(name) = (unsafe { from_c_str(name) }) else {
+        return -1;
+    };
+    let cfg = &mut cfg.inner;
+    match name {
+        "eliminate_momentum" => cfg.eliminate_momentum = on,
+        "parity_odd" => cfg.include_parity_odd = on,
+        "mass_squares" => cfg.include_mass_squares = on,
+        "cyclic" => {
+            cfg.canonicalization = if on {
+                Canonicalization::Cyclic
+            } else {
+                Canonicalization::Labelled
+            }
+        }
+        _ => return fail(format!("unknown flag {:?}", name), -1),
+    }
+    0
+}
+
+/// Set one of the bounds `max_pp`, `loops`, `momentum_power` and
+/// `max_structures`; a negative `value` removes the bound.
+///
+/// Returns 0, or -1 for an unknown name or a value out of range.
+///
+/// # Safety
+///
+/// `name` is `NULL` or a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn treeamps_config_set_limit(
+    cfg: Option<&mut TreeampsConfig>,
+    name: *const c_char,
+    value: i64,
+) -> i32 {
+    let Some(cfg) = cfg else {
+        return fail("null config", -1);
+    };
+    // SAFETY: forwarded from the caller.
+    let Some(name) = (unsafe { from_c_str(name) }) else {
+        return -1;
+    };
+    let cfg = &mut cfg.inner;
+    let bound = |value: i64| match value {
+        ..0 => Ok(None),
+        v => u32::try_from(v).map(Some),
+    };
+    let set = match name {
+        "max_pp" => bound(value).map(|v| cfg.max_pp = v),
+        "momentum_power" => bound(value).map(|v| cfg.momentum_power = v),
+        "loops" => u8::try_from(value.max(0)).map(|v| cfg.n_loops = v),
+        "max_structures" => {
+            cfg.max_structures = u64::try_from(value).ok();
+            Ok(())
+        }
+        _ => return fail(format!("unknown limit {:?}", name), -1),
+    };
+    match set {
+        Ok(()) => 0,
+        Err(e) => fail(format!("{}: {}", name, e), -1),
+    }
+}
+
+/// Make leg `leg` (1-based) massive or massless.
+///
+/// Returns 0, or -1 for a leg out of range.
+#[unsafe(no_mangle)]
+pub extern "C" fn treeamps_config_set_massive(
+    cfg: Option<&mut TreeampsConfig>,
+    leg: u8,
+    massive: bool,
+) -> i32 {
+    let Some(cfg) = cfg else {
+        return fail("null config", -1);
+    };
+    match cfg.inner.masses.get_mut((leg as usize).wrapping_sub(1)) {
+        Some(m) => {
+            *m = if massive {
+                Mass::Massive
+            } else {
+                Mass::Massless
+            };
+            0
+        }
+        None => fail(format!("leg {} out of range", leg), -1),
+    }
+}
+
+/// Set the spin of leg `leg` (1-based): 0 for a scalar, 1 for a vector and
+/// 2 for a tensor.
+///
+/// Returns 0, or -1 for a leg or spin out of range.
+#[unsafe(no_mangle)]
+pub extern "C" fn treeamps_config_set_spin(
+    cfg: Option<&mut TreeampsConfig>,
+    leg: u8,
+    spin: u8,
+) -> i32 {
+    let Some(cfg) = cfg else {
+        return fail("null config", -1);
+    };
+    let spin = match spin {
+        0 => Spin::Scalar,
+        1 => Spin::Vector,
+        2 => Spin::Tensor,
+        _ => return fail(format!("unknown spin {}", spin), -1),
+    };
+    match cfg.inner.spins.get_mut((leg as usize).wrapping_sub(1)) {
+        Some(s) => {
+            *s = spin;
+            0
+        }
+        None => fail(format!("leg {} out of range", leg), -1),
+    }
+}
+
+/// The basis of structures with `deg` factors, `ee` of them EE, or `NULL`
+/// when the count exceeds `max_structures`.
+#[unsafe(no_mangle)]
+pub extern "C" fn treeamps_generate(
+    cfg: Option<&TreeampsConfig>,
+    deg: u32,
+    ee: u32,
+) -> Option<Box<TreeampsStructures>> {
+    let Some(cfg) = cfg else {
+        return fail("null config", None);
+    };
+    match try_generate_tensor_structures(&cfg.inner, deg, ee) {
+        Ok(structures) => Some(Box::new(TreeampsStructures {
+            items: structures
+                .into_iter()
+                .map(|inner| TreeampsStructure { inner })
+                .collect(),
+        })),
+        Err(e) => fail(e, None),
+    }
+}
+
+/// Call `visit` on each structure of the basis as the search finds it,
+/// without holding the basis, until it returns `false`.
+///
+/// The structure handle is valid only during the call. Returns the number
+/// of structures visited, or -1 for a `NULL` handle or a configuration
+/// combining `cyclic` with `eliminate_momentum`.
+///
+/// # Safety
+///
+/// `visit` is safe to call with `user_data`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn treeamps_for_each(
+    cfg: Option<&TreeampsConfig>,
+    deg: u32,
+    ee: u32,
+    visit: extern "C" fn(structure: &TreeampsStructure, user_data: *mut c_void) -> bool,
+    user_data: *mut c_void,
+) -> i64 {
+    let Some(cfg) = cfg else {
+        return fail("null config", -1);
+    };
+    if let Err(e) = cfg.inner.validate() {
+        return fail(e, -1);
+    }
+    let mut visited = 0;
+    for inner in generate_tensor_structures_iter(&cfg.inner, deg, ee) {
+        visited += 1;
+        if !visit(&TreeampsStructure { inner }, user_data) {
+            break;
+        }
+    }
+    visited
+}
+
+/// The number of structures [`treeamps_generate`] returns, without holding
+/// them; 0 for a `NULL` handle or a configuration combining `cyclic` with
+/// `eliminate_momentum`.
+#[unsafe(no_mangle)]
+pub extern "C" fn treeamps_count(cfg: Option<&TreeampsConfig>, deg: u32, ee: u32) -> u64 {
+    match cfg.map(|cfg| (cfg, cfg.inner.validate())) {
+        Some((cfg, Ok(()))) => count_tensor_structures(&cfg.inner, deg, ee),
+        Some((_, Err(e))) => fail(e, 0),
+        None => 0,
+    }
+}
+
+/// Store the count predicted by the Hilbert series in `*count` and return
+/// `true`, or return `false` where the prediction does not apply.
+#[unsafe(no_mangle)]
+pub extern "C" fn treeamps_predict_count(
+    cfg: Option<&TreeampsConfig>,
+    deg: u32,
+    ee: u32,
+    count: Option<&mut u64>,
+) -> bool {
+    let prediction = cfg.and_then(|cfg| predict_structure_count(&cfg.inner, deg, ee));
+    if let (Some(p), Some(count)) = (prediction, count) {
+        *count = p;
+    }
+    prediction.is_some()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn treeamps_structures_len(set: Option<&TreeampsStructures>) -> usize {
+    set.map_or(0, |set| set.items.len())
+}
+
+/// The structure at `index` (0-based), borrowed from `set`, or `NULL` past
+/// the end.
+#[unsafe(no_mangle)]
+pub extern "C" fn treeamps_structures_get(
+    set: Option<&TreeampsStructures>,
+    index: usize,
+) -> Option<&TreeampsStructure> {
+    set?.items.get(index)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn treeamps_structures_free(set: Option<Box<TreeampsStructures>>) {
+    drop(set);
+}
+
+/// Parse the printed form of a structure, e.g. `(p1·e2) · (e3·e4)`, or
+/// return `NULL`.
+///
+/// # Safety
+///
+/// `s` is `NULL` or a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn treeamps_structure_parse(
+    s: *const c_char,
+) -> Option<Box<TreeampsStructure>> {
+    // SAFETY: forwarded from the caller.
+    let s = unsafe { from_c_str(s) }?;
+    match s.parse() {
+        Ok(inner) => Some(Box::new(TreeampsStructure { inner })),
+        Err(e) => fail(e, None),
+    }
+}
+
+/// Release a structure from [`treeamps_structure_parse`]; never one
+/// borrowed from a set.
+#[unsafe(no_mangle)]
+pub extern "C" fn treeamps_structure_free(structure: Option<Box<TreeampsStructure>>) {
+    drop(structure);
+}
+
+/// Number of factors.
+#[unsafe(no_mangle)]
+pub extern "C" fn treeamps_structure_degree(structure: Option<&TreeampsStructure>) -> u32 {
+    structure.map_or(0, |t| t.inner.degree())
+}
+
+/// Number of EE factors.
+#[unsafe(no_mangle)]
+pub extern "C" fn treeamps_structure_ee(structure: Option<&TreeampsStructure>) -> u32 {
+    structure.map_or(0, |t| t.inner.ee_contractions)
+}
+
+/// Number of dot products and spinor brackets, the factors
+/// [`treeamps_structure_factor`] reads.
+#[unsafe(no_mangle)]
+pub extern "C" fn treeamps_structure_factor_count(structure: Option<&TreeampsStructure>) -> usize {
+    structure.map_or(0, |t| t.inner.factors.len())
+}
+
+/// Store factor `index` (0-based) in `*factor` and return `true`, or return
+/// `false` past the end.
+#[unsafe(no_mangle)]
+pub extern "C" fn treeamps_structure_factor(
+    structure: Option<&TreeampsStructure>,
+    index: usize,
+    factor: Option<&mut TreeampsFactor>,
+) -> bool {
+    let (Some(f), Some(factor)) = (structure.and_then(|t| t.inner.factors.get(index)), factor)
+    else {
+        return false;
+    };
+    let LegIndex(a) = f.a;
+    let LegIndex(b) = f.b;
+    let kind = match f.kind {
+        ScalarKind::PP => TreeampsFactorKind::PP,
+        ScalarKind::PE => TreeampsFactorKind::PE,
+        ScalarKind::EE => TreeampsFactorKind::EE,
+        ScalarKind::Angle => TreeampsFactorKind::Angle,
+        ScalarKind::Square => TreeampsFactorKind::Square,
+        ScalarKind::LP => TreeampsFactorKind::LP,
+        ScalarKind::LE => TreeampsFactorKind::LE,
+        ScalarKind::LL => TreeampsFactorKind::LL,
+    };
+    *factor = TreeampsFactor { kind, a, b };
+    true
+}
+
+/// The printed form, e.g. `(p1·e2) · (e3·e4)`.
+#[unsafe(no_mangle)]
+pub extern "C" fn treeamps_structure_to_string(
+    structure: Option<&TreeampsStructure>,
+) -> *mut c_char {
+    structure.map_or(ptr::null_mut(), |t| to_c_string(t.inner.to_string()))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn treeamps_structure_to_latex(
+    structure: Option<&TreeampsStructure>,
+) -> *mut c_char {
+    structure.map_or(ptr::null_mut(), |t| to_c_string(t.inner.to_latex()))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn treeamps_structure_to_wolfram(
+    structure: Option<&TreeampsStructure>,
+) -> *mut c_char {
+    structure.map_or(ptr::null_mut(), |t| to_c_string(t.inner.to_wolfram()))
+}
+
+/// FORM input. `names` is `NULL` for the defaults or renames functions and
+/// vectors as `--form-name` does, comma-separated: `"dot=SP,momentum=k"`.
+///
+/// # Safety
+///
+/// `names` is `NULL` or a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn treeamps_structure_to_form(
+    structure: Option<&TreeampsStructure>,
+    names: *const c_char,
+) -> *mut c_char {
+    let Some(t) = structure else {
+        return fail("null structure", ptr::null_mut());
+    };
+    let mut form = FormNames::default();
+    if !names.is_null() {
+        // SAFETY: forwarded from the caller.
+        let Some(names) = (unsafe { from_c_str(names) }) else {
+            return ptr::null_mut();
+        };
+        for pair in names.split(',').filter(|p| !p.is_empty()) {
+            let Some((kind, name)) = pair.split_once('=') else {
+                return fail(
+                    format!("expected KIND=NAME, got {:?}", pair),
+                    ptr::null_mut(),
+                );
+            };
+            if let Err(e) = form.set(kind.trim(), name.trim()) {
+                return fail(e, ptr::null_mut());
+            }
+        }
+    }
+    to_c_string(t.inner.to_form(&form))
+}
+
+/// The structure as JSON, as in `--format json`.
+#[unsafe(no_mangle)]
+pub extern "C" fn treeamps_structure_to_json(structure: Option<&TreeampsStructure>) -> *mut c_char {
+    let Some(t) = structure else {
+        return fail("null structure", ptr::null_mut());
+    };
+    match serde_json::to_string(&t.inner) {
+        Ok(json) => to_c_string(json),
+        Err(e) => fail(e, ptr::null_mut()),
+    }
+}
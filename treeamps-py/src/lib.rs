@@ -0,0 +1,258 @@
+//! Python bindings: configurations, structure generation and counts.
+//!
+//! Built into the `treeamps` extension module by maturin (see
+//! `pyproject.toml`). Structures come back as `Structure` objects that print
+//! like the CLI listing and convert to LaTeX, FORM, Wolfram or JSON.
+
+use std::collections::HashMap;
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+use treeamps_core::{
+    Canonicalization, FormNames, GenConfig, Mass, Spin, TensorStructure, count_tensor_structures,
+    predict_structure_count, try_generate_tensor_structures,
+};
+
+fn value_error(e: impl ToString) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+/// Which structures to generate.
+///
+/// The keywords cover the common settings; `GenConfig.from_json` accepts
+/// every field, in the form `gen-ts --format json` prints the `config`.
+#[pyclass(name = "GenConfig", module = "treeamps")]
+struct PyGenConfig {
+    inner: GenConfig,
+}
+
+#[pymethods]
+impl PyGenConfig {
+    #[new]
+    #[pyo3(signature = (
+        n_legs,
+        *,
+        eliminate_momentum = true,
+        parity_odd = false,
+        massive = Vec::new(),
+        mass_squares = false,
+        scalars = Vec::new(),
+        tensors = Vec::new(),
+        max_pp = None,
+        loops = 0,
+        momentum_power = None,
+        max_structures = None,
+        cyclic = false,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        n_legs: u8,
+        eliminate_momentum: bool,
+        parity_odd: bool,
+        massive: Vec<u8>,
+        mass_squares: bool,
+        scalars: Vec<u8>,
+        tensors: Vec<u8>,
+        max_pp: Option<u32>,
+        loops: u8,
+        momentum_power: Option<u32>,
+        max_structures: Option<u64>,
+        cyclic: bool,
+    ) -> PyResult<Self> {
+        for (name, legs) in [
+            ("massive", &massive),
+            ("scalars", &scalars),
+            ("tensors", &tensors),
+        ] {
+            if legs.iter().any(|&k| k == 0 || k > n_legs) {
+                return Err(value_error(format!(
+                    "{} legs must be between 1 and n_legs",
+                    name
+                )));
+            }
+        }
+        let inner = GenConfig {
+            n_legs,
+            eliminate_momentum,
+            include_parity_odd: parity_odd,
+            masses: (1..=n_legs)
+                .map(|k| {
+                    if massive.contains(&k) {
+                        Mass::Massive
+                    } else {
+                        Mass::Massless
+                    }
+                })
+                .collect(),
+            include_mass_squares: mass_squares,
+            spins: (1..=n_legs)
+                .map(|k| {
+                    if scalars.contains(&k) {
+                        Spin::Scalar
+                    } else if tensors.contains(&k) {
+                        Spin::Tensor
+                    } else {
+                        Spin::Vector
+                    }
+                })
+                .collect(),
+            max_pp,
+            n_loops: loops,
+            momentum_power,
+            max_structures,
+            canonicalization: if cyclic {
+                Canonicalization::Cyclic
+            } else {
+                Canonicalization::Labelled
+            },
+            ..GenConfig::default()
+        };
+        inner.validate().map_err(value_error)?;
+        Ok(Self { inner })
+    }
+
+    /// The configuration serialized as a JSON object.
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        let inner: GenConfig = serde_json::from_str(json).map_err(value_error)?;
+        inner.validate().map_err(value_error)?;
+        Ok(Self { inner })
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string(&self.inner).map_err(value_error)
+    }
+
+    #[getter]
+    fn n_legs(&self) -> u8 {
+        self.inner.n_legs
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "GenConfig.from_json({:?})",
+            self.to_json().unwrap_or_default()
+        )
+    }
+}
+
+/// One tensor structure, a product of dot products and other factors.
+#[pyclass(name = "Structure", module = "treeamps", frozen, eq, hash)]
+#[derive(PartialEq, Eq, Hash)]
+struct PyStructure {
+    inner: TensorStructure,
+}
+
+#[pymethods]
+impl PyStructure {
+    /// Parse the printed form, e.g. `(p1·e2) · (e3·e4)`.
+    #[staticmethod]
+    fn parse(s: &str) -> PyResult<Self> {
+        let inner = s.parse().map_err(value_error)?;
+        Ok(Self { inner })
+    }
+
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        let inner = serde_json::from_str(json).map_err(value_error)?;
+        Ok(Self { inner })
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string(&self.inner).map_err(value_error)
+    }
+
+    /// Number of factors.
+    #[getter]
+    fn degree(&self) -> u32 {
+        self.inner.degree()
+    }
+
+    /// Number of EE factors.
+    #[getter]
+    fn ee(&self) -> u32 {
+        self.inner.ee_contractions
+    }
+
+    /// The dot products and spinor brackets as `(kind, a, b)`, e.g.
+    /// `("PE", 1, 2)` for `p1·e2`.
+    #[getter]
+    fn factors(&self) -> Vec<(String, u8, u8)> {
+        self.inner
+            .factors
+            .iter()
+            .map(|f| (format!("{:?}", f.kind), f.a.0, f.b.0))
+            .collect()
+    }
+
+    fn to_latex(&self) -> String {
+        self.inner.to_latex()
+    }
+
+    /// FORM input; `names` renames functions and vectors as in
+    /// `--form-name`, e.g. `{"dot": "SP"}`.
+    #[pyo3(signature = (names = None))]
+    fn to_form(&self, names: Option<HashMap<String, String>>) -> PyResult<String> {
+        let mut form = FormNames::default();
+        for (kind, name) in names.unwrap_or_default() {
+            form.set(&kind, &name).map_err(value_error)?;
+        }
+        Ok(self.inner.to_form(&form))
+    }
+
+    fn to_wolfram(&self) -> String {
+        self.inner.to_wolfram()
+    }
+
+    fn __str__(&self) -> String {
+        self.inner.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Structure.parse({:?})", self.inner.to_string())
+    }
+}
+
+/// The basis of structures with `deg` factors, `ee` of them EE, in
+/// canonical order.
+///
+/// Raises `ValueError` when the count exceeds `max_structures`.
+#[pyfunction]
+fn generate(
+    py: Python<'_>,
+    cfg: PyRef<'_, PyGenConfig>,
+    deg: u32,
+    ee: u32,
+) -> PyResult<Vec<PyStructure>> {
+    let cfg = cfg.inner.clone();
+    let structures = py
+        .detach(|| try_generate_tensor_structures(&cfg, deg, ee))
+        .map_err(value_error)?;
+    Ok(structures
+        .into_iter()
+        .map(|inner| PyStructure { inner })
+        .collect())
+}
+
+/// The number of structures `generate` returns, without holding them.
+#[pyfunction]
+fn count(py: Python<'_>, cfg: PyRef<'_, PyGenConfig>, deg: u32, ee: u32) -> u64 {
+    let cfg = cfg.inner.clone();
+    py.detach(|| count_tensor_structures(&cfg, deg, ee))
+}
+
+/// The count predicted by the Hilbert series, or `None` where it does not
+/// apply.
+#[pyfunction]
+fn predict_count(cfg: PyRef<'_, PyGenConfig>, deg: u32, ee: u32) -> Option<u64> {
+    predict_structure_count(&cfg.inner, deg, ee)
+}
+
+#[pymodule]
+fn treeamps(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyGenConfig>()?;
+    m.add_class::<PyStructure>()?;
+    m.add_function(wrap_pyfunction!(generate, m)?)?;
+    m.add_function(wrap_pyfunction!(count, m)?)?;
+    m.add_function(wrap_pyfunction!(predict_count, m)?)?;
+    Ok(())
+}
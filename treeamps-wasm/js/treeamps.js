@@ -0,0 +1,47 @@
+// Thin wrapper over the module `wasm-pack build --target web` writes to
+// ../pkg, for pages that only want listings of a basis.
+//
+//   import { load, basis } from "./treeamps.js";
+//   await load();
+//   basis(4, 3, 1, { massive: [4] }).forEach((t) => console.log(t.index, t.text));
+
+import init, { GenConfig, generate, count, predictCount, Structure } from "../pkg/treeamps_wasm.js";
+
+export { GenConfig, Structure, generate, count, predictCount };
+
+// Fetch and instantiate the WebAssembly module; call once before the rest.
+export async function load(wasmUrl) {
+  await init(wasmUrl);
+}
+
+// The structures of `deg` factors, `ee` of them EE, for `nLegs` legs with the
+// given GenConfig options, as plain objects ready to render.
+export function basis(nLegs, deg, ee, options = {}) {
+  const cfg = new GenConfig(nLegs, options);
+  try {
+    return generate(cfg, deg, ee).map((t, i) => {
+      const row = {
+        index: i + 1,
+        text: t.toString(),
+        latex: t.toLatex(),
+        degree: t.degree,
+        ee: t.ee,
+      };
+      t.free();
+      return row;
+    });
+  } finally {
+    cfg.free();
+  }
+}
+
+// The count of `basis(nLegs, deg, ee, options)` and the Hilbert-series
+// prediction (undefined where there is none), without generating.
+export function counts(nLegs, deg, ee, options = {}) {
+  const cfg = new GenConfig(nLegs, options);
+  try {
+    return { count: count(cfg, deg, ee), predicted: predictCount(cfg, deg, ee) };
+  } finally {
+    cfg.free();
+  }
+}
@@ -0,0 +1,241 @@
+//! WebAssembly bindings for generating structure bases in the browser.
+//!
+//! Built with `wasm-pack build treeamps-wasm --target web`; `js/treeamps.js`
+//! wraps the generated module for a page that only wants the listings.
+//! Counts are returned as JS numbers, which hold them exactly below 2^53.
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use treeamps_core::{
+    Canonicalization, FormNames, GenConfig, Mass, Spin, TensorStructure, count_tensor_structures,
+    predict_structure_count, try_generate_tensor_structures,
+};
+use wasm_bindgen::prelude::*;
+
+fn js_error(e: impl ToString) -> JsError {
+    JsError::new(&e.to_string())
+}
+
+/// The options object of `new GenConfig(nLegs, options)`, with the keywords
+/// of the Python `GenConfig`.
+#[derive(Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+struct Options {
+    eliminate_momentum: bool,
+    parity_odd: bool,
+    massive: Vec<u8>,
+    mass_squares: bool,
+    scalars: Vec<u8>,
+    tensors: Vec<u8>,
+    max_pp: Option<u32>,
+    loops: u8,
+    momentum_power: Option<u32>,
+    max_structures: Option<u64>,
+    cyclic: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            eliminate_momentum: true,
+            parity_odd: false,
+            massive: Vec::new(),
+            mass_squares: false,
+            scalars: Vec::new(),
+            tensors: Vec::new(),
+            max_pp: None,
+            loops: 0,
+            momentum_power: None,
+            max_structures: None,
+            cyclic: false,
+        }
+    }
+}
+
+/// Which structures to generate.
+#[wasm_bindgen(js_name = GenConfig)]
+pub struct JsGenConfig {
+    inner: GenConfig,
+}
+
+#[wasm_bindgen(js_class = GenConfig)]
+impl JsGenConfig {
+    /// `options` is an optional object such as `{ massive: [4], parityOdd: true }`;
+    /// `cyclic: true` needs `eliminateMomentum: false`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(n_legs: u8, options: JsValue) -> Result<JsGenConfig, JsError> {
+        let o: Options = if options.is_undefined() || options.is_null() {
+            Options::default()
+        } else {
+            serde_wasm_bindgen::from_value(options).map_err(js_error)?
+        };
+        for (name, legs) in [
+            ("massive", &o.massive),
+            ("scalars", &o.scalars),
+            ("tensors", &o.tensors),
+        ] {
+            if legs.iter().any(|&k| k == 0 || k > n_legs) {
+                return Err(js_error(format!(
+                    "{} legs must be between 1 and nLegs",
+                    name
+                )));
+            }
+        }
+        let inner = GenConfig {
+            n_legs,
+            eliminate_momentum: o.eliminate_momentum,
+            include_parity_odd: o.parity_odd,
+            masses: (1..=n_legs)
+                .map(|k| {
+                    if o.massive.contains(&k) {
+                        Mass::Massive
+                    } else {
+                        Mass::Massless
+                    }
+                })
+                .collect(),
+            include_mass_squares: o.mass_squares,
+            spins: (1..=n_legs)
+                .map(|k| {
+                    if o.scalars.contains(&k) {
+                        Spin::Scalar
+                    } else if o.tensors.contains(&k) {
+                        Spin::Tensor
+                    } else {
+                        Spin::Vector
+                    }
+                })
+                .collect(),
+            max_pp: o.max_pp,
+            n_loops: o.loops,
+            momentum_power: o.momentum_power,
+            max_structures: o.max_structures,
+            canonicalization: if o.cyclic {
+                Canonicalization::Cyclic
+            } else {
+                Canonicalization::Labelled
+            },
+            ..GenConfig::default()
+        };
+        inner.validate().map_err(js_error)?;
+        Ok(Self { inner })
+    }
+
+    /// The `config` object of `gen-ts --format json`, with every field.
+    #[wasm_bindgen(js_name = fromJSON)]
+    pub fn from_json(value: JsValue) -> Result<JsGenConfig, JsError> {
+        let inner: GenConfig = serde_wasm_bindgen::from_value(value).map_err(js_error)?;
+        inner.validate().map_err(js_error)?;
+        Ok(Self { inner })
+    }
+
+    #[wasm_bindgen(js_name = toJSON)]
+    pub fn to_json(&self) -> Result<JsValue, JsError> {
+        serde_wasm_bindgen::to_value(&self.inner).map_err(js_error)
+    }
+
+    #[wasm_bindgen(getter, js_name = nLegs)]
+    pub fn n_legs(&self) -> u8 {
+        self.inner.n_legs
+    }
+}
+
+/// One tensor structure, a product of dot products and other factors.
+#[wasm_bindgen(js_name = Structure)]
+pub struct JsStructure {
+    inner: TensorStructure,
+}
+
+#[wasm_bindgen(js_class = Structure)]
+impl JsStructure {
+    /// Parse the printed form, e.g. `(p1·e2) · (e3·e4)`.
+    pub fn parse(s: &str) -> Result<JsStructure, JsError> {
+        let inner = s.parse().map_err(js_error)?;
+        Ok(Self { inner })
+    }
+
+    #[wasm_bindgen(js_name = fromJSON)]
+    pub fn from_json(value: JsValue) -> Result<JsStructure, JsError> {
+        let inner = serde_wasm_bindgen::from_value(value).map_err(js_error)?;
+        Ok(Self { inner })
+    }
+
+    /// The form of `--format json`, so `JSON.stringify` gives the same text.
+    #[wasm_bindgen(js_name = toJSON)]
+    pub fn to_json(&self) -> Result<JsValue, JsError> {
+        serde_wasm_bindgen::to_value(&self.inner).map_err(js_error)
+    }
+
+    /// Number of factors.
+    #[wasm_bindgen(getter)]
+    pub fn degree(&self) -> u32 {
+        self.inner.degree()
+    }
+
+    /// Number of EE factors.
+    #[wasm_bindgen(getter)]
+    pub fn ee(&self) -> u32 {
+        self.inner.ee_contractions
+    }
+
+    /// The dot products and spinor brackets as `{kind, a, b}` objects.
+    #[wasm_bindgen(getter)]
+    pub fn factors(&self) -> Result<JsValue, JsError> {
+        serde_wasm_bindgen::to_value(&self.inner.factors).map_err(js_error)
+    }
+
+    #[wasm_bindgen(js_name = toLatex)]
+    pub fn to_latex(&self) -> String {
+        self.inner.to_latex()
+    }
+
+    /// FORM input; `names` renames functions and vectors as in
+    /// `--form-name`, e.g. `{ dot: "SP" }`.
+    #[wasm_bindgen(js_name = toForm)]
+    pub fn to_form(&self, names: JsValue) -> Result<String, JsError> {
+        let mut form = FormNames::default();
+        if !names.is_undefined() && !names.is_null() {
+            let names: HashMap<String, String> =
+                serde_wasm_bindgen::from_value(names).map_err(js_error)?;
+            for (kind, name) in names {
+                form.set(&kind, &name).map_err(js_error)?;
+            }
+        }
+        Ok(self.inner.to_form(&form))
+    }
+
+    #[wasm_bindgen(js_name = toWolfram)]
+    pub fn to_wolfram(&self) -> String {
+        self.inner.to_wolfram()
+    }
+
+    #[wasm_bindgen(js_name = toString)]
+    pub fn to_string_js(&self) -> String {
+        self.inner.to_string()
+    }
+}
+
+/// The basis of structures with `deg` factors, `ee` of them EE, in
+/// canonical order; throws when the count exceeds `maxStructures`.
+#[wasm_bindgen]
+pub fn generate(cfg: &JsGenConfig, deg: u32, ee: u32) -> Result<Vec<JsStructure>, JsError> {
+    let structures = try_generate_tensor_structures(&cfg.inner, deg, ee).map_err(js_error)?;
+    Ok(structures
+        .into_iter()
+        .map(|inner| JsStructure { inner })
+        .collect())
+}
+
+/// The number of structures `generate` returns, without holding them.
+#[wasm_bindgen]
+pub fn count(cfg: &JsGenConfig, deg: u32, ee: u32) -> f64 {
+    count_tensor_structures(&cfg.inner, deg, ee) as f64
+}
+
+/// The count predicted by the Hilbert series, or `undefined` where it does
+/// not apply.
+#[wasm_bindgen(js_name = predictCount)]
+pub fn predict_count(cfg: &JsGenConfig, deg: u32, ee: u32) -> Option<f64> {
+    predict_structure_count(&cfg.inner, deg, ee).map(|c| c as f64)
+}